@@ -5,14 +5,20 @@
 
 use crate::commands;
 use crate::error::{BridgeError, BridgeResult};
-use adobe_common::{Command, CommandResponse, ResponseStatus};
-use adobe_common::socket_io::{decode_event, encode_event, ENGINE_PING, ENGINE_PONG, SOCKET_IO_CONNECT};
+use adobe_common::{epoch_millis, Command, CommandResponse, ResponseStatus};
+use adobe_common::socket_io::{decode_event, encode_event, split_payload, ENGINE_PING, ENGINE_PONG, SOCKET_IO_CONNECT};
 use futures_util::{SinkExt, StreamExt};
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use tokio::sync::mpsc;
+use tokio_tungstenite::tungstenite::client::IntoClientRequest;
+use tokio_tungstenite::tungstenite::http::header::{HeaderValue, AUTHORIZATION};
 use tokio_tungstenite::{connect_async, tungstenite::Message};
 
+/// `resumeToken` from the proxy's last `registration_response`, kept across
+/// connections so a reconnect can resume the session
+pub type ResumeToken = Arc<parking_lot::Mutex<Option<String>>>;
+
 /// Proxy client for WebSocket communication
 pub struct ProxyClient {
     /// Sender for outgoing messages
@@ -26,10 +32,29 @@ pub struct ProxyClient {
 impl ProxyClient {
     /// Create new client and connect to proxy
     ///
+    /// When `auth_token` is set it is sent as an `Authorization: Bearer` header
+    /// on the upgrade request. A token already in `resume_token` is sent with
+    /// `register`, and the one the proxy hands back replaces it.
+    ///
     /// # Errors
-    /// Returns error if WebSocket connection fails
-    pub async fn connect(proxy_url: &str) -> BridgeResult<Self> {
-        let (ws_stream, _) = connect_async(proxy_url)
+    /// Returns [`BridgeError::InvalidState`] if `proxy_url` is not a `ws://`
+    /// or `wss://` URL, or an error if the WebSocket connection fails
+    pub async fn connect(
+        proxy_url: &str,
+        auth_token: Option<&str>,
+        resume_token: ResumeToken,
+    ) -> BridgeResult<Self> {
+        crate::validate_proxy_url(proxy_url)?;
+        let mut request = proxy_url
+            .into_client_request()
+            .map_err(|e| BridgeError::ConnectionFailed(e.to_string()))?;
+        if let Some(token) = auth_token {
+            let value = HeaderValue::from_str(&format!("Bearer {}", token))
+                .map_err(|e| BridgeError::InvalidState(format!("Invalid auth token: {}", e)))?;
+            request.headers_mut().insert(AUTHORIZATION, value);
+        }
+
+        let (ws_stream, _) = connect_async(request)
             .await
             .map_err(|e| BridgeError::ConnectionFailed(e.to_string()))?;
 
@@ -44,6 +69,7 @@ impl ProxyClient {
         let connected = Arc::new(AtomicBool::new(true));
         let connected_write = connected.clone();
         let connected_read = connected.clone();
+        let previous_token = resume_token.lock().clone();
 
         // Send Socket.IO connect frame
         if let Err(e) = write.send(Message::Text("40".to_string())).await {
@@ -79,16 +105,22 @@ impl ProxyClient {
         tokio::spawn(async move {
             while let Some(msg) = read.next().await {
                 match msg {
-                    Ok(Message::Text(text)) => {
-                        if text == ENGINE_PING {
-                            let _ = response_tx.send(ENGINE_PONG.to_string()).await;
-                            continue;
-                        }
-                        if text == SOCKET_IO_CONNECT {
-                            continue;
-                        }
-                        if let Err(e) = Self::handle_message(&text, response_tx.clone()).await {
-                            tracing::error!("Error handling message: {}", e);
+                    Ok(Message::Text(frame)) => {
+                        for text in split_payload(&frame) {
+                            if text == ENGINE_PING {
+                                let _ = response_tx.send(ENGINE_PONG.to_string()).await;
+                                continue;
+                            }
+                            if text == SOCKET_IO_CONNECT {
+                                continue;
+                            }
+                            if let Some(token) = registration_resume_token(text) {
+                                *resume_token.lock() = Some(token);
+                                continue;
+                            }
+                            if let Err(e) = Self::handle_message(text, response_tx.clone()).await {
+                                tracing::error!("Error handling message: {}", e);
+                            }
                         }
                     }
                     Ok(Message::Close(_)) => {
@@ -116,18 +148,24 @@ impl ProxyClient {
         };
 
         // Register with proxy
-        client.register().await?;
+        client.register(previous_token).await?;
 
         Ok(client)
     }
 
-    /// Register this client as "acrobat" application
-    async fn register(&mut self) -> BridgeResult<()> {
+    /// Register this client as "acrobat" application, resuming the session
+    /// `resume_token` names when there is one
+    async fn register(&mut self, resume_token: Option<String>) -> BridgeResult<()> {
+        let mut registration = serde_json::json!({
+            "application": "acrobat",
+            "version": env!("CARGO_PKG_VERSION"),
+            "clientName": env!("CARGO_PKG_NAME"),
+        });
+        if let Some(token) = resume_token {
+            registration["resumeToken"] = serde_json::Value::String(token);
+        }
         self.tx
-            .send(encode_event(
-                "register",
-                serde_json::json!({ "application": "acrobat" }),
-            ))
+            .send(encode_event("register", registration))
             .await
             .map_err(|e| BridgeError::SendFailed(e.to_string()))?;
 
@@ -188,6 +226,10 @@ impl ProxyClient {
             // Parse the command
             let command: Command = serde_json::from_value(command_value.clone())
                 .map_err(|e| BridgeError::Deserialization(format!("Invalid command: {}", e)))?;
+            let request_id = command_value
+                .get("requestId")
+                .and_then(|v| v.as_str())
+                .map(str::to_string);
 
             tracing::info!("Executing command: {}", command.action);
 
@@ -195,6 +237,7 @@ impl ProxyClient {
             let response = match commands::execute_command(&command) {
                 Ok(mut resp) => {
                     resp.sender_id = sender_id.clone();
+                    resp.request_id = request_id;
                     resp
                 }
                 Err(e) => CommandResponse {
@@ -203,6 +246,10 @@ impl ProxyClient {
                     response: None,
                     message: Some(e.to_string()),
                     document: None,
+                    request_id,
+                    timestamp: Some(epoch_millis()),
+                    duration_ms: None,
+                    code: None,
                 },
             };
 
@@ -217,6 +264,9 @@ impl ProxyClient {
                             "response": response.response,
                             "message": response.message,
                             "document": response.document,
+                            "requestId": response.request_id,
+                            "timestamp": response.timestamp,
+                            "durationMs": response.duration_ms,
                         }
                     }),
                 ))
@@ -234,6 +284,23 @@ impl ProxyClient {
         Ok(())
     }
 
+    /// Push an application event (e.g. `documentOpened`) to the proxy, which
+    /// forwards it to the MCP servers as `app_event`
+    ///
+    /// The message is queued without waiting, so this is safe to call from
+    /// Acrobat's notification callbacks.
+    ///
+    /// # Errors
+    /// Returns error if the outgoing queue is full or the connection is gone
+    pub fn send_event(&self, event: &str, data: serde_json::Value) -> BridgeResult<()> {
+        self.tx
+            .try_send(encode_event(
+                "app_event",
+                serde_json::json!({ "event": event, "data": data }),
+            ))
+            .map_err(|e| BridgeError::SendFailed(e.to_string()))
+    }
+
     /// Send a raw message to the proxy
     ///
     /// # Errors
@@ -263,6 +330,17 @@ impl ProxyClient {
     }
 }
 
+/// `resumeToken` from a `registration_response` packet, if `text` is one
+fn registration_resume_token(text: &str) -> Option<String> {
+    match decode_event(text) {
+        Some((event, data)) if event == "registration_response" => data
+            .get("resumeToken")
+            .and_then(|v| v.as_str())
+            .map(str::to_string),
+        _ => None,
+    }
+}
+
 impl Drop for ProxyClient {
     fn drop(&mut self) {
         // Mark as disconnected on drop
@@ -281,6 +359,94 @@ mod tests {
         assert!(!connected.load(Ordering::SeqCst));
     }
 
+    #[test]
+    fn test_send_event_encoding() {
+        let (tx, mut rx) = mpsc::channel(10);
+        let client = ProxyClient {
+            tx,
+            connected: Arc::new(AtomicBool::new(true)),
+            shutdown_tx: None,
+        };
+
+        client
+            .send_event(
+                "documentOpened",
+                serde_json::json!({ "path": "/C/report.pdf" }),
+            )
+            .unwrap();
+
+        let frame = rx.try_recv().unwrap();
+        let (event, data) = decode_event(&frame).unwrap();
+        assert_eq!(event, "app_event");
+        assert_eq!(data["event"], "documentOpened");
+        assert_eq!(data["data"]["path"], "/C/report.pdf");
+    }
+
+    #[test]
+    fn test_send_event_after_disconnect_fails() {
+        let (tx, rx) = mpsc::channel(10);
+        let client = ProxyClient {
+            tx,
+            connected: Arc::new(AtomicBool::new(false)),
+            shutdown_tx: None,
+        };
+        drop(rx);
+
+        let result = client.send_event("documentWillClose", serde_json::json!({}));
+        assert!(matches!(result, Err(BridgeError::SendFailed(_))));
+    }
+
+    #[tokio::test]
+    async fn test_connect_rejects_invalid_proxy_url() {
+        let result = ProxyClient::connect("http://localhost:3001", None, ResumeToken::default()).await;
+        assert!(matches!(result, Err(BridgeError::InvalidState(_))));
+    }
+
+    #[tokio::test]
+    async fn test_reconnect_sends_stored_resume_token() {
+        use tokio_tungstenite::tungstenite::Message as WsMessage;
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("ws://{}", listener.local_addr().unwrap());
+        let (registers_tx, mut registers) = mpsc::channel(2);
+        tokio::spawn(async move {
+            for session in 1..=2 {
+                let (stream, _) = listener.accept().await.unwrap();
+                let mut ws = tokio_tungstenite::accept_async(stream).await.unwrap();
+                while let Some(Ok(WsMessage::Text(text))) = ws.next().await {
+                    if let Some((event, data)) = decode_event(&text) {
+                        if event == "register" {
+                            registers_tx.send(data).await.unwrap();
+                            let response = encode_event(
+                                "registration_response",
+                                serde_json::json!({"status": "success", "resumeToken": format!("tok-{}", session)}),
+                            );
+                            ws.send(WsMessage::Text(response)).await.unwrap();
+                            break;
+                        }
+                    }
+                }
+                // Drop the first connection as a proxy restart would
+                let _ = ws.close(None).await;
+            }
+        });
+
+        let resume_token = ResumeToken::default();
+        let first = ProxyClient::connect(&url, None, resume_token.clone()).await.unwrap();
+        assert!(registers.recv().await.unwrap().get("resumeToken").is_none());
+        for _ in 0..100 {
+            if resume_token.lock().is_some() {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        }
+        assert_eq!(resume_token.lock().as_deref(), Some("tok-1"));
+        drop(first);
+
+        let _second = ProxyClient::connect(&url, None, resume_token.clone()).await.unwrap();
+        assert_eq!(registers.recv().await.unwrap()["resumeToken"], "tok-1");
+    }
+
     #[tokio::test]
     async fn test_handle_message_invalid_json() {
         let (tx, _rx) = mpsc::channel(10);
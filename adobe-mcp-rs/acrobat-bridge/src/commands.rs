@@ -3,10 +3,14 @@
 //! Each command maps to an Acrobat JavaScript API call.
 //! Commands are executed via the js_bridge module.
 
-use crate::js_bridge;
-use adobe_common::{Command, CommandResponse, ResponseStatus};
+use crate::js_bridge::{self, JsScript};
+use adobe_common::{
+    epoch_millis, AdobeError, Color, Command, CommandResponse, PageBox, PageRange, PageSize,
+    ResponseStatus, Rotation,
+};
 use anyhow::Result;
 use serde_json::{json, Value};
+use std::time::Instant;
 
 /// Execute a command and return the response
 ///
@@ -18,36 +22,78 @@ pub fn execute_command(command: &Command) -> Result<CommandResponse> {
 
     tracing::info!("Executing command: {} with options: {:?}", action, options);
 
-    let result = match action {
+    let started = Instant::now();
+    let result = activate_target_document(action, options).and_then(|()| match action {
         // Document operations
         "createDocument" => create_document(options),
         "openDocument" => open_document(options),
         "saveDocument" => save_document(options),
         "closeDocument" => close_document(options),
         "getDocumentInfo" => get_document_info(options),
+        "getSecurityInfo" => get_security_info(options),
+        "getAppInfo" => get_app_info(options),
+        "listOpenDocuments" => list_open_documents(options),
+        "setActiveDocument" => set_active_document(options),
 
         // Text operations
         "addText" => add_text(options),
         "extractText" => extract_text(options),
+        "searchText" => search_text(options),
+        "getStatistics" => get_statistics(options),
+
+        // Annotation operations
+        "highlightText" => highlight_text(options),
+        "addComment" => add_comment(options),
 
         // Export operations
         "exportAs" => export_as(options),
+        "convertToPdfA" => convert_to_pdfa(options),
+        "linearizeDocument" => linearize_document(options),
+        "checkCompliance" => check_compliance(options),
+
+        // Form data operations
+        "exportFormData" => export_form_data(options),
+        "importFormData" => import_form_data(options),
+
+        // Attachment operations
+        "attachFile" => attach_file(options),
+        "extractAttachments" => extract_attachments(options),
 
         // Multi-document operations
         "mergeDocuments" => merge_documents(options),
         "splitDocument" => split_document(options),
+        "splitByBookmarks" => split_by_bookmarks(options),
+        "extractPages" => extract_pages(options),
+        "compareDocuments" => compare_documents(options),
 
         // Page operations
         "getPageCount" => get_page_count(options),
         "deletePages" => delete_pages(options),
         "rotatePages" => rotate_pages(options),
         "insertPages" => insert_pages(options),
+        "insertBlankPage" => insert_blank_page(options),
+        "duplicatePage" => duplicate_page(options),
+        "replacePage" => replace_page(options),
         "addBookmark" => add_bookmark(options),
+        "getBookmarks" => get_bookmarks(options),
+        "deleteBookmark" => delete_bookmark(options),
+        "generateToc" => generate_toc(options),
         "setMetadata" => set_metadata(options),
+        "setInitialView" => set_initial_view(options),
+        "sanitizeDocument" => sanitize_document(options),
+        "removeWatermarks" => remove_watermarks(options),
+
+        // Layer operations
+        "getLayers" => get_layers(options),
+        "setLayerVisibility" => set_layer_visibility(options),
+
+        // Raw JavaScript, when the plugin allows it
+        "executeJavaScript" => execute_javascript(options),
 
         // Unknown command
         _ => Err(anyhow::anyhow!("Unknown command: {}", action)),
-    };
+    });
+    let duration_ms = Some(u64::try_from(started.elapsed().as_millis()).unwrap_or(u64::MAX));
 
     match result {
         Ok(response) => Ok(CommandResponse {
@@ -56,6 +102,10 @@ pub fn execute_command(command: &Command) -> Result<CommandResponse> {
             response: Some(response),
             message: None,
             document: None,
+            request_id: None, // Echoed by caller from the command packet
+            timestamp: Some(epoch_millis()),
+            duration_ms,
+            code: None,
         }),
         Err(e) => Ok(CommandResponse {
             sender_id: String::new(),
@@ -63,10 +113,42 @@ pub fn execute_command(command: &Command) -> Result<CommandResponse> {
             response: None,
             message: Some(e.to_string()),
             document: None,
+            request_id: None,
+            timestamp: Some(epoch_millis()),
+            duration_ms,
+            code: None,
         }),
     }
 }
 
+/// Commands that do not act on the active document
+const DOCUMENT_INDEPENDENT: &[&str] = &[
+    "createDocument",
+    "openDocument",
+    "mergeDocuments",
+    "compareDocuments",
+    "getAppInfo",
+    "listOpenDocuments",
+    "setActiveDocument",
+];
+
+/// Bring `documentPath` to front before a command that acts on `this`
+///
+/// Scripts address the active document, so targeting another open document
+/// means activating it first; commands without `documentPath` are untouched.
+fn activate_target_document(action: &str, options: &Value) -> Result<()> {
+    let Some(path) = options.get("documentPath").and_then(|v| v.as_str()) else {
+        return Ok(());
+    };
+    if DOCUMENT_INDEPENDENT.contains(&action) {
+        return Ok(());
+    }
+
+    let js = set_active_document_script(Some(path), None).render();
+    execute_js_and_parse(&js, || json!({"status": "ok"}))?;
+    Ok(())
+}
+
 // ============================================================================
 // Document Operations
 // ============================================================================
@@ -80,20 +162,7 @@ fn create_document(options: &Value) -> Result<Value> {
         .get("pageCount")
         .and_then(|v| v.as_u64())
         .unwrap_or(1);
-    let page_size = options
-        .get("pageSize")
-        .and_then(|v| v.as_str())
-        .unwrap_or("LETTER");
-    let custom_width = options.get("width").and_then(|v| v.as_f64());
-    let custom_height = options.get("height").and_then(|v| v.as_f64());
-    let (page_width, page_height) = if page_size.eq_ignore_ascii_case("CUSTOM") {
-        (
-            custom_width.unwrap_or_else(|| page_size_width("LETTER")),
-            custom_height.unwrap_or_else(|| page_size_height("LETTER")),
-        )
-    } else {
-        (page_size_width(page_size), page_size_height(page_size))
-    };
+    let (page_width, page_height) = requested_page_size(options);
 
     // Generate JavaScript to create document
     let js = format!(
@@ -139,27 +208,7 @@ fn open_document(options: &Value) -> Result<Value> {
         .and_then(|v| v.as_str())
         .ok_or_else(|| anyhow::anyhow!("filePath required"))?;
 
-    let js = format!(
-        r#"
-        (function() {{
-            try {{
-                var doc = app.openDoc("{}");
-                if (doc) {{
-                    return JSON.stringify({{
-                        "success": true,
-                        "path": doc.path,
-                        "title": doc.info.Title || "",
-                        "numPages": doc.numPages
-                    }});
-                }}
-                return JSON.stringify({{"success": false, "error": "Failed to open document"}});
-            }} catch(e) {{
-                return JSON.stringify({{"success": false, "error": e.toString()}});
-            }}
-        }})()
-        "#,
-        escape_js_path(file_path)
-    );
+    let js = open_document_script(file_path).render();
 
     execute_js_and_parse(&js, || {
         json!({
@@ -169,40 +218,78 @@ fn open_document(options: &Value) -> Result<Value> {
     })
 }
 
+fn open_document_script(file_path: &str) -> JsScript {
+    JsScript::new(
+        r#"
+        try {
+            var doc = app.openDoc(args.filePath);
+            if (doc) {
+                return JSON.stringify({
+                    "success": true,
+                    "path": doc.path,
+                    "title": doc.info.Title || "",
+                    "numPages": doc.numPages
+                });
+            }
+            return JSON.stringify({"success": false, "error": "Failed to open document"});
+        } catch(e) {
+            return JSON.stringify({"success": false, "error": e.toString()});
+        }
+        "#,
+    )
+    .bind("filePath", escape_js_path(file_path))
+}
+
 fn save_document(options: &Value) -> Result<Value> {
     let file_path = options.get("filePath").and_then(|v| v.as_str());
+    let format = options
+        .get("format")
+        .and_then(|v| v.as_str())
+        .unwrap_or("PDF");
+    let conversion_id = save_conversion_id(format)?;
 
-    let js = if let Some(path) = file_path {
-        format!(
-            r#"
-            (function() {{
-                try {{
-                    var doc = this;
-                    doc.saveAs("{}");
-                    return JSON.stringify({{"success": true}});
-                }} catch(e) {{
-                    return JSON.stringify({{"success": false, "error": e.toString()}});
-                }}
-            }})()
-            "#,
-            escape_js_path(path)
-        )
-    } else {
+    let js = save_document_script(file_path, conversion_id).render();
+
+    execute_js_and_parse(&js, || json!({"status": "ok"}))
+}
+
+const PDFA_CONVERSION_ID: &str = "com.adobe.acrobat.pdfa";
+const PDFX_CONVERSION_ID: &str = "com.adobe.acrobat.pdfx";
+
+/// Conversion id for a `saveDocument` format; plain PDF needs none
+fn save_conversion_id(format: &str) -> Result<Option<&'static str>> {
+    match format.to_uppercase().replace('/', "_").as_str() {
+        "PDF" => Ok(None),
+        "PDF_A" | "PDFA" => Ok(Some(PDFA_CONVERSION_ID)),
+        "PDF_X" | "PDFX" => Ok(Some(PDFX_CONVERSION_ID)),
+        _ => Err(anyhow::anyhow!(
+            "Unsupported save format: {} (expected PDF, PDF_A or PDF_X)",
+            format
+        )),
+    }
+}
+
+/// Save in place, or to `file_path` when given, converting when `conversion_id` is set
+fn save_document_script(file_path: Option<&str>, conversion_id: Option<&str>) -> JsScript {
+    JsScript::new(
         r#"
-        (function() {
-            try {
-                var doc = this;
+        try {
+            var doc = this;
+            if (args.conversionId !== null) {
+                doc.saveAs(args.filePath !== null ? args.filePath : doc.path, args.conversionId);
+            } else if (args.filePath !== null) {
+                doc.saveAs(args.filePath);
+            } else {
                 doc.save();
-                return JSON.stringify({"success": true});
-            } catch(e) {
-                return JSON.stringify({"success": false, "error": e.toString()});
             }
-        })()
-        "#
-        .to_string()
-    };
-
-    execute_js_and_parse(&js, || json!({"status": "ok"}))
+            return JSON.stringify({"success": true});
+        } catch(e) {
+            return JSON.stringify({"success": false, "error": e.toString()});
+        }
+        "#,
+    )
+    .bind("filePath", file_path.map(escape_js_path))
+    .bind("conversionId", conversion_id)
 }
 
 fn close_document(options: &Value) -> Result<Value> {
@@ -230,34 +317,15 @@ fn close_document(options: &Value) -> Result<Value> {
     execute_js_and_parse(&js, || json!({"status": "ok"}))
 }
 
-fn get_document_info(_options: &Value) -> Result<Value> {
-    let js = r#"
-        (function() {
-            try {
-                var doc = this;
-                var box = doc.getPageBox("Crop", 0);
-                return JSON.stringify({
-                    "success": true,
-                    "title": doc.info.Title || "",
-                    "author": doc.info.Author || "",
-                    "subject": doc.info.Subject || "",
-                    "keywords": doc.info.Keywords || "",
-                    "creator": doc.info.Creator || "",
-                    "producer": doc.info.Producer || "",
-                    "numPages": doc.numPages,
-                    "pageSize": {
-                        "width": box[2] - box[0],
-                        "height": box[3] - box[1]
-                    },
-                    "path": doc.path
-                });
-            } catch(e) {
-                return JSON.stringify({"success": false, "error": e.toString()});
-            }
-        })()
-    "#;
+fn get_document_info(options: &Value) -> Result<Value> {
+    let page_box = match options.get("pageBox").and_then(|v| v.as_str()) {
+        Some(name) => name.parse::<PageBox>()?,
+        None => PageBox::default(),
+    };
 
-    execute_js_and_parse(js, || {
+    let js = get_document_info_script(page_box).render();
+
+    execute_js_and_parse(&js, || {
         json!({
             "title": "Document",
             "numPages": 1,
@@ -266,864 +334,4741 @@ fn get_document_info(_options: &Value) -> Result<Value> {
     })
 }
 
+fn get_security_info(_options: &Value) -> Result<Value> {
+    execute_js_and_parse(&get_security_info_script().render(), || {
+        json!({"securityHandler": null, "encrypted": false, "permissions": {}})
+    })
+}
+
+/// Security handler and permission flags. Acrobat JavaScript exposes no
+/// permission bits, so on an encrypted file copying and modifying are probed
+/// by reading a word and rewriting the title (restrictions throw
+/// `NotAllowedError`); flags that cannot be probed without side effects are
+/// null. An unencrypted file permits everything.
+fn get_security_info_script() -> JsScript {
+    JsScript::new(
+        r#"
+        try {
+            var doc = this;
+            var handler = doc.securityHandler;
+            var encrypted = handler != null;
+            var probe = function(operation) {
+                try {
+                    operation();
+                    return true;
+                } catch(e) {
+                    if (e.name === "NotAllowedError" || /security/i.test(e.toString())) {
+                        return false;
+                    }
+                    return null;
+                }
+            };
+
+            var permissions;
+            if (!encrypted) {
+                permissions = {
+                    "printing": true,
+                    "copying": true,
+                    "modifying": true,
+                    "annotating": true,
+                    "formFilling": true
+                };
+            } else {
+                var wasDirty = doc.dirty;
+                permissions = {
+                    "printing": null,
+                    "copying": doc.numPages > 0 ? probe(function() { doc.getPageNthWord(0, 0, false); }) : null,
+                    "modifying": probe(function() { doc.info.Title = doc.info.Title; }),
+                    "annotating": null,
+                    "formFilling": null
+                };
+                doc.dirty = wasDirty;
+            }
+
+            return JSON.stringify({
+                "success": true,
+                "securityHandler": handler,
+                "encrypted": encrypted,
+                "permissions": permissions
+            });
+        } catch(e) {
+            return JSON.stringify({"success": false, "error": e.toString()});
+        }
+        "#,
+    )
+}
+
 // ============================================================================
 // Text Operations
 // ============================================================================
 
-fn add_text(options: &Value) -> Result<Value> {
-    let page = options.get("page").and_then(|v| v.as_i64()).unwrap_or(1);
-    let page_index = normalize_page_index(page);
-    let text = options.get("text").and_then(|v| v.as_str()).unwrap_or("");
-    let x = options.get("x").and_then(|v| v.as_f64()).unwrap_or(72.0);
-    let y = options.get("y").and_then(|v| v.as_f64()).unwrap_or(720.0);
-    let font_size = options
-        .get("fontSize")
-        .and_then(|v| v.as_f64())
-        .unwrap_or(12.0);
-    let font_name = options
-        .get("fontName")
-        .and_then(|v| v.as_str())
-        .unwrap_or("Helvetica");
-
-    let js = format!(
-        r#"
-        (function() {{
-            try {{
-                var doc = this;
-                var annot = doc.addAnnot({{
-                    page: {},
-                    type: "FreeText",
-                    rect: [{}, {}, {}, {}],
-                    contents: "{}",
-                    textFont: "{}",
-                    textSize: {}
-                }});
-                return JSON.stringify({{"success": annot != null, "page": {}}});
-            }} catch(e) {{
-                return JSON.stringify({{"success": false, "error": e.toString()}});
-            }}
-        }})()
-        "#,
-        page_index,
-        x,
-        y,
-        x + 200.0,
-        y + font_size * 1.5,
-        escape_js_string(text),
-        escape_js_string(font_name),
-        font_size,
-        page_index + 1
-    );
+fn list_open_documents(_options: &Value) -> Result<Value> {
+    let js = list_open_documents_script().render();
 
-    execute_js_and_parse(&js, || json!({"status": "ok", "page": page_index + 1}))
+    execute_js_and_parse(&js, || json!({"status": "ok", "documents": [], "count": 0}))
 }
 
-fn extract_text(options: &Value) -> Result<Value> {
-    let (page_start, page_end) = parse_page_range(options);
-
-    let js = format!(
+fn list_open_documents_script() -> JsScript {
+    JsScript::new(
         r#"
-        (function() {{
-            try {{
-                var doc = this;
-                var start = {};
-                var end = {};
-                if (start < 0) {{
-                    start = 0;
-                }}
-                if (end < 0) {{
-                    end = doc.numPages - 1;
-                }}
-                if (end < start) {{
-                    end = start;
-                }}
-                var text = "";
-                for (var i = start; i <= end && i < doc.numPages; i++) {{
-                    for (var j = 0; j < doc.getPageNumWords(i); j++) {{
-                        text += doc.getPageNthWord(i, j) + " ";
-                    }}
-                    text += "\n";
-                }}
-                return JSON.stringify({{"success": true, "text": text}});
-            }} catch(e) {{
-                return JSON.stringify({{"success": false, "error": e.toString()}});
-            }}
-        }})()
+        try {
+            var docs = app.activeDocs;
+            var documents = [];
+            for (var i = 0; i < docs.length; i++) {
+                documents.push({
+                    "index": i,
+                    "path": docs[i].path,
+                    "fileName": docs[i].documentFileName,
+                    "title": docs[i].info.Title || "",
+                    "numPages": docs[i].numPages,
+                    "active": docs[i] === this
+                });
+            }
+            return JSON.stringify({"success": true, "documents": documents, "count": documents.length});
+        } catch(e) {
+            return JSON.stringify({"success": false, "error": e.toString()});
+        }
         "#,
-        page_start,
-        page_end
-    );
-
-    execute_js_and_parse(&js, || json!({"status": "ok", "text": ""}))
+    )
 }
 
-// ============================================================================
-// Export Operations
-// ============================================================================
+fn set_active_document(options: &Value) -> Result<Value> {
+    let path = options.get("documentPath").and_then(|v| v.as_str());
+    let index = options.get("index").and_then(|v| v.as_u64());
+    if path.is_none() && index.is_none() {
+        return Err(anyhow::anyhow!("documentPath or index required"));
+    }
 
-fn export_as(options: &Value) -> Result<Value> {
-    let file_path = options
-        .get("filePath")
-        .and_then(|v| v.as_str())
-        .ok_or_else(|| anyhow::anyhow!("filePath required"))?;
-    let format = options
-        .get("format")
-        .and_then(|v| v.as_str())
-        .unwrap_or("PDF");
+    let js = set_active_document_script(path, index).render();
 
-    let conversion_id = match format.to_uppercase().as_str() {
-        "DOCX" | "DOC" => "com.adobe.acrobat.docx",
-        "XLSX" | "XLS" => "com.adobe.acrobat.xlsx",
-        "PPTX" | "PPT" => "com.adobe.acrobat.pptx",
-        "RTF" => "com.adobe.acrobat.rtf",
-        "TXT" => "com.adobe.acrobat.txt",
-        "PNG" | "JPEG" | "JPG" | "TIFF" => "com.adobe.acrobat.image",
-        _ => "com.adobe.acrobat.pdf",
-    };
+    execute_js_and_parse(&js, || json!({"status": "ok"}))
+}
 
-    let js = format!(
+/// Bring an open document to front by path (opening it if needed) or by
+/// its index in `app.activeDocs`
+fn set_active_document_script(path: Option<&str>, index: Option<u64>) -> JsScript {
+    JsScript::new(
         r#"
-        (function() {{
-            try {{
-                var doc = this;
-                doc.saveAs("{}", "{}");
-                return JSON.stringify({{"success": true, "filePath": "{}", "format": "{}"}});
-            }} catch(e) {{
-                return JSON.stringify({{"success": false, "error": e.toString()}});
-            }}
-        }})()
+        try {
+            var target = null;
+            if (args.index !== null) {
+                target = app.activeDocs[args.index] || null;
+            } else {
+                target = app.openDoc(args.documentPath);
+            }
+            if (!target) {
+                return JSON.stringify({"success": false, "error": "Document not open: " + (args.documentPath || args.index)});
+            }
+            target.bringToFront();
+            return JSON.stringify({
+                "success": true,
+                "path": target.path,
+                "title": target.info.Title || "",
+                "numPages": target.numPages
+            });
+        } catch(e) {
+            return JSON.stringify({"success": false, "error": e.toString()});
+        }
         "#,
-        escape_js_path(file_path),
-        conversion_id,
-        escape_js_path(file_path),
-        format
-    );
+    )
+    .bind("documentPath", path.map(escape_js_path))
+    .bind("index", index)
+}
 
-    execute_js_and_parse(&js, || {
+fn get_app_info(_options: &Value) -> Result<Value> {
+    execute_js_and_parse(&get_app_info_script(), || {
         json!({
             "status": "ok",
-            "filePath": file_path,
-            "format": format
+            "viewerType": "unknown"
         })
     })
 }
 
-// ============================================================================
-// Multi-document Operations
-// ============================================================================
-
-fn merge_documents(options: &Value) -> Result<Value> {
-    let file_paths = options
-        .get("filePaths")
-        .and_then(|v| v.as_array())
-        .ok_or_else(|| anyhow::anyhow!("filePaths array required"))?;
-    let output_path = options
-        .get("outputPath")
-        .and_then(|v| v.as_str())
-        .ok_or_else(|| anyhow::anyhow!("outputPath required"))?;
-
-    let paths: Vec<&str> = file_paths.iter().filter_map(|v| v.as_str()).collect();
-
-    if paths.is_empty() {
-        return Err(anyhow::anyhow!("At least one file path required"));
-    }
-
-    let paths_json = serde_json::to_string(&paths)?;
+/// Viewer version, type and platform; needs no open document
+fn get_app_info_script() -> String {
+    use js_bridge::utils;
 
-    let js = format!(
+    format!(
         r#"
         (function() {{
             try {{
-                var paths = {};
-                if (paths.length === 0) {{
-                    return JSON.stringify({{"success": false, "error": "No paths provided"}});
-                }}
-
-                // Open first document as base
-                var doc = app.openDoc(paths[0]);
-                if (!doc) {{
-                    return JSON.stringify({{"success": false, "error": "Failed to open first document"}});
-                }}
-
-                // Insert pages from remaining documents
-                for (var i = 1; i < paths.length; i++) {{
-                    doc.insertPages({{
-                        nPage: doc.numPages - 1,
-                        cPath: paths[i]
-                    }});
-                }}
-
-                doc.saveAs("{}");
-                var pageCount = doc.numPages;
-                doc.closeDoc(true);
-
                 return JSON.stringify({{
                     "success": true,
-                    "outputPath": "{}",
-                    "mergedCount": paths.length,
-                    "totalPages": pageCount
+                    "viewerVersion": {},
+                    "viewerType": {},
+                    "viewerVariation": {},
+                    "platform": {},
+                    "language": app.language,
+                    "documentCount": {}
                 }});
             }} catch(e) {{
                 return JSON.stringify({{"success": false, "error": e.toString()}});
             }}
         }})()
         "#,
-        paths_json,
-        escape_js_path(output_path),
-        escape_js_path(output_path)
-    );
+        utils::GET_VERSION,
+        utils::GET_VIEWER_TYPE,
+        utils::GET_VIEWER_VARIATION,
+        utils::GET_PLATFORM,
+        utils::GET_DOC_COUNT
+    )
+}
 
-    execute_js_and_parse(&js, || {
-        json!({
-            "status": "ok",
-            "outputPath": output_path,
-            "mergedCount": paths.len()
-        })
-    })
+/// Document properties plus the first page's size measured by `page_box`
+fn get_document_info_script(page_box: PageBox) -> JsScript {
+    JsScript::new(
+        r#"
+        try {
+            var doc = this;
+            var box = doc.getPageBox(args.pageBox, 0);
+            return JSON.stringify({
+                "success": true,
+                "title": doc.info.Title || "",
+                "author": doc.info.Author || "",
+                "subject": doc.info.Subject || "",
+                "keywords": doc.info.Keywords || "",
+                "creator": doc.info.Creator || "",
+                "producer": doc.info.Producer || "",
+                "created": doc.creationDate ? doc.creationDate.toISOString() : null,
+                "modified": doc.modDate ? doc.modDate.toISOString() : null,
+                "numPages": doc.numPages,
+                "pageSize": {
+                    "width": box[2] - box[0],
+                    "height": box[3] - box[1]
+                },
+                "pageBox": args.pageBox,
+                "path": doc.path
+            });
+        } catch(e) {
+            return JSON.stringify({"success": false, "error": e.toString()});
+        }
+        "#,
+    )
+    .bind("pageBox", page_box.as_str())
 }
 
-fn split_document(options: &Value) -> Result<Value> {
-    let page_ranges_value = options
-        .get("pageRanges")
-        .ok_or_else(|| anyhow::anyhow!("pageRanges array required"))?;
-    let page_ranges = normalize_page_ranges(page_ranges_value)?;
-    let output_dir = options
-        .get("outputDir")
-        .and_then(|v| v.as_str())
-        .ok_or_else(|| anyhow::anyhow!("outputDir required"))?;
-    let name_pattern = options
-        .get("namePattern")
-        .and_then(|v| v.as_str())
-        .unwrap_or("split_{n}.pdf");
+fn add_text(options: &Value) -> Result<Value> {
+    let js = add_text_script(options)?;
+    let page_index = js.arg("pageIndex").and_then(|v| v.as_i64()).unwrap_or(0);
+    let rect = number_array(js.arg("rect").unwrap_or(&Value::Null), 4, "rect")?;
+    let [left, bottom, right, top] =
+        fit_rect_to_page([rect[0], rect[1], rect[2], rect[3]], page_index, options)?;
+    let js = js
+        .bind("rect", json!([left, bottom, right, top]))
+        .bind("x", left)
+        .bind("y", bottom);
+    let page = page_index + 1;
+    let mode = js.arg("mode").cloned().unwrap_or(Value::Null);
 
-    let ranges_json = serde_json::to_string(&page_ranges)?;
+    execute_js_and_parse(&js.render(), || json!({"status": "ok", "page": page, "mode": mode}))
+}
 
-    let js = format!(
-        r#"
-        (function() {{
-            try {{
-                var doc = this;
-                var ranges = {};
-                var outputDir = "{}";
-                var namePattern = "{}";
-                var outputs = [];
+/// FreeText annotation: editable and movable, but flattening and some print
+/// paths treat it differently from page text
+const ADD_TEXT_ANNOTATION: &str = r#"
+        try {
+            var doc = this;
+            var props = {
+                page: args.pageIndex,
+                type: "FreeText",
+                rect: args.rect,
+                contents: args.text,
+                textFont: args.fontName,
+                textSize: args.fontSize
+            };
+            if (args.color !== null) {
+                props.textColor = args.color;
+            }
+            if (args.alignment !== null) {
+                props.alignment = args.alignment;
+            }
+            if (args.rotation !== null) {
+                props.rotate = args.rotation;
+            }
+            var annot = doc.addAnnot(props);
+            return JSON.stringify({"success": annot != null, "page": args.pageIndex + 1, "mode": args.mode});
+        } catch(e) {
+            return JSON.stringify({"success": false, "error": e.toString()});
+        }
+        "#;
 
-                for (var i = 0; i < ranges.length; i++) {{
-                    var range = ranges[i];
-                    var start = range.start;
-                    var end = range.end;
-                    if (start < 0) {{
-                        start = 0;
-                    }}
-                    if (end < 0) {{
-                        end = doc.numPages - 1;
-                    }}
-                    if (end < start) {{
-                        end = start;
-                    }}
+/// Written into the page content as an opaque, fixed-position watermark so it
+/// prints and flattens like native text; it can't be edited as an annotation later
+const ADD_TEXT_CONTENT: &str = r#"
+        try {
+            var doc = this;
+            var aligns = [app.constants.align.left, app.constants.align.center, app.constants.align.right];
+            var props = {
+                cText: args.text,
+                cFont: args.fontName,
+                nFontSize: args.fontSize,
+                nStart: args.pageIndex,
+                nEnd: args.pageIndex,
+                nTextAlign: aligns[args.alignment || 0],
+                nHorizAlign: app.constants.align.left,
+                nVertAlign: app.constants.align.bottom,
+                nHorizValue: args.x,
+                nVertValue: args.y,
+                bOnTop: true,
+                bOnScreen: true,
+                bOnPrint: true,
+                bFixedPrint: true,
+                nOpacity: 1.0
+            };
+            if (args.color !== null) {
+                props.aColor = args.color;
+            }
+            if (args.rotation !== null) {
+                props.nRotation = args.rotation;
+            }
+            doc.addWatermarkFromText(props);
+            return JSON.stringify({"success": true, "page": args.pageIndex + 1, "mode": args.mode});
+        } catch(e) {
+            return JSON.stringify({"success": false, "error": e.toString()});
+        }
+        "#;
 
-                    var fileName = namePattern.replace("{{n}}", (i + 1).toString());
-                    var outputPath = outputDir + "/" + fileName;
-                    doc.extractPages({{
-                        nStart: start,
-                        nEnd: end,
-                        cPath: outputPath
-                    }});
-                    outputs.push(outputPath);
-                }}
+fn add_text_script(options: &Value) -> Result<JsScript> {
+    let page = options.get("page").and_then(|v| v.as_i64()).unwrap_or(1);
+    let page_index = normalize_page_index(page);
+    let text = options.get("text").and_then(|v| v.as_str()).unwrap_or("");
+    let x = options.get("x").and_then(|v| v.as_f64()).unwrap_or(72.0);
+    let y = options.get("y").and_then(|v| v.as_f64()).unwrap_or(720.0);
+    let font_size = options
+        .get("fontSize")
+        .and_then(|v| v.as_f64())
+        .unwrap_or(12.0);
+    let font_name = options
+        .get("fontName")
+        .and_then(|v| v.as_str())
+        .unwrap_or("Helvetica");
+    let mode = options
+        .get("mode")
+        .and_then(|v| v.as_str())
+        .unwrap_or("annotation");
 
-                return JSON.stringify({{
-                    "success": true,
-                    "outputDir": outputDir,
-                    "splitCount": ranges.length,
-                    "outputs": outputs
-                }});
-            }} catch(e) {{
-                return JSON.stringify({{"success": false, "error": e.toString()}});
-            }}
-        }})()
-        "#,
-        ranges_json,
-        escape_js_path(output_dir),
-        escape_js_string(name_pattern)
-    );
+    let body = match mode.to_lowercase().as_str() {
+        "annotation" => ADD_TEXT_ANNOTATION,
+        "content" => ADD_TEXT_CONTENT,
+        _ => {
+            return Err(anyhow::anyhow!(
+                "Invalid mode: {}. Must be annotation or content",
+                mode
+            ))
+        }
+    };
 
-    execute_js_and_parse(&js, || {
-        json!({
-            "status": "ok",
-            "outputDir": output_dir,
-            "splitCount": page_ranges.len()
+    let color = options.get("color").map(acrobat_color).transpose()?;
+    let alignment = options
+        .get("textAlign")
+        .and_then(|v| v.as_str())
+        .map(|align| match align.to_lowercase().as_str() {
+            "left" => Ok(0),
+            "center" => Ok(1),
+            "right" => Ok(2),
+            _ => Err(anyhow::anyhow!(
+                "Invalid textAlign: {}. Must be left, center, or right",
+                align
+            )),
         })
+        .transpose()?;
+    let rotation = options
+        .get("rotation")
+        .and_then(|v| v.as_i64())
+        .map(Rotation::try_from)
+        .transpose()?
+        .map(|rotation| rotation.to_degrees());
+
+    Ok(JsScript::new(body)
+        .bind("mode", mode.to_lowercase())
+        .bind("color", color)
+        .bind("alignment", alignment)
+        .bind("rotation", rotation)
+        .bind("pageIndex", page_index)
+        .bind("x", x)
+        .bind("y", y)
+        .bind("rect", json!([x, y, x + 200.0, y + font_size * 1.5]))
+        .bind("text", text)
+        .bind("fontName", font_name)
+        .bind("fontSize", font_size))
+}
+
+fn extract_text(options: &Value) -> Result<Value> {
+    let ranges = parse_page_range(options)?;
+    let output = match options
+        .get("output")
+        .and_then(|v| v.as_str())
+        .unwrap_or("plain")
+        .to_lowercase()
+        .as_str()
+    {
+        "plain" => "plain",
+        "perpage" => "perPage",
+        "withcoords" => "withCoords",
+        other => {
+            return Err(anyhow::anyhow!(
+                "Invalid output: {}. Must be plain, perPage, or withCoords",
+                other
+            ))
+        }
+    };
+
+    let js = extract_text_script(ranges, output).render();
+
+    execute_js_and_parse(&js, || json!({"status": "ok", "text": ""}))
+}
+
+/// `plain` returns one string; `perPage` and `withCoords` return `pages`
+const EXTRACT_TEXT: &str = r#"
+        try {
+            var doc = this;
+            var ranges = args.ranges;
+            var text = "";
+            var pages = [];
+            for (var r = 0; r < ranges.length; r++) {
+                var start = ranges[r].start;
+                var end = ranges[r].end;
+                if (start < 0) {
+                    start = 0;
+                }
+                if (end < 0) {
+                    end = doc.numPages - 1;
+                }
+                if (end < start) {
+                    end = start;
+                }
+                for (var i = start; i <= end && i < doc.numPages; i++) {
+                    var words = [];
+                    var coords = [];
+                    for (var j = 0; j < doc.getPageNumWords(i); j++) {
+                        var word = doc.getPageNthWord(i, j);
+                        words.push(word);
+                        text += word + " ";
+                        if (args.output === "withCoords") {
+                            coords.push({"word": word, "quads": doc.getPageNthWordQuads(i, j)});
+                        }
+                    }
+                    text += "\n";
+
+                    var page = {"page": i + 1, "text": words.join(" ")};
+                    if (args.output === "withCoords") {
+                        page.words = coords;
+                    }
+                    pages.push(page);
+                }
+            }
+            if (args.output === "plain") {
+                return JSON.stringify({"success": true, "text": text});
+            }
+            return JSON.stringify({"success": true, "output": args.output, "pages": pages});
+        } catch(e) {
+            return JSON.stringify({"success": false, "error": e.toString()});
+        }
+        "#;
+
+fn extract_text_script(ranges: Vec<Value>, output: &str) -> JsScript {
+    JsScript::new(EXTRACT_TEXT)
+        .bind("ranges", ranges)
+        .bind("output", output)
+}
+
+/// Default cap on `searchText` matches
+const DEFAULT_SEARCH_RESULTS: u64 = 100;
+
+fn search_text(options: &Value) -> Result<Value> {
+    let query = options
+        .get("query")
+        .and_then(|v| v.as_str())
+        .filter(|q| !q.trim().is_empty())
+        .ok_or_else(|| anyhow::anyhow!("query required"))?;
+    let case_sensitive = options
+        .get("caseSensitive")
+        .and_then(|v| v.as_bool())
+        .unwrap_or(false);
+    let max_results = options
+        .get("maxResults")
+        .and_then(|v| v.as_u64())
+        .unwrap_or(DEFAULT_SEARCH_RESULTS);
+    if max_results == 0 {
+        return Err(anyhow::anyhow!("maxResults must be at least 1"));
+    }
+
+    let js = search_text_script(query, case_sensitive, max_results).render();
+
+    execute_js_and_parse(&js, || json!({"status": "ok", "matches": [], "count": 0}))
+}
+
+/// Word-by-word search; multi-word queries match runs of consecutive words
+const SEARCH_TEXT: &str = r#"
+        try {
+            var doc = this;
+            var normalize = function(s) {
+                return args.caseSensitive ? s : s.toLowerCase();
+            };
+            var query = normalize(args.query.replace(/^\s+|\s+$/g, "").replace(/\s+/g, " "));
+            var span = query.split(" ").length;
+            var matches = [];
+            var truncated = false;
+
+            for (var p = 0; p < doc.numPages && !truncated; p++) {
+                var count = doc.getPageNumWords(p);
+                for (var w = 0; w + span <= count; w++) {
+                    var words = [];
+                    for (var k = 0; k < span; k++) {
+                        words.push(doc.getPageNthWord(p, w + k));
+                    }
+                    if (normalize(words.join(" ")).indexOf(query) === -1) {
+                        continue;
+                    }
+                    if (matches.length >= args.maxResults) {
+                        truncated = true;
+                        break;
+                    }
+
+                    var match = {"page": p + 1, "pageIndex": p, "wordIndex": w, "text": words.join(" ")};
+                    try {
+                        var quads = [];
+                        for (var k = 0; k < span; k++) {
+                            quads = quads.concat(doc.getPageNthWordQuads(p, w + k));
+                        }
+                        var xs = [], ys = [];
+                        for (var q = 0; q < quads.length; q++) {
+                            for (var c = 0; c < 8; c += 2) {
+                                xs.push(quads[q][c]);
+                                ys.push(quads[q][c + 1]);
+                            }
+                        }
+                        if (xs.length) {
+                            match.quads = quads;
+                            match.rect = [Math.min.apply(null, xs), Math.min.apply(null, ys),
+                                          Math.max.apply(null, xs), Math.max.apply(null, ys)];
+                        }
+                    } catch (qe) {
+                        // Quads are unavailable for some scanned/tagged pages
+                    }
+                    matches.push(match);
+                    w += span - 1;
+                }
+            }
+
+            return JSON.stringify({
+                "success": true,
+                "query": args.query,
+                "matches": matches,
+                "count": matches.length,
+                "truncated": truncated
+            });
+        } catch(e) {
+            return JSON.stringify({"success": false, "error": e.toString()});
+        }
+        "#;
+
+fn search_text_script(query: &str, case_sensitive: bool, max_results: u64) -> JsScript {
+    JsScript::new(SEARCH_TEXT)
+        .bind("query", query)
+        .bind("caseSensitive", case_sensitive)
+        .bind("maxResults", max_results)
+}
+
+const DEFAULT_STATISTICS_PAGES: u64 = 500;
+
+fn get_statistics(options: &Value) -> Result<Value> {
+    let ranges = parse_page_range(options)?;
+    let max_pages = options
+        .get("maxPages")
+        .and_then(|v| v.as_u64())
+        .unwrap_or(DEFAULT_STATISTICS_PAGES);
+    if max_pages == 0 {
+        return Err(anyhow::anyhow!("maxPages must be at least 1"));
+    }
+
+    let js = get_statistics_script(ranges, max_pages).render();
+
+    execute_js_and_parse(&js, || {
+        json!({"status": "ok", "numPages": 0, "pagesCounted": 0, "wordCount": 0, "characterCount": 0, "truncated": false})
     })
 }
 
+/// Word and character totals over the selected pages, stopping after
+/// `maxPages` pages; characters are those of the words, without separators
+const GET_STATISTICS: &str = r#"
+        try {
+            var doc = this;
+            var ranges = args.ranges;
+            var counted = 0;
+            var words = 0;
+            var chars = 0;
+            var truncated = false;
+            for (var r = 0; r < ranges.length && !truncated; r++) {
+                var start = Math.max(ranges[r].start, 0);
+                var end = ranges[r].end < 0 ? doc.numPages - 1 : Math.min(ranges[r].end, doc.numPages - 1);
+                for (var i = start; i <= end; i++) {
+                    if (counted >= args.maxPages) {
+                        truncated = true;
+                        break;
+                    }
+                    var n = doc.getPageNumWords(i);
+                    words += n;
+                    for (var j = 0; j < n; j++) {
+                        chars += doc.getPageNthWord(i, j).length;
+                    }
+                    counted++;
+                }
+            }
+            return JSON.stringify({
+                "success": true,
+                "numPages": doc.numPages,
+                "pagesCounted": counted,
+                "wordCount": words,
+                "characterCount": chars,
+                "truncated": truncated
+            });
+        } catch(e) {
+            return JSON.stringify({"success": false, "error": e.toString()});
+        }
+        "#;
+
+fn get_statistics_script(ranges: Vec<Value>, max_pages: u64) -> JsScript {
+    JsScript::new(GET_STATISTICS)
+        .bind("ranges", ranges)
+        .bind("maxPages", max_pages)
+}
+
 // ============================================================================
-// Page Operations
+// Annotation Operations
 // ============================================================================
 
-fn get_page_count(_options: &Value) -> Result<Value> {
-    let js = r#"
-        (function() {
-            try {
-                var doc = this;
-                return JSON.stringify({"success": true, "pageCount": doc.numPages});
-            } catch(e) {
-                return JSON.stringify({"success": false, "error": e.toString()});
+fn highlight_text(options: &Value) -> Result<Value> {
+    let js = highlight_text_script(options)?;
+    let page_index = js.arg("pageIndex").and_then(|v| v.as_i64()).unwrap_or(0);
+    let rect = number_array(js.arg("rect").unwrap_or(&Value::Null), 4, "rect")?;
+    let [left, bottom, right, top] =
+        fit_rect_to_page([rect[0], rect[1], rect[2], rect[3]], page_index, options)?;
+    // Pull the quads in with the rect so the highlight itself stays on the page
+    let quads: Vec<Vec<f64>> =
+        serde_json::from_value(js.arg("quads").cloned().unwrap_or_default())?;
+    let quads: Vec<Vec<f64>> = quads
+        .into_iter()
+        .map(|quad| {
+            quad.chunks(2)
+                .flat_map(|point| [point[0].clamp(left, right), point[1].clamp(bottom, top)])
+                .collect()
+        })
+        .collect();
+    let js = js
+        .bind("rect", json!([left, bottom, right, top]))
+        .bind("quads", json!(quads));
+    let page = page_index + 1;
+
+    execute_js_and_parse(&js.render(), || json!({"status": "ok", "page": page, "type": "Highlight"}))
+}
+
+/// Highlight over `quads`, defaulting to Acrobat's yellow
+const HIGHLIGHT_TEXT: &str = r#"
+        try {
+            var doc = this;
+            var props = {
+                page: args.pageIndex,
+                type: "Highlight",
+                quads: args.quads,
+                rect: args.rect,
+                strokeColor: args.color !== null ? args.color : color.yellow
+            };
+            if (args.contents !== null) {
+                props.contents = args.contents;
             }
-        })()
-    "#;
+            if (args.author !== null) {
+                props.author = args.author;
+            }
+            var annot = doc.addAnnot(props);
+            return JSON.stringify({"success": annot != null, "page": args.pageIndex + 1, "type": "Highlight"});
+        } catch(e) {
+            return JSON.stringify({"success": false, "error": e.toString()});
+        }
+        "#;
 
-    execute_js_and_parse(js, || json!({"status": "ok", "pageCount": 1}))
+/// `quads` as returned by `searchText`, or a single quad covering `rect`
+fn highlight_text_script(options: &Value) -> Result<JsScript> {
+    let page = options.get("page").and_then(|v| v.as_i64()).unwrap_or(1);
+    let quads: Vec<Vec<f64>> = match (options.get("quads"), options.get("rect")) {
+        (Some(quads), _) if !quads.is_null() => quads
+            .as_array()
+            .filter(|quads| !quads.is_empty())
+            .ok_or_else(|| anyhow::anyhow!("quads must be a non-empty array"))?
+            .iter()
+            .map(|quad| number_array(quad, 8, "quad"))
+            .collect::<Result<_>>()?,
+        (_, Some(rect)) if !rect.is_null() => {
+            let r = number_array(rect, 4, "rect")?;
+            let (left, right) = (r[0].min(r[2]), r[0].max(r[2]));
+            let (bottom, top) = (r[1].min(r[3]), r[1].max(r[3]));
+            vec![vec![left, top, right, top, left, bottom, right, bottom]]
+        }
+        _ => return Err(anyhow::anyhow!("quads or rect required")),
+    };
+
+    // Bounding rect of all quads, as [left, bottom, right, top]
+    let xs = quads.iter().flat_map(|q| q.iter().step_by(2).copied());
+    let ys = quads.iter().flat_map(|q| q.iter().skip(1).step_by(2).copied());
+    let (left, right) = xs.fold((f64::MAX, f64::MIN), |(lo, hi), x| (lo.min(x), hi.max(x)));
+    let (bottom, top) = ys.fold((f64::MAX, f64::MIN), |(lo, hi), y| (lo.min(y), hi.max(y)));
+
+    let color = options.get("color").map(acrobat_color).transpose()?;
+
+    Ok(JsScript::new(HIGHLIGHT_TEXT)
+        .bind("pageIndex", normalize_page_index(page))
+        .bind("quads", json!(quads))
+        .bind("rect", json!([left, bottom, right, top]))
+        .bind("color", color)
+        .bind("contents", options.get("contents").and_then(|v| v.as_str()))
+        .bind("author", options.get("author").and_then(|v| v.as_str())))
 }
 
-fn delete_pages(options: &Value) -> Result<Value> {
-    let pages_value = options
-        .get("pages")
-        .or_else(|| options.get("pageNumbers"))
-        .ok_or_else(|| anyhow::anyhow!("pages array required"))?;
-    let page_nums = normalize_page_numbers(pages_value)?;
+fn add_comment(options: &Value) -> Result<Value> {
+    let js = add_comment_script(options)?;
+    let page_index = js.arg("pageIndex").and_then(|v| v.as_i64()).unwrap_or(0);
+    let point = number_array(js.arg("point").unwrap_or(&Value::Null), 2, "point")?;
+    let [x, y, _, _] = fit_rect_to_page(
+        [point[0], point[1], point[0], point[1]],
+        page_index,
+        options,
+    )?;
+    let js = js.bind("point", json!([x, y]));
+    let page = page_index + 1;
 
-    if page_nums.is_empty() {
-        return Err(anyhow::anyhow!("At least one page number required"));
-    }
+    execute_js_and_parse(&js.render(), || json!({"status": "ok", "page": page, "type": "Text"}))
+}
 
-    let pages_json = serde_json::to_string(&page_nums)?;
+/// Sticky note: a `Text` annotation whose icon sits at `point` and whose
+/// `contents` show in a pop-up
+const ADD_COMMENT: &str = r#"
+        try {
+            var doc = this;
+            var props = {
+                page: args.pageIndex,
+                type: "Text",
+                point: args.point,
+                contents: args.contents
+            };
+            if (args.color !== null) {
+                props.strokeColor = args.color;
+            }
+            if (args.author !== null) {
+                props.author = args.author;
+            }
+            var annot = doc.addAnnot(props);
+            return JSON.stringify({"success": annot != null, "page": args.pageIndex + 1, "type": "Text"});
+        } catch(e) {
+            return JSON.stringify({"success": false, "error": e.toString()});
+        }
+        "#;
+
+fn add_comment_script(options: &Value) -> Result<JsScript> {
+    let contents = options
+        .get("contents")
+        .and_then(|v| v.as_str())
+        .filter(|c| !c.trim().is_empty())
+        .ok_or_else(|| anyhow::anyhow!("contents required"))?;
+    let page = options.get("page").and_then(|v| v.as_i64()).unwrap_or(1);
+    let x = options.get("x").and_then(|v| v.as_f64()).unwrap_or(72.0);
+    let y = options.get("y").and_then(|v| v.as_f64()).unwrap_or(720.0);
+    let color = options.get("color").map(acrobat_color).transpose()?;
+
+    Ok(JsScript::new(ADD_COMMENT)
+        .bind("pageIndex", normalize_page_index(page))
+        .bind("point", json!([x, y]))
+        .bind("contents", contents)
+        .bind("color", color)
+        .bind("author", options.get("author").and_then(|v| v.as_str())))
+}
+
+// ============================================================================
+// Export Operations
+// ============================================================================
+
+fn export_as(options: &Value) -> Result<Value> {
+    let file_path = options
+        .get("filePath")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| anyhow::anyhow!("filePath required"))?;
+    let format = options
+        .get("format")
+        .and_then(|v| v.as_str())
+        .unwrap_or("PDF");
+
+    let conversion_id = match format.to_uppercase().as_str() {
+        "DOCX" | "DOC" => "com.adobe.acrobat.docx",
+        "XLSX" | "XLS" => "com.adobe.acrobat.xlsx",
+        "PPTX" | "PPT" => "com.adobe.acrobat.pptx",
+        "RTF" => "com.adobe.acrobat.rtf",
+        "TXT" => "com.adobe.acrobat.txt",
+        "PNG" | "JPEG" | "JPG" | "TIFF" => "com.adobe.acrobat.image",
+        _ => "com.adobe.acrobat.pdf",
+    };
 
     let js = format!(
         r#"
         (function() {{
             try {{
                 var doc = this;
-                var pages = {};
-                // Sort in reverse order to maintain indices
-                pages.sort(function(a, b) {{ return b - a; }});
-
-                var deleted = 0;
-                for (var i = 0; i < pages.length; i++) {{
-                    if (pages[i] < doc.numPages) {{
-                        doc.deletePages(pages[i]);
-                        deleted++;
-                    }}
-                }}
-
-                return JSON.stringify({{"success": true, "deletedCount": deleted}});
+                doc.saveAs("{}", "{}");
+                return JSON.stringify({{"success": true, "filePath": "{}", "format": "{}"}});
             }} catch(e) {{
                 return JSON.stringify({{"success": false, "error": e.toString()}});
             }}
         }})()
         "#,
-        pages_json
+        escape_js_path(file_path),
+        conversion_id,
+        escape_js_path(file_path),
+        format
     );
 
     execute_js_and_parse(&js, || {
         json!({
             "status": "ok",
-            "deletedCount": page_nums.len()
+            "filePath": file_path,
+            "format": format
         })
     })
 }
 
-fn rotate_pages(options: &Value) -> Result<Value> {
-    let pages_value = options
-        .get("pages")
-        .or_else(|| options.get("pageNumbers"))
-        .ok_or_else(|| anyhow::anyhow!("pages array required"))?;
-    let angle = options.get("angle").and_then(|v| v.as_i64()).unwrap_or(90);
-
-    // Validate angle
-    if ![0, 90, 180, 270].contains(&angle) {
-        return Err(anyhow::anyhow!(
-            "Invalid angle: {}. Must be 0, 90, 180, or 270",
-            angle
-        ));
-    }
+fn linearize_document(options: &Value) -> Result<Value> {
+    let output_path = options
+        .get("outputPath")
+        .and_then(|v| v.as_str())
+        .filter(|p| !p.trim().is_empty())
+        .ok_or_else(|| anyhow::anyhow!("outputPath required"))?;
 
-    let page_nums = normalize_page_numbers(pages_value)?;
-    let pages_json = serde_json::to_string(&page_nums)?;
+    let js = linearize_document_script(output_path).render();
 
-    let js = format!(
+    execute_js_and_parse(&js, || {
+        json!({"status": "ok", "outputPath": escape_js_path(output_path), "linearized": true})
+    })
+}
+
+/// Full `saveAs` to a new file
+///
+/// Acrobat's JavaScript API has no linearize flag: a full save is written for
+/// Fast Web View whenever the "Save As optimizes for Fast Web View" document
+/// preference is on (the default), so an incremental `save()` is never used here.
+fn linearize_document_script(output_path: &str) -> JsScript {
+    JsScript::new(
         r#"
-        (function() {{
-            try {{
-                var doc = this;
-                var pages = {};
-                var angle = {};
-                var rotated = 0;
+        try {
+            var doc = this;
+            doc.saveAs({"cPath": args.outputPath, "bPromptToOverwrite": false});
+            return JSON.stringify({"success": true, "outputPath": args.outputPath, "linearized": true});
+        } catch(e) {
+            return JSON.stringify({"success": false, "error": e.toString()});
+        }
+        "#,
+    )
+    .bind("outputPath", escape_js_path(output_path))
+}
 
-                for (var i = 0; i < pages.length; i++) {{
-                    if (pages[i] < doc.numPages) {{
-                        doc.setPageRotations(pages[i], pages[i], angle);
-                        rotated++;
-                    }}
-                }}
+const PDFA_VERSIONS: &[&str] = &["1a", "1b", "2a", "2b", "2u", "3a", "3b", "3u"];
 
-                return JSON.stringify({{"success": true, "rotatedCount": rotated, "angle": angle}});
-            }} catch(e) {{
-                return JSON.stringify({{"success": false, "error": e.toString()}});
-            }}
-        }})()
-        "#,
-        pages_json, angle
-    );
+/// `version` option as a PDF/A conformance level such as `1b`, defaulting to `1b`
+fn pdfa_version(options: &Value) -> Result<String> {
+    let version = options
+        .get("version")
+        .and_then(|v| v.as_str())
+        .unwrap_or("1b")
+        .to_lowercase();
+    let version = version.trim_start_matches("pdf/a-").to_string();
 
-    execute_js_and_parse(&js, || {
-        json!({
-            "status": "ok",
-            "rotatedCount": page_nums.len(),
-            "angle": angle
-        })
-    })
+    if PDFA_VERSIONS.contains(&version.as_str()) {
+        Ok(version)
+    } else {
+        Err(anyhow::anyhow!(
+            "Unsupported PDF/A version: {} (expected one of {})",
+            version,
+            PDFA_VERSIONS.join(", ")
+        ))
+    }
 }
 
-fn insert_pages(options: &Value) -> Result<Value> {
-    let source_path = options
-        .get("sourcePath")
+/// Preflight profile name, e.g. `Convert to PDF/A-1b` or `Verify compliance with PDF/A-2b`
+fn pdfa_profile_name(prefix: &str, version: &str) -> String {
+    format!("{} PDF/A-{}", prefix, version)
+}
+
+fn convert_to_pdfa(options: &Value) -> Result<Value> {
+    let file_path = options
+        .get("filePath")
         .and_then(|v| v.as_str())
-        .ok_or_else(|| anyhow::anyhow!("sourcePath required"))?;
-    let after_page = options
-        .get("afterPage")
-        .and_then(|v| v.as_i64())
-        .unwrap_or(-1);
+        .ok_or_else(|| anyhow::anyhow!("filePath required"))?;
+    let version = pdfa_version(options)?;
 
-    let js = format!(
-        r#"
-        (function() {{
-            try {{
-                var doc = this;
-                var insertAt = {};
-                if (insertAt < 0) {{
-                    insertAt = doc.numPages - 1;
-                }}
+    let js = convert_to_pdfa_script(file_path, &version).render();
 
-                doc.insertPages({{
-                    nPage: insertAt,
-                    cPath: "{}"
-                }});
+    execute_js_and_parse(&js, || {
+        json!({"status": "ok", "filePath": escape_js_path(file_path), "version": version})
+    })
+}
 
-                return JSON.stringify({{
-                    "success": true,
-                    "sourcePath": "{}",
-                    "insertedAt": insertAt
-                }});
-            }} catch(e) {{
-                return JSON.stringify({{"success": false, "error": e.toString()}});
-            }}
-        }})()
+/// Run the preflight conversion fixup for `version`, then save through the PDF/A conversion id
+fn convert_to_pdfa_script(file_path: &str, version: &str) -> JsScript {
+    JsScript::new(
+        r#"
+        try {
+            var doc = this;
+            var profile = Preflight.getProfileByName(args.profile);
+            if (!profile) {
+                return JSON.stringify({"success": false, "error": "Preflight profile not found: " + args.profile});
+            }
+            var result = doc.preflight(profile);
+            if (result.numErrors > 0) {
+                return JSON.stringify({
+                    "success": false,
+                    "error": "PDF/A-" + args.version + " conversion left " + result.numErrors + " error(s)"
+                });
+            }
+            doc.saveAs(args.filePath, args.conversionId);
+            return JSON.stringify({
+                "success": true,
+                "filePath": args.filePath,
+                "version": args.version,
+                "warnings": result.numWarnings
+            });
+        } catch(e) {
+            return JSON.stringify({"success": false, "error": e.toString()});
+        }
         "#,
-        after_page,
-        escape_js_path(source_path),
-        escape_js_path(source_path)
-    );
+    )
+    .bind("filePath", escape_js_path(file_path))
+    .bind("version", version)
+    .bind("profile", pdfa_profile_name("Convert to", version))
+    .bind("conversionId", PDFA_CONVERSION_ID)
+}
+
+fn check_compliance(options: &Value) -> Result<Value> {
+    let version = pdfa_version(options)?;
+
+    let js = check_compliance_script(&version).render();
 
     execute_js_and_parse(&js, || {
-        json!({
-            "status": "ok",
-            "sourcePath": source_path
-        })
+        json!({"status": "ok", "version": version, "compliant": true, "errors": 0, "warnings": 0})
     })
 }
 
+/// Verify the document against the preflight compliance profile for `version`
+fn check_compliance_script(version: &str) -> JsScript {
+    JsScript::new(
+        r#"
+        try {
+            var doc = this;
+            var profile = Preflight.getProfileByName(args.profile);
+            if (!profile) {
+                return JSON.stringify({"success": false, "error": "Preflight profile not found: " + args.profile});
+            }
+            var result = doc.preflight(profile);
+            return JSON.stringify({
+                "success": true,
+                "version": args.version,
+                "profile": args.profile,
+                "compliant": result.numErrors === 0,
+                "errors": result.numErrors,
+                "warnings": result.numWarnings
+            });
+        } catch(e) {
+            return JSON.stringify({"success": false, "error": e.toString()});
+        }
+        "#,
+    )
+    .bind("version", version)
+    .bind("profile", pdfa_profile_name("Verify compliance with", version))
+}
+
 // ============================================================================
-// Bookmark & Metadata Operations
+// Form Data Operations
 // ============================================================================
 
-fn add_bookmark(options: &Value) -> Result<Value> {
-    let title = options
-        .get("title")
-        .and_then(|v| v.as_str())
-        .ok_or_else(|| anyhow::anyhow!("title required"))?;
-    let page = options
-        .get("page")
-        .and_then(|v| v.as_i64())
-        .ok_or_else(|| anyhow::anyhow!("page required"))?;
-    let parent = options.get("parent").and_then(|v| v.as_str()).unwrap_or("");
-    let page_index = normalize_page_index(page);
-
-    let js = format!(
-        r#"
-        (function() {{
-            try {{
-                var doc = this;
-                var root = doc.bookmarkRoot;
-                var title = "{}";
-                var parentName = "{}";
-                var pageIndex = {};
-
-                function findBookmark(node, name) {{
-                    if (!node) return null;
-                    if (node.name === name) return node;
-                    if (node.children && node.children.length) {{
-                        for (var i = 0; i < node.children.length; i++) {{
-                            var found = findBookmark(node.children[i], name);
-                            if (found) return found;
-                        }}
-                    }}
-                    return null;
-                }}
+/// `format` option, or the file extension when it is absent
+fn form_data_format(options: &Value, file_path: &str) -> Result<&'static str> {
+    let format = match options.get("format").and_then(|v| v.as_str()) {
+        Some(format) => format.to_string(),
+        None => std::path::Path::new(file_path)
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .unwrap_or("fdf")
+            .to_string(),
+    };
 
-                var parentNode = root;
-                if (parentName) {{
-                    var match = findBookmark(root, parentName);
-                    if (match) {{
-                        parentNode = match;
-                    }}
-                }}
+    match format.to_uppercase().as_str() {
+        "FDF" => Ok("FDF"),
+        "XFDF" => Ok("XFDF"),
+        other => Err(anyhow::anyhow!(
+            "Unsupported form data format: {} (expected FDF or XFDF)",
+            other
+        )),
+    }
+}
 
-                var action = "this.pageNum=" + pageIndex;
-                var bookmark = parentNode.createChild(title, action);
+fn export_form_data(options: &Value) -> Result<Value> {
+    let file_path = options
+        .get("filePath")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| anyhow::anyhow!("filePath required"))?;
+    let format = form_data_format(options, file_path)?;
 
-                return JSON.stringify({{
-                    "success": bookmark != null,
-                    "title": title,
-                    "page": pageIndex + 1,
-                    "parent": parentName
-                }});
-            }} catch(e) {{
-                return JSON.stringify({{"success": false, "error": e.toString()}});
-            }}
-        }})()
-        "#,
-        escape_js_string(title),
-        escape_js_string(parent),
-        page_index
-    );
+    let js = export_form_data_script(file_path, format).render();
 
     execute_js_and_parse(&js, || {
-        json!({
-            "status": "ok",
-            "title": title,
-            "page": page_index + 1
-        })
+        json!({"status": "ok", "filePath": escape_js_path(file_path), "format": format})
     })
 }
 
-fn set_metadata(options: &Value) -> Result<Value> {
-    let title = options.get("title").and_then(|v| v.as_str());
-    let author = options.get("author").and_then(|v| v.as_str());
-    let subject = options.get("subject").and_then(|v| v.as_str());
-    let keywords = options.get("keywords").and_then(|v| v.as_str());
+/// Write every field to an FDF or XFDF file
+fn export_form_data_script(file_path: &str, format: &str) -> JsScript {
+    JsScript::new(
+        r#"
+        try {
+            var doc = this;
+            var params = {"cPath": args.filePath, "bAllFields": true};
+            if (args.format === "XFDF") {
+                doc.exportAsXFDF(params);
+            } else {
+                doc.exportAsFDF(params);
+            }
+            return JSON.stringify({
+                "success": true,
+                "filePath": args.filePath,
+                "format": args.format,
+                "fieldCount": doc.numFields
+            });
+        } catch(e) {
+            return JSON.stringify({"success": false, "error": e.toString()});
+        }
+        "#,
+    )
+    .bind("filePath", escape_js_path(file_path))
+    .bind("format", format)
+}
 
-    let mut assignments = String::new();
-    if let Some(value) = title {
-        assignments.push_str(&format!(
-            "doc.info.Title = \"{}\";\n",
-            escape_js_string(value)
-        ));
-    }
-    if let Some(value) = author {
-        assignments.push_str(&format!(
-            "doc.info.Author = \"{}\";\n",
-            escape_js_string(value)
-        ));
-    }
-    if let Some(value) = subject {
-        assignments.push_str(&format!(
-            "doc.info.Subject = \"{}\";\n",
-            escape_js_string(value)
-        ));
-    }
-    if let Some(value) = keywords {
-        assignments.push_str(&format!(
-            "doc.info.Keywords = \"{}\";\n",
-            escape_js_string(value)
-        ));
-    }
-    if assignments.is_empty() {
-        assignments.push_str("// No metadata changes provided\n");
-    }
+fn import_form_data(options: &Value) -> Result<Value> {
+    let file_path = options
+        .get("filePath")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| anyhow::anyhow!("filePath required"))?;
+    let format = form_data_format(options, file_path)?;
 
-    let js = format!(
+    let js = import_form_data_script(file_path, format).render();
+
+    execute_js_and_parse(&js, || json!({"status": "ok", "fieldsImported": 0}))
+}
+
+/// Import an FDF or XFDF file, counting the fields whose value it changed
+fn import_form_data_script(file_path: &str, format: &str) -> JsScript {
+    JsScript::new(
         r#"
-        (function() {{
-            try {{
-                var doc = this;
-                {}
-                return JSON.stringify({{"success": true}});
-            }} catch(e) {{
-                return JSON.stringify({{"success": false, "error": e.toString()}});
-            }}
-        }})()
-        "#,
-        assignments
-    );
+        try {
+            var doc = this;
+            var snapshot = function() {
+                var values = {};
+                for (var i = 0; i < doc.numFields; i++) {
+                    var name = doc.getNthFieldName(i);
+                    var field = doc.getField(name);
+                    values[name] = field ? String(field.value) : null;
+                }
+                return values;
+            };
 
-    execute_js_and_parse(&js, || json!({"status": "ok"}))
+            var before = snapshot();
+            if (args.format === "XFDF") {
+                doc.importAnXFDF(args.filePath);
+            } else {
+                doc.importAnFDF(args.filePath);
+            }
+            var after = snapshot();
+
+            var imported = [];
+            for (var name in after) {
+                if (after[name] !== before[name]) {
+                    imported.push(name);
+                }
+            }
+            return JSON.stringify({
+                "success": true,
+                "filePath": args.filePath,
+                "format": args.format,
+                "fieldsImported": imported.length,
+                "fields": imported
+            });
+        } catch(e) {
+            return JSON.stringify({"success": false, "error": e.toString()});
+        }
+        "#,
+    )
+    .bind("filePath", escape_js_path(file_path))
+    .bind("format", format)
 }
 
 // ============================================================================
-// Helper Functions
+// Attachment Operations
 // ============================================================================
 
-fn normalize_page_index(value: i64) -> i64 {
-    if value <= 0 {
-        0
-    } else {
-        value - 1
-    }
-}
+fn attach_file(options: &Value) -> Result<Value> {
+    let source_path = options
+        .get("sourcePath")
+        .and_then(|v| v.as_str())
+        .filter(|p| !p.trim().is_empty())
+        .ok_or_else(|| anyhow::anyhow!("sourcePath required"))?;
+    let name = match options.get("name").and_then(|v| v.as_str()) {
+        Some(name) if !name.trim().is_empty() => name.to_string(),
+        Some(_) => return Err(anyhow::anyhow!("name must not be empty")),
+        None => std::path::Path::new(&escape_js_path(source_path))
+            .file_name()
+            .and_then(|n| n.to_str())
+            .map(str::to_string)
+            .ok_or_else(|| anyhow::anyhow!("Cannot derive attachment name from {}", source_path))?,
+    };
 
-fn normalize_page_numbers(value: &Value) -> Result<Vec<i64>> {
-    let pages = value
-        .as_array()
-        .ok_or_else(|| anyhow::anyhow!("pages array required"))?;
-    let mut page_nums: Vec<i64> = pages.iter().filter_map(|v| v.as_i64()).collect();
+    let js = attach_file_script(source_path, &name).render();
+    execute_js_and_parse(&js, || {
+        json!({"status": "ok", "name": name, "sourcePath": escape_js_path(source_path)})
+    })
+}
 
-    if page_nums.is_empty() {
+/// Embed `sourcePath` as a data object called `name`
+fn attach_file_script(source_path: &str, name: &str) -> JsScript {
+    JsScript::new(
+        r#"
+        try {
+            var doc = this;
+            doc.importDataObject(args.name, args.sourcePath);
+            var obj = doc.getDataObject(args.name);
+            return JSON.stringify({
+                "success": true,
+                "name": args.name,
+                "sourcePath": args.sourcePath,
+                "size": obj ? obj.size : null
+            });
+        } catch(e) {
+            return JSON.stringify({"success": false, "error": e.toString()});
+        }
+        "#,
+    )
+    .bind("sourcePath", escape_js_path(source_path))
+    .bind("name", name)
+}
+
+fn extract_attachments(options: &Value) -> Result<Value> {
+    let output_dir = options
+        .get("outputDir")
+        .and_then(|v| v.as_str())
+        .filter(|p| !p.trim().is_empty())
+        .ok_or_else(|| anyhow::anyhow!("outputDir required"))?;
+
+    let js = extract_attachments_script(output_dir).render();
+    execute_js_and_parse(&js, || {
+        json!({"status": "ok", "outputDir": escape_js_path(output_dir), "attachments": []})
+    })
+}
+
+/// Write every data object into `outputDir`, named after its original file.
+/// `doc.dataObjects` is null rather than empty when there are none.
+fn extract_attachments_script(output_dir: &str) -> JsScript {
+    JsScript::new(
+        r#"
+        try {
+            var doc = this;
+            var objects = doc.dataObjects || [];
+            var dir = args.outputDir.replace(/\/+$/, "");
+            var attachments = [];
+            for (var i = 0; i < objects.length; i++) {
+                var obj = objects[i];
+                var fileName = (obj.path || obj.name).replace(/[\\\/:*?"<>|]/g, "_");
+                var outputPath = dir + "/" + fileName;
+                doc.exportDataObject({cName: obj.name, cDIPath: outputPath, nLaunch: 0});
+                attachments.push({
+                    "name": obj.name,
+                    "path": outputPath,
+                    "size": obj.size
+                });
+            }
+            return JSON.stringify({
+                "success": true,
+                "outputDir": dir,
+                "attachments": attachments
+            });
+        } catch(e) {
+            return JSON.stringify({"success": false, "error": e.toString()});
+        }
+        "#,
+    )
+    .bind("outputDir", escape_js_path(output_dir))
+}
+
+// ============================================================================
+// Multi-document Operations
+// ============================================================================
+
+fn merge_documents(options: &Value) -> Result<Value> {
+    let file_paths = options
+        .get("filePaths")
+        .and_then(|v| v.as_array())
+        .ok_or_else(|| anyhow::anyhow!("filePaths array required"))?;
+    let output_path = options
+        .get("outputPath")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| anyhow::anyhow!("outputPath required"))?;
+    let preserve_bookmarks = options
+        .get("preserveBookmarks")
+        .and_then(|v| v.as_bool())
+        .unwrap_or(false);
+    let titles: Vec<&str> = options
+        .get("titles")
+        .and_then(|v| v.as_array())
+        .map(|arr| arr.iter().filter_map(|v| v.as_str()).collect())
+        .unwrap_or_default();
+
+    let paths: Vec<&str> = file_paths.iter().filter_map(|v| v.as_str()).collect();
+
+    if paths.is_empty() {
+        return Err(anyhow::anyhow!("At least one file path required"));
+    }
+
+    let js = merge_documents_script(&paths, output_path, preserve_bookmarks, &titles).render();
+
+    execute_js_and_parse(&js, || {
+        json!({
+            "status": "ok",
+            "outputPath": output_path,
+            "mergedCount": paths.len(),
+            "bookmarks": []
+        })
+    })
+}
+
+const MERGE_DOCUMENTS: &str = r#"
+        try {
+            var paths = args.paths;
+            var outputPath = args.outputPath;
+            var preserveBookmarks = args.preserveBookmarks;
+            var titles = args.titles;
+            if (paths.length === 0) {
+                return JSON.stringify({"success": false, "error": "No paths provided"});
+            }
+
+            function sectionTitle(index) {
+                if (index < titles.length && titles[index]) {
+                    return titles[index];
+                }
+                return paths[index].split(/[\\\/]/).pop().replace(/\.pdf$/i, "");
+            }
+
+            // Open first document as base
+            var doc = app.openDoc(paths[0]);
+            if (!doc) {
+                return JSON.stringify({"success": false, "error": "Failed to open first document"});
+            }
+
+            // Record where each source starts before inserting the next one
+            var offsets = [0];
+            for (var i = 1; i < paths.length; i++) {
+                offsets.push(doc.numPages);
+                doc.insertPages({
+                    nPage: doc.numPages - 1,
+                    cPath: paths[i]
+                });
+            }
+
+            var bookmarks = [];
+            if (preserveBookmarks) {
+                for (var j = 0; j < paths.length; j++) {
+                    var title = sectionTitle(j);
+                    doc.bookmarkRoot.createChild(title, "this.pageNum=" + offsets[j], j);
+                    bookmarks.push({"title": title, "page": offsets[j] + 1});
+                }
+            }
+
+            doc.saveAs(outputPath);
+            var pageCount = doc.numPages;
+            doc.closeDoc(true);
+
+            return JSON.stringify({
+                "success": true,
+                "outputPath": outputPath,
+                "mergedCount": paths.length,
+                "totalPages": pageCount,
+                "bookmarks": bookmarks
+            });
+        } catch(e) {
+            return JSON.stringify({"success": false, "error": e.toString()});
+        }
+"#;
+
+fn merge_documents_script(
+    paths: &[&str],
+    output_path: &str,
+    preserve_bookmarks: bool,
+    titles: &[&str],
+) -> JsScript {
+    JsScript::new(MERGE_DOCUMENTS)
+        .bind("paths", paths)
+        .bind("outputPath", escape_js_path(output_path))
+        .bind("preserveBookmarks", preserve_bookmarks)
+        .bind("titles", titles)
+}
+
+fn split_document(options: &Value) -> Result<Value> {
+    let page_ranges_value = options
+        .get("pageRanges")
+        .ok_or_else(|| anyhow::anyhow!("pageRanges array required"))?;
+    let page_ranges = normalize_page_ranges(page_ranges_value)?;
+    let output_dir = options
+        .get("outputDir")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| anyhow::anyhow!("outputDir required"))?;
+    let name_pattern = options
+        .get("namePattern")
+        .and_then(|v| v.as_str())
+        .unwrap_or("split_{n}.pdf");
+
+    let ranges_json = serde_json::to_string(&page_ranges)?;
+
+    let js = format!(
+        r#"
+        (function() {{
+            try {{
+                var doc = this;
+                var ranges = {};
+                var outputDir = "{}";
+                var namePattern = "{}";
+                var outputs = [];
+
+                for (var i = 0; i < ranges.length; i++) {{
+                    var range = ranges[i];
+                    var start = range.start;
+                    var end = range.end;
+                    if (start < 0) {{
+                        start = 0;
+                    }}
+                    if (end < 0) {{
+                        end = doc.numPages - 1;
+                    }}
+                    if (end < start) {{
+                        end = start;
+                    }}
+
+                    var fileName = namePattern.replace("{{n}}", (i + 1).toString());
+                    var outputPath = outputDir + "/" + fileName;
+                    doc.extractPages({{
+                        nStart: start,
+                        nEnd: end,
+                        cPath: outputPath
+                    }});
+                    outputs.push(outputPath);
+                }}
+
+                return JSON.stringify({{
+                    "success": true,
+                    "outputDir": outputDir,
+                    "splitCount": ranges.length,
+                    "outputs": outputs
+                }});
+            }} catch(e) {{
+                return JSON.stringify({{"success": false, "error": e.toString()}});
+            }}
+        }})()
+        "#,
+        ranges_json,
+        escape_js_path(output_dir),
+        escape_js_string(name_pattern)
+    );
+
+    execute_js_and_parse(&js, || {
+        json!({
+            "status": "ok",
+            "outputDir": output_dir,
+            "splitCount": page_ranges.len()
+        })
+    })
+}
+
+fn extract_pages(options: &Value) -> Result<Value> {
+    let output_path = options
+        .get("outputPath")
+        .and_then(|v| v.as_str())
+        .filter(|p| !p.trim().is_empty())
+        .ok_or_else(|| anyhow::anyhow!("outputPath required"))?;
+    let spans = parse_page_range(options)?;
+    let [span] = spans.as_slice() else {
+        return Err(anyhow::anyhow!("extractPages needs a single contiguous page range"));
+    };
+
+    let js = extract_pages_script(span.clone(), output_path).render();
+    execute_js_and_parse(&js, || json!({"status": "ok", "outputPath": escape_js_path(output_path)}))
+}
+
+/// Copy one 0-based `{start, end}` span into a new file; the source is left as is
+fn extract_pages_script(span: Value, output_path: &str) -> JsScript {
+    JsScript::new(
+        r#"
+        try {
+            var doc = this;
+            var start = Math.max(args.range.start, 0);
+            var end = args.range.end < 0 ? doc.numPages - 1 : Math.min(args.range.end, doc.numPages - 1);
+            if (start > end) {
+                throw new Error("Page " + (start + 1) + " out of range (document has " + doc.numPages + " pages)");
+            }
+            doc.extractPages({nStart: start, nEnd: end, cPath: args.outputPath});
+            return JSON.stringify({
+                "success": true,
+                "outputPath": args.outputPath,
+                "pageCount": end - start + 1
+            });
+        } catch(e) {
+            return JSON.stringify({"success": false, "error": e.toString()});
+        }
+        "#,
+    )
+    .bind("range", span)
+    .bind("outputPath", escape_js_path(output_path))
+}
+
+fn compare_documents(options: &Value) -> Result<Value> {
+    let path = |key: &str| {
+        options
+            .get(key)
+            .and_then(|v| v.as_str())
+            .filter(|p| !p.trim().is_empty())
+            .ok_or_else(|| anyhow::anyhow!("{} required", key))
+    };
+    let base_path = path("basePath")?;
+    let compare_path = path("comparePath")?;
+    let mode = match options
+        .get("mode")
+        .and_then(|v| v.as_str())
+        .unwrap_or("text")
+    {
+        "text" => "text",
+        "pageCount" => "pageCount",
+        other => {
+            return Err(anyhow::anyhow!(
+                "Unknown mode: {} (expected text or pageCount)",
+                other
+            ))
+        }
+    };
+
+    let js = compare_documents_script(base_path, compare_path, mode).render();
+    execute_js_and_parse(&js, || {
+        json!({
+            "mode": mode,
+            "basePath": escape_js_path(base_path),
+            "comparePath": escape_js_path(compare_path),
+            "basePages": 0,
+            "comparePages": 0,
+            "pagesAdded": [],
+            "pagesRemoved": [],
+            "pages": [],
+            "differingPages": [],
+            "identical": true
+        })
+    })
+}
+
+/// Page-count and per-page text diff of two files. Pages are matched by
+/// position: extra pages in `comparePath` are added, missing ones removed,
+/// and in `text` mode each shared page is flagged `identical` when its
+/// whitespace-normalized words match. Documents that were not already open
+/// are opened hidden and closed again.
+const COMPARE_DOCUMENTS: &str = r#"
+        var opened = [];
+        try {
+            var normalize = function(path) {
+                return path.replace(/\\/g, "/").replace(/^\/?([A-Za-z]):?\//, "$1/").toLowerCase();
+            };
+            var open = function(path) {
+                var docs = app.activeDocs;
+                for (var i = 0; i < docs.length; i++) {
+                    if (normalize(docs[i].path) === normalize(path)) {
+                        return docs[i];
+                    }
+                }
+                var doc = app.openDoc({cPath: path, bHidden: true});
+                if (!doc) {
+                    throw new Error("Cannot open " + path);
+                }
+                opened.push(doc);
+                return doc;
+            };
+            var pageText = function(doc, page) {
+                var words = [];
+                var count = doc.getPageNumWords(page);
+                for (var w = 0; w < count; w++) {
+                    words.push(doc.getPageNthWord(page, w, true));
+                }
+                return words.join(" ").replace(/\s+/g, " ");
+            };
+
+            var base = open(args.basePath);
+            var other = open(args.comparePath);
+            var shared = Math.min(base.numPages, other.numPages);
+
+            var pagesAdded = [];
+            for (var a = shared; a < other.numPages; a++) {
+                pagesAdded.push(a + 1);
+            }
+            var pagesRemoved = [];
+            for (var r = shared; r < base.numPages; r++) {
+                pagesRemoved.push(r + 1);
+            }
+
+            var pages = [];
+            var differingPages = [];
+            if (args.mode === "text") {
+                for (var p = 0; p < shared; p++) {
+                    var identical = pageText(base, p) === pageText(other, p);
+                    pages.push({"page": p + 1, "identical": identical});
+                    if (!identical) {
+                        differingPages.push(p + 1);
+                    }
+                }
+            }
+
+            var report = {
+                "success": true,
+                "mode": args.mode,
+                "basePath": args.basePath,
+                "comparePath": args.comparePath,
+                "basePages": base.numPages,
+                "comparePages": other.numPages,
+                "pagesAdded": pagesAdded,
+                "pagesRemoved": pagesRemoved,
+                "pages": pages,
+                "differingPages": differingPages,
+                "identical": pagesAdded.length === 0 && pagesRemoved.length === 0 && differingPages.length === 0
+            };
+            for (var c = 0; c < opened.length; c++) {
+                opened[c].closeDoc(true);
+            }
+            return JSON.stringify(report);
+        } catch(e) {
+            for (var d = 0; d < opened.length; d++) {
+                try { opened[d].closeDoc(true); } catch(ignored) {}
+            }
+            return JSON.stringify({"success": false, "error": e.toString()});
+        }
+        "#;
+
+fn compare_documents_script(base_path: &str, compare_path: &str, mode: &str) -> JsScript {
+    JsScript::new(COMPARE_DOCUMENTS)
+        .bind("basePath", escape_js_path(base_path))
+        .bind("comparePath", escape_js_path(compare_path))
+        .bind("mode", mode)
+}
+
+fn split_by_bookmarks(options: &Value) -> Result<Value> {
+    let output_dir = options
+        .get("outputDir")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| anyhow::anyhow!("outputDir required"))?;
+    let sanitize = options
+        .get("sanitizeFilenames")
+        .and_then(|v| v.as_bool())
+        .unwrap_or(true);
+
+    let js = split_by_bookmarks_script(output_dir, sanitize).render();
+
+    execute_js_and_parse(&js, || {
+        json!({
+            "status": "ok",
+            "outputDir": output_dir,
+            "files": [],
+            "skipped": []
+        })
+    })
+}
+
+/// Files come back as `[{title, path}]` in page order, so bookmarks sharing
+/// a title each keep their own entry. Bookmarks that do not go to a page
+/// are left out of the split and listed in `skipped`.
+const SPLIT_BY_BOOKMARKS: &str = r#"
+        try {
+            var doc = this;
+            var outputDir = args.outputDir;
+            var sanitize = args.sanitize;
+            var root = doc.bookmarkRoot;
+            var children = (root && root.children) ? root.children : [];
+            if (children.length === 0) {
+                return JSON.stringify({"success": false, "error": "Document has no top-level bookmarks"});
+            }
+
+            // Resolve each bookmark's target page by executing its action from a
+            // known page; one that never moves off it does not navigate
+            var lastPage = doc.numPages - 1;
+            var followFrom = function(bookmark, from) {
+                doc.pageNum = from;
+                bookmark.execute();
+                return doc.pageNum;
+            };
+            var targetPage = function(bookmark) {
+                var landed = followFrom(bookmark, 0);
+                if (landed !== 0 || lastPage === 0) {
+                    return landed;
+                }
+                return followFrom(bookmark, lastPage) === 0 ? 0 : null;
+            };
+
+            var originalPage = doc.pageNum;
+            var sections = [];
+            var skipped = [];
+            for (var i = 0; i < children.length; i++) {
+                var target = null;
+                try {
+                    target = targetPage(children[i]);
+                } catch(e) {}
+                if (target === null) {
+                    skipped.push(children[i].name);
+                } else {
+                    sections.push({"title": children[i].name, "start": target});
+                }
+            }
+            doc.pageNum = originalPage;
+            if (sections.length === 0) {
+                return JSON.stringify({"success": false, "error": "No top-level bookmark goes to a page"});
+            }
+            sections.sort(function(a, b) { return a.start - b.start; });
+
+            var files = [];
+            var used = {};
+            for (var j = 0; j < sections.length; j++) {
+                var start = sections[j].start;
+                var end = (j + 1 < sections.length) ? sections[j + 1].start - 1 : doc.numPages - 1;
+                if (end < start) {
+                    end = start;
+                }
+
+                var baseName = sections[j].title;
+                if (sanitize) {
+                    baseName = baseName.replace(/[\\\/:*?"<>|\x00-\x1f]/g, "_").replace(/^[\s.]+|[\s.]+$/g, "");
+                    if (baseName.length === 0) {
+                        baseName = "section_" + (j + 1);
+                    }
+                }
+                var fileName = baseName;
+                var suffix = 2;
+                while (used[fileName]) {
+                    fileName = baseName + "_" + suffix;
+                    suffix++;
+                }
+                used[fileName] = true;
+
+                var outputPath = outputDir + "/" + fileName + ".pdf";
+                doc.extractPages({
+                    nStart: start,
+                    nEnd: end,
+                    cPath: outputPath
+                });
+                files.push({"title": sections[j].title, "path": outputPath});
+            }
+
+            return JSON.stringify({
+                "success": true,
+                "outputDir": outputDir,
+                "splitCount": sections.length,
+                "files": files,
+                "skipped": skipped
+            });
+        } catch(e) {
+            return JSON.stringify({"success": false, "error": e.toString()});
+        }
+        "#;
+
+fn split_by_bookmarks_script(output_dir: &str, sanitize: bool) -> JsScript {
+    JsScript::new(SPLIT_BY_BOOKMARKS)
+        .bind("outputDir", escape_js_path(output_dir))
+        .bind("sanitize", sanitize)
+}
+
+// ============================================================================
+// Page Operations
+// ============================================================================
+
+fn get_page_count(_options: &Value) -> Result<Value> {
+    let js = r#"
+        (function() {
+            try {
+                var doc = this;
+                return JSON.stringify({"success": true, "pageCount": doc.numPages});
+            } catch(e) {
+                return JSON.stringify({"success": false, "error": e.toString()});
+            }
+        })()
+    "#;
+
+    execute_js_and_parse(js, || json!({"status": "ok", "pageCount": 1}))
+}
+
+fn delete_pages(options: &Value) -> Result<Value> {
+    let pages_value = options
+        .get("pages")
+        .or_else(|| options.get("pageNumbers"))
+        .ok_or_else(|| anyhow::anyhow!("pages array required"))?;
+    let page_nums = normalize_page_numbers(pages_value, page_base_option(options)?)?;
+
+    if page_nums.is_empty() {
         return Err(anyhow::anyhow!("At least one page number required"));
     }
 
-    let treat_as_one_based = !page_nums.contains(&0);
-    if treat_as_one_based {
-        page_nums = page_nums
-            .into_iter()
-            .map(|n| if n <= 0 { 0 } else { n - 1 })
+    let pages_json = serde_json::to_string(&page_nums)?;
+
+    let js = format!(
+        r#"
+        (function() {{
+            try {{
+                var doc = this;
+                var pages = {};
+                // Sort in reverse order to maintain indices
+                pages.sort(function(a, b) {{ return b - a; }});
+
+                var deleted = 0;
+                for (var i = 0; i < pages.length; i++) {{
+                    if (pages[i] < doc.numPages) {{
+                        doc.deletePages(pages[i]);
+                        deleted++;
+                    }}
+                }}
+
+                return JSON.stringify({{"success": true, "deletedCount": deleted}});
+            }} catch(e) {{
+                return JSON.stringify({{"success": false, "error": e.toString()}});
+            }}
+        }})()
+        "#,
+        pages_json
+    );
+
+    execute_js_and_parse(&js, || {
+        json!({
+            "status": "ok",
+            "deletedCount": page_nums.len()
+        })
+    })
+}
+
+fn rotate_pages(options: &Value) -> Result<Value> {
+    let pages_value = options
+        .get("pages")
+        .or_else(|| options.get("pageNumbers"))
+        .ok_or_else(|| anyhow::anyhow!("pages array required"))?;
+    let degrees = options.get("angle").and_then(|v| v.as_i64()).unwrap_or(90);
+    let angle = Rotation::from_degrees(degrees)
+        .ok_or(AdobeError::InvalidRotation(degrees))?
+        .to_degrees();
+
+    let page_nums = normalize_page_numbers(pages_value, page_base_option(options)?)?;
+    let pages_json = serde_json::to_string(&page_nums)?;
+
+    let js = format!(
+        r#"
+        (function() {{
+            try {{
+                var doc = this;
+                var pages = {};
+                var angle = {};
+                var rotated = 0;
+
+                for (var i = 0; i < pages.length; i++) {{
+                    if (pages[i] < doc.numPages) {{
+                        doc.setPageRotations(pages[i], pages[i], angle);
+                        rotated++;
+                    }}
+                }}
+
+                return JSON.stringify({{"success": true, "rotatedCount": rotated, "angle": angle}});
+            }} catch(e) {{
+                return JSON.stringify({{"success": false, "error": e.toString()}});
+            }}
+        }})()
+        "#,
+        pages_json, angle
+    );
+
+    execute_js_and_parse(&js, || {
+        json!({
+            "status": "ok",
+            "rotatedCount": page_nums.len(),
+            "angle": angle
+        })
+    })
+}
+
+fn insert_pages(options: &Value) -> Result<Value> {
+    let source_path = options
+        .get("sourcePath")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| anyhow::anyhow!("sourcePath required"))?;
+    let after_page = options
+        .get("afterPage")
+        .and_then(|v| v.as_i64())
+        .unwrap_or(-1);
+
+    let js = format!(
+        r#"
+        (function() {{
+            try {{
+                var doc = this;
+                var insertAt = {};
+                if (insertAt < 0) {{
+                    insertAt = doc.numPages - 1;
+                }}
+
+                doc.insertPages({{
+                    nPage: insertAt,
+                    cPath: "{}"
+                }});
+
+                return JSON.stringify({{
+                    "success": true,
+                    "sourcePath": "{}",
+                    "insertedAt": insertAt
+                }});
+            }} catch(e) {{
+                return JSON.stringify({{"success": false, "error": e.toString()}});
+            }}
+        }})()
+        "#,
+        after_page,
+        escape_js_path(source_path),
+        escape_js_path(source_path)
+    );
+
+    execute_js_and_parse(&js, || {
+        json!({
+            "status": "ok",
+            "sourcePath": source_path
+        })
+    })
+}
+
+fn insert_blank_page(options: &Value) -> Result<Value> {
+    let after_page = after_page_option(options)?;
+    let (width, height) = requested_page_size(options);
+
+    let js = insert_blank_page_script(after_page, width, height).render();
+    execute_js_and_parse(&js, || json!({"status": "ok", "width": width, "height": height}))
+}
+
+/// `afterPage` is 1-based with 0 meaning before the first page; `None` appends
+fn insert_blank_page_script(after_page: Option<u64>, width: f64, height: f64) -> JsScript {
+    JsScript::new(
+        r#"
+        try {
+            var doc = this;
+            var after = args.afterPage === null ? doc.numPages : args.afterPage;
+            if (after > doc.numPages) {
+                throw new Error("Page " + after + " out of range (document has " + doc.numPages + " pages)");
+            }
+            doc.newPage({nPage: after, nWidth: args.width, nHeight: args.height});
+            return JSON.stringify({"success": true, "insertedAt": after + 1, "pageCount": doc.numPages});
+        } catch(e) {
+            return JSON.stringify({"success": false, "error": e.toString()});
+        }
+        "#,
+    )
+    .bind("afterPage", after_page)
+    .bind("width", width)
+    .bind("height", height)
+}
+
+fn duplicate_page(options: &Value) -> Result<Value> {
+    let page = required_page_number(options, "page")?;
+    let after_page = after_page_option(options)?.unwrap_or(page);
+
+    let js = duplicate_page_script(page, after_page).render();
+    execute_js_and_parse(&js, || json!({"status": "ok", "page": page, "insertedAt": after_page + 1}))
+}
+
+/// Copy 1-based `page` to just after `after_page` (0 = the front)
+///
+/// `insertPages` can only read from a file, so the copy comes from the saved
+/// document on disk: unsaved changes to the page are not duplicated.
+fn duplicate_page_script(page: u64, after_page: u64) -> JsScript {
+    JsScript::new(
+        r#"
+        try {
+            var doc = this;
+            if (args.page > doc.numPages || args.afterPage > doc.numPages) {
+                throw new Error("Page out of range (document has " + doc.numPages + " pages)");
+            }
+            doc.insertPages({
+                nPage: args.afterPage - 1,
+                cPath: doc.path,
+                nStart: args.page - 1,
+                nEnd: args.page - 1
+            });
+            return JSON.stringify({
+                "success": true,
+                "page": args.page,
+                "insertedAt": args.afterPage + 1,
+                "pageCount": doc.numPages
+            });
+        } catch(e) {
+            return JSON.stringify({"success": false, "error": e.toString()});
+        }
+        "#,
+    )
+    .bind("page", page)
+    .bind("afterPage", after_page)
+}
+
+fn replace_page(options: &Value) -> Result<Value> {
+    let page = required_page_number(options, "page")?;
+    let source_path = options
+        .get("sourcePath")
+        .and_then(|v| v.as_str())
+        .filter(|p| !p.trim().is_empty())
+        .ok_or_else(|| anyhow::anyhow!("sourcePath required"))?;
+    let source_page = match options.get("sourcePage") {
+        None | Some(Value::Null) => 1,
+        Some(_) => required_page_number(options, "sourcePage")?,
+    };
+
+    let js = replace_page_script(page, source_path, source_page).render();
+    execute_js_and_parse(&js, || json!({"status": "ok", "page": page, "sourcePath": escape_js_path(source_path)}))
+}
+
+/// Replace 1-based `page` with `source_page` of the PDF at `source_path`
+fn replace_page_script(page: u64, source_path: &str, source_page: u64) -> JsScript {
+    JsScript::new(
+        r#"
+        try {
+            var doc = this;
+            if (args.page > doc.numPages) {
+                throw new Error("Page " + args.page + " out of range (document has " + doc.numPages + " pages)");
+            }
+            doc.replacePages({
+                nPage: args.page - 1,
+                cPath: args.sourcePath,
+                nStart: args.sourcePage - 1,
+                nEnd: args.sourcePage - 1
+            });
+            return JSON.stringify({
+                "success": true,
+                "page": args.page,
+                "sourcePath": args.sourcePath,
+                "pageCount": doc.numPages
+            });
+        } catch(e) {
+            return JSON.stringify({"success": false, "error": e.toString()});
+        }
+        "#,
+    )
+    .bind("page", page)
+    .bind("sourcePath", escape_js_path(source_path))
+    .bind("sourcePage", source_page)
+}
+
+// ============================================================================
+// Bookmark & Metadata Operations
+// ============================================================================
+
+fn add_bookmark(options: &Value) -> Result<Value> {
+    let title = options
+        .get("title")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| anyhow::anyhow!("title required"))?;
+    let page = options
+        .get("page")
+        .and_then(|v| v.as_i64())
+        .ok_or_else(|| anyhow::anyhow!("page required"))?;
+    let parent = options.get("parent").and_then(|v| v.as_str()).unwrap_or("");
+    let page_index = normalize_page_index(page);
+    let action = bookmark_action(page_index, options)?;
+
+    let js = format!(
+        r#"
+        (function() {{
+            try {{
+                var doc = this;
+                var root = doc.bookmarkRoot;
+                var title = "{}";
+                var parentName = "{}";
+                var pageIndex = {};
+                var action = "{}";
+
+                function findBookmark(node, name) {{
+                    if (!node) return null;
+                    if (node.name === name) return node;
+                    if (node.children && node.children.length) {{
+                        for (var i = 0; i < node.children.length; i++) {{
+                            var found = findBookmark(node.children[i], name);
+                            if (found) return found;
+                        }}
+                    }}
+                    return null;
+                }}
+
+                var parentNode = root;
+                if (parentName) {{
+                    var match = findBookmark(root, parentName);
+                    if (match) {{
+                        parentNode = match;
+                    }}
+                }}
+
+                var bookmark = parentNode.createChild(title, action);
+
+                return JSON.stringify({{
+                    "success": bookmark != null,
+                    "title": title,
+                    "page": pageIndex + 1,
+                    "parent": parentName
+                }});
+            }} catch(e) {{
+                return JSON.stringify({{"success": false, "error": e.toString()}});
+            }}
+        }})()
+        "#,
+        escape_js_string(title),
+        escape_js_string(parent),
+        page_index,
+        escape_js_string(&action)
+    );
+
+    execute_js_and_parse(&js, || {
+        json!({
+            "status": "ok",
+            "title": title,
+            "page": page_index + 1
+        })
+    })
+}
+
+fn get_bookmarks(_options: &Value) -> Result<Value> {
+    let js = bookmarks_script(None).render();
+    execute_js_and_parse(&js, || json!({"bookmarks": []}))
+}
+
+fn delete_bookmark(options: &Value) -> Result<Value> {
+    let path = bookmark_path(options)?;
+    let js = bookmarks_script(Some(path)).render();
+    execute_js_and_parse(&js, || json!({"status": "ok", "bookmarks": []}))
+}
+
+/// `path` as a list of titles from the top level down, e.g.
+/// `["Chapter 1", "Section 1.2"]`
+fn bookmark_path(options: &Value) -> Result<Vec<String>> {
+    let path = options
+        .get("path")
+        .and_then(|v| v.as_array())
+        .ok_or_else(|| anyhow::anyhow!("path required"))?;
+    if path.is_empty() {
+        return Err(anyhow::anyhow!("path must name at least one bookmark"));
+    }
+
+    path.iter()
+        .map(|title| {
+            title
+                .as_str()
+                .map(str::to_string)
+                .ok_or_else(|| anyhow::anyhow!("path must be an array of bookmark titles"))
+        })
+        .collect()
+}
+
+/// Nested `{name, page, children}` tree of the outline, after removing the
+/// bookmark at `deletePath` when one is bound. Bookmarks expose no
+/// destination, so each is executed and the resulting page recorded; one
+/// that leaves the page unchanged gets a null page. The original page is
+/// restored afterwards.
+const BOOKMARKS: &str = r#"
+        try {
+            var doc = this;
+            var root = doc.bookmarkRoot;
+
+            if (args.deletePath !== null) {
+                var node = root;
+                for (var i = 0; i < args.deletePath.length; i++) {
+                    var next = null;
+                    var kids = node.children || [];
+                    for (var j = 0; j < kids.length; j++) {
+                        if (kids[j].name === args.deletePath[i]) {
+                            next = kids[j];
+                            break;
+                        }
+                    }
+                    if (!next) {
+                        throw new Error("Bookmark not found: " + args.deletePath.slice(0, i + 1).join(" > "));
+                    }
+                    node = next;
+                }
+                node.remove();
+            }
+
+            // Follow the bookmark from a known page; when it stays put, retry
+            // from another page to tell "goes to this page" from "goes nowhere"
+            var lastPage = doc.numPages - 1;
+            var followFrom = function(bookmark, from) {
+                doc.pageNum = from;
+                bookmark.execute();
+                return doc.pageNum;
+            };
+            var targetPage = function(bookmark) {
+                var landed = followFrom(bookmark, 0);
+                if (landed !== 0 || lastPage === 0) {
+                    return landed;
+                }
+                return followFrom(bookmark, lastPage) === 0 ? 0 : null;
+            };
+
+            var originalPage = doc.pageNum;
+            var walk = function(bookmark) {
+                var page = null;
+                try {
+                    var target = targetPage(bookmark);
+                    if (target !== null) {
+                        page = target + 1;
+                    }
+                } catch(e) {}
+                var children = [];
+                var kids = bookmark.children || [];
+                for (var k = 0; k < kids.length; k++) {
+                    children.push(walk(kids[k]));
+                }
+                return {"name": bookmark.name, "page": page, "children": children};
+            };
+
+            var bookmarks = [];
+            var top = root.children || [];
+            for (var t = 0; t < top.length; t++) {
+                bookmarks.push(walk(top[t]));
+            }
+            doc.pageNum = originalPage;
+
+            return JSON.stringify({"success": true, "bookmarks": bookmarks});
+        } catch(e) {
+            return JSON.stringify({"success": false, "error": e.toString()});
+        }
+        "#;
+
+fn bookmarks_script(delete_path: Option<Vec<String>>) -> JsScript {
+    JsScript::new(BOOKMARKS).bind("deletePath", delete_path)
+}
+
+/// Margin around table of contents pages, in points
+const TOC_MARGIN: f64 = 72.0;
+/// Indent per bookmark level
+const TOC_INDENT: f64 = 18.0;
+/// Width reserved for the right-aligned page number column
+const TOC_NUMBER_WIDTH: f64 = 48.0;
+
+/// A bookmark that becomes one table of contents line
+#[derive(Debug, Clone, PartialEq)]
+struct TocEntry {
+    title: String,
+    /// 0 for top-level bookmarks
+    level: usize,
+    /// 0-based target page before the contents pages are inserted
+    page_index: u64,
+}
+
+/// One line of the laid-out contents: its page among the inserted pages,
+/// the rectangles for title, page number, and link, and the final target
+#[derive(Debug, Clone, PartialEq)]
+struct TocLine {
+    page: u64,
+    title: String,
+    text_rect: [f64; 4],
+    number_rect: [f64; 4],
+    link_rect: [f64; 4],
+    target: u64,
+}
+
+fn generate_toc(options: &Value) -> Result<Value> {
+    let title = options
+        .get("title")
+        .and_then(|v| v.as_str())
+        .unwrap_or("Table of Contents");
+    let font_size = match options.get("fontSize") {
+        None | Some(Value::Null) => 12.0,
+        Some(v) => v
+            .as_f64()
+            .filter(|size| *size > 0.0)
+            .ok_or_else(|| anyhow::anyhow!("fontSize must be a positive number"))?,
+    };
+    let include_levels = match options.get("includeLevels") {
+        None | Some(Value::Null) => None,
+        Some(v) => Some(
+            v.as_u64()
+                .filter(|levels| *levels >= 1)
+                .ok_or_else(|| anyhow::anyhow!("includeLevels must be at least 1"))?
+                as usize,
+        ),
+    };
+
+    let tree = execute_js_and_parse(
+        &bookmarks_script(None).render(),
+        || json!({"bookmarks": []}),
+    )?;
+    let entries = toc_entries(&tree["bookmarks"], include_levels);
+    if entries.is_empty() {
+        return Err(anyhow::anyhow!(
+            "Document has no bookmarks with target pages to list"
+        ));
+    }
+
+    let info = execute_js_and_parse(&get_document_info_script(PageBox::Crop).render(), || {
+        json!({})
+    })?;
+    let size = &info["pageSize"];
+    let (width, height) = match (size["width"].as_f64(), size["height"].as_f64()) {
+        (Some(width), Some(height)) => (width, height),
+        _ => PageSize::Letter.dimensions(),
+    };
+
+    let (page_count, lines) = toc_layout(&entries, font_size, width, height);
+    let js = generate_toc_script(title, font_size, width, height, page_count, &lines).render();
+    execute_js_and_parse(
+        &js,
+        || json!({"status": "ok", "entries": lines.len(), "pagesAdded": page_count}),
+    )
+}
+
+/// Flatten the `getBookmarks` tree in reading order, down to
+/// `include_levels` deep; bookmarks without a target page are left out but
+/// their children are kept
+fn toc_entries(bookmarks: &Value, include_levels: Option<usize>) -> Vec<TocEntry> {
+    fn collect(
+        nodes: &Value,
+        level: usize,
+        include_levels: Option<usize>,
+        entries: &mut Vec<TocEntry>,
+    ) {
+        if include_levels.is_some_and(|levels| level >= levels) {
+            return;
+        }
+        for node in nodes.as_array().into_iter().flatten() {
+            if let (Some(title), Some(page)) = (node["name"].as_str(), node["page"].as_u64()) {
+                if page >= 1 {
+                    entries.push(TocEntry {
+                        title: title.to_string(),
+                        level,
+                        page_index: page - 1,
+                    });
+                }
+            }
+            collect(&node["children"], level + 1, include_levels, entries);
+        }
+    }
+
+    let mut entries = Vec::new();
+    collect(bookmarks, 0, include_levels, &mut entries);
+    entries
+}
+
+/// Lay `entries` out top to bottom over as many `width` x `height` pages as
+/// they need, below a title on the first page. Returns the page count and
+/// the lines, whose targets account for the inserted pages.
+fn toc_layout(
+    entries: &[TocEntry],
+    font_size: f64,
+    width: f64,
+    height: f64,
+) -> (u64, Vec<TocLine>) {
+    let line_height = font_size * 1.6;
+    let top = height - TOC_MARGIN;
+    let right = width - TOC_MARGIN;
+
+    let mut page = 0;
+    let mut y = top - toc_title_height(font_size) - font_size;
+    let mut lines = Vec::with_capacity(entries.len());
+    for entry in entries {
+        if y - line_height < TOC_MARGIN {
+            page += 1;
+            y = top;
+        }
+        let bottom = y - line_height;
+        let left = (TOC_MARGIN + TOC_INDENT * entry.level as f64).min(right - TOC_NUMBER_WIDTH);
+        lines.push(TocLine {
+            page,
+            title: entry.title.clone(),
+            text_rect: [left, bottom, right - TOC_NUMBER_WIDTH, y],
+            number_rect: [right - TOC_NUMBER_WIDTH, bottom, right, y],
+            link_rect: [left, bottom, right, y],
+            target: entry.page_index,
+        });
+        y = bottom;
+    }
+
+    let page_count = page + 1;
+    for line in &mut lines {
+        line.target += page_count;
+    }
+    (page_count, lines)
+}
+
+fn toc_title_height(font_size: f64) -> f64 {
+    font_size * 1.5 * 1.5
+}
+
+/// Insert the contents pages at the front, then write each line as
+/// borderless FreeText (title and page number) under an invisible link
+const GENERATE_TOC: &str = r#"
+        try {
+            var doc = this;
+            for (var p = 0; p < args.pageCount; p++) {
+                doc.newPage({nPage: 0, nWidth: args.width, nHeight: args.height});
+            }
+
+            var text = function(page, rect, contents, size, alignment) {
+                doc.addAnnot({
+                    page: page,
+                    type: "FreeText",
+                    rect: rect,
+                    contents: contents,
+                    textFont: "Helvetica",
+                    textSize: size,
+                    alignment: alignment,
+                    strokeColor: color.transparent,
+                    width: 0,
+                    readOnly: true
+                });
+            };
+
+            text(0, args.titleRect, args.title, args.titleSize, 1);
+            for (var i = 0; i < args.lines.length; i++) {
+                var line = args.lines[i];
+                text(line.page, line.textRect, line.title, args.fontSize, 0);
+                text(line.page, line.numberRect, String(line.target + 1), args.fontSize, 2);
+                var link = doc.addLink(line.page, line.linkRect);
+                link.borderWidth = 0;
+                link.setAction("this.pageNum = " + line.target + ";");
+            }
+
+            return JSON.stringify({
+                "success": true,
+                "entries": args.lines.length,
+                "pagesAdded": args.pageCount
+            });
+        } catch(e) {
+            return JSON.stringify({"success": false, "error": e.toString()});
+        }
+        "#;
+
+fn generate_toc_script(
+    title: &str,
+    font_size: f64,
+    width: f64,
+    height: f64,
+    page_count: u64,
+    lines: &[TocLine],
+) -> JsScript {
+    let title_height = toc_title_height(font_size);
+    let lines: Vec<Value> = lines
+        .iter()
+        .map(|line| {
+            json!({
+                "page": line.page,
+                "title": line.title,
+                "textRect": line.text_rect,
+                "numberRect": line.number_rect,
+                "linkRect": line.link_rect,
+                "target": line.target,
+            })
+        })
+        .collect();
+
+    JsScript::new(GENERATE_TOC)
+        .bind("title", title)
+        .bind("titleSize", font_size * 1.5)
+        .bind(
+            "titleRect",
+            json!([
+                TOC_MARGIN,
+                height - TOC_MARGIN - title_height,
+                width - TOC_MARGIN,
+                height - TOC_MARGIN
+            ]),
+        )
+        .bind("fontSize", font_size)
+        .bind("width", width)
+        .bind("height", height)
+        .bind("pageCount", page_count)
+        .bind("lines", lines)
+}
+
+/// Script run when the bookmark is clicked: just the page jump unless a
+/// `zoom` (FitPage, FitWidth, FitHeight or a percentage) or a
+/// `left`/`top` scroll position in page coordinates is given
+fn bookmark_action(page_index: i64, options: &Value) -> Result<String> {
+    let mut action = format!("this.pageNum={}", page_index);
+
+    match options.get("zoom") {
+        None | Some(Value::Null) => {}
+        Some(Value::String(fit)) => {
+            let zoom_type = match fit.to_lowercase().as_str() {
+                "fitpage" => "fitP",
+                "fitwidth" => "fitW",
+                "fitheight" => "fitH",
+                other => {
+                    return Err(anyhow::anyhow!(
+                        "Unknown zoom: {} (expected FitPage, FitWidth, FitHeight or a percentage)",
+                        other
+                    ))
+                }
+            };
+            action.push_str(&format!("; this.zoomType=zoomtype.{}", zoom_type));
+        }
+        Some(v) => match v.as_f64().filter(|z| *z > 0.0) {
+            Some(percent) => action.push_str(&format!("; this.zoom={}", percent)),
+            None => {
+                return Err(anyhow::anyhow!(
+                    "zoom must be FitPage, FitWidth, FitHeight or a positive percentage"
+                ))
+            }
+        },
+    }
+
+    let left = options.get("left").and_then(|v| v.as_f64());
+    let top = options.get("top").and_then(|v| v.as_f64());
+    if left.is_some() || top.is_some() {
+        action.push_str(&format!(
+            "; this.scroll({}, {})",
+            left.unwrap_or(0.0),
+            top.unwrap_or(0.0)
+        ));
+    }
+
+    Ok(action)
+}
+
+fn set_metadata(options: &Value) -> Result<Value> {
+    let title = options.get("title").and_then(|v| v.as_str());
+    let author = options.get("author").and_then(|v| v.as_str());
+    let subject = options.get("subject").and_then(|v| v.as_str());
+    let keywords = options.get("keywords").and_then(|v| v.as_str());
+
+    let mut assignments = String::new();
+    if let Some(value) = title {
+        assignments.push_str(&format!(
+            "doc.info.Title = \"{}\";\n",
+            escape_js_string(value)
+        ));
+    }
+    if let Some(value) = author {
+        assignments.push_str(&format!(
+            "doc.info.Author = \"{}\";\n",
+            escape_js_string(value)
+        ));
+    }
+    if let Some(value) = subject {
+        assignments.push_str(&format!(
+            "doc.info.Subject = \"{}\";\n",
+            escape_js_string(value)
+        ));
+    }
+    if let Some(value) = keywords {
+        assignments.push_str(&format!(
+            "doc.info.Keywords = \"{}\";\n",
+            escape_js_string(value)
+        ));
+    }
+    if assignments.is_empty() {
+        assignments.push_str("// No metadata changes provided\n");
+    }
+
+    let js = format!(
+        r#"
+        (function() {{
+            try {{
+                var doc = this;
+                {}
+                return JSON.stringify({{"success": true}});
+            }} catch(e) {{
+                return JSON.stringify({{"success": false, "error": e.toString()}});
+            }}
+        }})()
+        "#,
+        assignments
+    );
+
+    execute_js_and_parse(&js, || json!({"status": "ok"}))
+}
+
+fn set_initial_view(options: &Value) -> Result<Value> {
+    let str_option = |key: &str| options.get(key).and_then(|v| v.as_str());
+
+    let page_mode = str_option("pageMode")
+        .map(|mode| match mode.to_lowercase().as_str() {
+            "none" => Ok("UseNone"),
+            "bookmarks" => Ok("UseOutlines"),
+            "thumbnails" => Ok("UseThumbs"),
+            "fullscreen" => Ok("FullScreen"),
+            other => Err(anyhow::anyhow!(
+                "Unknown pageMode: {} (expected none, bookmarks, thumbnails or fullscreen)",
+                other
+            )),
+        })
+        .transpose()?;
+    let layout = str_option("pageLayout")
+        .map(|layout| match layout.to_lowercase().as_str() {
+            "single" => Ok("SinglePage"),
+            "continuous" => Ok("OneColumn"),
+            "facing" => Ok("TwoPageLeft"),
+            other => Err(anyhow::anyhow!(
+                "Unknown pageLayout: {} (expected single, continuous or facing)",
+                other
+            )),
+        })
+        .transpose()?;
+    let page_index = match options.get("pageNum") {
+        None | Some(Value::Null) => None,
+        Some(_) => Some(required_page_number(options, "pageNum")? - 1),
+    };
+    let (zoom, zoom_type) = match options.get("zoom") {
+        None | Some(Value::Null) => (None, None),
+        Some(Value::String(fit)) => match fit.to_lowercase().as_str() {
+            "fit-page" => (None, Some("FitPage")),
+            "fit-width" => (None, Some("FitWidth")),
+            other => {
+                return Err(anyhow::anyhow!(
+                    "Unknown zoom: {} (expected a percentage, fit-page or fit-width)",
+                    other
+                ))
+            }
+        },
+        Some(v) => match v.as_f64().filter(|z| *z > 0.0) {
+            Some(percent) => (Some(percent), None),
+            None => {
+                return Err(anyhow::anyhow!(
+                    "zoom must be a positive percentage, fit-page or fit-width"
+                ))
+            }
+        },
+    };
+
+    if page_mode.is_none()
+        && layout.is_none()
+        && page_index.is_none()
+        && zoom.is_none()
+        && zoom_type.is_none()
+    {
+        return Err(anyhow::anyhow!(
+            "Specify at least one of pageMode, pageLayout, pageNum or zoom"
+        ));
+    }
+
+    let settings = json!({
+        "pageMode": str_option("pageMode").map(str::to_lowercase),
+        "pageLayout": str_option("pageLayout").map(str::to_lowercase),
+        "pageNum": page_index.map(|i| i + 1),
+        "zoom": options.get("zoom"),
+    });
+    let js = set_initial_view_script(
+        page_mode,
+        layout,
+        page_index,
+        zoom,
+        zoom_type,
+        settings.clone(),
+    )
+    .render();
+    execute_js_and_parse(&js, || json!({"status": "ok", "settings": settings}))
+}
+
+/// Apply the view now and store the same statements as an `InitialView`
+/// document script, which Acrobat runs each time the file is opened
+fn set_initial_view_script(
+    page_mode: Option<&str>,
+    layout: Option<&str>,
+    page_index: Option<u64>,
+    zoom: Option<f64>,
+    zoom_type: Option<&str>,
+    settings: Value,
+) -> JsScript {
+    JsScript::new(
+        r#"
+        try {
+            var doc = this;
+            if (args.pageIndex !== null && args.pageIndex >= doc.numPages) {
+                throw new Error("Page " + (args.pageIndex + 1) + " out of range (document has " + doc.numPages + " pages)");
+            }
+
+            var onOpen = [];
+            if (args.pageMode !== null) {
+                doc.pageMode = args.pageMode;
+                onOpen.push("this.pageMode = " + JSON.stringify(args.pageMode) + ";");
+            }
+            if (args.layout !== null) {
+                doc.layout = args.layout;
+                onOpen.push("this.layout = " + JSON.stringify(args.layout) + ";");
+            }
+            if (args.pageIndex !== null) {
+                doc.pageNum = args.pageIndex;
+                onOpen.push("this.pageNum = " + args.pageIndex + ";");
+            }
+            if (args.zoomType !== null) {
+                doc.zoomType = args.zoomType;
+                onOpen.push("this.zoomType = " + JSON.stringify(args.zoomType) + ";");
+            } else if (args.zoom !== null) {
+                doc.zoom = args.zoom;
+                onOpen.push("this.zoom = " + args.zoom + ";");
+            }
+            doc.addScript("InitialView", onOpen.join("\n"));
+
+            return JSON.stringify({"success": true, "settings": args.settings});
+        } catch(e) {
+            return JSON.stringify({"success": false, "error": e.toString()});
+        }
+        "#,
+    )
+    .bind("pageMode", page_mode)
+    .bind("layout", layout)
+    .bind("pageIndex", page_index)
+    .bind("zoom", zoom)
+    .bind("zoomType", zoom_type)
+    .bind("settings", settings)
+}
+
+// ============================================================================
+// Layer Operations
+// ============================================================================
+
+fn get_layers(_options: &Value) -> Result<Value> {
+    execute_js_and_parse(&get_layers_script().render(), || json!({"layers": []}))
+}
+
+/// Every optional content group in document order; `getOCGs` returns
+/// null rather than an empty array when there are none
+fn get_layers_script() -> JsScript {
+    JsScript::new(
+        r#"
+        try {
+            var ocgs = this.getOCGs() || [];
+            var layers = [];
+            for (var i = 0; i < ocgs.length; i++) {
+                layers.push({"name": ocgs[i].name, "visible": ocgs[i].state});
+            }
+            return JSON.stringify({"success": true, "layers": layers});
+        } catch(e) {
+            return JSON.stringify({"success": false, "error": e.toString()});
+        }
+        "#,
+    )
+}
+
+fn set_layer_visibility(options: &Value) -> Result<Value> {
+    let name = options
+        .get("name")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| anyhow::anyhow!("name required"))?;
+    let visible = options
+        .get("visible")
+        .and_then(|v| v.as_bool())
+        .ok_or_else(|| anyhow::anyhow!("visible required"))?;
+
+    let js = set_layer_visibility_script(name, visible).render();
+    execute_js_and_parse(&js, || json!({"status": "ok", "visibility": {name: visible}}))
+}
+
+/// Set `state` on every OCG called `name` (names need not be unique) and
+/// report the visibility of all layers afterwards
+fn set_layer_visibility_script(name: &str, visible: bool) -> JsScript {
+    JsScript::new(
+        r#"
+        try {
+            var ocgs = this.getOCGs() || [];
+            var matched = 0;
+            var visibility = {};
+            for (var i = 0; i < ocgs.length; i++) {
+                if (ocgs[i].name === args.name) {
+                    ocgs[i].state = args.visible;
+                    matched++;
+                }
+                visibility[ocgs[i].name] = ocgs[i].state;
+            }
+            if (matched === 0) {
+                throw new Error("Layer not found: " + args.name);
+            }
+            return JSON.stringify({"success": true, "matched": matched, "visibility": visibility});
+        } catch(e) {
+            return JSON.stringify({"success": false, "error": e.toString()});
+        }
+        "#,
+    )
+    .bind("name", name)
+    .bind("visible", visible)
+}
+
+// ============================================================================
+// Raw JavaScript
+// ============================================================================
+
+/// Message of the `executeJavaScript` failure while the plugin disallows it
+pub const RAW_JS_DISABLED: &str =
+    "Raw JavaScript disabled: set ACROBAT_ALLOW_RAW_JS=1 for Acrobat to allow executeJavaScript";
+
+fn execute_javascript(options: &Value) -> Result<Value> {
+    let allowed = crate::get_state().lock().allow_raw_js;
+    run_raw_javascript(options, allowed)
+}
+
+/// Run the caller's `script` verbatim and return what it evaluated to
+///
+/// Unlike every other command the script is not a fixed template, so it gets
+/// the full Acrobat JavaScript API; `allowed` comes from
+/// [`crate::PluginState::allow_raw_js`].
+fn run_raw_javascript(options: &Value, allowed: bool) -> Result<Value> {
+    if !allowed {
+        anyhow::bail!(RAW_JS_DISABLED);
+    }
+    let script = options
+        .get("script")
+        .and_then(|v| v.as_str())
+        .filter(|s| !s.trim().is_empty())
+        .ok_or_else(|| anyhow::anyhow!("script required"))?;
+
+    let result = js_bridge::execute_js(script)?;
+    if !result.success {
+        anyhow::bail!(
+            "JavaScript execution failed: {}",
+            result.error.unwrap_or_else(|| "Unknown error".to_string())
+        );
+    }
+    Ok(json!({"result": result.value}))
+}
+
+// ============================================================================
+// Sanitize Operations
+// ============================================================================
+
+/// Hidden-data categories `sanitizeDocument` can strip, each on by default
+const SANITIZE_CATEGORIES: &[&str] = &["metadata", "javascript", "hiddenLayers", "attachments"];
+
+fn sanitize_document(options: &Value) -> Result<Value> {
+    let mut categories = serde_json::Map::new();
+    for &category in SANITIZE_CATEGORIES {
+        let enabled = match options.get(category) {
+            None | Some(Value::Null) => true,
+            Some(Value::Bool(enabled)) => *enabled,
+            Some(other) => {
+                return Err(anyhow::anyhow!("{} must be a boolean, got {}", category, other))
+            }
+        };
+        categories.insert(category.to_string(), json!(enabled));
+    }
+    let dry_run = options
+        .get("dryRun")
+        .and_then(|v| v.as_bool())
+        .unwrap_or(false);
+
+    let js = sanitize_document_script(Value::Object(categories), dry_run).render();
+
+    execute_js_and_parse(&js, || json!({"status": "ok", "dryRun": dry_run, "removed": {}}))
+}
+
+/// Document-level scripts the JavaScript category blanks by name, since
+/// Acrobat JavaScript cannot list them; `setInitialView` installs
+/// `InitialView`
+const SANITIZE_DOCUMENT_SCRIPTS: &[&str] = &["InitialView"];
+
+/// Find (and unless `dryRun`, remove) hidden data in each enabled category
+///
+/// Acrobat JavaScript cannot delete optional content groups, so hidden layers
+/// are only reported; they are listed under `notRemoved` instead of `removed`.
+/// Nor can it read actions, so a dry run notes that under `notRemoved` and a
+/// real run lists the triggers it blanked.
+const SANITIZE_DOCUMENT: &str = r#"
+        try {
+            var doc = this;
+            var removed = {};
+            var notRemoved = {};
+
+            if (args.categories.metadata) {
+                var keys = ["Title", "Author", "Subject", "Keywords", "Creator", "Producer"];
+                var found = [];
+                for (var i = 0; i < keys.length; i++) {
+                    if (doc.info[keys[i]]) {
+                        found.push(keys[i]);
+                        if (!args.dryRun) {
+                            doc.info[keys[i]] = "";
+                        }
+                    }
+                }
+                var xmp = "";
+                try { xmp = doc.metadata || ""; } catch (me) {}
+                if (xmp.length) {
+                    found.push("XMP");
+                    if (!args.dryRun) {
+                        try { doc.metadata = ""; } catch (me) {}
+                    }
+                }
+                removed.metadata = found;
+            }
+
+            if (args.categories.javascript) {
+                // Actions can be overwritten but not read, so only a real run
+                // reports them, as the triggers it blanked
+                var cleared = [];
+                if (args.dryRun) {
+                    notRemoved.javascript = ["Acrobat JavaScript cannot read actions or list document-level scripts, so a dry run cannot tell which exist"];
+                } else {
+                    var triggers = ["WillClose", "WillSave", "DidSave", "WillPrint", "DidPrint"];
+                    for (var t = 0; t < triggers.length; t++) {
+                        doc.setAction(triggers[t], "");
+                    }
+                    cleared.push("document actions");
+                    for (var p = 0; p < doc.numPages; p++) {
+                        doc.setPageAction(p, "Open", "");
+                        doc.setPageAction(p, "Close", "");
+                    }
+                    cleared.push("page actions on " + doc.numPages + " page(s)");
+                    var fieldTriggers = ["MouseUp", "MouseDown", "MouseEnter", "MouseExit", "OnFocus", "OnBlur"];
+                    var fieldCount = 0;
+                    for (var f = 0; f < doc.numFields; f++) {
+                        var field = doc.getField(doc.getNthFieldName(f));
+                        if (!field) {
+                            continue;
+                        }
+                        fieldCount++;
+                        for (var ft = 0; ft < fieldTriggers.length; ft++) {
+                            try { field.setAction(fieldTriggers[ft], ""); } catch (fe) {}
+                        }
+                    }
+                    if (fieldCount > 0) {
+                        cleared.push("actions on " + fieldCount + " field(s)");
+                    }
+                    for (var ds = 0; ds < args.documentScripts.length; ds++) {
+                        doc.addScript(args.documentScripts[ds], "");
+                    }
+                    cleared.push("document-level scripts: " + args.documentScripts.join(", "));
+                }
+                removed.javascript = cleared;
+            }
+
+            if (args.categories.hiddenLayers) {
+                var hidden = [];
+                var ocgs = doc.getOCGs() || [];
+                for (var o = 0; o < ocgs.length; o++) {
+                    if (!ocgs[o].state) {
+                        hidden.push(ocgs[o].name);
+                    }
+                }
+                notRemoved.hiddenLayers = hidden;
+            }
+
+            if (args.categories.attachments) {
+                var attachments = [];
+                var objects = doc.dataObjects || [];
+                for (var d = 0; d < objects.length; d++) {
+                    attachments.push(objects[d].name);
+                    if (!args.dryRun) {
+                        doc.removeDataObject(objects[d].name);
+                    }
+                }
+                var annots = doc.getAnnots() || [];
+                for (var a = 0; a < annots.length; a++) {
+                    if (annots[a].type === "FileAttachment") {
+                        attachments.push(annots[a].name);
+                        if (!args.dryRun) {
+                            annots[a].destroy();
+                        }
+                    }
+                }
+                removed.attachments = attachments;
+            }
+
+            return JSON.stringify({
+                "success": true,
+                "dryRun": args.dryRun,
+                "removed": removed,
+                "notRemoved": notRemoved
+            });
+        } catch(e) {
+            return JSON.stringify({"success": false, "error": e.toString()});
+        }
+        "#;
+
+fn sanitize_document_script(categories: Value, dry_run: bool) -> JsScript {
+    JsScript::new(SANITIZE_DOCUMENT)
+        .bind("categories", categories)
+        .bind("dryRun", dry_run)
+        .bind("documentScripts", SANITIZE_DOCUMENT_SCRIPTS)
+}
+
+fn remove_watermarks(options: &Value) -> Result<Value> {
+    let whole_document = ["pageRange", "pageStart", "pageEnd"]
+        .iter()
+        .all(|key| options.get(*key).is_none_or(Value::is_null));
+    let ranges = parse_page_range(options)?;
+
+    let js = remove_watermarks_script(ranges, whole_document).render();
+    execute_js_and_parse(&js, || json!({"status": "ok", "removedAnnotations": 0, "removedLayers": 0, "hiddenLayers": 0}))
+}
+
+/// Watermarks are Watermark annotations or page content in a layer named
+/// like "Watermark" (where `addWatermarkFromText` puts them). For the whole
+/// document `doc.removeWatermarks()` deletes both where the viewer has it.
+/// Otherwise annotations on the pages are destroyed and watermark layers,
+/// which Acrobat JavaScript cannot delete, are hidden. Counts cover only what
+/// changed: hidden layers are reported apart from removed ones, and the
+/// `removeWatermarks()` path counts what a rescan no longer finds.
+const REMOVE_WATERMARKS: &str = r#"
+        try {
+            var doc = this;
+            var isWatermarkLayer = function(ocg) {
+                return /watermark/i.test(ocg.name);
+            };
+
+            var scan = function() {
+                var found = {"annots": [], "layers": [], "pages": 0};
+                var seen = {};
+                for (var r = 0; r < args.ranges.length; r++) {
+                    var start = Math.max(args.ranges[r].start, 0);
+                    var end = args.ranges[r].end < 0 ? doc.numPages - 1 : Math.min(args.ranges[r].end, doc.numPages - 1);
+                    for (var p = start; p <= end; p++) {
+                        found.pages++;
+                        var pageAnnots = doc.getAnnots({nPage: p}) || [];
+                        for (var a = 0; a < pageAnnots.length; a++) {
+                            if (pageAnnots[a].type === "Watermark") {
+                                found.annots.push(pageAnnots[a]);
+                            }
+                        }
+                        var pageLayers = doc.getOCGs(p) || [];
+                        for (var l = 0; l < pageLayers.length; l++) {
+                            var key = pageLayers[l].name;
+                            if (isWatermarkLayer(pageLayers[l]) && !seen[key]) {
+                                seen[key] = true;
+                                found.layers.push(pageLayers[l]);
+                            }
+                        }
+                    }
+                }
+                return found;
+            };
+
+            var found = scan();
+            var method;
+            var removedAnnotations = 0;
+            var removedLayers = 0;
+            var hiddenLayers = 0;
+            if (args.wholeDocument && typeof doc.removeWatermarks === "function") {
+                // Acrobat reports nothing back, so count what is gone afterwards
+                doc.removeWatermarks();
+                var remaining = scan();
+                removedAnnotations = found.annots.length - remaining.annots.length;
+                removedLayers = found.layers.length - remaining.layers.length;
+                method = "removeWatermarks";
+            } else {
+                for (var i = 0; i < found.annots.length; i++) {
+                    found.annots[i].destroy();
+                    removedAnnotations++;
+                }
+                for (var j = 0; j < found.layers.length; j++) {
+                    if (found.layers[j].state) {
+                        found.layers[j].state = false;
+                        hiddenLayers++;
+                    }
+                }
+                method = "annotations";
+            }
+
+            return JSON.stringify({
+                "success": true,
+                "removedAnnotations": removedAnnotations,
+                "removedLayers": removedLayers,
+                "hiddenLayers": hiddenLayers,
+                "method": method,
+                "pagesScanned": found.pages
+            });
+        } catch(e) {
+            return JSON.stringify({"success": false, "error": e.toString()});
+        }
+        "#;
+
+fn remove_watermarks_script(ranges: Vec<Value>, whole_document: bool) -> JsScript {
+    JsScript::new(REMOVE_WATERMARKS)
+        .bind("ranges", ranges)
+        .bind("wholeDocument", whole_document)
+}
+
+// ============================================================================
+// Helper Functions
+// ============================================================================
+
+/// 1-based page number under `key`
+fn required_page_number(options: &Value, key: &str) -> Result<u64> {
+    options
+        .get(key)
+        .and_then(|v| v.as_u64())
+        .filter(|&n| n >= 1)
+        .ok_or_else(|| anyhow::anyhow!("{} must be a page number of at least 1", key))
+}
+
+/// Optional 1-based `afterPage`, where 0 means before the first page
+fn after_page_option(options: &Value) -> Result<Option<u64>> {
+    match options.get("afterPage") {
+        None | Some(Value::Null) => Ok(None),
+        Some(v) => v
+            .as_u64()
+            .map(Some)
+            .ok_or_else(|| anyhow::anyhow!("afterPage must be a page number, or 0 for the front")),
+    }
+}
+
+fn normalize_page_index(value: i64) -> i64 {
+    if value <= 0 {
+        0
+    } else {
+        value - 1
+    }
+}
+
+/// Optional `pageBase`: whether a command's page numbers count from 0 or 1
+fn page_base_option(options: &Value) -> Result<Option<i64>> {
+    match options.get("pageBase") {
+        None | Some(Value::Null) => Ok(None),
+        Some(v) => v
+            .as_i64()
+            .filter(|base| matches!(base, 0 | 1))
+            .map(Some)
+            .ok_or_else(|| anyhow::anyhow!("pageBase must be 0 or 1")),
+    }
+}
+
+/// 0-based page indices from a page number array
+///
+/// With an explicit `base` every number must be at least `base`. Without one
+/// the array is taken as 0-based if it contains a 0 and 1-based otherwise,
+/// which misreads 0-based arrays that leave out the first page.
+fn normalize_page_numbers(value: &Value, base: Option<i64>) -> Result<Vec<i64>> {
+    let pages = value
+        .as_array()
+        .ok_or_else(|| anyhow::anyhow!("pages array required"))?;
+    let mut page_nums: Vec<i64> = pages.iter().filter_map(|v| v.as_i64()).collect();
+
+    if page_nums.is_empty() {
+        return Err(anyhow::anyhow!("At least one page number required"));
+    }
+
+    if let Some(base) = base {
+        if let Some(&bad) = page_nums.iter().find(|&&n| n < base) {
+            anyhow::bail!("Page number {} is invalid with pageBase {}", bad, base);
+        }
+        return Ok(page_nums.into_iter().map(|n| n - base).collect());
+    }
+
+    let treat_as_one_based = !page_nums.contains(&0);
+    if treat_as_one_based {
+        page_nums = page_nums
+            .into_iter()
+            .map(|n| if n <= 0 { 0 } else { n - 1 })
+            .collect();
+    }
+
+    Ok(page_nums)
+}
+
+/// 0-based `{start, end}` spans for the scripts; `end: -1` means the last page
+fn page_index_spans(range: &PageRange) -> Vec<Value> {
+    range
+        .spans()
+        .iter()
+        .map(|span| {
+            let start = i64::from(span.start) - 1;
+            let end = span.end.map_or(-1, |end| i64::from(end) - 1);
+            json!({"start": start, "end": end})
+        })
+        .collect()
+}
+
+fn parse_page_range_str(range: &str) -> Result<Vec<Value>> {
+    let parsed: PageRange = range.parse()?;
+    Ok(page_index_spans(&parsed))
+}
+
+fn parse_page_range(options: &Value) -> Result<Vec<Value>> {
+    if let Some(range) = options.get("pageRange").and_then(|v| v.as_str()) {
+        return parse_page_range_str(range);
+    }
+
+    let start = options.get("pageStart").and_then(|v| v.as_i64()).unwrap_or(0);
+    let end = options.get("pageEnd").and_then(|v| v.as_i64()).unwrap_or(-1);
+    let end_idx = if end < 0 { -1 } else { normalize_page_index(end) };
+
+    Ok(vec![json!({"start": normalize_page_index(start), "end": end_idx})])
+}
+
+fn normalize_page_ranges(value: &Value) -> Result<Vec<Value>> {
+    let ranges = value
+        .as_array()
+        .ok_or_else(|| anyhow::anyhow!("pageRanges array required"))?;
+    let mut normalized = Vec::new();
+
+    for range in ranges {
+        if let Some(range_str) = range.as_str() {
+            // Each entry becomes one output file, so it has to be contiguous
+            let mut spans = parse_page_range_str(range_str)?;
+            if spans.len() != 1 {
+                return Err(anyhow::anyhow!(
+                    "pageRanges entry '{}' must be a single range",
+                    range_str
+                ));
+            }
+            normalized.append(&mut spans);
+            continue;
+        }
+
+        if let Some(range_array) = range.as_array() {
+            if range_array.is_empty() {
+                continue;
+            }
+            let start = range_array.first().and_then(|v| v.as_i64()).unwrap_or(0);
+            let end = range_array
+                .get(1)
+                .and_then(|v| v.as_i64())
+                .unwrap_or(start);
+            let start_idx = normalize_page_index(start);
+            let end_idx = normalize_page_index(end).max(start_idx);
+            normalized.push(json!({"start": start_idx, "end": end_idx}));
+            continue;
+        }
+
+        if let Some(range_obj) = range.as_object() {
+            let start = range_obj
+                .get("start")
+                .and_then(|v| v.as_i64())
+                .unwrap_or(0);
+            let end = range_obj
+                .get("end")
+                .and_then(|v| v.as_i64())
+                .unwrap_or(start);
+            let start_idx = normalize_page_index(start);
+            let end_idx = normalize_page_index(end).max(start_idx);
+            normalized.push(json!({"start": start_idx, "end": end_idx}));
+        }
+    }
+
+    if normalized.is_empty() {
+        return Err(anyhow::anyhow!("pageRanges array required"));
+    }
+
+    Ok(normalized)
+}
+
+/// Execute JavaScript and parse the result, falling back to default on error
+fn execute_js_and_parse<F>(script: &str, default_fn: F) -> Result<Value>
+where
+    F: FnOnce() -> Value,
+{
+    match js_bridge::execute_js(script) {
+        Ok(result) => {
+            if result.success {
+                if let Some(value) = result.value {
+                    // Try to parse as JSON
+                    match serde_json::from_str::<Value>(&value) {
+                        Ok(parsed) => {
+                            // Check if the JS reported success
+                            if parsed
+                                .get("success")
+                                .and_then(|v| v.as_bool())
+                                .unwrap_or(true)
+                            {
+                                Ok(parsed)
+                            } else {
+                                let error = parsed
+                                    .get("error")
+                                    .and_then(|v| v.as_str())
+                                    .unwrap_or("Unknown error");
+                                Err(anyhow::anyhow!("JavaScript error: {}", error))
+                            }
+                        }
+                        Err(_) => {
+                            // Return raw value as string
+                            Ok(json!({"result": value}))
+                        }
+                    }
+                } else {
+                    Ok(default_fn())
+                }
+            } else {
+                Err(anyhow::anyhow!(
+                    "JavaScript execution failed: {}",
+                    result.error.unwrap_or_default()
+                ))
+            }
+        }
+        Err(e) => {
+            tracing::warn!("JS execution error, using default: {}", e);
+            Ok(default_fn())
+        }
+    }
+}
+
+/// Escape a string for use in JavaScript
+fn escape_js_string(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    let mut prev = '\0';
+    for c in s.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            '"' => escaped.push_str("\\\""),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            // Line/paragraph separators terminate string literals in pre-ES2019 engines
+            '\u{2028}' | '\u{2029}' => escaped.push_str(&format!("\\u{:04x}", c as u32)),
+            c if c.is_control() && (c as u32) < 0x80 => {
+                escaped.push_str(&format!("\\u{:04x}", c as u32))
+            }
+            // Keep "</script" from closing an enclosing HTML script block
+            '/' if prev == '<' => escaped.push_str("\\/"),
+            c => escaped.push(c),
+        }
+        prev = c;
+    }
+    escaped
+}
+
+/// Parse any [`Color`] form into the Acrobat color array it serializes to
+fn acrobat_color(value: &Value) -> Result<Value> {
+    let color: Color = serde_json::from_value(value.clone())
+        .map_err(|e| anyhow::anyhow!("Invalid color: {}", e))?;
+    Ok(serde_json::to_value(color)?)
+}
+
+/// Exactly `len` numbers, e.g. a `[x1, y1, x2, y2]` rect or an 8-number quad
+fn number_array(value: &Value, len: usize, name: &str) -> Result<Vec<f64>> {
+    value
+        .as_array()
+        .filter(|items| items.len() == len)
+        .and_then(|items| items.iter().map(Value::as_f64).collect::<Option<Vec<f64>>>())
+        .ok_or_else(|| anyhow::anyhow!("{} must be an array of {} numbers", name, len))
+}
+
+/// `rect` as `[left, bottom, right, top]` within a `page_width` x
+/// `page_height` page whose origin is the bottom-left corner
+///
+/// Edges past the page are pulled onto it, or rejected when
+/// `reject_out_of_bounds` is set. A rect entirely off the page is always
+/// rejected, since clamping would leave nothing visible.
+fn validate_rect(
+    rect: [f64; 4],
+    page_width: f64,
+    page_height: f64,
+    reject_out_of_bounds: bool,
+) -> Result<[f64; 4]> {
+    let (left, right) = (rect[0].min(rect[2]), rect[0].max(rect[2]));
+    let (bottom, top) = (rect[1].min(rect[3]), rect[1].max(rect[3]));
+
+    if right < 0.0 || left > page_width || top < 0.0 || bottom > page_height {
+        anyhow::bail!(
+            "Rect [{}, {}, {}, {}] lies entirely outside the {}x{} page",
+            left,
+            bottom,
+            right,
+            top,
+            page_width,
+            page_height
+        );
+    }
+    let inside = left >= 0.0 && bottom >= 0.0 && right <= page_width && top <= page_height;
+    if !inside && reject_out_of_bounds {
+        anyhow::bail!(
+            "Rect [{}, {}, {}, {}] extends past the {}x{} page",
+            left,
+            bottom,
+            right,
+            top,
+            page_width,
+            page_height
+        );
+    }
+
+    Ok([
+        left.max(0.0),
+        bottom.max(0.0),
+        right.min(page_width),
+        top.min(page_height),
+    ])
+}
+
+/// Width and height of the crop box of the page at `page_index`
+const PAGE_DIMENSIONS: &str = r#"
+        try {
+            var box = this.getPageBox("Crop", args.pageIndex);
+            return JSON.stringify({
+                "success": true,
+                "width": Math.abs(box[2] - box[0]),
+                "height": Math.abs(box[3] - box[1])
+            });
+        } catch(e) {
+            return JSON.stringify({"success": false, "error": e.toString()});
+        }
+        "#;
+
+/// Page size from Acrobat, or `None` when the reply carries no dimensions
+fn page_dimensions(page_index: i64) -> Result<Option<(f64, f64)>> {
+    let js = JsScript::new(PAGE_DIMENSIONS).bind("pageIndex", page_index);
+    let size = execute_js_and_parse(&js.render(), || json!({}))?;
+    Ok(size["width"].as_f64().zip(size["height"].as_f64()))
+}
+
+/// `rect` checked against its page with [`validate_rect`], honoring the
+/// command's `rejectOutOfBounds` option
+fn fit_rect_to_page(rect: [f64; 4], page_index: i64, options: &Value) -> Result<[f64; 4]> {
+    let reject = options
+        .get("rejectOutOfBounds")
+        .and_then(|v| v.as_bool())
+        .unwrap_or(false);
+    match page_dimensions(page_index)? {
+        Some((width, height)) => validate_rect(rect, width, height, reject),
+        None => Ok(rect),
+    }
+}
+
+/// Escape a file path for use in JavaScript
+fn escape_js_path(path: &str) -> String {
+    // Convert backslashes to forward slashes for cross-platform compatibility
+    path.replace('\\', "/")
+}
+
+/// Resolve a named page size, defaulting to letter for unknown names
+fn page_size_dimensions(size: &str) -> (f64, f64) {
+    size.parse::<PageSize>().unwrap_or(PageSize::Letter).dimensions()
+}
+
+/// Page size named by `pageSize`, or `width`/`height` for `CUSTOM`
+/// (letter-sized when missing)
+fn requested_page_size(options: &Value) -> (f64, f64) {
+    let page_size = options
+        .get("pageSize")
+        .and_then(|v| v.as_str())
+        .unwrap_or("LETTER");
+    if page_size.eq_ignore_ascii_case("CUSTOM") {
+        (
+            options.get("width").and_then(|v| v.as_f64()).unwrap_or_else(|| page_size_width("LETTER")),
+            options.get("height").and_then(|v| v.as_f64()).unwrap_or_else(|| page_size_height("LETTER")),
+        )
+    } else {
+        page_size_dimensions(page_size)
+    }
+}
+
+/// Get page width for a named page size
+fn page_size_width(size: &str) -> f64 {
+    page_size_dimensions(size).0
+}
+
+/// Get page height for a named page size
+fn page_size_height(size: &str) -> f64 {
+    page_size_dimensions(size).1
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use adobe_common::CommandBuilder;
+
+    #[test]
+    fn test_escape_js_string() {
+        assert_eq!(escape_js_string("hello"), "hello");
+        assert_eq!(escape_js_string("hello\"world"), "hello\\\"world");
+        assert_eq!(escape_js_string("line1\nline2"), "line1\\nline2");
+        assert_eq!(escape_js_string("path\\to\\file"), "path\\\\to\\\\file");
+    }
+
+    #[test]
+    fn test_escape_js_string_control_chars() {
+        assert_eq!(escape_js_string("a\u{2028}b\u{2029}c"), "a\\u2028b\\u2029c");
+        assert_eq!(escape_js_string("\u{0}\u{1b}\u{7f}"), "\\u0000\\u001b\\u007f");
+        assert_eq!(escape_js_string("</script>"), "<\\/script>");
+        assert_eq!(escape_js_string("a/b"), "a/b");
+    }
+
+    #[test]
+    fn test_escape_js_string_is_single_literal() {
+        // JSON strings are a subset of JS string literals, so a JSON round-trip
+        // proves the escaped text stays one literal and decodes to the input
+        let input = "line\u{2028}sep\u{2029}\"quoted\"\\\n\u{8}</script>";
+        let escaped = escape_js_string(input);
+        assert!(!escaped.chars().any(|c| c.is_control() || c == '\u{2028}' || c == '\u{2029}'));
+        let literal = format!("\"{}\"", escaped);
+        let decoded: String = serde_json::from_str(&literal).unwrap();
+        assert_eq!(decoded, input);
+    }
+
+    #[test]
+    fn test_escape_js_path() {
+        assert_eq!(escape_js_path("C:\\Users\\test"), "C:/Users/test");
+        assert_eq!(escape_js_path("/home/test"), "/home/test");
+    }
+
+    #[test]
+    fn test_page_sizes() {
+        assert_eq!(page_size_width("LETTER"), 612.0);
+        assert_eq!(page_size_height("LETTER"), 792.0);
+        assert_eq!(page_size_width("A4"), 595.0);
+        assert_eq!(page_size_height("A4"), 842.0);
+        assert_eq!(page_size_width("tabloid"), 792.0);
+        assert_eq!(page_size_height("executive"), 756.0);
+        assert_eq!(page_size_width("unknown"), 612.0);
+    }
+
+    #[test]
+    fn test_execute_command_unknown() {
+        let cmd = Command {
+            action: "unknownCommand".to_string(),
+            options: json!({}),
+        };
+        let result = execute_command(&cmd).unwrap();
+        assert_eq!(result.status, ResponseStatus::Failure);
+        assert!(result.message.is_some());
+        assert!(result.duration_ms.is_some());
+    }
+
+    #[test]
+    fn test_execute_command_get_page_count() {
+        let cmd = Command {
+            action: "getPageCount".to_string(),
+            options: json!({}),
+        };
+        let result = execute_command(&cmd).unwrap();
+        assert_eq!(result.status, ResponseStatus::Success);
+        assert!(result.duration_ms.is_some());
+
+        let json = serde_json::to_value(&result).unwrap();
+        assert!(json["durationMs"].as_u64().is_some());
+    }
+
+    #[test]
+    fn test_open_document_missing_path() {
+        let cmd = Command {
+            action: "openDocument".to_string(),
+            options: json!({}),
+        };
+        let result = execute_command(&cmd).unwrap();
+        assert_eq!(result.status, ResponseStatus::Failure);
+        assert!(result.message.unwrap().contains("filePath required"));
+    }
+
+    #[test]
+    fn test_rotate_pages_invalid_angle() {
+        let cmd = Command {
+            action: "rotatePages".to_string(),
+            options: json!({"pages": [0], "angle": 45}),
+        };
+        let result = execute_command(&cmd).unwrap();
+        assert_eq!(result.status, ResponseStatus::Failure);
+        assert!(result.message.unwrap().contains("Invalid angle"));
+    }
+
+    #[test]
+    fn test_get_document_info_page_box() {
+        let script = get_document_info_script("media".parse().unwrap());
+        assert_eq!(script.arg("pageBox"), Some(&json!("Media")));
+        assert!(script.render().contains("doc.getPageBox(args.pageBox, 0)"));
+        assert!(script.render().contains("doc.modDate.toISOString()"));
+        assert_eq!(get_document_info_script(PageBox::default()).arg("pageBox"), Some(&json!("Crop")));
+
+        let cmd = Command {
+            action: "getDocumentInfo".to_string(),
+            options: json!({"pageBox": "Bounding"}),
+        };
+        let result = execute_command(&cmd).unwrap();
+        assert_eq!(result.status, ResponseStatus::Failure);
+        assert!(result.message.unwrap().contains("Unknown page box"));
+    }
+
+    #[test]
+    #[cfg(not(feature = "acrobat-sdk"))]
+    fn test_extract_text_output_modes() {
+        let script = extract_text_script(vec![json!({"start": 0, "end": -1})], "withCoords");
+        assert_eq!(script.arg("output"), Some(&json!("withCoords")));
+        assert!(script.render().contains("doc.getPageNthWordQuads(i, j)"));
+
+        let cmd = Command {
+            action: "extractText".to_string(),
+            options: json!({"output": "perPage"}),
+        };
+        let result = execute_command(&cmd).unwrap();
+        assert_eq!(result.status, ResponseStatus::Success);
+        let pages = &result.response.unwrap()["pages"];
+        assert_eq!(pages[0]["page"], 1);
+        assert!(pages[0]["text"].is_string());
+        assert!(pages[0].get("words").is_none());
+
+        let cmd = Command {
+            action: "extractText".to_string(),
+            options: json!({"output": "withCoords"}),
+        };
+        let response = execute_command(&cmd).unwrap().response.unwrap();
+        assert_eq!(response["pages"][0]["words"][0]["quads"][0].as_array().unwrap().len(), 8);
+
+        let cmd = Command {
+            action: "extractText".to_string(),
+            options: json!({}),
+        };
+        execute_command(&cmd).unwrap();
+        assert!(js_bridge::mock::last_script().unwrap().contains(r#""output":"plain""#));
+    }
+
+    #[test]
+    fn test_extract_text_invalid_output() {
+        let cmd = Command {
+            action: "extractText".to_string(),
+            options: json!({"output": "html"}),
+        };
+        let result = execute_command(&cmd).unwrap();
+        assert_eq!(result.status, ResponseStatus::Failure);
+        assert!(result.message.unwrap().contains("Invalid output"));
+    }
+
+    #[test]
+    fn test_search_text_script_args() {
+        let script = search_text_script("Total Due", true, 5);
+        assert_eq!(script.arg("query"), Some(&json!("Total Due")));
+        assert_eq!(script.arg("caseSensitive"), Some(&json!(true)));
+        assert_eq!(script.arg("maxResults"), Some(&json!(5)));
+        assert!(script.render().contains("doc.getPageNthWordQuads"));
+    }
+
+    #[test]
+    #[cfg(not(feature = "acrobat-sdk"))]
+    fn test_search_text_returns_structured_matches() {
+        js_bridge::mock::set_response(
+            js_bridge::mock::Matcher::Contains("getPageNthWordQuads".into()),
+            js_bridge::JsResult::success(
+                json!({
+                    "success": true,
+                    "query": "invoice",
+                    "matches": [{
+                        "page": 2,
+                        "pageIndex": 1,
+                        "wordIndex": 14,
+                        "text": "Invoice",
+                        "quads": [[72, 700, 120, 700, 72, 688, 120, 688]],
+                        "rect": [72, 688, 120, 700]
+                    }],
+                    "count": 1,
+                    "truncated": false
+                })
+                .to_string(),
+            ),
+        );
+
+        let cmd = Command {
+            action: "searchText".to_string(),
+            options: json!({"query": "invoice", "maxResults": 10}),
+        };
+        let result = execute_command(&cmd).unwrap();
+        js_bridge::mock::clear();
+
+        assert_eq!(result.status, ResponseStatus::Success);
+        let response = result.response.unwrap();
+        assert_eq!(response["count"], 1);
+        assert_eq!(response["matches"][0]["pageIndex"], 1);
+        assert_eq!(response["matches"][0]["wordIndex"], 14);
+        assert_eq!(response["matches"][0]["rect"], json!([72, 688, 120, 700]));
+    }
+
+    #[test]
+    #[cfg(not(feature = "acrobat-sdk"))]
+    fn test_get_statistics_returns_counts() {
+        let script = get_statistics_script(vec![json!({"start": 0, "end": -1})], 50);
+        assert_eq!(script.arg("maxPages"), Some(&json!(50)));
+        assert!(script.render().contains("doc.getPageNumWords(i)"));
+
+        js_bridge::mock::set_response(
+            js_bridge::mock::Matcher::Contains("characterCount".into()),
+            js_bridge::JsResult::success(
+                json!({
+                    "success": true,
+                    "numPages": 12,
+                    "pagesCounted": 2,
+                    "wordCount": 830,
+                    "characterCount": 4120,
+                    "truncated": true
+                })
+                .to_string(),
+            ),
+        );
+        let cmd = Command {
+            action: "getStatistics".to_string(),
+            options: json!({"pageRange": "3-", "maxPages": 2}),
+        };
+        let result = execute_command(&cmd).unwrap();
+        let script = js_bridge::mock::last_script().unwrap();
+        js_bridge::mock::clear();
+
+        assert!(script.contains(r#""ranges":[{"end":-1,"start":2}]"#));
+        assert_eq!(result.status, ResponseStatus::Success);
+        let response = result.response.unwrap();
+        assert_eq!(response["numPages"], 12);
+        assert_eq!(response["wordCount"], 830);
+        assert_eq!(response["characterCount"], 4120);
+        assert_eq!(response["truncated"], true);
+
+        let cmd = Command {
+            action: "getStatistics".to_string(),
+            options: json!({"maxPages": 0}),
+        };
+        assert_eq!(execute_command(&cmd).unwrap().status, ResponseStatus::Failure);
+    }
+
+    #[test]
+    fn test_highlight_text_script_from_quads() {
+        let quads = json!([[72, 720, 110, 720, 72, 708, 110, 708], [112, 720, 150, 720, 112, 708, 150, 708]]);
+        let script = highlight_text_script(&json!({
+            "page": 2,
+            "quads": quads,
+            "color": "#00ff00",
+            "contents": "Check this",
+            "author": "Reviewer"
+        }))
+        .unwrap();
+        assert_eq!(script.arg("pageIndex"), Some(&json!(1)));
+        assert_eq!(script.arg("quads"), Some(&json!([[72.0, 720.0, 110.0, 720.0, 72.0, 708.0, 110.0, 708.0], [112.0, 720.0, 150.0, 720.0, 112.0, 708.0, 150.0, 708.0]])));
+        assert_eq!(script.arg("rect"), Some(&json!([72.0, 708.0, 150.0, 720.0])));
+        assert_eq!(script.arg("color"), Some(&json!(["RGB", 0.0, 1.0, 0.0])));
+        assert_eq!(script.arg("author"), Some(&json!("Reviewer")));
+        let rendered = script.render();
+        assert!(rendered.contains(r#"type: "Highlight""#));
+        assert!(rendered.contains("doc.addAnnot(props)"));
+    }
+
+    #[test]
+    fn test_validate_rect_in_bounds() {
+        let rect = validate_rect([72.0, 700.0, 272.0, 718.0], 612.0, 792.0, true).unwrap();
+        assert_eq!(rect, [72.0, 700.0, 272.0, 718.0]);
+        // Corners may come in either order
+        let rect = validate_rect([272.0, 718.0, 72.0, 700.0], 612.0, 792.0, false).unwrap();
+        assert_eq!(rect, [72.0, 700.0, 272.0, 718.0]);
+    }
+
+    #[test]
+    fn test_validate_rect_clamped() {
+        let rect = validate_rect([500.0, 780.0, 700.0, 800.0], 612.0, 792.0, false).unwrap();
+        assert_eq!(rect, [500.0, 780.0, 612.0, 792.0]);
+        let rect = validate_rect([-10.0, -5.0, 20.0, 30.0], 612.0, 792.0, false).unwrap();
+        assert_eq!(rect, [0.0, 0.0, 20.0, 30.0]);
+    }
+
+    #[test]
+    fn test_validate_rect_rejected() {
+        let err = validate_rect([500.0, 780.0, 700.0, 800.0], 612.0, 792.0, true).unwrap_err();
+        assert!(err.to_string().contains("extends past the 612x792 page"), "{}", err);
+
+        let err = validate_rect([700.0, 100.0, 900.0, 120.0], 612.0, 792.0, false).unwrap_err();
+        assert!(err.to_string().contains("entirely outside"), "{}", err);
+    }
+
+    #[test]
+    #[cfg(not(feature = "acrobat-sdk"))]
+    fn test_annotation_commands_fit_rect_to_page() {
+        js_bridge::mock::set_response(
+            js_bridge::mock::Matcher::Contains(r#"getPageBox("Crop", args.pageIndex)"#.into()),
+            js_bridge::JsResult::success(r#"{"success": true, "width": 612, "height": 792}"#),
+        );
+
+        let cmd = CommandBuilder::new("addText")
+            .option("text", "Edge")
+            .option("x", 500)
+            .option("y", 785)
+            .build();
+        assert_eq!(
+            execute_command(&cmd).unwrap().status,
+            ResponseStatus::Success
+        );
+        let script = js_bridge::mock::last_script().unwrap();
+        assert!(
+            script.contains(r#""rect":[500.0,785.0,612.0,792.0]"#),
+            "{}",
+            script
+        );
+
+        let cmd = CommandBuilder::new("highlightText")
+            .option("rect", json!([600, 700, 650, 720]))
+            .option("rejectOutOfBounds", true)
+            .build();
+        let result = execute_command(&cmd).unwrap();
+        assert_eq!(result.status, ResponseStatus::Failure);
+        assert!(result.message.unwrap().contains("extends past"));
+
+        let cmd = CommandBuilder::new("addComment")
+            .option("contents", "Off page")
+            .option("x", 72)
+            .option("y", 900)
+            .build();
+        let result = execute_command(&cmd).unwrap();
+        js_bridge::mock::clear();
+        assert_eq!(result.status, ResponseStatus::Failure);
+        assert!(result.message.unwrap().contains("entirely outside"));
+    }
+
+    #[test]
+    fn test_highlight_text_script_from_rect() {
+        let script = highlight_text_script(&json!({"rect": [150, 708, 72, 720]})).unwrap();
+        assert_eq!(script.arg("quads"), Some(&json!([[72.0, 720.0, 150.0, 720.0, 72.0, 708.0, 150.0, 708.0]])));
+        assert_eq!(script.arg("color"), Some(&Value::Null));
+        assert_eq!(script.arg("contents"), Some(&Value::Null));
+
+        for options in [json!({}), json!({"rect": [1, 2, 3]}), json!({"quads": []}), json!({"quads": [[1, 2]]})] {
+            assert!(highlight_text_script(&options).is_err(), "{}", options);
+        }
+
+        let cmd = Command {
+            action: "highlightText".to_string(),
+            options: json!({"page": 3, "rect": [72, 708, 150, 720]}),
+        };
+        let result = execute_command(&cmd).unwrap();
+        assert_eq!(result.status, ResponseStatus::Success);
+    }
+
+    #[test]
+    fn test_add_comment_script() {
+        let script = add_comment_script(&json!({
+            "page": 1,
+            "x": 300,
+            "y": 500,
+            "contents": "Please revise",
+            "author": "Editor"
+        }))
+        .unwrap();
+        assert_eq!(script.arg("pageIndex"), Some(&json!(0)));
+        assert_eq!(script.arg("point"), Some(&json!([300.0, 500.0])));
+        assert_eq!(script.arg("contents"), Some(&json!("Please revise")));
+        assert_eq!(script.arg("author"), Some(&json!("Editor")));
+        assert!(script.render().contains(r#"type: "Text""#));
+
+        let cmd = Command {
+            action: "addComment".to_string(),
+            options: json!({"page": 1}),
+        };
+        let result = execute_command(&cmd).unwrap();
+        assert_eq!(result.status, ResponseStatus::Failure);
+        assert!(result.message.unwrap().contains("contents required"));
+    }
+
+    #[test]
+    fn test_search_text_validation() {
+        for options in [json!({}), json!({"query": "  "}), json!({"query": "a", "maxResults": 0})] {
+            let cmd = Command {
+                action: "searchText".to_string(),
+                options,
+            };
+            let result = execute_command(&cmd).unwrap();
+            assert_eq!(result.status, ResponseStatus::Failure);
+        }
+    }
+
+    #[test]
+    fn test_get_app_info_script() {
+        let script = get_app_info_script();
+        assert!(script.contains("app.viewerVersion"));
+        assert!(script.contains("app.viewerType"));
+        assert!(script.contains("app.platform"));
+
+        let cmd = Command {
+            action: "getAppInfo".to_string(),
+            options: json!({}),
+        };
+        let response = execute_command(&cmd).unwrap().response.unwrap();
+        assert_eq!(response["viewerType"], "Exchange-Pro");
+        assert_eq!(response["platform"], "WIN");
+    }
+
+    #[test]
+    #[cfg(not(feature = "acrobat-sdk"))]
+    fn test_list_open_documents_script() {
+        let rendered = list_open_documents_script().render();
+        assert!(rendered.contains("app.activeDocs"));
+        assert!(rendered.contains(r#""numPages": docs[i].numPages"#));
+
+        js_bridge::mock::set_response(
+            js_bridge::mock::Matcher::Contains("documentFileName".into()),
+            js_bridge::JsResult::success(
+                json!({
+                    "success": true,
+                    "documents": [
+                        {"index": 0, "path": "/C/a.pdf", "title": "A", "numPages": 3, "active": true},
+                        {"index": 1, "path": "/C/b.pdf", "title": "B", "numPages": 1, "active": false}
+                    ],
+                    "count": 2
+                })
+                .to_string(),
+            ),
+        );
+        let cmd = Command {
+            action: "listOpenDocuments".to_string(),
+            options: json!({}),
+        };
+        let response = execute_command(&cmd).unwrap().response.unwrap();
+        js_bridge::mock::clear();
+        assert_eq!(response["count"], 2);
+        assert_eq!(response["documents"][1]["path"], "/C/b.pdf");
+    }
+
+    #[test]
+    fn test_set_active_document_script() {
+        let script = set_active_document_script(Some(r"C:\docs\b.pdf"), None);
+        assert_eq!(script.arg("documentPath"), Some(&json!("C:/docs/b.pdf")));
+        assert_eq!(script.arg("index"), Some(&Value::Null));
+        assert!(script.render().contains("target.bringToFront()"));
+
+        let script = set_active_document_script(None, Some(1));
+        assert_eq!(script.arg("index"), Some(&json!(1)));
+
+        let cmd = Command {
+            action: "setActiveDocument".to_string(),
+            options: json!({}),
+        };
+        assert_eq!(execute_command(&cmd).unwrap().status, ResponseStatus::Failure);
+    }
+
+    #[test]
+    #[cfg(not(feature = "acrobat-sdk"))]
+    fn test_document_path_activates_target_first() {
+        js_bridge::mock::set_response(
+            js_bridge::mock::Matcher::Contains("bringToFront".into()),
+            js_bridge::JsResult::success(r#"{"success": false, "error": "Document not open: /x.pdf"}"#),
+        );
+        let cmd = Command {
+            action: "getPageCount".to_string(),
+            options: json!({"documentPath": "/x.pdf"}),
+        };
+        let result = execute_command(&cmd).unwrap();
+        assert_eq!(result.status, ResponseStatus::Failure);
+        assert!(result.message.unwrap().contains("Document not open"));
+
+        // Document-independent commands ignore documentPath
+        let cmd = Command {
+            action: "getAppInfo".to_string(),
+            options: json!({"documentPath": "/x.pdf"}),
+        };
+        let result = execute_command(&cmd).unwrap();
+        js_bridge::mock::clear();
+        assert_eq!(result.status, ResponseStatus::Success);
+    }
+
+    #[test]
+    fn test_form_data_format() {
+        assert_eq!(form_data_format(&json!({}), "C:/f/data.xfdf").unwrap(), "XFDF");
+        assert_eq!(form_data_format(&json!({}), "C:/f/data").unwrap(), "FDF");
+        assert_eq!(
+            form_data_format(&json!({"format": "xfdf"}), "C:/f/data.fdf").unwrap(),
+            "XFDF"
+        );
+        assert!(form_data_format(&json!({"format": "csv"}), "C:/f/data.fdf").is_err());
+    }
+
+    #[test]
+    fn test_form_data_scripts() {
+        let script = export_form_data_script(r"C:\forms\out.xfdf", "XFDF");
+        assert_eq!(script.arg("filePath"), Some(&json!("C:/forms/out.xfdf")));
+        let js = script.render();
+        assert!(js.contains("doc.exportAsXFDF(params)"));
+        assert!(js.contains("doc.exportAsFDF(params)"));
+
+        let script = import_form_data_script(r"C:\forms\in.fdf", "FDF");
+        assert_eq!(script.arg("filePath"), Some(&json!("C:/forms/in.fdf")));
+        assert_eq!(script.arg("format"), Some(&json!("FDF")));
+        assert!(script.render().contains("doc.importAnFDF(args.filePath)"));
+    }
+
+    #[test]
+    #[cfg(not(feature = "acrobat-sdk"))]
+    fn test_import_form_data_reports_count() {
+        js_bridge::mock::set_response(
+            js_bridge::mock::Matcher::Contains("importAnFDF".into()),
+            js_bridge::JsResult::success(
+                r#"{"success": true, "format": "FDF", "fieldsImported": 2, "fields": ["name", "date"]}"#,
+            ),
+        );
+        let cmd = CommandBuilder::new("importFormData")
+            .option("filePath", "/forms/in.fdf")
+            .build();
+        let result = execute_command(&cmd).unwrap();
+        js_bridge::mock::clear();
+        assert_eq!(result.status, ResponseStatus::Success);
+        assert_eq!(result.response.unwrap()["fieldsImported"], 2);
+    }
+
+    #[test]
+    #[cfg(not(feature = "acrobat-sdk"))]
+    fn test_attach_file_defaults_name_to_file_name() {
+        let cmd = CommandBuilder::new("attachFile")
+            .option("sourcePath", "C:\\data\\report.csv")
+            .build();
+        let result = execute_command(&cmd).unwrap();
+        let script = js_bridge::mock::last_script().unwrap();
+        assert_eq!(result.status, ResponseStatus::Success);
+        assert!(script.contains("doc.importDataObject(args.name, args.sourcePath)"));
+        assert!(script.contains(r#""sourcePath":"C:/data/report.csv""#));
+        assert!(script.contains(r#""name":"report.csv""#));
+
+        let script = attach_file_script("/data/a.csv", "Q3 \"final\"").render();
+        assert!(script.contains(r#""name":"Q3 \"final\"""#));
+    }
+
+    #[test]
+    #[cfg(not(feature = "acrobat-sdk"))]
+    fn test_extract_attachments() {
+        let script = extract_attachments_script("C:\\out\\").render();
+        assert!(script.contains("doc.dataObjects || []"));
+        assert!(script.contains(r#""size": obj.size"#));
+        assert!(!script.contains("getDataObjectContents"));
+        assert!(script.contains(r#""outputDir":"C:/out/""#));
+
+        js_bridge::mock::set_response(
+            js_bridge::mock::Matcher::Contains(r#""size": obj.size"#.into()),
+            js_bridge::JsResult::success(
+                r#"{"success": true, "outputDir": "/out", "attachments": [{"name": "data.csv", "path": "/out/data.csv", "size": 2048}]}"#,
+            ),
+        );
+        let cmd = CommandBuilder::new("extractAttachments").option("outputDir", "/out").build();
+        let result = execute_command(&cmd).unwrap();
+        js_bridge::mock::clear();
+        assert_eq!(result.status, ResponseStatus::Success);
+        let attachments = &result.response.unwrap()["attachments"];
+        assert_eq!(attachments[0]["size"], 2048);
+        assert_eq!(attachments[0]["path"], "/out/data.csv");
+
+        let cmd = CommandBuilder::new("extractAttachments").build();
+        let result = execute_command(&cmd).unwrap();
+        assert_eq!(result.status, ResponseStatus::Failure);
+        assert!(result.message.unwrap().contains("outputDir required"));
+    }
+
+    #[test]
+    fn test_bookmark_action_zoom_modes() {
+        let action = |options: Value| bookmark_action(2, &options).unwrap();
+
+        assert_eq!(action(json!({})), "this.pageNum=2");
+        assert_eq!(action(json!({"zoom": "FitPage"})), "this.pageNum=2; this.zoomType=zoomtype.fitP");
+        assert_eq!(action(json!({"zoom": "FitWidth"})), "this.pageNum=2; this.zoomType=zoomtype.fitW");
+        assert_eq!(action(json!({"zoom": "fitheight"})), "this.pageNum=2; this.zoomType=zoomtype.fitH");
+        assert_eq!(action(json!({"zoom": 150})), "this.pageNum=2; this.zoom=150");
+        assert_eq!(
+            action(json!({"zoom": 75.5, "left": 36, "top": 700})),
+            "this.pageNum=2; this.zoom=75.5; this.scroll(36, 700)"
+        );
+        assert!(bookmark_action(0, &json!({"zoom": "FitVisible"}))
+            .unwrap_err()
+            .to_string()
+            .contains("Unknown zoom"));
+        assert!(bookmark_action(0, &json!({"zoom": 0})).is_err());
+    }
+
+    #[test]
+    #[cfg(not(feature = "acrobat-sdk"))]
+    fn test_add_bookmark_emits_action() {
+        let cmd = CommandBuilder::new("addBookmark")
+            .option("title", "Chapter \"2\"")
+            .option("page", 5)
+            .option("zoom", "FitWidth")
+            .build();
+        let result = execute_command(&cmd).unwrap();
+        let script = js_bridge::mock::last_script().unwrap();
+        assert_eq!(result.status, ResponseStatus::Success);
+        assert!(script.contains(r#"var action = "this.pageNum=4; this.zoomType=zoomtype.fitW";"#));
+        assert!(script.contains(r#"var title = "Chapter \"2\"";"#));
+        assert!(script.contains("parentNode.createChild(title, action)"));
+    }
+
+    #[test]
+    #[cfg(not(feature = "acrobat-sdk"))]
+    fn test_get_bookmarks_script_walks_tree() {
+        let script = bookmarks_script(None);
+        assert_eq!(script.arg("deletePath"), Some(&Value::Null));
+        let rendered = script.render();
+        assert!(rendered.contains("doc.bookmarkRoot"));
+        assert!(rendered.contains("children.push(walk(kids[k]))"));
+        assert!(rendered.contains("doc.pageNum = from;"));
+        assert!(rendered.contains("return followFrom(bookmark, lastPage) === 0 ? 0 : null;"));
+        assert!(rendered.contains("doc.pageNum = originalPage"));
+
+        js_bridge::mock::set_response(
+            js_bridge::mock::Matcher::Contains("bookmarkRoot".into()),
+            js_bridge::JsResult::success(
+                r#"{"success": true, "bookmarks": [{"name": "Chapter 1", "page": 1, "children": [{"name": "Section 1.2", "page": 3, "children": []}]}, {"name": "Website", "page": null, "children": []}]}"#,
+            ),
+        );
+        let cmd = CommandBuilder::new("getBookmarks").build();
+        let result = execute_command(&cmd).unwrap();
+        js_bridge::mock::clear();
+        assert_eq!(result.status, ResponseStatus::Success);
+        let response = result.response.unwrap();
+        assert_eq!(response["bookmarks"][0]["children"][0]["page"], 3);
+        assert_eq!(response["bookmarks"][1]["page"], Value::Null);
+    }
+
+    #[test]
+    #[cfg(not(feature = "acrobat-sdk"))]
+    fn test_delete_bookmark_path_lookup() {
+        let options = json!({"path": ["Chapter 1", "Section 1.2"]});
+        let path = bookmark_path(&options).unwrap();
+        assert_eq!(path, vec!["Chapter 1", "Section 1.2"]);
+
+        let rendered = bookmarks_script(Some(path)).render();
+        assert!(rendered.contains(r#""deletePath":["Chapter 1","Section 1.2"]"#));
+        assert!(rendered.contains("kids[j].name === args.deletePath[i]"));
+        assert!(rendered.contains("node.remove()"));
+
+        assert!(bookmark_path(&json!({"path": []})).is_err());
+        assert!(bookmark_path(&json!({"path": ["Chapter 1", 2]})).is_err());
+
+        js_bridge::mock::set_response(
+            js_bridge::mock::Matcher::Contains("deletePath".into()),
+            js_bridge::JsResult::success(
+                r#"{"success": false, "error": "Error: Bookmark not found: Chapter 9"}"#,
+            ),
+        );
+        let cmd = CommandBuilder::new("deleteBookmark").option("path", json!(["Chapter 9"])).build();
+        let result = execute_command(&cmd).unwrap();
+        js_bridge::mock::clear();
+        assert_eq!(result.status, ResponseStatus::Failure);
+        assert!(result.message.unwrap().contains("Bookmark not found: Chapter 9"));
+    }
+
+    #[test]
+    fn test_toc_entries_flatten_and_limit_depth() {
+        let tree = json!([
+            {"name": "Chapter 1", "page": 2, "children": [
+                {"name": "Section 1.1", "page": 3, "children": [
+                    {"name": "Detail", "page": 4, "children": []}
+                ]}
+            ]},
+            {"name": "Web link", "page": null, "children": [
+                {"name": "Appendix", "page": 9, "children": []}
+            ]}
+        ]);
+
+        let titles = |entries: Vec<TocEntry>| {
+            entries
+                .into_iter()
+                .map(|e| (e.title, e.level, e.page_index))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            titles(toc_entries(&tree, None)),
+            vec![
+                ("Chapter 1".to_string(), 0, 1),
+                ("Section 1.1".to_string(), 1, 2),
+                ("Detail".to_string(), 2, 3),
+                ("Appendix".to_string(), 1, 8),
+            ]
+        );
+        assert_eq!(
+            titles(toc_entries(&tree, Some(1))),
+            vec![("Chapter 1".to_string(), 0, 1)]
+        );
+    }
+
+    #[test]
+    fn test_toc_layout_paginates_and_offsets_targets() {
+        let entries: Vec<TocEntry> = (0..60)
+            .map(|i| TocEntry {
+                title: format!("Entry {}", i),
+                level: i % 2,
+                page_index: i as u64,
+            })
             .collect();
+        let (pages, lines) = toc_layout(&entries, 12.0, 612.0, 792.0);
+
+        assert_eq!(pages, 2);
+        assert_eq!(lines.len(), 60);
+        assert_eq!(lines[0].page, 0);
+        assert_eq!(lines[59].page, 1);
+        assert_eq!(lines[0].target, 2);
+        assert_eq!(lines[59].target, 61);
+        assert_eq!(lines[1].text_rect[0], TOC_MARGIN + TOC_INDENT);
+        assert_eq!(lines[0].number_rect[2], 612.0 - TOC_MARGIN);
+        assert!(lines.iter().all(|line| line.link_rect[1] >= TOC_MARGIN));
+        let second_page = lines.iter().find(|line| line.page == 1).unwrap();
+        assert_eq!(second_page.link_rect[3], 792.0 - TOC_MARGIN);
+    }
+
+    #[test]
+    #[cfg(not(feature = "acrobat-sdk"))]
+    fn test_generate_toc_script_links_entries() {
+        let entries = vec![TocEntry {
+            title: "Intro".to_string(),
+            level: 0,
+            page_index: 0,
+        }];
+        let (pages, lines) = toc_layout(&entries, 10.0, 612.0, 792.0);
+        let script = generate_toc_script("Contents", 10.0, 612.0, 792.0, pages, &lines);
+        assert_eq!(script.arg("pageCount"), Some(&json!(1)));
+        assert_eq!(script.arg("lines").unwrap()[0]["target"], 1);
+        let rendered = script.render();
+        assert!(
+            rendered.contains("doc.newPage({nPage: 0, nWidth: args.width, nHeight: args.height})")
+        );
+        assert!(rendered.contains("doc.addLink(line.page, line.linkRect)"));
+        assert!(rendered.contains(r#"link.setAction("this.pageNum = " + line.target + ";")"#));
+
+        js_bridge::mock::set_response(
+            js_bridge::mock::Matcher::Contains("bookmarkRoot".into()),
+            js_bridge::JsResult::success(
+                r#"{"success": true, "bookmarks": [{"name": "Intro", "page": 1, "children": []}]}"#,
+            ),
+        );
+        let cmd = CommandBuilder::new("generateToc")
+            .option("title", "Contents")
+            .build();
+        let result = execute_command(&cmd).unwrap();
+        let last = js_bridge::mock::last_script().unwrap();
+        js_bridge::mock::clear();
+        assert_eq!(result.status, ResponseStatus::Success);
+        assert!(last.contains(r#""title":"Contents""#));
+        assert!(last.contains(r#""target":1"#));
+
+        let cmd = CommandBuilder::new("generateToc")
+            .option("includeLevels", 0)
+            .build();
+        let result = execute_command(&cmd).unwrap();
+        assert!(result
+            .message
+            .unwrap()
+            .contains("includeLevels must be at least 1"));
+    }
+
+    #[test]
+    #[cfg(not(feature = "acrobat-sdk"))]
+    fn test_remove_watermarks_script() {
+        let cmd = CommandBuilder::new("removeWatermarks").build();
+        let result = execute_command(&cmd).unwrap();
+        let script = js_bridge::mock::last_script().unwrap();
+        assert_eq!(result.status, ResponseStatus::Success);
+        assert!(script.contains(r#""wholeDocument":true"#));
+        assert!(script.contains(r#""ranges":[{"end":-1,"start":0}]"#));
+        assert!(script.contains("doc.removeWatermarks()"));
+        assert!(script.contains("found.annots[i].destroy()"));
+        assert!(script.contains("var remaining = scan();"));
+
+        let cmd = CommandBuilder::new("removeWatermarks").option("pageRange", "2-3,7").build();
+        execute_command(&cmd).unwrap();
+        let script = js_bridge::mock::last_script().unwrap();
+        assert!(script.contains(r#""wholeDocument":false"#));
+        assert!(script.contains(r#""ranges":[{"end":2,"start":1},{"end":6,"start":6}]"#));
+        assert!(script.contains("doc.getAnnots({nPage: p})"));
+        assert!(script.contains("doc.getOCGs(p)"));
+    }
+
+    #[test]
+    #[cfg(not(feature = "acrobat-sdk"))]
+    fn test_get_security_info_script() {
+        let script = get_security_info_script().render();
+        assert!(script.contains("doc.securityHandler"));
+        assert!(script.contains(r#""permissions": permissions"#));
+        assert!(script.contains("doc.dirty = wasDirty"));
+
+        js_bridge::mock::set_response(
+            js_bridge::mock::Matcher::Contains("securityHandler".into()),
+            js_bridge::JsResult::success(
+                r#"{"success": true, "securityHandler": "Standard", "encrypted": true, "permissions": {"copying": false}}"#,
+            ),
+        );
+        let cmd = CommandBuilder::new("getSecurityInfo").build();
+        let result = execute_command(&cmd).unwrap();
+        js_bridge::mock::clear();
+        assert_eq!(result.status, ResponseStatus::Success);
+        let info = result.response.unwrap();
+        assert_eq!(info["securityHandler"], "Standard");
+        assert_eq!(info["permissions"]["copying"], false);
+    }
+
+    #[test]
+    #[cfg(not(feature = "acrobat-sdk"))]
+    fn test_set_initial_view_script() {
+        let cmd = CommandBuilder::new("setInitialView")
+            .option("pageMode", "bookmarks")
+            .option("pageLayout", "facing")
+            .option("pageNum", 1)
+            .option("zoom", "fit-page")
+            .build();
+        let result = execute_command(&cmd).unwrap();
+        let script = js_bridge::mock::last_script().unwrap();
+        assert_eq!(result.status, ResponseStatus::Success);
+        assert!(script.contains(r#""pageMode":"UseOutlines""#));
+        assert!(script.contains(r#""layout":"TwoPageLeft""#));
+        assert!(script.contains(r#""pageIndex":0"#));
+        assert!(script.contains(r#""zoomType":"FitPage""#));
+        assert!(script.contains(r#"doc.addScript("InitialView", onOpen.join("\n"))"#));
+
+        let script =
+            set_initial_view_script(None, None, None, Some(150.0), None, json!({})).render();
+        assert!(script.contains(r#""zoom":150.0"#));
+        assert!(script.contains(r#""pageMode":null"#));
+    }
+
+    #[test]
+    fn test_set_initial_view_validation() {
+        for (key, value, message) in [
+            ("pageMode", json!("outline"), "Unknown pageMode"),
+            ("pageLayout", json!("twoup"), "Unknown pageLayout"),
+            ("pageNum", json!(0), "pageNum must be a page number"),
+            ("zoom", json!(-10), "zoom must be a positive percentage"),
+        ] {
+            let cmd = CommandBuilder::new("setInitialView")
+                .option(key, value)
+                .build();
+            let result = execute_command(&cmd).unwrap();
+            assert_eq!(result.status, ResponseStatus::Failure);
+            assert!(result.message.unwrap().contains(message));
+        }
+
+        let cmd = CommandBuilder::new("setInitialView").build();
+        let result = execute_command(&cmd).unwrap();
+        assert!(result.message.unwrap().contains("at least one of"));
+    }
+
+    #[test]
+    #[cfg(not(feature = "acrobat-sdk"))]
+    fn test_get_layers_script() {
+        let script = get_layers_script().render();
+        assert!(script.contains("this.getOCGs() || []"));
+        assert!(script.contains(r#""visible": ocgs[i].state"#));
+
+        js_bridge::mock::set_response(
+            js_bridge::mock::Matcher::Contains("getOCGs".into()),
+            js_bridge::JsResult::success(
+                r#"{"success": true, "layers": [{"name": "Notes", "visible": false}]}"#,
+            ),
+        );
+        let cmd = CommandBuilder::new("getLayers").build();
+        let result = execute_command(&cmd).unwrap();
+        js_bridge::mock::clear();
+        assert_eq!(result.status, ResponseStatus::Success);
+        assert_eq!(result.response.unwrap()["layers"][0]["name"], "Notes");
+    }
+
+    #[test]
+    fn test_set_layer_visibility_script() {
+        let script = set_layer_visibility_script("Watermark", false).render();
+        assert!(script.contains(r#""name":"Watermark""#));
+        assert!(script.contains(r#""visible":false"#));
+        assert!(script.contains("ocgs[i].state = args.visible"));
+        assert!(script.contains("Layer not found"));
+
+        let cmd = CommandBuilder::new("setLayerVisibility").option("name", "Watermark").build();
+        let result = execute_command(&cmd).unwrap();
+        assert_eq!(result.status, ResponseStatus::Failure);
+        assert!(result.message.unwrap().contains("visible required"));
+    }
+
+    #[test]
+    fn test_compare_documents_script() {
+        let script = compare_documents_script("C:\\docs\\v1.pdf", "C:\\docs\\v2.pdf", "text");
+        assert_eq!(script.arg("basePath"), Some(&json!("C:/docs/v1.pdf")));
+        assert_eq!(script.arg("comparePath"), Some(&json!("C:/docs/v2.pdf")));
+        let rendered = script.render();
+        assert!(rendered.contains("app.openDoc({cPath: path, bHidden: true})"));
+        assert!(rendered.contains("pageText(base, p) === pageText(other, p)"));
+        assert!(rendered.contains("opened[c].closeDoc(true)"));
+        for key in [
+            "basePages",
+            "comparePages",
+            "pagesAdded",
+            "pagesRemoved",
+            "differingPages",
+            "identical",
+        ] {
+            assert!(
+                rendered.contains(&format!(r#""{}":"#, key)),
+                "missing {}",
+                key
+            );
+        }
+    }
+
+    #[test]
+    #[cfg(not(feature = "acrobat-sdk"))]
+    fn test_compare_documents_report() {
+        js_bridge::mock::set_response(
+            js_bridge::mock::Matcher::Contains("differingPages".into()),
+            js_bridge::JsResult::success(
+                r#"{"success": true, "mode": "text", "basePages": 2, "comparePages": 3, "pagesAdded": [3], "pagesRemoved": [], "pages": [{"page": 1, "identical": true}, {"page": 2, "identical": false}], "differingPages": [2], "identical": false}"#,
+            ),
+        );
+        let cmd = CommandBuilder::new("compareDocuments")
+            .option("basePath", "/docs/v1.pdf")
+            .option("comparePath", "/docs/v2.pdf")
+            .option("documentPath", "/docs/other.pdf")
+            .build();
+        let result = execute_command(&cmd).unwrap();
+        js_bridge::mock::clear();
+        assert_eq!(result.status, ResponseStatus::Success);
+        let report = result.response.unwrap();
+        assert_eq!(report["pagesAdded"], json!([3]));
+        assert_eq!(report["pages"][1]["identical"], false);
+        assert_eq!(report["identical"], false);
+
+        let cmd = CommandBuilder::new("compareDocuments")
+            .option("basePath", "/docs/v1.pdf")
+            .option("comparePath", "/docs/v2.pdf")
+            .option("mode", "visual")
+            .build();
+        let result = execute_command(&cmd).unwrap();
+        assert!(result.message.unwrap().contains("Unknown mode"));
+
+        let cmd = CommandBuilder::new("compareDocuments")
+            .option("basePath", "/docs/v1.pdf")
+            .build();
+        let result = execute_command(&cmd).unwrap();
+        assert!(result.message.unwrap().contains("comparePath required"));
+    }
+
+    #[test]
+    fn test_split_by_bookmarks_missing_output_dir() {
+        let cmd = Command {
+            action: "splitByBookmarks".to_string(),
+            options: json!({}),
+        };
+        let result = execute_command(&cmd).unwrap();
+        assert_eq!(result.status, ResponseStatus::Failure);
+        assert!(result.message.unwrap().contains("outputDir required"));
     }
 
-    Ok(page_nums)
-}
+    #[test]
+    fn test_split_by_bookmarks_script() {
+        let script = split_by_bookmarks_script("C:\\out", true);
+        assert_eq!(script.arg("outputDir"), Some(&json!("C:/out")));
+        assert_eq!(script.arg("sanitize"), Some(&json!(true)));
+        let js = script.render();
+        assert!(js.contains("doc.bookmarkRoot"));
+        assert!(js.contains(".execute()"));
+        assert!(js.contains(r#"files.push({"title": sections[j].title, "path": outputPath})"#));
+        assert!(js.contains("skipped.push(children[i].name)"));
+        assert!(js.contains("No top-level bookmark goes to a page"));
 
-fn parse_page_range_str(range: &str) -> Option<(i64, i64)> {
-    let trimmed = range.trim();
-    if trimmed.is_empty() {
-        return None;
+        let script = split_by_bookmarks_script("/out", false);
+        assert_eq!(script.arg("sanitize"), Some(&json!(false)));
     }
-    if trimmed.eq_ignore_ascii_case("all") {
-        return Some((0, -1));
+
+    #[test]
+    fn test_split_by_bookmarks_hostile_dir_is_data() {
+        let script = split_by_bookmarks_script(r#"C:\out");app.alert("pwned"#, true);
+        assert_eq!(script.arg("outputDir"), Some(&json!(r#"C:/out");app.alert("pwned"#)));
+
+        let rendered = script.render();
+        let (_, body) = rendered.split_once(";\n").unwrap();
+        assert!(!body.contains("pwned"));
+        assert!(body.contains("var outputDir = args.outputDir;"));
     }
-    if let Some((start_raw, end_raw)) = trimmed.split_once('-') {
-        let start = start_raw.trim().parse::<i64>().ok()?;
-        let end = end_raw.trim().parse::<i64>().ok()?;
-        let start_idx = normalize_page_index(start);
-        let end_idx = normalize_page_index(end);
-        return Some((start_idx, end_idx.max(start_idx)));
+
+    #[test]
+    fn test_merge_documents_script_bookmarks() {
+        let script = merge_documents_script(&["/a.pdf", "/b.pdf"], "/out.pdf", true, &["Intro"]);
+        assert_eq!(script.arg("preserveBookmarks"), Some(&json!(true)));
+        assert_eq!(script.arg("titles"), Some(&json!(["Intro"])));
+        assert_eq!(script.arg("paths"), Some(&json!(["/a.pdf", "/b.pdf"])));
+        let js = script.render();
+        assert!(js.contains("offsets.push(doc.numPages)"));
+        assert!(js.contains("bookmarkRoot.createChild"));
+
+        let script = merge_documents_script(&["/a.pdf"], "/out.pdf", false, &[]);
+        assert_eq!(script.arg("preserveBookmarks"), Some(&json!(false)));
+        assert_eq!(script.arg("titles"), Some(&json!([])));
     }
-    if let Ok(value) = trimmed.parse::<i64>() {
-        let idx = normalize_page_index(value);
-        return Some((idx, idx));
+
+    #[test]
+    fn test_merge_documents_script_is_injection_safe() {
+        let title = "Intro\u{2028}</script>\");app.alert(\"pwned";
+        let script = merge_documents_script(&["C:\\in\\a.pdf"], "/out.pdf", true, &[title]);
+        assert_eq!(script.arg("titles"), Some(&json!([title])));
+        assert_eq!(script.arg("paths"), Some(&json!(["C:\\in\\a.pdf"])));
+
+        let rendered = script.render();
+        let (_, body) = rendered.split_once(";\n").unwrap();
+        assert!(!body.contains("pwned"));
+        assert!(!rendered.contains('\u{2028}'));
+        assert!(!rendered.contains("</script"));
+        assert!(body.contains("var titles = args.titles;"));
     }
-    None
-}
 
-fn parse_page_range(options: &Value) -> (i64, i64) {
-    if let Some(range) = options.get("pageRange").and_then(|v| v.as_str()) {
-        if let Some(parsed) = parse_page_range_str(range) {
-            return parsed;
-        }
+    #[test]
+    fn test_parse_page_range_str() {
+        let spans = parse_page_range_str("1-3,7,9-").unwrap();
+        assert_eq!(
+            spans,
+            vec![
+                json!({"start": 0, "end": 2}),
+                json!({"start": 6, "end": 6}),
+                json!({"start": 8, "end": -1}),
+            ]
+        );
+        assert_eq!(parse_page_range_str("all").unwrap(), vec![json!({"start": 0, "end": -1})]);
+        assert!(parse_page_range_str("0").is_err());
     }
 
-    let start = options.get("pageStart").and_then(|v| v.as_i64()).unwrap_or(0);
-    let end = options.get("pageEnd").and_then(|v| v.as_i64()).unwrap_or(-1);
+    #[test]
+    fn test_parse_page_range_fallback_to_start_end() {
+        let spans = parse_page_range(&json!({"pageStart": 2, "pageEnd": 4})).unwrap();
+        assert_eq!(spans, vec![json!({"start": 1, "end": 3})]);
+    }
 
-    (normalize_page_index(start), if end < 0 { -1 } else { normalize_page_index(end) })
-}
+    #[test]
+    fn test_extract_text_invalid_range() {
+        let cmd = Command {
+            action: "extractText".to_string(),
+            options: json!({"pageRange": "5-2"}),
+        };
+        let result = execute_command(&cmd).unwrap();
+        assert_eq!(result.status, ResponseStatus::Failure);
+        assert!(result.message.unwrap().contains("Invalid page range"));
+    }
 
-fn normalize_page_ranges(value: &Value) -> Result<Vec<Value>> {
-    let ranges = value
-        .as_array()
-        .ok_or_else(|| anyhow::anyhow!("pageRanges array required"))?;
-    let mut normalized = Vec::new();
+    #[test]
+    fn test_normalize_page_ranges_rejects_lists() {
+        assert!(normalize_page_ranges(&json!(["1-3,5"])).is_err());
+        let ranges = normalize_page_ranges(&json!(["1-3", [4, 6], {"start": 7}])).unwrap();
+        assert_eq!(ranges.len(), 3);
+        assert_eq!(ranges[1], json!({"start": 3, "end": 5}));
+    }
 
-    for range in ranges {
-        if let Some(range_str) = range.as_str() {
-            if let Some((start, end)) = parse_page_range_str(range_str) {
-                normalized.push(json!({"start": start, "end": end}));
-            }
-            continue;
-        }
+    #[test]
+    fn test_open_document_script_hostile_path_is_data() {
+        let hostile = r#"C:\docs\");app.alert("pwned.pdf"#;
+        let script = open_document_script(hostile);
+        assert_eq!(
+            script.arg("filePath"),
+            Some(&json!(r#"C:/docs/");app.alert("pwned.pdf"#))
+        );
 
-        if let Some(range_array) = range.as_array() {
-            if range_array.is_empty() {
-                continue;
-            }
-            let start = range_array.first().and_then(|v| v.as_i64()).unwrap_or(0);
-            let end = range_array
-                .get(1)
-                .and_then(|v| v.as_i64())
-                .unwrap_or(start);
-            let start_idx = normalize_page_index(start);
-            let end_idx = normalize_page_index(end).max(start_idx);
-            normalized.push(json!({"start": start_idx, "end": end_idx}));
-            continue;
-        }
+        // The only code is the fixed template; the payload appears solely in the args JSON
+        let rendered = script.render();
+        let (preamble, body) = rendered.split_once(";\n").unwrap();
+        assert!(preamble.starts_with("(function() {\nvar args = "));
+        assert!(!body.contains("pwned"));
+        assert!(body.contains("app.openDoc(args.filePath)"));
+    }
 
-        if let Some(range_obj) = range.as_object() {
-            let start = range_obj
-                .get("start")
-                .and_then(|v| v.as_i64())
-                .unwrap_or(0);
-            let end = range_obj
-                .get("end")
-                .and_then(|v| v.as_i64())
-                .unwrap_or(start);
-            let start_idx = normalize_page_index(start);
-            let end_idx = normalize_page_index(end).max(start_idx);
-            normalized.push(json!({"start": start_idx, "end": end_idx}));
-        }
+    #[test]
+    fn test_save_document_script_optional_path() {
+        assert_eq!(save_document_script(None, None).arg("filePath"), Some(&Value::Null));
+        assert_eq!(
+            save_document_script(Some("C:\\out.pdf"), None).arg("filePath"),
+            Some(&json!("C:/out.pdf"))
+        );
     }
 
-    if normalized.is_empty() {
-        return Err(anyhow::anyhow!("pageRanges array required"));
+    #[test]
+    fn test_save_document_format_selects_conversion_id() {
+        assert_eq!(save_conversion_id("PDF").unwrap(), None);
+        assert_eq!(save_conversion_id("PDF_A").unwrap(), Some("com.adobe.acrobat.pdfa"));
+        assert_eq!(save_conversion_id("pdf/x").unwrap(), Some("com.adobe.acrobat.pdfx"));
+        assert!(save_conversion_id("DOCX").is_err());
+
+        let script = save_document_script(Some("C:/out.pdf"), Some(PDFA_CONVERSION_ID));
+        assert_eq!(script.arg("conversionId"), Some(&json!("com.adobe.acrobat.pdfa")));
+        assert!(script.render().contains("args.conversionId"));
     }
 
-    Ok(normalized)
-}
+    #[test]
+    fn test_convert_to_pdfa_script() {
+        let version = pdfa_version(&json!({"version": "2B"})).unwrap();
+        let script = convert_to_pdfa_script("C:\\out\\a.pdf", &version);
+        assert_eq!(script.arg("conversionId"), Some(&json!("com.adobe.acrobat.pdfa")));
+        assert_eq!(script.arg("profile"), Some(&json!("Convert to PDF/A-2b")));
+        assert_eq!(script.arg("filePath"), Some(&json!("C:/out/a.pdf")));
+        assert!(script.render().contains("doc.saveAs(args.filePath, args.conversionId)"));
 
-/// Execute JavaScript and parse the result, falling back to default on error
-fn execute_js_and_parse<F>(script: &str, default_fn: F) -> Result<Value>
-where
-    F: FnOnce() -> Value,
-{
-    match js_bridge::execute_js(script) {
-        Ok(result) => {
-            if result.success {
-                if let Some(value) = result.value {
-                    // Try to parse as JSON
-                    match serde_json::from_str::<Value>(&value) {
-                        Ok(parsed) => {
-                            // Check if the JS reported success
-                            if parsed
-                                .get("success")
-                                .and_then(|v| v.as_bool())
-                                .unwrap_or(true)
-                            {
-                                Ok(parsed)
-                            } else {
-                                let error = parsed
-                                    .get("error")
-                                    .and_then(|v| v.as_str())
-                                    .unwrap_or("Unknown error");
-                                Err(anyhow::anyhow!("JavaScript error: {}", error))
-                            }
-                        }
-                        Err(_) => {
-                            // Return raw value as string
-                            Ok(json!({"result": value}))
-                        }
-                    }
-                } else {
-                    Ok(default_fn())
-                }
-            } else {
-                Err(anyhow::anyhow!(
-                    "JavaScript execution failed: {}",
-                    result.error.unwrap_or_default()
-                ))
-            }
-        }
-        Err(e) => {
-            tracing::warn!("JS execution error, using default: {}", e);
-            Ok(default_fn())
-        }
+        assert_eq!(pdfa_version(&json!({})).unwrap(), "1b");
+        assert!(pdfa_version(&json!({"version": "4b"})).is_err());
     }
-}
 
-/// Escape a string for use in JavaScript
-fn escape_js_string(s: &str) -> String {
-    s.replace('\\', "\\\\")
-        .replace('"', "\\\"")
-        .replace('\n', "\\n")
-        .replace('\r', "\\r")
-        .replace('\t', "\\t")
-}
+    #[test]
+    fn test_insert_blank_page_script() {
+        let script = insert_blank_page_script(Some(0), 595.0, 842.0);
+        assert_eq!(script.arg("afterPage"), Some(&json!(0)));
+        assert_eq!(script.arg("width"), Some(&json!(595.0)));
+        assert!(script.render().contains("doc.newPage({nPage: after, nWidth: args.width, nHeight: args.height})"));
+        assert_eq!(insert_blank_page_script(None, 612.0, 792.0).arg("afterPage"), Some(&Value::Null));
 
-/// Escape a file path for use in JavaScript
-fn escape_js_path(path: &str) -> String {
-    // Convert backslashes to forward slashes for cross-platform compatibility
-    path.replace('\\', "/")
-}
+        assert_eq!(requested_page_size(&json!({"pageSize": "A4"})), (595.0, 842.0));
+        assert_eq!(
+            requested_page_size(&json!({"pageSize": "CUSTOM", "width": 300, "height": 400})),
+            (300.0, 400.0)
+        );
+        assert_eq!(requested_page_size(&json!({})), (612.0, 792.0));
 
-/// Get page width for a named page size
-fn page_size_width(size: &str) -> f64 {
-    match size.to_uppercase().as_str() {
-        "LETTER" => 612.0,
-        "LEGAL" => 612.0,
-        "A4" => 595.0,
-        "A3" => 842.0,
-        "TABLOID" => 792.0,
-        _ => 612.0, // Default to letter
+        let cmd = Command {
+            action: "insertBlankPage".to_string(),
+            options: json!({"afterPage": -1}),
+        };
+        assert_eq!(execute_command(&cmd).unwrap().status, ResponseStatus::Failure);
     }
-}
 
-/// Get page height for a named page size
-fn page_size_height(size: &str) -> f64 {
-    match size.to_uppercase().as_str() {
-        "LETTER" => 792.0,
-        "LEGAL" => 1008.0,
-        "A4" => 842.0,
-        "A3" => 1191.0,
-        "TABLOID" => 1224.0,
-        _ => 792.0, // Default to letter
-    }
-}
+    #[test]
+    fn test_duplicate_page_script() {
+        let script = duplicate_page_script(3, 5);
+        assert_eq!(script.arg("page"), Some(&json!(3)));
+        assert_eq!(script.arg("afterPage"), Some(&json!(5)));
+        let rendered = script.render();
+        assert!(rendered.contains("doc.insertPages({"));
+        assert!(rendered.contains("cPath: doc.path"));
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+        // Without afterPage the copy lands right after the original
+        assert_eq!(after_page_option(&json!({"page": 2})).unwrap(), None);
+        assert_eq!(after_page_option(&json!({"afterPage": 0})).unwrap(), Some(0));
 
-    #[test]
-    fn test_escape_js_string() {
-        assert_eq!(escape_js_string("hello"), "hello");
-        assert_eq!(escape_js_string("hello\"world"), "hello\\\"world");
-        assert_eq!(escape_js_string("line1\nline2"), "line1\\nline2");
-        assert_eq!(escape_js_string("path\\to\\file"), "path\\\\to\\\\file");
+        let cmd = Command {
+            action: "duplicatePage".to_string(),
+            options: json!({"page": 0}),
+        };
+        let result = execute_command(&cmd).unwrap();
+        assert_eq!(result.status, ResponseStatus::Failure);
+        assert!(result.message.unwrap().contains("page must be a page number"));
     }
 
     #[test]
-    fn test_escape_js_path() {
-        assert_eq!(escape_js_path("C:\\Users\\test"), "C:/Users/test");
-        assert_eq!(escape_js_path("/home/test"), "/home/test");
+    fn test_replace_page_script() {
+        let script = replace_page_script(2, "C:\\scans\\fixed.pdf", 1);
+        assert_eq!(script.arg("sourcePath"), Some(&json!("C:/scans/fixed.pdf")));
+        assert_eq!(script.arg("sourcePage"), Some(&json!(1)));
+        assert!(script.render().contains("doc.replacePages({"));
+
+        let cmd = Command {
+            action: "replacePage".to_string(),
+            options: json!({"page": 1}),
+        };
+        let result = execute_command(&cmd).unwrap();
+        assert_eq!(result.status, ResponseStatus::Failure);
+        assert!(result.message.unwrap().contains("sourcePath required"));
+
+        let cmd = Command {
+            action: "replacePage".to_string(),
+            options: json!({"page": 1, "sourcePath": "/x.pdf", "sourcePage": 0}),
+        };
+        assert_eq!(execute_command(&cmd).unwrap().status, ResponseStatus::Failure);
     }
 
     #[test]
-    fn test_page_sizes() {
-        assert_eq!(page_size_width("LETTER"), 612.0);
-        assert_eq!(page_size_height("LETTER"), 792.0);
-        assert_eq!(page_size_width("A4"), 595.0);
-        assert_eq!(page_size_height("A4"), 842.0);
+    fn test_extract_pages_script() {
+        let script = extract_pages_script(json!({"start": 4, "end": 7}), "C:\\out\\pages.pdf");
+        assert_eq!(script.arg("range"), Some(&json!({"start": 4, "end": 7})));
+        assert_eq!(script.arg("outputPath"), Some(&json!("C:/out/pages.pdf")));
+        let rendered = script.render();
+        assert!(rendered.contains("doc.extractPages({nStart: start, nEnd: end, cPath: args.outputPath})"));
+        assert!(!rendered.contains("deletePages"));
+
+        assert_eq!(parse_page_range(&json!({"pageRange": "5-8"})).unwrap(), vec![json!({"start": 4, "end": 7})]);
+
+        let cmd = Command {
+            action: "extractPages".to_string(),
+            options: json!({"pageRange": "1-2,5", "outputPath": "/out.pdf"}),
+        };
+        let result = execute_command(&cmd).unwrap();
+        assert_eq!(result.status, ResponseStatus::Failure);
+        assert!(result.message.unwrap().contains("single contiguous page range"));
+
+        let cmd = Command {
+            action: "extractPages".to_string(),
+            options: json!({"pageRange": "5-8"}),
+        };
+        assert!(execute_command(&cmd).unwrap().message.unwrap().contains("outputPath required"));
     }
 
     #[test]
-    fn test_execute_command_unknown() {
+    fn test_linearize_document() {
+        let script = linearize_document_script("C:\\web\\doc.pdf");
+        assert_eq!(script.arg("outputPath"), Some(&json!("C:/web/doc.pdf")));
+        assert!(script.render().contains("doc.saveAs({\"cPath\": args.outputPath"));
+
         let cmd = Command {
-            action: "unknownCommand".to_string(),
-            options: json!({}),
+            action: "linearizeDocument".to_string(),
+            options: json!({"outputPath": " "}),
         };
         let result = execute_command(&cmd).unwrap();
         assert_eq!(result.status, ResponseStatus::Failure);
-        assert!(result.message.is_some());
+        assert!(result.message.unwrap().contains("outputPath required"));
     }
 
     #[test]
-    fn test_execute_command_get_page_count() {
+    #[cfg(not(feature = "acrobat-sdk"))]
+    fn test_sanitize_document_defaults_to_everything() {
+        js_bridge::mock::set_response(
+            js_bridge::mock::Matcher::Contains("removeDataObject".into()),
+            js_bridge::JsResult::success(r#"{"success": true, "dryRun": true, "removed": {}}"#),
+        );
+        let cmd = CommandBuilder::new("sanitizeDocument")
+            .option("attachments", false)
+            .option("dryRun", true)
+            .build();
+        let result = execute_command(&cmd).unwrap();
+        let script = js_bridge::mock::last_script().unwrap();
+        js_bridge::mock::clear();
+
+        assert_eq!(result.status, ResponseStatus::Success);
+        assert!(script.contains(
+            r#""categories":{"attachments":false,"hiddenLayers":true,"javascript":true,"metadata":true}"#
+        ));
+        assert!(script.contains(r#""dryRun":true"#));
+        assert!(script.contains(r#""documentScripts":["InitialView"]"#));
+
+        let cmd = CommandBuilder::new("sanitizeDocument")
+            .option("metadata", "yes")
+            .build();
+        assert_eq!(execute_command(&cmd).unwrap().status, ResponseStatus::Failure);
+    }
+
+    #[test]
+    fn test_sanitize_dry_run_does_not_report_actions() {
+        let js = sanitize_document_script(json!({"javascript": true}), true).render();
+        let (_, body) = js.split_once("if (args.categories.javascript) {").unwrap();
+        let (dry_run, real_run) = body.split_once("} else {").unwrap();
+        assert!(!dry_run.contains("cleared.push"));
+        assert!(dry_run.contains("notRemoved.javascript"));
+        assert!(real_run.contains("doc.addScript(args.documentScripts[ds], \"\")"));
+    }
+
+    #[test]
+    fn test_check_compliance_script() {
+        let script = check_compliance_script("3u");
+        assert_eq!(script.arg("profile"), Some(&json!("Verify compliance with PDF/A-3u")));
+        assert!(script.render().contains("doc.preflight(profile)"));
+    }
+
+    #[test]
+    fn test_add_text_hostile_text_succeeds() {
         let cmd = Command {
-            action: "getPageCount".to_string(),
-            options: json!({}),
+            action: "addText".to_string(),
+            options: json!({"text": "\");app.alert(\"pwned", "fontName": "\"}); x({"}),
         };
         let result = execute_command(&cmd).unwrap();
         assert_eq!(result.status, ResponseStatus::Success);
     }
 
     #[test]
-    fn test_open_document_missing_path() {
+    #[cfg(not(feature = "acrobat-sdk"))]
+    fn test_add_text_reports_script_failure() {
+        js_bridge::mock::set_response(
+            js_bridge::mock::Matcher::Contains("addAnnot".into()),
+            js_bridge::JsResult::success(r#"{"success":false,"error":"page out of range"}"#),
+        );
         let cmd = Command {
-            action: "openDocument".to_string(),
-            options: json!({}),
+            action: "addText".to_string(),
+            options: json!({"text": "hello", "page": 99}),
         };
         let result = execute_command(&cmd).unwrap();
         assert_eq!(result.status, ResponseStatus::Failure);
-        assert!(result.message.unwrap().contains("filePath required"));
+        assert!(result.message.unwrap().contains("page out of range"));
+
+        let script = js_bridge::mock::last_script().unwrap();
+        assert!(script.contains(r#""pageIndex":98"#));
     }
 
     #[test]
-    fn test_rotate_pages_invalid_angle() {
-        let cmd = Command {
-            action: "rotatePages".to_string(),
-            options: json!({"pages": [0], "angle": 45}),
-        };
+    fn test_add_text_script_annotation_by_default() {
+        let script = add_text_script(&json!({"text": "hi", "x": 10, "y": 20})).unwrap();
+        let rendered = script.render();
+        assert!(rendered.contains(r#"type: "FreeText""#));
+        assert!(!rendered.contains("addWatermarkFromText"));
+        assert_eq!(script.arg("mode"), Some(&json!("annotation")));
+        assert_eq!(script.arg("rect"), Some(&json!([10.0, 20.0, 210.0, 38.0])));
+    }
+
+    #[test]
+    fn test_add_text_script_content_mode() {
+        let script =
+            add_text_script(&json!({"text": "hi", "page": 2, "mode": "content"})).unwrap();
+        let rendered = script.render();
+        assert!(rendered.contains("doc.addWatermarkFromText"));
+        assert!(rendered.contains("nOpacity: 1.0"));
+        assert!(!rendered.contains("addAnnot"));
+        assert_eq!(script.arg("pageIndex"), Some(&json!(1)));
+    }
+
+    #[test]
+    fn test_add_text_script_color_align_rotation() {
+        let script = add_text_script(&json!({
+            "text": "hi",
+            "color": "#FF0000",
+            "textAlign": "center",
+            "rotation": 90
+        }))
+        .unwrap();
+        assert_eq!(script.arg("color"), Some(&json!(["RGB", 1.0, 0.0, 0.0])));
+        assert_eq!(script.arg("alignment"), Some(&json!(1)));
+        assert_eq!(script.arg("rotation"), Some(&json!(90)));
+
+        let rendered = script.render();
+        assert!(rendered.contains(r#""color":["RGB",1.0,0.0,0.0]"#));
+        assert!(rendered.contains("props.textColor = args.color"));
+        assert!(rendered.contains("props.alignment = args.alignment"));
+    }
+
+    #[test]
+    fn test_add_text_script_defaults_unchanged() {
+        let script = add_text_script(&json!({"text": "hi"})).unwrap();
+        assert_eq!(script.arg("color"), Some(&Value::Null));
+        assert_eq!(script.arg("alignment"), Some(&Value::Null));
+        assert_eq!(script.arg("rotation"), Some(&Value::Null));
+    }
+
+    #[test]
+    fn test_add_text_script_rejects_bad_styles() {
+        assert!(add_text_script(&json!({"text": "a", "textAlign": "justify"})).is_err());
+        assert!(add_text_script(&json!({"text": "a", "rotation": 45})).is_err());
+        assert!(add_text_script(&json!({"text": "a", "color": "#12"})).is_err());
+        assert!(add_text_script(&json!({"text": "a", "color": {"red": 300, "green": 0, "blue": 0}})).is_err());
+    }
+
+    #[test]
+    fn test_acrobat_color_forms() {
+        let color = acrobat_color(&json!({"red": 0, "green": 255, "blue": 0})).unwrap();
+        assert_eq!(color, json!(["RGB", 0.0, 1.0, 0.0]));
+        let color = acrobat_color(&json!(["CMYK", 0.0, 1.0, 1.0, 0.0])).unwrap();
+        assert_eq!(color, json!(["CMYK", 0.0, 1.0, 1.0, 0.0]));
+        let color = acrobat_color(&json!(["G", 0.25])).unwrap();
+        assert_eq!(color, json!(["G", 0.25]));
+    }
+
+    #[test]
+    fn test_add_text_invalid_mode() {
+        let err = add_text_script(&json!({"text": "hi", "mode": "stamp"})).unwrap_err();
+        assert!(err.to_string().contains("Invalid mode"));
+    }
+
+    #[test]
+    fn test_normalize_page_numbers_explicit_base() {
+        let pages = json!([1, 2, 5]);
+        assert_eq!(normalize_page_numbers(&pages, Some(0)).unwrap(), vec![1, 2, 5]);
+        assert_eq!(normalize_page_numbers(&pages, Some(1)).unwrap(), vec![0, 1, 4]);
+
+        let err = normalize_page_numbers(&json!([0, 3]), Some(1)).unwrap_err();
+        assert!(err.to_string().contains("Page number 0 is invalid with pageBase 1"));
+        assert!(normalize_page_numbers(&json!([-1]), Some(0)).is_err());
+
+        assert_eq!(page_base_option(&json!({})).unwrap(), None);
+        assert_eq!(page_base_option(&json!({"pageBase": 0})).unwrap(), Some(0));
+        for bad in [json!(2), json!("1"), json!(-1)] {
+            assert!(page_base_option(&json!({"pageBase": bad})).is_err(), "{}", bad);
+        }
+    }
+
+    #[test]
+    fn test_normalize_page_numbers_heuristic_without_base() {
+        assert_eq!(normalize_page_numbers(&json!([1, 3]), None).unwrap(), vec![0, 2]);
+        assert_eq!(normalize_page_numbers(&json!([0, 3]), None).unwrap(), vec![0, 3]);
+    }
+
+    #[test]
+    #[cfg(not(feature = "acrobat-sdk"))]
+    fn test_page_base_reaches_page_commands() {
+        js_bridge::mock::clear();
+        let cmd = CommandBuilder::new("deletePages")
+            .option("pages", json!([1, 2]))
+            .option("pageBase", 0)
+            .build();
+        assert_eq!(execute_command(&cmd).unwrap().status, ResponseStatus::Success);
+        assert!(js_bridge::mock::last_script().unwrap().contains("var pages = [1,2];"));
+
+        let cmd = CommandBuilder::new("rotatePages")
+            .option("pageNumbers", json!([0]))
+            .option("pageBase", 1)
+            .build();
         let result = execute_command(&cmd).unwrap();
         assert_eq!(result.status, ResponseStatus::Failure);
-        assert!(result.message.unwrap().contains("Invalid angle"));
+        assert!(result.message.unwrap().contains("pageBase 1"));
     }
 
     #[test]
@@ -1135,4 +5080,37 @@ mod tests {
         let result = execute_command(&cmd).unwrap();
         assert_eq!(result.status, ResponseStatus::Failure);
     }
+
+    #[test]
+    fn test_execute_javascript_disabled_by_default() {
+        assert!(!crate::PluginState::default().allow_raw_js);
+
+        let cmd = CommandBuilder::new("executeJavaScript").option("script", "this.numPages").build();
+        let result = execute_command(&cmd).unwrap();
+        assert_eq!(result.status, ResponseStatus::Failure);
+        assert_eq!(result.message.as_deref(), Some(RAW_JS_DISABLED));
+    }
+
+    #[test]
+    #[cfg(not(feature = "acrobat-sdk"))]
+    fn test_run_raw_javascript_when_allowed() {
+        js_bridge::mock::set_response(
+            js_bridge::mock::Matcher::Exact("this.numPages".into()),
+            js_bridge::JsResult::success("12"),
+        );
+        let result = run_raw_javascript(&json!({"script": "this.numPages"}), true).unwrap();
+        assert_eq!(js_bridge::mock::last_script().as_deref(), Some("this.numPages"));
+        assert_eq!(result, json!({"result": "12"}));
+
+        js_bridge::mock::set_response(
+            js_bridge::mock::Matcher::Contains("throw".into()),
+            js_bridge::JsResult::failure("ReferenceError: nope"),
+        );
+        let err = run_raw_javascript(&json!({"script": "throw nope"}), true).unwrap_err();
+        js_bridge::mock::clear();
+        assert!(err.to_string().contains("ReferenceError: nope"));
+
+        let err = run_raw_javascript(&json!({"script": "  "}), true).unwrap_err();
+        assert!(err.to_string().contains("script required"));
+    }
 }
@@ -38,6 +38,41 @@ pub enum BridgeError {
     Io(String),
 }
 
+impl BridgeError {
+    /// Whether the failure came from the transport, so resending the same
+    /// command after a reconnect may succeed
+    pub fn is_retryable(&self) -> bool {
+        matches!(
+            self,
+            Self::ConnectionFailed(_)
+                | Self::SendFailed(_)
+                | Self::ReceiveFailed(_)
+                | Self::Timeout(_)
+        )
+    }
+
+    /// Stable numeric code for reporting across the FFI boundary
+    ///
+    /// Codes start at 1; 0 is reserved for "no error".
+    pub fn code(&self) -> i32 {
+        match self {
+            Self::ConnectionFailed(_) => 1,
+            Self::SendFailed(_) => 2,
+            Self::ReceiveFailed(_) => 3,
+            Self::CommandFailed(_) => 4,
+            Self::JsExecutionFailed(_) => 5,
+            Self::InvalidCommand(_) => 6,
+            Self::Timeout(_) => 7,
+            Self::NotInitialized => 8,
+            Self::AlreadyInitialized => 9,
+            Self::Serialization(_) => 10,
+            Self::Deserialization(_) => 11,
+            Self::InvalidState(_) => 12,
+            Self::Io(_) => 13,
+        }
+    }
+}
+
 impl fmt::Display for BridgeError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
@@ -84,6 +119,12 @@ impl From<tokio::sync::mpsc::error::SendError<String>> for BridgeError {
     }
 }
 
+impl From<anyhow::Error> for BridgeError {
+    fn from(err: anyhow::Error) -> Self {
+        BridgeError::CommandFailed(format!("{:#}", err))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -102,6 +143,64 @@ mod tests {
         assert!(matches!(bridge_err, BridgeError::Serialization(_)));
     }
 
+    #[test]
+    fn test_error_from_anyhow() {
+        let err: BridgeError = anyhow::anyhow!("filePath required")
+            .context("openDocument")
+            .into();
+        match err {
+            BridgeError::CommandFailed(msg) => assert_eq!(msg, "openDocument: filePath required"),
+            other => panic!("expected CommandFailed, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_is_retryable() {
+        let cases = vec![
+            (BridgeError::ConnectionFailed("test".into()), true),
+            (BridgeError::SendFailed("test".into()), true),
+            (BridgeError::ReceiveFailed("test".into()), true),
+            (BridgeError::Timeout("test".into()), true),
+            (BridgeError::CommandFailed("test".into()), false),
+            (BridgeError::JsExecutionFailed("test".into()), false),
+            (BridgeError::InvalidCommand("test".into()), false),
+            (BridgeError::NotInitialized, false),
+            (BridgeError::AlreadyInitialized, false),
+            (BridgeError::Serialization("test".into()), false),
+            (BridgeError::Deserialization("test".into()), false),
+            (BridgeError::InvalidState("test".into()), false),
+            (BridgeError::Io("test".into()), false),
+        ];
+
+        for (err, retryable) in cases {
+            assert_eq!(err.is_retryable(), retryable, "{:?}", err);
+        }
+    }
+
+    #[test]
+    fn test_error_codes_are_distinct() {
+        let errors = [
+            BridgeError::ConnectionFailed("x".into()),
+            BridgeError::SendFailed("x".into()),
+            BridgeError::ReceiveFailed("x".into()),
+            BridgeError::CommandFailed("x".into()),
+            BridgeError::JsExecutionFailed("x".into()),
+            BridgeError::InvalidCommand("x".into()),
+            BridgeError::Timeout("x".into()),
+            BridgeError::NotInitialized,
+            BridgeError::AlreadyInitialized,
+            BridgeError::Serialization("x".into()),
+            BridgeError::Deserialization("x".into()),
+            BridgeError::InvalidState("x".into()),
+            BridgeError::Io("x".into()),
+        ];
+        let mut codes: Vec<i32> = errors.iter().map(BridgeError::code).collect();
+        assert!(codes.iter().all(|&code| code > 0));
+        codes.sort_unstable();
+        codes.dedup();
+        assert_eq!(codes.len(), errors.len());
+    }
+
     #[test]
     fn test_all_error_variants_display() {
         let errors = vec![
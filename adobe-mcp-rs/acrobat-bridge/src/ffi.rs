@@ -6,7 +6,7 @@
 
 use crate::client::ProxyClient;
 use libc::{c_char, c_int, c_void};
-use std::ffi::CStr;
+use std::ffi::{CStr, CString};
 use std::sync::atomic::{AtomicBool, Ordering};
 
 /// Acrobat SDK version we target
@@ -45,6 +45,32 @@ pub struct PluginExportInfo {
     pub handshake_version: u32,
 }
 
+/// Connection diagnostics filled by `GetPluginStatus`
+#[repr(C)]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct PluginStatus {
+    /// 1 when connected to the proxy
+    pub connected: c_int,
+    /// 1 after `PluginInit` succeeded
+    pub initialized: c_int,
+    /// Failed connection attempts since the last successful connect
+    pub reconnect_attempts: u32,
+    /// `BridgeError::code` of the last error, 0 when there is none
+    pub last_error_code: c_int,
+}
+
+impl PluginStatus {
+    /// Snapshot of `state`
+    pub fn from_state(state: &crate::PluginState, initialized: bool) -> Self {
+        Self {
+            connected: c_int::from(state.is_connected()),
+            initialized: c_int::from(initialized),
+            reconnect_attempts: state.reconnect_attempts,
+            last_error_code: state.last_error_code,
+        }
+    }
+}
+
 // ============================================================================
 // Runtime Management
 // ============================================================================
@@ -75,24 +101,30 @@ fn start_client() -> bool {
     };
 
     let state = crate::get_state();
-    let proxy_url = {
+    let (proxy_url, auth_token, resume_token) = {
         let guard = state.lock();
-        guard.proxy_url().to_string()
+        (
+            guard.proxy_url().to_string(),
+            guard.auth_token().map(str::to_string),
+            guard.resume_token.clone(),
+        )
     };
 
     // Spawn the connection task
     let client_state = state.clone();
     runtime.spawn(async move {
-        match ProxyClient::connect(&proxy_url).await {
+        match ProxyClient::connect(&proxy_url, auth_token.as_deref(), resume_token).await {
             Ok(client) => {
                 let mut guard = client_state.lock();
                 guard.client = Some(client);
                 guard.clear_error();
+                guard.reconnect_attempts = 0;
                 tracing::info!("WebSocket client connected to {}", proxy_url);
             }
             Err(e) => {
                 let mut guard = client_state.lock();
-                guard.set_error(format!("Connection failed: {}", e));
+                guard.record_error(&e);
+                guard.reconnect_attempts = guard.reconnect_attempts.saturating_add(1);
             }
         }
     });
@@ -178,6 +210,11 @@ pub extern "C" fn PluginInit() -> c_int {
         return 0; // Failure
     }
 
+    // A missing menu item leaves the bridge usable, so it doesn't fail init
+    if !register_menu() {
+        tracing::warn!("Failed to register the MCP Bridge menu item");
+    }
+
     1 // Success
 }
 
@@ -232,6 +269,143 @@ pub extern "C" fn IsPluginConnected() -> c_int {
     }
 }
 
+/// Fill `out` with the plugin's connection diagnostics
+///
+/// Returns 1 on success, 0 if `out` is null.
+///
+/// # Safety
+/// `out` must be null or point to writable memory for a `PluginStatus`.
+#[no_mangle]
+pub unsafe extern "C" fn GetPluginStatus(out: *mut PluginStatus) -> c_int {
+    if out.is_null() {
+        return 0;
+    }
+
+    let status = {
+        let state = crate::get_state();
+        let guard = state.lock();
+        PluginStatus::from_state(&guard, INITIALIZED.load(Ordering::SeqCst))
+    };
+    // SAFETY: Caller ensures out is valid, we've checked for null
+    out.write(status);
+
+    1
+}
+
+/// Point the plugin at a different proxy
+///
+/// Returns 1 when `url` is a valid `ws://` or `wss://` URL, 0 otherwise. An
+/// existing connection is dropped and re-established against the new URL.
+///
+/// # Safety
+/// `url` must be null or a valid null-terminated C string.
+#[no_mangle]
+pub unsafe extern "C" fn SetProxyUrl(url: *const c_char) -> c_int {
+    if url.is_null() {
+        return 0;
+    }
+
+    // SAFETY: Caller ensures url is valid, we've checked for null
+    let Ok(url) = CStr::from_ptr(url).to_str() else {
+        tracing::error!("Invalid UTF-8 in proxy URL");
+        return 0;
+    };
+
+    let state = crate::get_state();
+    let reconnect = {
+        let mut guard = state.lock();
+        if let Err(e) = guard.set_proxy_url(url) {
+            guard.record_error(&e);
+            return 0;
+        }
+        guard.client.is_some()
+    };
+    tracing::info!("Proxy URL set to {}", url);
+
+    if reconnect {
+        stop_client();
+        state.lock().active = true;
+        if !start_client() {
+            return 0;
+        }
+    }
+
+    1
+}
+
+thread_local! {
+    static PROXY_URL_BUF: std::cell::RefCell<CString> = std::cell::RefCell::new(CString::default());
+}
+
+/// Get the proxy URL the plugin connects to
+///
+/// The string stays valid until the next `GetProxyUrl` call on the same thread.
+#[no_mangle]
+pub extern "C" fn GetProxyUrl() -> *const c_char {
+    let url = crate::get_state().lock().proxy_url().to_string();
+    PROXY_URL_BUF.with(|buf| {
+        let mut buf = buf.borrow_mut();
+        // A URL with an interior NUL cannot be set through SetProxyUrl
+        *buf = CString::new(url).unwrap_or_default();
+        buf.as_ptr()
+    })
+}
+
+/// `NotifyDocumentEvent` kind for Acrobat's `AVDocDidOpen` notification
+pub const DOCUMENT_DID_OPEN: c_int = 0;
+
+/// `NotifyDocumentEvent` kind for Acrobat's `AVDocWillClose` notification
+pub const DOCUMENT_WILL_CLOSE: c_int = 1;
+
+/// `app_event` name for a document event kind
+fn document_event_name(kind: c_int) -> Option<&'static str> {
+    match kind {
+        DOCUMENT_DID_OPEN => Some("documentOpened"),
+        DOCUMENT_WILL_CLOSE => Some("documentWillClose"),
+        _ => None,
+    }
+}
+
+/// Tell the proxy a document was opened or is about to close
+///
+/// Called by the SDK notification wiring with one of the `DOCUMENT_*` kinds
+/// and the document's path. Returns 1 when the event was queued, 0 for an
+/// unknown kind, invalid path, or when not connected.
+///
+/// # Safety
+/// `path` must be null or a valid null-terminated C string.
+#[no_mangle]
+pub unsafe extern "C" fn NotifyDocumentEvent(kind: c_int, path: *const c_char) -> c_int {
+    let Some(event) = document_event_name(kind) else {
+        tracing::warn!("Unknown document event kind: {}", kind);
+        return 0;
+    };
+    if path.is_null() {
+        return 0;
+    }
+
+    // SAFETY: Caller ensures path is valid, we've checked for null
+    let Ok(path) = CStr::from_ptr(path).to_str() else {
+        tracing::error!("Invalid UTF-8 in document path");
+        return 0;
+    };
+
+    let state = crate::get_state();
+    let guard = state.lock();
+    let Some(client) = guard.client.as_ref().filter(|c| c.is_connected()) else {
+        tracing::debug!("Not connected, dropping {} for {}", event, path);
+        return 0;
+    };
+
+    match client.send_event(event, serde_json::json!({ "path": path })) {
+        Ok(()) => 1,
+        Err(e) => {
+            tracing::warn!("Failed to send {}: {}", event, e);
+            0
+        }
+    }
+}
+
 // Thread-local buffer for error messages (avoids static mut)
 thread_local! {
     static LAST_ERROR_BUF: std::cell::RefCell<[u8; 512]> = const { std::cell::RefCell::new([0u8; 512]) };
@@ -260,6 +434,147 @@ pub extern "C" fn PluginGetLastError() -> *const c_char {
     }
 }
 
+// ============================================================================
+// Menu Integration
+// ============================================================================
+
+/// Flip whether the bridge should be connected and return the new setting
+///
+/// The previous error and retry count are cleared either way: a manual
+/// disconnect is not a failure, and a manual connect starts a fresh attempt.
+pub fn toggle_connection(state: &mut crate::PluginState) -> bool {
+    state.active = !state.active;
+    state.clear_error();
+    state.reconnect_attempts = 0;
+    state.active
+}
+
+/// Acrobat SDK menu API, reached through the SDK's HFT glue at link time
+#[cfg(feature = "acrobat-sdk")]
+mod av_menu {
+    use libc::{c_char, c_void};
+
+    pub type AVMenubar = *mut c_void;
+    pub type AVMenu = *mut c_void;
+    pub type AVMenuItem = *mut c_void;
+    pub type AVExecuteProc = extern "C" fn(client_data: *mut c_void);
+
+    /// `APPEND_MENUITEM` from AVExpT.h
+    pub const APPEND_MENUITEM: i32 = 9999;
+
+    extern "C" {
+        pub fn AVAppGetMenubar() -> AVMenubar;
+        pub fn AVMenubarAcquireMenuByName(menubar: AVMenubar, name: *const c_char) -> AVMenu;
+        pub fn AVMenuItemNew(
+            title: *const c_char,
+            name: *const c_char,
+            submenu: AVMenu,
+            long_menus_only: u16,
+            shortcut: c_char,
+            flags: u16,
+            icon: *mut c_void,
+            owner: *mut c_void,
+        ) -> AVMenuItem;
+        pub fn AVMenuItemSetExecuteProc(item: AVMenuItem, proc_: AVExecuteProc, data: *mut c_void);
+        pub fn AVMenuItemSetTitle(item: AVMenuItem, title: *const c_char);
+        pub fn AVMenuAddMenuItem(menu: AVMenu, item: AVMenuItem, position: i32);
+        pub fn AVMenuRelease(menu: AVMenu);
+    }
+}
+
+/// Menu item titles, chosen by whether the bridge is active
+#[cfg(feature = "acrobat-sdk")]
+const MENU_ITEM_CONNECT: &CStr = c"MCP Bridge: Connect";
+#[cfg(feature = "acrobat-sdk")]
+const MENU_ITEM_DISCONNECT: &CStr = c"MCP Bridge: Disconnect";
+
+/// The registered menu item, kept so its title can follow the connection
+#[cfg(feature = "acrobat-sdk")]
+static MENU_ITEM: std::sync::atomic::AtomicPtr<c_void> =
+    std::sync::atomic::AtomicPtr::new(std::ptr::null_mut());
+
+#[cfg(feature = "acrobat-sdk")]
+fn menu_item_title(active: bool) -> *const c_char {
+    let title = if active {
+        MENU_ITEM_DISCONNECT
+    } else {
+        MENU_ITEM_CONNECT
+    };
+    title.as_ptr()
+}
+
+/// Menu callback: connect or disconnect, then retitle the item
+#[cfg(feature = "acrobat-sdk")]
+extern "C" fn on_toggle_connection(_client_data: *mut c_void) {
+    let state = crate::get_state();
+    let connect = toggle_connection(&mut state.lock());
+
+    if connect {
+        if !start_client() {
+            state.lock().set_error("Failed to start WebSocket client");
+        }
+    } else {
+        stop_client();
+    }
+
+    let item = MENU_ITEM.load(Ordering::SeqCst);
+    if !item.is_null() {
+        // SAFETY: `item` was created by AVMenuItemNew and is never released
+        unsafe { av_menu::AVMenuItemSetTitle(item, menu_item_title(connect)) };
+    }
+}
+
+/// Add the "MCP Bridge" connect/disconnect item to Acrobat's Extensions menu
+///
+/// Returns false if the menu could not be found or the item not created.
+#[cfg(feature = "acrobat-sdk")]
+pub fn register_menu() -> bool {
+    use av_menu::*;
+
+    if !MENU_ITEM.load(Ordering::SeqCst).is_null() {
+        return true;
+    }
+
+    let active = crate::get_state().lock().active;
+
+    // SAFETY: Called on Acrobat's main thread during PluginInit, with
+    // NUL-terminated strings that outlive the calls
+    unsafe {
+        let menu = AVMenubarAcquireMenuByName(AVAppGetMenubar(), c"Extensions".as_ptr());
+        if menu.is_null() {
+            return false;
+        }
+
+        let item = AVMenuItemNew(
+            menu_item_title(active),
+            c"MCPB:ToggleConnection".as_ptr(),
+            std::ptr::null_mut(),
+            0,
+            0,
+            0,
+            std::ptr::null_mut(),
+            std::ptr::null_mut(),
+        );
+        if item.is_null() {
+            AVMenuRelease(menu);
+            return false;
+        }
+
+        AVMenuItemSetExecuteProc(item, on_toggle_connection, std::ptr::null_mut());
+        AVMenuAddMenuItem(menu, item, APPEND_MENUITEM);
+        AVMenuRelease(menu);
+        MENU_ITEM.store(item, Ordering::SeqCst);
+    }
+
+    true
+}
+
+/// Without the SDK there is no menu to register
+#[cfg(not(feature = "acrobat-sdk"))]
+pub fn register_menu() -> bool {
+    true
+}
+
 // ============================================================================
 // JavaScript Bridge Exports
 // ============================================================================
@@ -269,6 +584,12 @@ pub extern "C" fn PluginGetLastError() -> *const c_char {
 /// This function is called by the js_bridge module when the acrobat-sdk feature is enabled.
 /// The actual implementation requires linking against the Acrobat SDK.
 ///
+/// # Ownership
+/// By default the returned string is allocated by this plugin and owned by
+/// the caller, who must release it with [`PluginFreeString`]. With the
+/// `sdk-owns-memory` feature the pointer refers to memory the SDK manages;
+/// callers copy it and must not free it.
+///
 /// # Safety
 /// The script pointer must be a valid null-terminated C string.
 /// The caller is responsible for ensuring the pointer is valid.
@@ -302,6 +623,25 @@ pub unsafe extern "C" fn ExecuteJavaScript(script: *const c_char) -> *mut c_char
     std::ptr::null_mut()
 }
 
+/// Free a string returned by [`ExecuteJavaScript`]
+///
+/// Only pass pointers this plugin handed out, once each; null is ignored.
+/// Strings the SDK owns (the `sdk-owns-memory` build) must never be passed.
+///
+/// # Safety
+/// `ptr` must be null or a pointer returned by this plugin that has not
+/// already been freed.
+#[no_mangle]
+pub unsafe extern "C" fn PluginFreeString(ptr: *mut c_char) {
+    if ptr.is_null() {
+        return;
+    }
+
+    // SAFETY: Caller guarantees the pointer came from `CString::into_raw`
+    // in this plugin and is freed only once
+    drop(CString::from_raw(ptr));
+}
+
 // ============================================================================
 // Windows DLL Entry Point
 // ============================================================================
@@ -386,6 +726,127 @@ mod tests {
         assert!(result.is_null());
     }
 
+    #[test]
+    fn test_free_string_null_is_noop() {
+        // SAFETY: Null is explicitly allowed
+        unsafe { PluginFreeString(std::ptr::null_mut()) };
+    }
+
+    #[test]
+    fn test_free_string_releases_plugin_string() {
+        let ptr = CString::new("{\"success\": true}").unwrap().into_raw();
+        // SAFETY: `ptr` came from `CString::into_raw` and is freed once
+        unsafe { PluginFreeString(ptr) };
+    }
+
+    #[test]
+    fn test_plugin_status_from_state() {
+        let mut state = crate::PluginState::default();
+        assert_eq!(
+            PluginStatus::from_state(&state, false),
+            PluginStatus::default()
+        );
+
+        let err = crate::BridgeError::ConnectionFailed("refused".to_string());
+        state.record_error(&err);
+        state.reconnect_attempts = 3;
+
+        let status = PluginStatus::from_state(&state, true);
+        assert_eq!(status.connected, 0);
+        assert_eq!(status.initialized, 1);
+        assert_eq!(status.reconnect_attempts, 3);
+        assert_eq!(status.last_error_code, err.code());
+    }
+
+    #[test]
+    fn test_get_plugin_status() {
+        // SAFETY: Passing null is safe - the function handles it gracefully
+        assert_eq!(unsafe { GetPluginStatus(std::ptr::null_mut()) }, 0);
+
+        let mut status = PluginStatus {
+            connected: -1,
+            ..Default::default()
+        };
+        // SAFETY: `status` is a valid, writable PluginStatus
+        assert_eq!(unsafe { GetPluginStatus(&mut status) }, 1);
+        assert_eq!(status.connected, IsPluginConnected());
+        assert_eq!(status.initialized, IsPluginInitialized());
+    }
+
+    #[test]
+    fn test_toggle_connection() {
+        let mut state = crate::PluginState::default();
+        state.record_error(&crate::BridgeError::ConnectionFailed("refused".to_string()));
+        state.reconnect_attempts = 2;
+
+        assert!(toggle_connection(&mut state));
+        assert!(state.active);
+        assert!(!state.has_error());
+        assert_eq!(state.last_error_code, 0);
+        assert_eq!(state.reconnect_attempts, 0);
+
+        state.set_error("Connection lost");
+        assert!(!toggle_connection(&mut state));
+        assert!(!state.active);
+        assert!(!state.has_error());
+    }
+
+    #[test]
+    fn test_document_event_names() {
+        assert_eq!(
+            document_event_name(DOCUMENT_DID_OPEN),
+            Some("documentOpened")
+        );
+        assert_eq!(
+            document_event_name(DOCUMENT_WILL_CLOSE),
+            Some("documentWillClose")
+        );
+        assert_eq!(document_event_name(7), None);
+    }
+
+    #[test]
+    fn test_notify_document_event_rejects_bad_input() {
+        let path = CString::new("/C/report.pdf").unwrap();
+        // SAFETY: `path` is a valid C string and null is handled
+        unsafe {
+            assert_eq!(NotifyDocumentEvent(7, path.as_ptr()), 0);
+            assert_eq!(NotifyDocumentEvent(DOCUMENT_DID_OPEN, std::ptr::null()), 0);
+        }
+    }
+
+    #[test]
+    fn test_set_proxy_url_null() {
+        // SAFETY: Passing null is safe - the function handles it gracefully
+        assert_eq!(unsafe { SetProxyUrl(std::ptr::null()) }, 0);
+    }
+
+    #[test]
+    fn test_set_proxy_url_invalid_scheme() {
+        let _guard = crate::lock_global_state();
+        let before = unsafe { CStr::from_ptr(GetProxyUrl()).to_str().unwrap().to_string() };
+
+        let url = CString::new("http://localhost:3001").unwrap();
+        // SAFETY: `url` is a valid C string
+        assert_eq!(unsafe { SetProxyUrl(url.as_ptr()) }, 0);
+
+        let after = unsafe { CStr::from_ptr(GetProxyUrl()).to_str().unwrap().to_string() };
+        assert_eq!(after, before);
+    }
+
+    #[test]
+    fn test_set_and_get_proxy_url() {
+        let _guard = crate::lock_global_state();
+        let before = crate::get_state().lock().proxy_url().to_string();
+
+        let url = CString::new("ws://127.0.0.1:39001").unwrap();
+        // SAFETY: `url` is a valid C string
+        assert_eq!(unsafe { SetProxyUrl(url.as_ptr()) }, 1);
+        let current = unsafe { CStr::from_ptr(GetProxyUrl()).to_str().unwrap() };
+        assert_eq!(current, "ws://127.0.0.1:39001");
+
+        crate::get_state().lock().set_proxy_url(&before).unwrap();
+    }
+
     #[test]
     fn test_is_plugin_initialized() {
         // Should be false initially (or after reset)
@@ -395,6 +856,7 @@ mod tests {
 
     #[test]
     fn test_get_last_error_none() {
+        let _guard = crate::lock_global_state();
         let state = crate::get_state();
         {
             let mut guard = state.lock();
@@ -406,6 +868,7 @@ mod tests {
 
     #[test]
     fn test_get_last_error_some() {
+        let _guard = crate::lock_global_state();
         let state = crate::get_state();
         {
             let mut guard = state.lock();
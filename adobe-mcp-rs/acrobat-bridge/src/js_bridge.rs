@@ -4,6 +4,7 @@
 //! The actual execution requires linking against the Acrobat SDK.
 
 use anyhow::Result;
+use serde_json::{Map, Value};
 
 #[cfg(feature = "acrobat-sdk")]
 use std::ffi::{CStr, CString};
@@ -48,6 +49,56 @@ impl JsResult {
     }
 }
 
+/// A JavaScript program whose caller-supplied values travel as data, never as code
+///
+/// Bindings are serialized with `serde_json` into a `var args = {...};` preamble
+/// and the fixed template body reads them as `args.name`. Because the body is a
+/// `&'static str`, nothing bound at runtime can change the shape of the program.
+#[derive(Debug, Clone)]
+pub struct JsScript {
+    body: &'static str,
+    args: Map<String, Value>,
+}
+
+impl JsScript {
+    /// Start a script from a fixed template body
+    pub fn new(body: &'static str) -> Self {
+        Self {
+            body,
+            args: Map::new(),
+        }
+    }
+
+    /// Expose `value` to the body as `args.<name>`
+    pub fn bind(mut self, name: &str, value: impl Into<Value>) -> Self {
+        self.args.insert(name.to_string(), value.into());
+        self
+    }
+
+    /// Value bound under `name`, if any
+    pub fn arg(&self, name: &str) -> Option<&Value> {
+        self.args.get(name)
+    }
+
+    /// Render the complete program, wrapped in an immediately-invoked function
+    pub fn render(&self) -> String {
+        let args = Value::Object(self.args.clone()).to_string();
+        format!(
+            "(function() {{\nvar args = {};\n{}\n}})()",
+            js_safe_json(&args),
+            self.body
+        )
+    }
+}
+
+/// JSON is valid JavaScript except for raw U+2028/U+2029, which older engines
+/// treat as line terminators; `</` is escaped so the script can be inlined in HTML
+fn js_safe_json(json: &str) -> String {
+    json.replace('\u{2028}', "\\u2028")
+        .replace('\u{2029}', "\\u2029")
+        .replace("</", "<\\/")
+}
+
 /// Execute JavaScript code in Acrobat's context
 ///
 /// This is the main entry point for JavaScript execution.
@@ -91,13 +142,15 @@ fn execute_js_sdk(script: &str) -> Result<JsResult> {
         return Ok(JsResult::failure("JavaScript execution returned null"));
     }
 
-    // SAFETY: The SDK returns a valid C string that we need to read
+    // SAFETY: ExecuteJavaScript returns a valid, NUL-terminated C string
     let result_str = unsafe { CStr::from_ptr(result_ptr).to_string_lossy().into_owned() };
 
-    // Note: Memory management depends on the SDK.
-    // The Acrobat SDK typically manages its own memory, so we don't free here.
-    // If the SDK requires us to free the string, we would do:
-    // unsafe { libc::free(result_ptr as *mut libc::c_void); }
+    // The result is ours to free unless the SDK owns it (see `ExecuteJavaScript`)
+    #[cfg(not(feature = "sdk-owns-memory"))]
+    {
+        // SAFETY: The pointer came from ExecuteJavaScript and is freed exactly once
+        unsafe { ffi::PluginFreeString(result_ptr) };
+    }
 
     Ok(JsResult::success(result_str))
 }
@@ -107,11 +160,22 @@ fn execute_js_sdk(script: &str) -> Result<JsResult> {
 fn execute_js_mock(script: &str) -> Result<JsResult> {
     tracing::warn!("JS Bridge: SDK not linked, returning mock result");
 
+    #[cfg(test)]
+    if let Some(result) = mock::respond(script) {
+        return Ok(result);
+    }
+
     // Parse the script to provide more realistic mock responses
     let script_lower = script.to_lowercase();
 
     // Mock different responses based on script content
-    let mock_response = if script_lower.contains("numpages") || script_lower.contains("pagecount") {
+    let mock_response = if script_lower.contains("app.viewerversion") {
+        r#"{"success": true, "viewerVersion": 24.001, "viewerType": "Exchange-Pro", "viewerVariation": "Full", "platform": "WIN", "language": "ENU", "documentCount": 1}"#
+    } else if script_lower.contains(r#""output":"perpage""#) {
+        r#"{"success": true, "output": "perPage", "pages": [{"page": 1, "text": "Mock extracted text content."}]}"#
+    } else if script_lower.contains(r#""output":"withcoords""#) {
+        r#"{"success": true, "output": "withCoords", "pages": [{"page": 1, "text": "Mock", "words": [{"word": "Mock", "quads": [[72, 720, 110, 720, 72, 708, 110, 708]]}]}]}"#
+    } else if script_lower.contains("numpages") || script_lower.contains("pagecount") {
         r#"{"success": true, "pageCount": 1}"#
     } else if script_lower.contains("info.title") || script_lower.contains("documentinfo") {
         r#"{"success": true, "title": "Mock Document", "numPages": 1}"#
@@ -141,6 +205,78 @@ fn execute_js_mock(script: &str) -> Result<JsResult> {
     Ok(JsResult::success(mock_response))
 }
 
+/// Canned responses for tests, keyed by script matchers
+///
+/// Registrations are thread-local, so each test sees only its own. Later
+/// registrations take precedence; scripts that match nothing fall back to the
+/// substring heuristic in `execute_js_mock`, so none of this exists in SDK
+/// builds.
+#[cfg(all(test, not(feature = "acrobat-sdk")))]
+pub mod mock {
+    use super::JsResult;
+    use regex::Regex;
+    use std::cell::RefCell;
+
+    /// How a registered response selects scripts
+    #[derive(Debug, Clone)]
+    pub enum Matcher {
+        /// The whole script, compared verbatim
+        Exact(String),
+        /// Any script containing this text
+        Contains(String),
+        /// Any script the pattern matches
+        Regex(Regex),
+    }
+
+    impl Matcher {
+        /// Build a regex matcher; panics on an invalid pattern
+        pub fn regex(pattern: &str) -> Self {
+            Self::Regex(Regex::new(pattern).expect("invalid mock pattern"))
+        }
+
+        fn matches(&self, script: &str) -> bool {
+            match self {
+                Self::Exact(expected) => script == expected,
+                Self::Contains(needle) => script.contains(needle.as_str()),
+                Self::Regex(pattern) => pattern.is_match(script),
+            }
+        }
+    }
+
+    thread_local! {
+        static RESPONSES: RefCell<Vec<(Matcher, JsResult)>> = const { RefCell::new(Vec::new()) };
+        static LAST_SCRIPT: RefCell<Option<String>> = const { RefCell::new(None) };
+    }
+
+    /// Return `result` for every script `matcher` accepts
+    pub fn set_response(matcher: Matcher, result: JsResult) {
+        RESPONSES.with(|responses| responses.borrow_mut().push((matcher, result)));
+    }
+
+    /// Drop all registrations and the recorded script
+    pub fn clear() {
+        RESPONSES.with(|responses| responses.borrow_mut().clear());
+        LAST_SCRIPT.with(|last| last.borrow_mut().take());
+    }
+
+    /// The most recent script passed to the mock on this thread
+    pub fn last_script() -> Option<String> {
+        LAST_SCRIPT.with(|last| last.borrow().clone())
+    }
+
+    pub(super) fn respond(script: &str) -> Option<JsResult> {
+        LAST_SCRIPT.with(|last| *last.borrow_mut() = Some(script.to_string()));
+        RESPONSES.with(|responses| {
+            responses
+                .borrow()
+                .iter()
+                .rev()
+                .find(|(matcher, _)| matcher.matches(script))
+                .map(|(_, result)| result.clone())
+        })
+    }
+}
+
 /// Execute JavaScript and parse the result as JSON
 ///
 /// This is a convenience function that executes JavaScript and attempts
@@ -191,6 +327,12 @@ pub mod utils {
     /// Get viewer type (Reader, Exchange, etc.)
     pub const GET_VIEWER_TYPE: &str = "app.viewerType";
 
+    /// Get viewer variation (Reader, Standard, Full)
+    pub const GET_VIEWER_VARIATION: &str = "app.viewerVariation";
+
+    /// Get host platform (WIN, MAC, UNIX)
+    pub const GET_PLATFORM: &str = "app.platform";
+
     /// Check if document has been modified
     pub const IS_MODIFIED: &str = "this.dirty";
 
@@ -260,6 +402,76 @@ mod tests {
         assert!(result.success);
     }
 
+    /// Split a rendered script into its bound args and its body
+    fn split_rendered(rendered: &str) -> (Value, &str) {
+        let rest = rendered
+            .strip_prefix("(function() {\nvar args = ")
+            .unwrap();
+        let (args, body) = rest.split_once(";\n").unwrap();
+        (serde_json::from_str(args).unwrap(), body.strip_suffix("\n})()").unwrap())
+    }
+
+    #[test]
+    fn test_js_script_render() {
+        let script = JsScript::new("return args.a + args.b;")
+            .bind("a", 1)
+            .bind("b", "two");
+        let rendered = script.render();
+        let (args, body) = split_rendered(&rendered);
+        assert_eq!(args, serde_json::json!({"a": 1, "b": "two"}));
+        assert_eq!(body, "return args.a + args.b;");
+        assert_eq!(script.arg("b"), Some(&Value::from("two")));
+    }
+
+    #[test]
+    fn test_js_script_injection_is_inert() {
+        let hostile = "\");app.alert(\"pwned";
+        let script = JsScript::new("app.openDoc(args.path);").bind("path", hostile);
+        let rendered = script.render();
+        let (args, body) = split_rendered(&rendered);
+        assert_eq!(args["path"], hostile);
+        assert_eq!(body, "app.openDoc(args.path);");
+    }
+
+    #[test]
+    fn test_js_script_escapes_line_separators() {
+        let script = JsScript::new("").bind("text", "a\u{2028}b</script>");
+        let rendered = script.render();
+        assert!(!rendered.contains('\u{2028}'));
+        assert!(!rendered.contains("</"));
+        let (args, _) = split_rendered(&rendered);
+        assert_eq!(args["text"], "a\u{2028}b</script>");
+    }
+
+    #[test]
+    #[cfg(not(feature = "acrobat-sdk"))]
+    fn test_mock_registry_matchers() {
+        mock::set_response(mock::Matcher::Exact("exact()".into()), JsResult::success("1"));
+        mock::set_response(mock::Matcher::Contains("needle".into()), JsResult::success("2"));
+        mock::set_response(mock::Matcher::regex(r"page\s*=\s*\d+"), JsResult::failure("3"));
+
+        assert_eq!(execute_js("exact()").unwrap().value.as_deref(), Some("1"));
+        assert_eq!(execute_js("a needle b").unwrap().value.as_deref(), Some("2"));
+        assert_eq!(execute_js("page = 4").unwrap().error.as_deref(), Some("3"));
+        assert_eq!(mock::last_script().as_deref(), Some("page = 4"));
+
+        // Unmatched scripts keep the heuristic response
+        let fallback = execute_js("this.numPages").unwrap().value.unwrap();
+        assert!(fallback.contains("pageCount"));
+
+        mock::clear();
+        assert!(mock::last_script().is_none());
+        assert!(execute_js("exact()").unwrap().value.unwrap().contains("MOCK_SUCCESS"));
+    }
+
+    #[test]
+    #[cfg(not(feature = "acrobat-sdk"))]
+    fn test_mock_registry_latest_wins() {
+        mock::set_response(mock::Matcher::Contains("x".into()), JsResult::success("old"));
+        mock::set_response(mock::Matcher::Contains("x".into()), JsResult::success("new"));
+        assert_eq!(execute_js("x").unwrap().value.as_deref(), Some("new"));
+    }
+
     #[test]
     fn test_utils_constants() {
         assert_eq!(utils::GET_ACTIVE_DOC, "this");
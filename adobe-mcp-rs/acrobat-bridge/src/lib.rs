@@ -65,10 +65,25 @@ pub struct PluginState {
     pub active: bool,
     /// Proxy server URL
     pub proxy_url: String,
+    /// Shared secret for proxies started with `--auth-token`
+    pub auth_token: Option<String>,
     /// Last error message for diagnostics
     pub last_error: Option<String>,
+    /// [`BridgeError::code`] of the last error, 0 when there is none and
+    /// [`UNCLASSIFIED_ERROR_CODE`] for errors set from a plain message
+    pub last_error_code: i32,
+    /// Failed connection attempts since the last successful connect
+    pub reconnect_attempts: u32,
+    /// Session token the proxy issued at registration, sent back on reconnect
+    pub resume_token: client::ResumeToken,
+    /// Whether `executeJavaScript` may run caller-supplied scripts; off
+    /// unless `ACROBAT_ALLOW_RAW_JS` is `1` or `true`
+    pub allow_raw_js: bool,
 }
 
+/// `last_error_code` for errors recorded with [`PluginState::set_error`]
+pub const UNCLASSIFIED_ERROR_CODE: i32 = -1;
+
 impl Default for PluginState {
     fn default() -> Self {
         Self {
@@ -76,16 +91,35 @@ impl Default for PluginState {
             active: false,
             proxy_url: std::env::var("ACROBAT_PROXY_URL")
                 .unwrap_or_else(|_| "ws://localhost:3001".to_string()),
+            auth_token: std::env::var("ADOBE_PROXY_AUTH_TOKEN")
+                .ok()
+                .filter(|t| !t.is_empty()),
             last_error: None,
+            last_error_code: 0,
+            reconnect_attempts: 0,
+            resume_token: client::ResumeToken::default(),
+            allow_raw_js: std::env::var("ACROBAT_ALLOW_RAW_JS")
+                .is_ok_and(|v| v == "1" || v.eq_ignore_ascii_case("true")),
         }
     }
 }
 
+/// Check that `url` is a `ws://` or `wss://` URL with a host
+pub fn validate_proxy_url(url: &str) -> BridgeResult<()> {
+    adobe_common::validate_proxy_url(url).map_err(|e| BridgeError::InvalidState(e.to_string()))
+}
+
 impl PluginState {
     /// Create a new plugin state with a custom proxy URL
+    ///
+    /// An invalid URL is logged here and rejected when connecting.
     pub fn with_proxy_url(url: impl Into<String>) -> Self {
+        let proxy_url = url.into();
+        if let Err(e) = validate_proxy_url(&proxy_url) {
+            tracing::warn!("{}", e);
+        }
         Self {
-            proxy_url: url.into(),
+            proxy_url,
             ..Default::default()
         }
     }
@@ -103,11 +137,19 @@ impl PluginState {
         let error_msg = msg.into();
         tracing::error!("Plugin error: {}", error_msg);
         self.last_error = Some(error_msg);
+        self.last_error_code = UNCLASSIFIED_ERROR_CODE;
+    }
+
+    /// Set an error state from a bridge error, keeping its code
+    pub fn record_error(&mut self, err: &BridgeError) {
+        self.set_error(err.to_string());
+        self.last_error_code = err.code();
     }
 
     /// Clear error state
     pub fn clear_error(&mut self) {
         self.last_error = None;
+        self.last_error_code = 0;
     }
 
     /// Get proxy URL
@@ -115,6 +157,20 @@ impl PluginState {
         &self.proxy_url
     }
 
+    /// Change the proxy URL used by the next connection
+    ///
+    /// Only `ws://` and `wss://` URLs with a host are accepted.
+    pub fn set_proxy_url(&mut self, url: &str) -> BridgeResult<()> {
+        validate_proxy_url(url)?;
+        self.proxy_url = url.to_string();
+        Ok(())
+    }
+
+    /// Get the proxy auth token, if any
+    pub fn auth_token(&self) -> Option<&str> {
+        self.auth_token.as_deref()
+    }
+
     /// Check if there's an active error
     pub fn has_error(&self) -> bool {
         self.last_error.is_some()
@@ -155,6 +211,14 @@ pub fn reset_state() {
     }
 }
 
+/// Held by tests that read or write the global plugin state, so one can't
+/// observe another's changes
+#[cfg(test)]
+pub(crate) fn lock_global_state() -> std::sync::MutexGuard<'static, ()> {
+    static LOCK: std::sync::Mutex<()> = std::sync::Mutex::new(());
+    LOCK.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -202,6 +266,52 @@ mod tests {
         assert!(!state.has_error());
     }
 
+    #[test]
+    fn test_state_error_codes() {
+        let mut state = PluginState::default();
+        assert_eq!(state.last_error_code, 0);
+
+        state.set_error("Plain message");
+        assert_eq!(state.last_error_code, UNCLASSIFIED_ERROR_CODE);
+
+        let err = BridgeError::Timeout("no reply".to_string());
+        state.record_error(&err);
+        assert_eq!(state.last_error(), Some("Timeout: no reply"));
+        assert_eq!(state.last_error_code, err.code());
+
+        state.clear_error();
+        assert_eq!(state.last_error_code, 0);
+    }
+
+    #[test]
+    fn test_set_proxy_url() {
+        let mut state = PluginState::with_proxy_url("ws://localhost:3001");
+
+        state.set_proxy_url("wss://proxy.local:8443").unwrap();
+        assert_eq!(state.proxy_url(), "wss://proxy.local:8443");
+
+        for url in ["http://proxy.local", "ws://", "wss:///path", "localhost:3001", ""] {
+            assert!(state.set_proxy_url(url).is_err(), "{:?} should be rejected", url);
+        }
+        assert_eq!(state.proxy_url(), "wss://proxy.local:8443");
+    }
+
+    #[test]
+    fn test_validate_proxy_url() {
+        for url in ["ws://localhost:3001", "wss://proxy.local:8443/socket.io/"] {
+            assert!(validate_proxy_url(url).is_ok(), "{:?} should be accepted", url);
+        }
+        for url in ["http://localhost:3001", "", "ws://", "garbage", "ws://host:notaport"] {
+            let err = validate_proxy_url(url).unwrap_err();
+            assert!(matches!(err, BridgeError::InvalidState(_)), "{:?}", url);
+            assert!(err.to_string().contains("Invalid proxy URL"), "{}", err);
+        }
+
+        // Invalid URLs are kept so the failure surfaces when connecting
+        let state = PluginState::with_proxy_url("http://localhost:3001");
+        assert_eq!(state.proxy_url(), "http://localhost:3001");
+    }
+
     #[test]
     fn test_is_connected_when_no_client() {
         let state = PluginState::default();
@@ -210,6 +320,7 @@ mod tests {
 
     #[test]
     fn test_state_singleton() {
+        let _guard = lock_global_state();
         let state1 = get_state();
         let state2 = get_state();
 
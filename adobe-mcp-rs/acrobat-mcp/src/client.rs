@@ -1,139 +1,261 @@
 //! WebSocket client for communicating with Adobe proxy server
 
-use adobe_common::{AdobeApplication, Command, CommandPacket, CommandResponse, ResponseStatus};
-use adobe_common::socket_io::{decode_event, encode_event, ENGINE_PING, ENGINE_PONG};
-use anyhow::{anyhow, Result};
-use futures_util::{SinkExt, StreamExt};
+use adobe_common::client::ProxyCommandClient;
+use adobe_common::{AdobeApplication, CommandResponse};
+use anyhow::Result;
 use serde_json::Value;
-use std::sync::Arc;
-use tokio::net::TcpStream;
-use tokio::sync::Mutex;
-use tokio::time::{timeout, Duration};
-use tokio_tungstenite::{connect_async, MaybeTlsStream, WebSocketStream};
-use tracing::{debug, info};
 
-type WsStream = WebSocketStream<MaybeTlsStream<TcpStream>>;
+pub use adobe_common::client::MAX_TIMEOUT_MS;
 
 /// WebSocket client for Acrobat commands
 pub struct AcrobatClient {
-    ws: Arc<Mutex<WsStream>>,
-    timeout_ms: u64,
+    inner: ProxyCommandClient,
+    allow_raw_js: bool,
 }
 
 impl AcrobatClient {
     /// Create new client and connect to proxy
-    pub async fn new(proxy_url: &str, timeout_ms: u64) -> Result<Self> {
-        info!("Connecting to proxy at {}", proxy_url);
-
-        let (ws_stream, _) = connect_async(proxy_url)
-            .await
-            .map_err(|e| anyhow!("Failed to connect to proxy: {}", e))?;
-
-        info!("WebSocket connection established");
+    ///
+    /// `auth_token` is sent as an `Authorization: Bearer` header when the proxy
+    /// requires a shared secret.
+    pub async fn new(proxy_url: &str, timeout_ms: u64, auth_token: Option<&str>) -> Result<Self> {
+        let inner = ProxyCommandClient::connect(AdobeApplication::Acrobat, proxy_url, timeout_ms, auth_token).await?;
+        Ok(Self {
+            inner,
+            allow_raw_js: false,
+        })
+    }
 
-        let client = Self {
-            ws: Arc::new(Mutex::new(ws_stream)),
-            timeout_ms,
-        };
+    /// Id the proxy assigned this connection, once its open packet is read
+    pub fn client_id(&self) -> Option<String> {
+        self.inner.client_id()
+    }
 
-        {
-            let mut ws = client.ws.lock().await;
-            ws.send(tokio_tungstenite::tungstenite::Message::Text("40".to_string()))
-                .await
-                .map_err(|e| anyhow!("Failed to send Socket.IO connect: {}", e))?;
-        }
+    /// Allow the `execute_javascript` tool, which is refused by default
+    pub fn with_raw_js(mut self, allow: bool) -> Self {
+        self.allow_raw_js = allow;
+        self
+    }
 
-        Ok(client)
+    /// Whether `execute_javascript` may send scripts to Acrobat
+    pub fn raw_js_allowed(&self) -> bool {
+        self.allow_raw_js
     }
 
     /// Send command to Acrobat and wait for response
+    ///
+    /// `timeout_ms` overrides the configured timeout for this call and is
+    /// capped at [`MAX_TIMEOUT_MS`].
     pub async fn send_command(
         &self,
         action: impl Into<String>,
         options: Value,
+        timeout_ms: Option<u64>,
     ) -> Result<CommandResponse> {
-        let command = Command::new(action, options);
-        let packet = CommandPacket::new(AdobeApplication::Acrobat, command);
+        Ok(self.inner.send_command(action, options, timeout_ms).await?)
+    }
 
-        debug!("Sending command: {:?}", packet);
+    /// Disconnect from the proxy so it unregisters this client right away
+    pub async fn close(&self) -> Result<()> {
+        Ok(self.inner.close().await?)
+    }
 
-        let payload = serde_json::json!({
-            "type": packet.packet_type,
-            "application": packet.application,
-            "command": packet.command,
-        });
+    /// Get response data as JSON value
+    pub fn extract_response(response: &CommandResponse) -> Option<&Value> {
+        ProxyCommandClient::extract_response(response)
+    }
+
+    /// Get document info from response
+    #[allow(dead_code)]
+    pub fn extract_document(response: &CommandResponse) -> Option<&Value> {
+        ProxyCommandClient::extract_document(response)
+    }
+}
 
-        let message = encode_event("command_packet", payload);
-        let mut ws = self.ws.lock().await;
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use adobe_common::socket_io::{decode_event, encode_event};
+    use adobe_common::test_util::MockProxy;
+    use adobe_common::AdobeError;
+    use serde::Deserialize;
+    use futures_util::{SinkExt, StreamExt};
+    use tokio::time::Duration;
+    use tokio_tungstenite::tungstenite::Message;
 
-        ws.send(tokio_tungstenite::tungstenite::Message::Text(message))
+    #[tokio::test]
+    async fn test_get_page_count_through_mock_proxy() {
+        #[derive(Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct PageCount {
+            page_count: u32,
+        }
+
+        let (url, proxy) = MockProxy::new()
+            .respond("getPageCount", serde_json::json!({"pageCount": 12}))
+            .serve()
+            .await;
+        let client = AcrobatClient::new(&url, 5_000, None).await.unwrap();
+
+        let response = client
+            .send_command("getPageCount", serde_json::json!({}), None)
             .await
-            .map_err(|e| anyhow!("Failed to send message: {}", e))?;
-
-        let timeout_duration = Duration::from_millis(self.timeout_ms);
-
-        let response = timeout(timeout_duration, async {
-            loop {
-                let msg = ws.next().await.ok_or_else(|| anyhow!("WebSocket closed"))?;
-                let msg = msg.map_err(|e| anyhow!("WebSocket error: {}", e))?;
-
-                match msg {
-                    tokio_tungstenite::tungstenite::Message::Text(text) => {
-                        if text == ENGINE_PING {
-                            ws.send(tokio_tungstenite::tungstenite::Message::Text(ENGINE_PONG.to_string()))
-                                .await
-                                .map_err(|e| anyhow!("Failed to send pong: {}", e))?;
-                            continue;
-                        }
-
-                        if let Some((event, data)) = decode_event(&text) {
-                            if event == "packet_response" {
-                                let response: CommandResponse = serde_json::from_value(data)
-                                    .map_err(|e| anyhow!("Failed to parse response: {}", e))?;
-                                return Ok(response);
-                            }
-                            continue;
-                        }
-
-                        if text.starts_with('{') {
-                            let response: CommandResponse = serde_json::from_str(&text)
-                                .map_err(|e| anyhow!("Failed to parse response: {}", e))?;
-                            return Ok(response);
-                        }
-                    }
-                    tokio_tungstenite::tungstenite::Message::Close(_) => {
-                        return Err(anyhow!("WebSocket connection closed"));
-                    }
-                    tokio_tungstenite::tungstenite::Message::Ping(_) => {
-                        ws.send(tokio_tungstenite::tungstenite::Message::Text(ENGINE_PONG.to_string()))
-                            .await
-                            .map_err(|e| anyhow!("Failed to send pong: {}", e))?;
-                    }
-                    _ => {}
-                }
+            .unwrap();
+        let count: PageCount = response.parse_response().unwrap();
+        assert_eq!(count.page_count, 12);
+
+        let echoed = client
+            .send_command("getDocumentInfo", serde_json::json!({"pageBox": "Crop"}), None)
+            .await
+            .unwrap();
+        let echoed = AcrobatClient::extract_response(&echoed).unwrap();
+        assert_eq!(echoed["action"], "getDocumentInfo");
+        assert_eq!(echoed["options"]["pageBox"], "Crop");
+
+        proxy.shutdown().await;
+    }
+
+    #[tokio::test]
+    async fn test_captures_client_id_from_open_packet() {
+        let (url, proxy) = MockProxy::new()
+            .respond("getPageCount", serde_json::json!({"pageCount": 1}))
+            .serve()
+            .await;
+        let client = AcrobatClient::new(&url, 5_000, None).await.unwrap();
+
+        // The open packet precedes any response, so it has been read by now
+        let response = client
+            .send_command("getPageCount", serde_json::json!({}), None)
+            .await
+            .unwrap();
+        let client_id = client.client_id().expect("client id from the open packet");
+        assert!(uuid::Uuid::parse_str(&client_id).is_ok(), "{}", client_id);
+        // The mock proxy answers as the session it assigned
+        assert_eq!(response.sender_id, client_id);
+
+        proxy.shutdown().await;
+    }
+
+    #[tokio::test]
+    async fn test_close_disconnects_from_proxy() {
+        let (url, proxy) = MockProxy::start().await;
+        let client = AcrobatClient::new(&url, 5_000, None).await.unwrap();
+        client
+            .send_command("getPageCount", serde_json::json!({}), None)
+            .await
+            .unwrap();
+
+        client.close().await.unwrap();
+        for _ in 0..100 {
+            if proxy.disconnects() == 1 {
+                break;
             }
-        })
-        .await
-        .map_err(|_| anyhow!("Command timeout after {}ms", self.timeout_ms))??;
-
-        if response.status == ResponseStatus::Success {
-            Ok(response)
-        } else {
-            Err(anyhow!(
-                "Command failed: {}",
-                response.message.unwrap_or_else(|| "Unknown error".to_string())
-            ))
+            tokio::time::sleep(Duration::from_millis(10)).await;
         }
+        assert_eq!(proxy.disconnects(), 1);
+
+        proxy.shutdown().await;
     }
 
-    /// Get response data as JSON value
-    pub fn extract_response(response: &CommandResponse) -> Option<&Value> {
-        response.response.as_ref()
+    #[tokio::test]
+    async fn test_keepalive_survives_idle_ping_cycles() {
+        let (url, proxy) = MockProxy::new()
+            .respond("getPageCount", serde_json::json!({"pageCount": 2}))
+            .ping_interval(Duration::from_millis(50))
+            .serve()
+            .await;
+        let client = AcrobatClient::new(&url, 5_000, None).await.unwrap();
+
+        // Several ping intervals pass with no command in flight
+        tokio::time::sleep(Duration::from_millis(300)).await;
+
+        let response = client
+            .send_command("getPageCount", serde_json::json!({}), None)
+            .await
+            .unwrap();
+        assert_eq!(AcrobatClient::extract_response(&response).unwrap()["pageCount"], 2);
+        assert_eq!(proxy.connections(), 1, "client should not have needed to reconnect");
+
+        proxy.shutdown().await;
     }
 
-    /// Get document info from response
-    #[allow(dead_code)]
-    pub fn extract_document(response: &CommandResponse) -> Option<&Value> {
-        response.document.as_ref()
+    #[tokio::test]
+    async fn test_reconnects_after_proxy_restart() {
+        let (url, proxy) = MockProxy::new()
+            .respond("getPageCount", serde_json::json!({"pageCount": 3}))
+            .serve()
+            .await;
+        let client = AcrobatClient::new(&url, 5_000, None).await.unwrap();
+        client
+            .send_command("getPageCount", serde_json::json!({}), None)
+            .await
+            .unwrap();
+        let first_id = client.client_id().unwrap();
+
+        proxy.shutdown().await;
+        // Let the client read the close, so the next command is never sent
+        // on the dead connection (and so is safe to retry)
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        let addr = url.trim_start_matches("ws://").parse().unwrap();
+        let (_, proxy) = MockProxy::new()
+            .respond("getPageCount", serde_json::json!({"pageCount": 5}))
+            .serve_on(addr)
+            .await;
+
+        let response = client
+            .send_command("getPageCount", serde_json::json!({}), None)
+            .await
+            .unwrap();
+        assert_eq!(AcrobatClient::extract_response(&response).unwrap()["pageCount"], 5);
+        assert_eq!(client.client_id().as_deref(), Some(response.sender_id.as_str()));
+        assert_ne!(client.client_id().unwrap(), first_id);
+
+        proxy.shutdown().await;
+        let err = client
+            .send_command("getPageCount", serde_json::json!({}), None)
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<AdobeError>(),
+            Some(AdobeError::ConnectionFailed(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_not_connected_fails_fast() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            let mut ws = tokio_tungstenite::accept_async(stream).await.unwrap();
+            while let Some(Ok(Message::Text(text))) = ws.next().await {
+                if decode_event(&text).is_some_and(|(event, _)| event == "command_packet") {
+                    let response = encode_event(
+                        "packet_response",
+                        serde_json::json!({
+                            "senderId": "test",
+                            "status": "FAILURE",
+                            "code": adobe_common::NOT_CONNECTED_CODE,
+                            "message": "No clients registered for application: acrobat"
+                        }),
+                    );
+                    if ws.send(Message::Text(response)).await.is_err() {
+                        break;
+                    }
+                }
+            }
+        });
+        let client = AcrobatClient::new(&format!("ws://{}", addr), 30_000, None).await.unwrap();
+
+        let started = std::time::Instant::now();
+        let err = client
+            .send_command("getPageCount", serde_json::json!({}), None)
+            .await
+            .unwrap_err();
+        assert!(started.elapsed() < Duration::from_secs(5));
+        assert!(matches!(
+            err.downcast_ref::<AdobeError>(),
+            Some(AdobeError::ApplicationNotConnected(_))
+        ));
     }
 }
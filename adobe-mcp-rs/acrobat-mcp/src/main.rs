@@ -4,78 +4,80 @@
 
 mod client;
 mod mcp;
+mod resources;
 mod tools;
 
+use adobe_common::stdio::{self, Framing};
+use adobe_common::{error_codes, paginate, AdobeError, TOOLS_PAGE_SIZE};
 use clap::Parser;
 use serde_json::json;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
-use tokio::io::{AsyncBufReadExt, BufReader};
-use tracing::{error, info};
+use tokio::io::BufReader;
+use tracing::{debug, error, info, warn};
 
 #[derive(Parser, Debug)]
 #[command(author, version, about, long_about = None)]
 struct Args {
-    /// WebSocket proxy URL
+    /// WebSocket proxy URL (`ws://` or `wss://` selects the transport)
     #[arg(long, env = "ACROBAT_PROXY_URL", default_value = "ws://localhost:3001")]
     proxy_url: String,
 
     /// Command timeout in milliseconds
     #[arg(long, env = "ACROBAT_TIMEOUT", default_value = "30000")]
     timeout: u64,
+
+    /// Shared secret for a proxy started with `--auth-token`
+    #[arg(long, env = "ADOBE_PROXY_AUTH_TOKEN")]
+    auth_token: Option<String>,
+
+    /// Stdio message framing: `line` (one JSON message per line) or `lsp`
+    /// (`Content-Length` headers, allowing multi-line JSON)
+    #[arg(long, env = "ACROBAT_MCP_FRAMING", default_value = "line")]
+    framing: Framing,
+
+    /// Enable the `execute_javascript` tool, which runs arbitrary Acrobat
+    /// JavaScript (the bridge must allow it as well)
+    #[arg(long, env = "ACROBAT_MCP_ALLOW_RAW_JS", value_parser = clap::builder::BoolishValueParser::new())]
+    allow_raw_js: bool,
 }
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
-    // CRITICAL: Log to stderr to avoid corrupting JSON-RPC stdout stream
-    tracing_subscriber::fmt()
-        .with_writer(std::io::stderr)
-        .init();
+    // CRITICAL: Log to stderr to avoid corrupting JSON-RPC stdout stream;
+    // only `notifications/message` log entries are written to stdout
+    adobe_common::logging::init(|level, data| {
+        print_notification(&mcp::protocol::JsonRpcNotification::log_message(level, data))
+    });
 
     let args = Args::parse();
+    stdio::set_output_framing(args.framing);
     info!("Starting acrobat-mcp with proxy: {}", args.proxy_url);
 
     // Initialize WebSocket client
-    let client = Arc::new(client::AcrobatClient::new(&args.proxy_url, args.timeout).await?);
+    let client = Arc::new(
+        client::AcrobatClient::new(&args.proxy_url, args.timeout, args.auth_token.as_deref())
+            .await?
+            .with_raw_js(args.allow_raw_js),
+    );
+    if args.allow_raw_js {
+        info!("Raw JavaScript execution is enabled");
+    }
     info!("Connected to proxy at {}", args.proxy_url);
 
     // Start JSON-RPC loop over stdio
     info!("Listening on stdin for MCP requests...");
 
-    let stdin = tokio::io::stdin();
-    let reader = BufReader::new(stdin);
-    let mut lines = reader.lines();
+    let mut reader = BufReader::new(tokio::io::stdin());
+    let session = Session::default();
 
     loop {
         tokio::select! {
-            line_result = lines.next_line() => {
-                match line_result {
-                    Ok(Some(line)) => {
-                        let line = line.trim();
-                        if line.is_empty() {
-                            continue;
-                        }
-
-                        let request: Result<mcp::protocol::JsonRpcRequest, _> = serde_json::from_str(line);
-                        match request {
-                            Ok(req) => {
-                                let _id = req.id.clone();
-                                let response = handle_request(req, &client).await;
-                                let response_json = serde_json::to_string(&response)?;
-                                println!("{}", response_json);
-                            }
-                            Err(e) => {
-                                error!("Failed to parse JSON-RPC: {}", e);
-                                let err_resp = mcp::protocol::JsonRpcResponse {
-                                    jsonrpc: "2.0".into(),
-                                    id: None,
-                                    result: None,
-                                    error: Some(mcp::protocol::JsonRpcError::new(
-                                        mcp::protocol::JsonRpcError::PARSE_ERROR,
-                                        e.to_string()
-                                    )),
-                                };
-                                println!("{}", serde_json::to_string(&err_resp)?);
-                            }
+            message_result = args.framing.read_message(&mut reader) => {
+                match message_result {
+                    Ok(Some(message)) => {
+                        if let Some(output) = handle_line(&message, &session, &client, &print_notification).await? {
+                            stdio::write_message(&output);
                         }
                     }
                     Ok(None) => break, // EOF
@@ -92,24 +94,131 @@ async fn main() -> anyhow::Result<()> {
         }
     }
 
+    if let Err(e) = client.close().await {
+        debug!("Failed to close proxy connection: {}", e);
+    }
     info!("Acrobat MCP server shutting down. Goodbye.");
     Ok(())
 }
 
+/// Handshake state for the one MCP client on stdio
+#[derive(Debug, Default)]
+struct Session {
+    /// Set by the first `initialize`; until then only `initialize` and
+    /// `ping` are answered
+    initialized: AtomicBool,
+}
+
+/// Dispatch one stdin message holding either a single request or a batch array
+///
+/// Returns the message to write back, or `None` when the message (or every
+/// element of a batch) was a notification.
+async fn handle_line(
+    line: &str,
+    session: &Session,
+    client: &Arc<client::AcrobatClient>,
+    notify: &dyn Fn(&mcp::protocol::JsonRpcNotification),
+) -> anyhow::Result<Option<String>> {
+    let parsed = serde_json::from_str::<serde_json::Value>(line).and_then(|value| match value {
+        serde_json::Value::Array(batch) => Ok(Err(batch)),
+        value => serde_json::from_value::<mcp::protocol::JsonRpcRequest>(value).map(Ok),
+    });
+
+    let batch = match parsed {
+        Ok(Ok(req)) => {
+            return match handle_request(req, session, client, notify).await {
+                Some(response) => Ok(Some(serde_json::to_string(&response)?)),
+                None => Ok(None),
+            };
+        }
+        Ok(Err(batch)) => batch,
+        Err(e) => {
+            error!("Failed to parse JSON-RPC: {}", e);
+            let err_resp = mcp::protocol::JsonRpcResponse::error(
+                None,
+                mcp::protocol::JsonRpcError::new(
+                    mcp::protocol::JsonRpcError::PARSE_ERROR,
+                    e.to_string(),
+                ),
+            );
+            return Ok(Some(serde_json::to_string(&err_resp)?));
+        }
+    };
+
+    if batch.is_empty() {
+        let err_resp = mcp::protocol::JsonRpcResponse::error(
+            None,
+            mcp::protocol::JsonRpcError::invalid_request("empty batch"),
+        );
+        return Ok(Some(serde_json::to_string(&err_resp)?));
+    }
+
+    let mut responses = Vec::new();
+    for item in batch {
+        match serde_json::from_value::<mcp::protocol::JsonRpcRequest>(item) {
+            Ok(req) => {
+                if let Some(response) = handle_request(req, session, client, notify).await {
+                    responses.push(response);
+                }
+            }
+            Err(e) => responses.push(mcp::protocol::JsonRpcResponse::error(
+                None,
+                mcp::protocol::JsonRpcError::invalid_request(e.to_string()),
+            )),
+        }
+    }
+
+    if responses.is_empty() {
+        return Ok(None);
+    }
+    Ok(Some(serde_json::to_string(&responses)?))
+}
+
+/// Answer a request, or return `None` for a notification (no `id`)
+///
+/// Requests other than `initialize` and `ping` are rejected with
+/// `INVALID_REQUEST` until the client has sent `initialize`; a repeated
+/// `initialize` gets the same result again. `notify` writes server-initiated
+/// notifications, such as progress for a `tools/call` carrying
+/// `_meta.progressToken`, ahead of the response.
 async fn handle_request(
     req: mcp::protocol::JsonRpcRequest,
+    session: &Session,
     client: &Arc<client::AcrobatClient>,
-) -> mcp::protocol::JsonRpcResponse {
+    notify: &dyn Fn(&mcp::protocol::JsonRpcNotification),
+) -> Option<mcp::protocol::JsonRpcResponse> {
+    if req.id.is_none() {
+        handle_notification(&req.method);
+        return None;
+    }
     let id = req.id.clone();
 
-    match req.method.as_str() {
+    if req.method == "initialize" {
+        if session.initialized.swap(true, Ordering::SeqCst) {
+            debug!("Client sent initialize again");
+        }
+    } else if req.method != "ping" && !session.initialized.load(Ordering::SeqCst) {
+        return Some(mcp::protocol::JsonRpcResponse::error(
+            id,
+            mcp::protocol::JsonRpcError::invalid_request(format!(
+                "{} sent before initialize",
+                req.method
+            )),
+        ));
+    }
+
+    let response = match req.method.as_str() {
         "ping" => mcp::protocol::JsonRpcResponse::success(id, json!({"status": "ok"})),
 
         "initialize" => mcp::protocol::JsonRpcResponse::success(
             id,
             json!({
                 "protocolVersion": "2024-11-05",
-                "capabilities": { "tools": { "listChanged": false } },
+                "capabilities": {
+                    "tools": { "listChanged": false },
+                    "resources": { "subscribe": false, "listChanged": false },
+                    "logging": {}
+                },
                 "serverInfo": {
                     "name": "acrobat-mcp",
                     "version": env!("CARGO_PKG_VERSION")
@@ -118,31 +227,104 @@ async fn handle_request(
         ),
 
         "tools/list" => {
-            let tools = tools::get_tool_definitions();
-            mcp::protocol::JsonRpcResponse::success(id, json!({"tools": tools}))
+            let cursor = req.params.as_ref().and_then(|p| p.get("cursor")).and_then(|c| c.as_str());
+            match paginate(&tools::get_tool_definitions(), cursor, TOOLS_PAGE_SIZE) {
+                Ok((tools, next_cursor)) => {
+                    let mut result = json!({"tools": tools});
+                    if let Some(next_cursor) = next_cursor {
+                        result["nextCursor"] = json!(next_cursor);
+                    }
+                    mcp::protocol::JsonRpcResponse::success(id, result)
+                }
+                Err(e) => mcp::protocol::JsonRpcResponse::error(id, (&e).into()),
+            }
+        }
+
+        "logging/setLevel" => {
+            match req.params.as_ref().and_then(|p| p.get("level")).and_then(|l| l.as_str()) {
+                Some(level) => match adobe_common::logging::set_level(level) {
+                    Ok(_) => mcp::protocol::JsonRpcResponse::success(id, json!({})),
+                    Err(e) => mcp::protocol::JsonRpcResponse::error(id, (&e).into()),
+                },
+                None => mcp::protocol::JsonRpcResponse::error(
+                    id,
+                    mcp::protocol::JsonRpcError::invalid_params("Missing level"),
+                ),
+            }
+        }
+
+        "resources/list" => match resources::list_resources(client).await {
+            Ok(result) => mcp::protocol::JsonRpcResponse::success(id, result),
+            Err(e) => mcp::protocol::JsonRpcResponse::error(id, resource_error(&e)),
+        },
+
+        "resources/read" => {
+            match req.params.as_ref().and_then(|p| p.get("uri")).and_then(|u| u.as_str()) {
+                Some(uri) => match resources::read_resource(client, uri).await {
+                    Ok(result) => mcp::protocol::JsonRpcResponse::success(id, result),
+                    Err(e) => mcp::protocol::JsonRpcResponse::error(id, resource_error(&e)),
+                },
+                None => mcp::protocol::JsonRpcResponse::error(
+                    id,
+                    mcp::protocol::JsonRpcError::invalid_params("Missing resource uri"),
+                ),
+            }
         }
 
         "tools/call" => {
             if let Some(params) = req.params {
                 let name = params.get("name").and_then(|v| v.as_str());
                 let args = params.get("arguments").cloned().unwrap_or(json!({}));
+                let progress_token = params.get("_meta").and_then(|meta| meta.get("progressToken"));
+                let report_progress = |progress: u64, message: &str| {
+                    if let Some(token) = progress_token {
+                        notify(&mcp::protocol::JsonRpcNotification::progress(
+                            token.clone(),
+                            progress,
+                            1,
+                            message,
+                        ));
+                    }
+                };
 
                 if let Some(tool_name) = name {
-                    match tools::handle_tool_call(client, tool_name, args).await {
-                        Ok(result) => mcp::protocol::JsonRpcResponse::success(
-                            id,
-                            json!({
-                                "content": [{ "type": "text", "text": result }],
-                                "isError": false
-                            }),
-                        ),
-                        Err(e) => mcp::protocol::JsonRpcResponse::success(
-                            id,
-                            json!({
-                                "content": [{ "type": "text", "text": format!("Error: {}", e) }],
-                                "isError": true
-                            }),
-                        ),
+                    report_progress(0, "started");
+                    let result = tools::handle_tool_call_content(client, tool_name, args).await;
+                    report_progress(1, "completed");
+
+                    match result {
+                        Ok(blocks) => {
+                            let content: Vec<serde_json::Value> = blocks
+                                .into_iter()
+                                .map(|text| json!({ "type": "text", "text": text }))
+                                .collect();
+                            mcp::protocol::JsonRpcResponse::success(
+                                id,
+                                json!({
+                                    "content": content,
+                                    "isError": false
+                                }),
+                            )
+                        }
+                        Err(e) => {
+                            // The proxy logs commands under this id
+                            warn!(
+                                "Tool {} failed for proxy client {}: {}",
+                                tool_name,
+                                client.client_id().as_deref().unwrap_or("(unassigned)"),
+                                e
+                            );
+                            match rpc_error(&e) {
+                                Some(error) => mcp::protocol::JsonRpcResponse::error(id, error),
+                                None => mcp::protocol::JsonRpcResponse::success(
+                                    id,
+                                    json!({
+                                        "content": [{ "type": "text", "text": format!("Error: {}", e) }],
+                                        "isError": true
+                                    }),
+                                ),
+                            }
+                        }
                     }
                 } else {
                     mcp::protocol::JsonRpcResponse::error(
@@ -162,5 +344,551 @@ async fn handle_request(
             id,
             mcp::protocol::JsonRpcError::method_not_found(),
         ),
+    };
+
+    Some(response)
+}
+
+/// JSON-RPC error for failures the tool never got to run through
+///
+/// Bad arguments, a missing Acrobat, timeouts and transport errors are
+/// classified by [`AdobeError::to_rpc_code`]. `None` means a tool-level failure
+/// (Acrobat ran the command and reported an error, or a handler rejected its
+/// input), which is returned as an `isError` result instead.
+fn rpc_error(error: &anyhow::Error) -> Option<mcp::protocol::JsonRpcError> {
+    let error = error.downcast_ref::<AdobeError>()?;
+    (error.to_rpc_code() != error_codes::COMMAND_FAILED).then(|| error.into())
+}
+
+/// JSON-RPC error for a failed `resources/*` request
+///
+/// Resources have no `isError` result, so every failure is an error response.
+fn resource_error(error: &anyhow::Error) -> mcp::protocol::JsonRpcError {
+    match error.downcast_ref::<AdobeError>() {
+        Some(error) => error.into(),
+        None => mcp::protocol::JsonRpcError::internal_error(error.to_string()),
+    }
+}
+
+/// Write a server notification as its own stdout message
+fn print_notification(notification: &mcp::protocol::JsonRpcNotification) {
+    match serde_json::to_string(notification) {
+        Ok(message) => stdio::write_message(&message),
+        Err(e) => error!("Failed to serialize notification: {}", e),
+    }
+}
+
+/// Side effects for client notifications, which never get a response
+fn handle_notification(method: &str) {
+    match method {
+        "notifications/initialized" => info!("Client finished initialization"),
+        "notifications/cancelled" => debug!("Client cancelled a request"),
+        other => debug!("Ignoring notification: {}", other),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures_util::StreamExt;
+    use serde_json::Value;
+
+    /// Session past the `initialize` handshake
+    fn initialized_session() -> Session {
+        Session {
+            initialized: AtomicBool::new(true),
+        }
+    }
+
+    /// Client connected to a local WebSocket that accepts and ignores everything
+    async fn test_client() -> Arc<client::AcrobatClient> {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            let mut ws = tokio_tungstenite::accept_async(stream).await.unwrap();
+            while let Some(Ok(_)) = ws.next().await {}
+        });
+        Arc::new(
+            client::AcrobatClient::new(&format!("ws://{}", addr), 1000, None)
+                .await
+                .unwrap(),
+        )
+    }
+
+    /// Client connected to a local WebSocket that answers each command with
+    /// `reply(action, options)` as its response payload
+    async fn scripted_client(reply: fn(&str, &Value) -> Value) -> Arc<client::AcrobatClient> {
+        Arc::new(
+            client::AcrobatClient::new(&scripted_proxy(reply).await, 1000, None)
+                .await
+                .unwrap(),
+        )
+    }
+
+    /// URL of a local WebSocket that answers each command with
+    /// `reply(action, options)` as its response payload
+    async fn scripted_proxy(reply: fn(&str, &Value) -> Value) -> String {
+        use adobe_common::socket_io::{decode_event, encode_event};
+        use futures_util::SinkExt;
+        use tokio_tungstenite::tungstenite::Message;
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            let mut ws = tokio_tungstenite::accept_async(stream).await.unwrap();
+            while let Some(Ok(Message::Text(text))) = ws.next().await {
+                let Some((event, data)) = decode_event(&text) else {
+                    continue;
+                };
+                if event != "command_packet" {
+                    continue;
+                }
+                let command = &data["command"];
+                let payload = reply(command["action"].as_str().unwrap_or_default(), &command["options"]);
+                let response = encode_event(
+                    "packet_response",
+                    json!({"senderId": "test", "status": "SUCCESS", "response": payload}),
+                );
+                if ws.send(Message::Text(response)).await.is_err() {
+                    break;
+                }
+            }
+        });
+        format!("ws://{}", addr)
+    }
+
+    /// Client connected to a local WebSocket that fails every command with
+    /// `message`, as the proxy or bridge would
+    async fn failing_client(message: &'static str) -> Arc<client::AcrobatClient> {
+        use adobe_common::socket_io::{decode_event, encode_event};
+        use futures_util::SinkExt;
+        use tokio_tungstenite::tungstenite::Message;
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            let mut ws = tokio_tungstenite::accept_async(stream).await.unwrap();
+            while let Some(Ok(Message::Text(text))) = ws.next().await {
+                if decode_event(&text).is_none_or(|(event, _)| event != "command_packet") {
+                    continue;
+                }
+                let response = encode_event(
+                    "packet_response",
+                    json!({"senderId": "test", "status": "FAILURE", "message": message}),
+                );
+                if ws.send(Message::Text(response)).await.is_err() {
+                    break;
+                }
+            }
+        });
+        Arc::new(
+            client::AcrobatClient::new(&format!("ws://{}", addr), 1000, None)
+                .await
+                .unwrap(),
+        )
+    }
+
+    async fn call_tool(client: &Arc<client::AcrobatClient>, name: &str, arguments: Value) -> Value {
+        let line = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "tools/call",
+            "params": {"name": name, "arguments": arguments}
+        })
+        .to_string();
+        let output = handle_line(&line, &initialized_session(), client, &|_| {}).await.unwrap().unwrap();
+        serde_json::from_str(&output).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_tool_call_not_connected_is_rpc_error() {
+        let client = failing_client("No clients registered for application: acrobat").await;
+
+        let response = call_tool(&client, "get_page_count", json!({})).await;
+        assert!(response.get("result").is_none());
+        assert_eq!(response["error"]["code"], error_codes::APPLICATION_NOT_CONNECTED);
+        assert!(response["error"]["data"]["detail"]
+            .as_str()
+            .unwrap()
+            .contains("No clients registered"));
+
+        let client = test_client().await;
+        let response = call_tool(&client, "get_page_count", json!({"timeout_ms": 50})).await;
+        assert!(response.get("result").is_none());
+        assert_eq!(response["error"]["code"], error_codes::COMMAND_TIMEOUT);
+    }
+
+    #[tokio::test]
+    async fn test_zero_timeout_uses_default() {
+        let client = scripted_client(|_, _| json!({"pageCount": 3})).await;
+        let response = call_tool(&client, "get_page_count", json!({"timeout_ms": 0})).await;
+        assert_eq!(response["result"]["isError"], false);
+        assert_eq!(response["result"]["content"][0]["text"], "Page count: 3");
+    }
+
+    #[tokio::test]
+    async fn test_tool_level_failures_are_is_error_results() {
+        let client = test_client().await;
+        let response = call_tool(&client, "add_comment", json!({"page": 1})).await;
+        assert!(response.get("error").is_none());
+        assert_eq!(response["result"]["isError"], true);
+        assert!(response["result"]["content"][0]["text"]
+            .as_str()
+            .unwrap()
+            .contains("Missing required field: contents"));
+
+        let client = failing_client("JavaScript error: Page 9 out of range").await;
+        let response = call_tool(&client, "duplicate_page", json!({"page": 9})).await;
+        assert!(response.get("error").is_none());
+        assert_eq!(response["result"]["isError"], true);
+        assert!(response["result"]["content"][0]["text"]
+            .as_str()
+            .unwrap()
+            .contains("Page 9 out of range"));
+    }
+
+    #[tokio::test]
+    async fn test_resources_list_and_read() {
+        let client = scripted_client(|action, options| match action {
+            "listOpenDocuments" => json!({
+                "documents": [
+                    {"index": 0, "path": "/C/docs/report.pdf", "fileName": "report.pdf", "title": "Q3 Report", "numPages": 4},
+                    {"index": 1, "path": "/C/docs/notes.pdf", "fileName": "notes.pdf", "title": "", "numPages": 1}
+                ],
+                "count": 2
+            }),
+            "getDocumentInfo" => json!({"title": "Q3 Report", "numPages": 4, "path": options["documentPath"]}),
+            "extractText" => json!({"text": format!("text of {}", options["documentPath"].as_str().unwrap())}),
+            _ => json!({}),
+        })
+        .await;
+
+        let output = handle_line(r#"{"jsonrpc":"2.0","id":1,"method":"resources/list"}"#, &initialized_session(), &client, &|_| {})
+            .await
+            .unwrap()
+            .unwrap();
+        let response: Value = serde_json::from_str(&output).unwrap();
+        let listed = response["result"]["resources"].as_array().unwrap();
+        assert_eq!(listed.len(), 2);
+        assert_eq!(listed[0]["name"], "report.pdf");
+        assert_eq!(listed[0]["description"], "Q3 Report");
+        assert!(listed[1].get("description").is_none());
+
+        let uri = listed[0]["uri"].as_str().unwrap();
+        let line = json!({"jsonrpc": "2.0", "id": 2, "method": "resources/read", "params": {"uri": uri}}).to_string();
+        let output = handle_line(&line, &initialized_session(), &client, &|_| {}).await.unwrap().unwrap();
+        let response: Value = serde_json::from_str(&output).unwrap();
+        let contents = response["result"]["contents"].as_array().unwrap();
+        assert_eq!(contents.len(), 2);
+        assert_eq!(contents[0]["uri"], uri);
+        assert_eq!(contents[0]["mimeType"], "application/json");
+        let metadata: Value = serde_json::from_str(contents[0]["text"].as_str().unwrap()).unwrap();
+        assert_eq!(metadata["path"], "/C/docs/report.pdf");
+        assert_eq!(contents[1]["mimeType"], "text/plain");
+        assert_eq!(contents[1]["text"], "text of /C/docs/report.pdf");
+    }
+
+    #[tokio::test]
+    async fn test_resources_read_unknown_uri() {
+        let client = test_client().await;
+        let line = r#"{"jsonrpc":"2.0","id":3,"method":"resources/read","params":{"uri":"file:///x.pdf"}}"#;
+
+        let output = handle_line(line, &initialized_session(), &client, &|_| {}).await.unwrap().unwrap();
+        let response: Value = serde_json::from_str(&output).unwrap();
+        assert_eq!(response["error"]["code"], mcp::protocol::JsonRpcError::INVALID_PARAMS);
+    }
+
+    #[tokio::test]
+    async fn test_logging_set_level() {
+        let client = test_client().await;
+
+        let output = handle_line(
+            r#"{"jsonrpc":"2.0","id":1,"method":"logging/setLevel","params":{"level":"debug"}}"#,
+            &initialized_session(),
+            &client,
+            &|_| {},
+        )
+        .await
+        .unwrap()
+        .unwrap();
+        let response: Value = serde_json::from_str(&output).unwrap();
+        assert_eq!(response["id"], 1);
+        assert_eq!(response["result"], json!({}));
+
+        let output = handle_line(
+            r#"{"jsonrpc":"2.0","id":2,"method":"logging/setLevel","params":{"level":"loud"}}"#,
+            &initialized_session(),
+            &client,
+            &|_| {},
+        )
+        .await
+        .unwrap()
+        .unwrap();
+        let response: Value = serde_json::from_str(&output).unwrap();
+        assert_eq!(response["error"]["code"], mcp::protocol::JsonRpcError::INVALID_PARAMS);
+    }
+
+    #[tokio::test]
+    async fn test_batch_with_call_and_notification() {
+        let client = test_client().await;
+        let line = r#"[{"jsonrpc":"2.0","id":1,"method":"tools/list"},{"jsonrpc":"2.0","method":"notifications/initialized"}]"#;
+
+        let output = handle_line(line, &initialized_session(), &client, &|_| {}).await.unwrap().unwrap();
+        let responses: Value = serde_json::from_str(&output).unwrap();
+        let responses = responses.as_array().unwrap();
+        assert_eq!(responses.len(), 1);
+        assert_eq!(responses[0]["id"], 1);
+        assert!(responses[0]["result"]["tools"].is_array());
+    }
+
+    #[tokio::test]
+    async fn test_tools_list_pages_cover_every_tool() {
+        let client = test_client().await;
+        let mut names = Vec::new();
+        let mut cursor: Option<String> = None;
+        let mut pages = 0;
+
+        loop {
+            let params = match &cursor {
+                Some(cursor) => json!({ "cursor": cursor }),
+                None => json!({}),
+            };
+            let line = json!({"jsonrpc": "2.0", "id": pages, "method": "tools/list", "params": params}).to_string();
+            let output = handle_line(&line, &initialized_session(), &client, &|_| {}).await.unwrap().unwrap();
+            let response: Value = serde_json::from_str(&output).unwrap();
+            let result = &response["result"];
+            pages += 1;
+
+            for tool in result["tools"].as_array().unwrap() {
+                names.push(tool["name"].as_str().unwrap().to_string());
+            }
+            match result.get("nextCursor") {
+                Some(next) => cursor = Some(next.as_str().unwrap().to_string()),
+                None => break,
+            }
+        }
+
+        let expected: Vec<String> = tools::get_tool_definitions()
+            .iter()
+            .map(|tool| tool["name"].as_str().unwrap().to_string())
+            .collect();
+        assert!(pages > 1);
+        assert_eq!(names, expected);
+
+        let output = handle_line(
+            r#"{"jsonrpc":"2.0","id":9,"method":"tools/list","params":{"cursor":"bogus"}}"#,
+            &initialized_session(),
+            &client,
+            &|_| {},
+        )
+        .await
+        .unwrap()
+        .unwrap();
+        let response: Value = serde_json::from_str(&output).unwrap();
+        assert_eq!(response["error"]["code"], mcp::protocol::JsonRpcError::INVALID_PARAMS);
+    }
+
+    #[tokio::test]
+    async fn test_batch_of_notifications_produces_no_output() {
+        let client = test_client().await;
+        let line = r#"[{"jsonrpc":"2.0","method":"notifications/initialized"},{"jsonrpc":"2.0","method":"notifications/cancelled"}]"#;
+        assert!(handle_line(line, &initialized_session(), &client, &|_| {}).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_batch_errors() {
+        let client = test_client().await;
+
+        let output = handle_line("[]", &initialized_session(), &client, &|_| {}).await.unwrap().unwrap();
+        let response: Value = serde_json::from_str(&output).unwrap();
+        assert_eq!(response["error"]["code"], mcp::protocol::JsonRpcError::INVALID_REQUEST);
+
+        let output = handle_line(r#"[{"id":2},{"jsonrpc":"2.0","id":3,"method":"ping"}]"#, &initialized_session(), &client, &|_| {})
+            .await
+            .unwrap()
+            .unwrap();
+        let responses: Value = serde_json::from_str(&output).unwrap();
+        assert_eq!(responses[0]["error"]["code"], mcp::protocol::JsonRpcError::INVALID_REQUEST);
+        assert_eq!(responses[1]["id"], 3);
+    }
+
+    #[tokio::test]
+    async fn test_lsp_framed_request_is_answered() {
+        let client = test_client().await;
+        let request = "{\n  \"jsonrpc\": \"2.0\",\n  \"id\": 7,\n  \"method\": \"ping\"\n}";
+        let input = Framing::Lsp.frame(request);
+
+        let mut reader = BufReader::new(input.as_bytes());
+        let message = Framing::Lsp.read_message(&mut reader).await.unwrap().unwrap();
+        assert_eq!(message, request);
+        assert!(Framing::Lsp.read_message(&mut reader).await.unwrap().is_none());
+
+        let output = handle_line(&message, &initialized_session(), &client, &|_| {}).await.unwrap().unwrap();
+        let framed = Framing::Lsp.frame(&output);
+        assert!(framed.starts_with(&format!("Content-Length: {}\r\n\r\n", output.len())));
+
+        let mut reader = BufReader::new(framed.as_bytes());
+        let echoed = Framing::Lsp.read_message(&mut reader).await.unwrap().unwrap();
+        let response: Value = serde_json::from_str(&echoed).unwrap();
+        assert_eq!(response["id"], 7);
+        assert_eq!(response["result"]["status"], "ok");
+    }
+
+    #[tokio::test]
+    async fn test_requests_before_initialize_rejected() {
+        let client = scripted_client(|_, _| json!({"pageCount": 3})).await;
+        let session = Session::default();
+        let send = |line: Value| {
+            let line = line.to_string();
+            let session = &session;
+            let client = &client;
+            async move {
+                let output = handle_line(&line, session, client, &|_| {}).await.unwrap().unwrap();
+                serde_json::from_str::<Value>(&output).unwrap()
+            }
+        };
+        let tool_call = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "tools/call",
+            "params": {"name": "get_page_count", "arguments": {}}
+        });
+
+        let response = send(tool_call.clone()).await;
+        assert_eq!(response["error"]["code"], mcp::protocol::JsonRpcError::INVALID_REQUEST);
+        assert!(response["error"]["message"].as_str().unwrap().contains("before initialize"));
+
+        let response = send(json!({"jsonrpc": "2.0", "id": 2, "method": "ping"})).await;
+        assert_eq!(response["result"]["status"], "ok");
+
+        let initialize = json!({"jsonrpc": "2.0", "id": 3, "method": "initialize", "params": {}});
+        let first = send(initialize.clone()).await;
+        assert_eq!(first["result"]["serverInfo"]["name"], "acrobat-mcp");
+        assert!(handle_line(
+            r#"{"jsonrpc":"2.0","method":"notifications/initialized"}"#,
+            &session,
+            &client,
+            &|_| {}
+        )
+        .await
+        .unwrap()
+        .is_none());
+
+        let response = send(tool_call).await;
+        assert_eq!(response["result"]["isError"], false);
+        assert!(response["result"]["content"][0]["text"].as_str().unwrap().contains('3'));
+
+        // A repeated initialize is answered the same way
+        assert_eq!(send(initialize).await, first);
+    }
+
+    #[tokio::test]
+    async fn test_execute_javascript_requires_allow_raw_js() {
+        fn echo_script(action: &str, options: &Value) -> Value {
+            assert_eq!(action, "executeJavaScript");
+            json!({"result": format!("ran {}", options["script"].as_str().unwrap())})
+        }
+
+        let client = scripted_client(echo_script).await;
+        let response = call_tool(&client, "execute_javascript", json!({"script": "this.numPages"})).await;
+        assert_eq!(response["result"]["isError"], true);
+        assert!(response["result"]["content"][0]["text"]
+            .as_str()
+            .unwrap()
+            .contains(tools::RAW_JS_DISABLED));
+
+        let client = Arc::new(
+            client::AcrobatClient::new(&scripted_proxy(echo_script).await, 1000, None)
+                .await
+                .unwrap()
+                .with_raw_js(true),
+        );
+        let response = call_tool(&client, "execute_javascript", json!({"script": "this.numPages"})).await;
+        assert_eq!(response["result"]["isError"], false);
+        assert_eq!(response["result"]["content"][0]["text"], "ran this.numPages");
+
+        let response = call_tool(&client, "execute_javascript", json!({})).await;
+        assert_eq!(response["result"]["isError"], true);
+    }
+
+    #[tokio::test]
+    async fn test_extract_text_chunks_become_content_blocks() {
+        let client = scripted_client(|action, options| match action {
+            "getPageCount" => json!({"pageCount": 5}),
+            "extractText" => json!({"text": format!("text of {}", options["pageRange"].as_str().unwrap())}),
+            _ => json!({}),
+        })
+        .await;
+
+        let response = call_tool(&client, "extract_text", json!({"page_range": "1-3,5", "chunk_size": 2})).await;
+        assert_eq!(response["result"]["isError"], false);
+        let content = response["result"]["content"].as_array().unwrap();
+        assert_eq!(content.len(), 2);
+        assert_eq!(content[0]["type"], "text");
+        assert_eq!(content[0]["text"], "Extracted text (plain, pages 1-2):\ntext of 1-2");
+        assert_eq!(content[1]["text"], "Extracted text (plain, pages 3,5):\ntext of 3,5");
+
+        let response = call_tool(&client, "extract_text", json!({"chunk_size": 1})).await;
+        assert_eq!(response["result"]["content"].as_array().unwrap().len(), 5);
+
+        let response = call_tool(&client, "extract_text", json!({"chunk_size": 0})).await;
+        assert_eq!(response["result"]["isError"], true);
+    }
+
+    #[tokio::test]
+    async fn test_notification_yields_no_output() {
+        let client = test_client().await;
+
+        let output = handle_line(r#"{"jsonrpc":"2.0","id":1,"method":"initialize","params":{}}"#, &initialized_session(), &client, &|_| {})
+            .await
+            .unwrap()
+            .unwrap();
+        let response: Value = serde_json::from_str(&output).unwrap();
+        assert_eq!(response["id"], 1);
+
+        let output = handle_line(r#"{"jsonrpc":"2.0","method":"notifications/initialized"}"#, &initialized_session(), &client, &|_| {})
+            .await
+            .unwrap();
+        assert!(output.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_tool_call_reports_progress() {
+        let client = test_client().await;
+        let notifications = std::sync::Mutex::new(Vec::new());
+        let collect = |n: &mcp::protocol::JsonRpcNotification| {
+            notifications.lock().unwrap().push(serde_json::to_value(n).unwrap());
+        };
+        let line = r#"{"jsonrpc":"2.0","id":7,"method":"tools/call","params":{"name":"get_page_count","arguments":{"timeout_ms":50},"_meta":{"progressToken":"tok-1"}}}"#;
+
+        let output = handle_line(line, &initialized_session(), &client, &collect).await.unwrap().unwrap();
+        let response: Value = serde_json::from_str(&output).unwrap();
+        assert_eq!(response["id"], 7);
+
+        let notifications = notifications.lock().unwrap();
+        assert_eq!(notifications.len(), 2);
+        assert_eq!(notifications[0]["method"], "notifications/progress");
+        assert_eq!(notifications[0]["params"]["progressToken"], "tok-1");
+        assert_eq!(notifications[0]["params"]["progress"], 0);
+        assert_eq!(notifications[1]["params"]["progress"], 1);
+        assert_eq!(notifications[1]["params"]["total"], 1);
+        assert!(notifications[0].get("id").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_tool_call_without_token_reports_nothing() {
+        let client = test_client().await;
+        let count = std::sync::atomic::AtomicUsize::new(0);
+        let line = r#"{"jsonrpc":"2.0","id":8,"method":"tools/call","params":{"name":"get_page_count","arguments":{"timeout_ms":50}}}"#;
+
+        handle_line(line, &initialized_session(), &client, &|_| {
+            count.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+        })
+        .await
+        .unwrap();
+        assert_eq!(count.load(std::sync::atomic::Ordering::SeqCst), 0);
     }
 }
@@ -1,5 +1,6 @@
 //! JSON-RPC 2.0 protocol types for MCP
 
+use adobe_common::AdobeError;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
@@ -22,6 +23,43 @@ pub struct JsonRpcResponse {
     pub error: Option<JsonRpcError>,
 }
 
+/// Server-to-client message that expects no response
+#[derive(Debug, Serialize)]
+pub struct JsonRpcNotification {
+    pub jsonrpc: String,
+    pub method: String,
+    pub params: Value,
+}
+
+impl JsonRpcNotification {
+    /// `notifications/progress` for the request that supplied `token`
+    pub fn progress(token: Value, progress: u64, total: u64, message: &str) -> Self {
+        Self {
+            jsonrpc: "2.0".to_string(),
+            method: "notifications/progress".to_string(),
+            params: serde_json::json!({
+                "progressToken": token,
+                "progress": progress,
+                "total": total,
+                "message": message
+            }),
+        }
+    }
+
+    /// `notifications/message` log entry at an MCP `level`
+    pub fn log_message(level: &str, data: Value) -> Self {
+        Self {
+            jsonrpc: "2.0".to_string(),
+            method: "notifications/message".to_string(),
+            params: serde_json::json!({
+                "level": level,
+                "logger": "acrobat-mcp",
+                "data": data
+            }),
+        }
+    }
+}
+
 #[derive(Debug, Serialize)]
 pub struct JsonRpcError {
     pub code: i32,
@@ -32,12 +70,12 @@ pub struct JsonRpcError {
 
 impl JsonRpcError {
     pub const PARSE_ERROR: i32 = -32700;
-    #[allow(dead_code)]
     pub const INVALID_REQUEST: i32 = -32600;
     pub const METHOD_NOT_FOUND: i32 = -32601;
     pub const INVALID_PARAMS: i32 = -32602;
     #[allow(dead_code)]
     pub const INTERNAL_ERROR: i32 = -32603;
+    pub const APPLICATION_NOT_CONNECTED: i32 = -32000;
 
     pub fn new(code: i32, message: impl Into<String>) -> Self {
         Self {
@@ -52,7 +90,6 @@ impl JsonRpcError {
         Self::new(Self::PARSE_ERROR, format!("Parse error: {}", detail.into()))
     }
 
-    #[allow(dead_code)]
     pub fn invalid_request(detail: impl Into<String>) -> Self {
         Self::new(
             Self::INVALID_REQUEST,
@@ -71,7 +108,16 @@ impl JsonRpcError {
         )
     }
 
-    #[allow(dead_code)]
+    pub fn application_not_connected(detail: impl Into<String>) -> Self {
+        Self {
+            data: Some(serde_json::json!({ "detail": detail.into() })),
+            ..Self::new(
+                Self::APPLICATION_NOT_CONNECTED,
+                "Acrobat is not running or not connected to the proxy",
+            )
+        }
+    }
+
     pub fn internal_error(detail: impl Into<String>) -> Self {
         Self::new(
             Self::INTERNAL_ERROR,
@@ -80,6 +126,16 @@ impl JsonRpcError {
     }
 }
 
+impl From<&AdobeError> for JsonRpcError {
+    fn from(error: &AdobeError) -> Self {
+        match error.to_rpc_code() {
+            Self::INVALID_PARAMS => Self::invalid_params(error.to_string()),
+            Self::APPLICATION_NOT_CONNECTED => Self::application_not_connected(error.to_string()),
+            code => Self::new(code, error.to_string()),
+        }
+    }
+}
+
 impl JsonRpcResponse {
     pub fn success(id: Option<Value>, result: Value) -> Self {
         Self {
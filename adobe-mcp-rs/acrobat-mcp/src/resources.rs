@@ -0,0 +1,103 @@
+//! Open Acrobat documents exposed as MCP resources
+
+use crate::client::AcrobatClient;
+use adobe_common::AdobeError;
+use anyhow::Result;
+use serde_json::{json, Value};
+use std::sync::Arc;
+
+/// Resource URIs are this prefix followed by the document's Acrobat path,
+/// e.g. `acrobat://document//C/docs/report.pdf`
+pub const DOCUMENT_URI_PREFIX: &str = "acrobat://document/";
+
+/// Resource URI for the document at `path`
+pub fn document_uri(path: &str) -> String {
+    format!("{}{}", DOCUMENT_URI_PREFIX, path)
+}
+
+/// Document path named by a resource URI
+pub fn document_path(uri: &str) -> Result<&str, AdobeError> {
+    uri.strip_prefix(DOCUMENT_URI_PREFIX)
+        .filter(|path| !path.is_empty())
+        .ok_or_else(|| AdobeError::UnknownResource(uri.to_string()))
+}
+
+/// `resources/list` result: one resource per open document
+pub async fn list_resources(client: &Arc<AcrobatClient>) -> Result<Value> {
+    let response = client.send_command("listOpenDocuments", json!({}), None).await?;
+    let documents = AcrobatClient::extract_response(&response)
+        .and_then(|data| data.get("documents"))
+        .and_then(|docs| docs.as_array())
+        .cloned()
+        .unwrap_or_default();
+
+    let resources: Vec<Value> = documents
+        .iter()
+        .filter_map(|doc| {
+            let path = doc.get("path").and_then(|v| v.as_str())?;
+            let name = doc.get("fileName").and_then(|v| v.as_str()).unwrap_or(path);
+            let mut resource = json!({ "uri": document_uri(path), "name": name });
+            if let Some(title) = doc.get("title").and_then(|v| v.as_str()).filter(|t| !t.is_empty()) {
+                resource["description"] = json!(title);
+            }
+            Some(resource)
+        })
+        .collect();
+
+    Ok(json!({ "resources": resources }))
+}
+
+/// `resources/read` result: the document's metadata as JSON, then its text
+pub async fn read_resource(client: &Arc<AcrobatClient>, uri: &str) -> Result<Value> {
+    let path = document_path(uri)?;
+
+    let info = client
+        .send_command("getDocumentInfo", json!({ "documentPath": path }), None)
+        .await?;
+    let metadata = AcrobatClient::extract_response(&info).cloned().unwrap_or(json!({}));
+
+    let extracted = client
+        .send_command(
+            "extractText",
+            json!({ "documentPath": path, "pageRange": "all", "output": "plain" }),
+            None,
+        )
+        .await?;
+    let text = AcrobatClient::extract_response(&extracted)
+        .and_then(|data| data.get("text"))
+        .and_then(|v| v.as_str())
+        .unwrap_or_default();
+
+    Ok(json!({
+        "contents": [
+            {
+                "uri": uri,
+                "mimeType": "application/json",
+                "text": serde_json::to_string_pretty(&metadata)?
+            },
+            { "uri": uri, "mimeType": "text/plain", "text": text }
+        ]
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_document_uri_roundtrip() {
+        let uri = document_uri("/C/docs/report.pdf");
+        assert_eq!(uri, "acrobat://document//C/docs/report.pdf");
+        assert_eq!(document_path(&uri).unwrap(), "/C/docs/report.pdf");
+    }
+
+    #[test]
+    fn test_document_path_rejects_other_uris() {
+        for uri in ["acrobat://document/", "file:///C/docs/report.pdf", ""] {
+            assert!(matches!(
+                document_path(uri),
+                Err(AdobeError::UnknownResource(_))
+            ));
+        }
+    }
+}
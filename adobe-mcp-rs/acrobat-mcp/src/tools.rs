@@ -1,13 +1,15 @@
 //! Acrobat tool definitions and handlers
 
-use crate::client::AcrobatClient;
+use crate::client::{AcrobatClient, MAX_TIMEOUT_MS};
+use adobe_common::{PageRange, PageSpan};
 use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
 use std::sync::Arc;
 
 /// Get all tool definitions for MCP tools/list
 pub fn get_tool_definitions() -> Vec<Value> {
-    vec![
+    let mut tools = vec![
         json!({
             "name": "create_document",
             "description": "Create a new PDF document with specified size and page count",
@@ -21,7 +23,7 @@ pub fn get_tool_definitions() -> Vec<Value> {
                     "page_size": {
                         "type": "string",
                         "description": "Page size preset",
-                        "enum": ["LETTER", "LEGAL", "A4", "A3", "CUSTOM"],
+                        "enum": ["LETTER", "LEGAL", "A4", "A3", "A5", "B4", "B5", "TABLOID", "EXECUTIVE", "CUSTOM"],
                         "default": "LETTER"
                     },
                     "page_count": {
@@ -92,6 +94,21 @@ pub fn get_tool_definitions() -> Vec<Value> {
         json!({
             "name": "get_document_info",
             "description": "Get information about the current document",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "page_box": {
+                        "type": "string",
+                        "enum": ["Crop", "Media", "Bleed", "Trim", "Art"],
+                        "description": "Page box used to measure the reported page size",
+                        "default": "Crop"
+                    }
+                }
+            }
+        }),
+        json!({
+            "name": "get_security_info",
+            "description": "Report the document's security handler, whether it is encrypted, and which operations it permits; check before printing, copying, or editing a protected file",
             "inputSchema": {
                 "type": "object",
                 "properties": {}
@@ -131,7 +148,26 @@ pub fn get_tool_definitions() -> Vec<Value> {
                         "type": "string",
                         "description": "Font name",
                         "default": "Helvetica"
-                    }
+                    },
+                    "mode": {
+                        "type": "string",
+                        "enum": ["annotation", "content"],
+                        "description": "annotation adds an editable FreeText annotation; content writes the text into the page so it prints and flattens like native text",
+                        "default": "annotation"
+                    },
+                    "color": color_schema("Text color; default black"),
+                    "text_align": {
+                        "type": "string",
+                        "enum": ["left", "center", "right"],
+                        "description": "Horizontal text alignment",
+                        "default": "left"
+                    },
+                    "rotation": {
+                        "type": "integer",
+                        "enum": [0, 90, 180, 270],
+                        "description": "Text rotation in degrees"
+                    },
+                    "reject_out_of_bounds": out_of_bounds_schema()
                 },
                 "required": ["text"]
             }
@@ -144,8 +180,140 @@ pub fn get_tool_definitions() -> Vec<Value> {
                 "properties": {
                     "page_range": {
                         "type": "string",
-                        "description": "Page range (e.g., '1-5', 'all')",
+                        "description": "1-based page range (e.g., '1-5', '1-3,7,9-', 'all')",
+                        "default": "all"
+                    },
+                    "output": {
+                        "type": "string",
+                        "enum": ["plain", "perPage", "withCoords"],
+                        "description": "plain returns one string; perPage returns [{page, text}]; withCoords also includes each word's quads",
+                        "default": "plain"
+                    },
+                    "chunk_size": {
+                        "type": "integer",
+                        "minimum": 1,
+                        "description": "Pages per content block; when set, each chunk is extracted with its own command and returned as its own block"
+                    }
+                }
+            }
+        }),
+        json!({
+            "name": "search_text",
+            "description": "Find a word or phrase in the document and return each match's page, word index, and bounding box",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "query": {
+                        "type": "string",
+                        "description": "Word or phrase to find; phrases match consecutive words"
+                    },
+                    "case_sensitive": {
+                        "type": "boolean",
+                        "description": "Match letter case exactly",
+                        "default": false
+                    },
+                    "max_results": {
+                        "type": "integer",
+                        "description": "Stop after this many matches",
+                        "minimum": 1,
+                        "default": 100
+                    }
+                },
+                "required": ["query"]
+            }
+        }),
+        json!({
+            "name": "highlight_text",
+            "description": "Highlight text, given the quads of matches from search_text or a rectangle",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "page": {
+                        "type": "integer",
+                        "description": "Page number (1-based)",
+                        "default": 1
+                    },
+                    "quads": {
+                        "type": "array",
+                        "items": {
+                            "type": "array",
+                            "items": { "type": "number" },
+                            "minItems": 8,
+                            "maxItems": 8
+                        },
+                        "description": "Word quads to cover, as returned by search_text"
+                    },
+                    "rect": {
+                        "type": "array",
+                        "items": { "type": "number" },
+                        "minItems": 4,
+                        "maxItems": 4,
+                        "description": "[x1, y1, x2, y2] in PDF points, used when quads are not given"
+                    },
+                    "color": color_schema("Highlight color; default yellow"),
+                    "contents": {
+                        "type": "string",
+                        "description": "Comment attached to the highlight"
+                    },
+                    "author": {
+                        "type": "string",
+                        "description": "Author shown on the annotation"
+                    },
+                    "reject_out_of_bounds": out_of_bounds_schema()
+                }
+            }
+        }),
+        json!({
+            "name": "add_comment",
+            "description": "Add a sticky-note comment at a point on a page",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "page": {
+                        "type": "integer",
+                        "description": "Page number (1-based)",
+                        "default": 1
+                    },
+                    "x": {
+                        "type": "number",
+                        "description": "X coordinate in points",
+                        "default": 72
+                    },
+                    "y": {
+                        "type": "number",
+                        "description": "Y coordinate in points",
+                        "default": 720
+                    },
+                    "contents": {
+                        "type": "string",
+                        "description": "Comment text"
+                    },
+                    "author": {
+                        "type": "string",
+                        "description": "Author shown on the comment"
+                    },
+                    "color": color_schema("Note color"),
+                    "reject_out_of_bounds": out_of_bounds_schema()
+                },
+                "required": ["contents"]
+            }
+        }),
+        json!({
+            "name": "get_statistics",
+            "description": "Count pages, words, and characters in the document or a page range",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "page_range": {
+                        "type": "string",
+                        "description": "1-based page range to count (e.g., '1-5', 'all')",
                         "default": "all"
+                    },
+                    "max_pages": {
+                        "type": "integer",
+                        "description": "Stop counting after this many pages; the result says when it was truncated",
+                        "minimum": 1,
+                        "default": 500
                     }
                 }
             }
@@ -175,6 +343,125 @@ pub fn get_tool_definitions() -> Vec<Value> {
                 "required": ["file_path", "format"]
             }
         }),
+        json!({
+            "name": "convert_to_pdfa",
+            "description": "Convert the document to PDF/A for archiving and save it",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "file_path": {
+                        "type": "string",
+                        "description": "Output file path"
+                    },
+                    "version": {
+                        "type": "string",
+                        "description": "PDF/A conformance level",
+                        "enum": ["1a", "1b", "2a", "2b", "2u", "3a", "3b", "3u"],
+                        "default": "1b"
+                    }
+                },
+                "required": ["file_path"]
+            }
+        }),
+        json!({
+            "name": "linearize_document",
+            "description": "Save a linearized (Fast Web View) copy that shows the first page before the whole file downloads",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "output_path": {
+                        "type": "string",
+                        "description": "Output file path"
+                    }
+                },
+                "required": ["output_path"]
+            }
+        }),
+        json!({
+            "name": "check_pdfa_compliance",
+            "description": "Run Acrobat preflight to check the document against a PDF/A conformance level",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "version": {
+                        "type": "string",
+                        "description": "PDF/A conformance level",
+                        "enum": ["1a", "1b", "2a", "2b", "2u", "3a", "3b", "3u"],
+                        "default": "1b"
+                    }
+                }
+            }
+        }),
+        json!({
+            "name": "export_form_data",
+            "description": "Export form field values to an FDF or XFDF file",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "file_path": {
+                        "type": "string",
+                        "description": "Output file path (.fdf or .xfdf)"
+                    },
+                    "format": {
+                        "type": "string",
+                        "description": "Form data format (defaults to the file extension)",
+                        "enum": ["FDF", "XFDF"]
+                    }
+                },
+                "required": ["file_path"]
+            }
+        }),
+        json!({
+            "name": "import_form_data",
+            "description": "Fill form fields from an FDF or XFDF file",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "file_path": {
+                        "type": "string",
+                        "description": "FDF or XFDF file to import"
+                    },
+                    "format": {
+                        "type": "string",
+                        "description": "Form data format (defaults to the file extension)",
+                        "enum": ["FDF", "XFDF"]
+                    }
+                },
+                "required": ["file_path"]
+            }
+        }),
+        json!({
+            "name": "attach_file",
+            "description": "Embed a file in the document as an attachment",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "source_path": {
+                        "type": "string",
+                        "description": "File to attach"
+                    },
+                    "name": {
+                        "type": "string",
+                        "description": "Attachment name (defaults to the file name)"
+                    }
+                },
+                "required": ["source_path"]
+            }
+        }),
+        json!({
+            "name": "extract_attachments",
+            "description": "Write every file attachment into a directory",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "output_dir": {
+                        "type": "string",
+                        "description": "Directory to write attachments into"
+                    }
+                },
+                "required": ["output_dir"]
+            }
+        }),
         json!({
             "name": "merge_documents",
             "description": "Merge multiple PDF documents into one",
@@ -189,6 +476,16 @@ pub fn get_tool_definitions() -> Vec<Value> {
                     "output_path": {
                         "type": "string",
                         "description": "Output file path for merged PDF"
+                    },
+                    "preserve_bookmarks": {
+                        "type": "boolean",
+                        "description": "Add a top-level bookmark at the first page of each merged source",
+                        "default": false
+                    },
+                    "section_titles": {
+                        "type": "array",
+                        "items": { "type": "string" },
+                        "description": "Bookmark titles per source (defaults to the source filenames)"
                     }
                 },
                 "required": ["file_paths", "output_path"]
@@ -218,6 +515,101 @@ pub fn get_tool_definitions() -> Vec<Value> {
                 "required": ["page_ranges", "output_dir"]
             }
         }),
+        json!({
+            "name": "extract_pages",
+            "description": "Copy a range of pages into a new PDF, leaving the document unchanged",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "page_range": {
+                        "type": "string",
+                        "description": "Contiguous 1-based page range to copy (e.g., '5-8', '3', '10-')"
+                    },
+                    "output_path": {
+                        "type": "string",
+                        "description": "Path for the new PDF"
+                    }
+                },
+                "required": ["page_range", "output_path"]
+            }
+        }),
+        json!({
+            "name": "compare_documents",
+            "description": "Compare two PDFs page by page: pages added or removed and, in text mode, which shared pages have different text",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "base_path": {
+                        "type": "string",
+                        "description": "Original PDF"
+                    },
+                    "compare_path": {
+                        "type": "string",
+                        "description": "Revised PDF"
+                    },
+                    "mode": {
+                        "type": "string",
+                        "enum": ["text", "pageCount"],
+                        "description": "text compares each page's extracted text; pageCount only compares page counts",
+                        "default": "text"
+                    }
+                },
+                "required": ["base_path", "compare_path"]
+            }
+        }),
+        json!({
+            "name": "split_by_bookmarks",
+            "description": "Split document into one PDF per top-level bookmark, named after the bookmark titles",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "output_dir": {
+                        "type": "string",
+                        "description": "Output directory for split PDFs"
+                    },
+                    "sanitize_filenames": {
+                        "type": "boolean",
+                        "description": "Replace characters that are invalid in filenames",
+                        "default": true
+                    }
+                },
+                "required": ["output_dir"]
+            }
+        }),
+        json!({
+            "name": "list_open_documents",
+            "description": "List every open document with its index, path, title, page count, and whether it is active",
+            "inputSchema": {
+                "type": "object",
+                "properties": {}
+            }
+        }),
+        json!({
+            "name": "set_active_document",
+            "description": "Bring an open document to front so later commands act on it",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "file_path": {
+                        "type": "string",
+                        "description": "Path of the document (opened if it is not already)"
+                    },
+                    "index": {
+                        "type": "integer",
+                        "minimum": 0,
+                        "description": "Index from list_open_documents"
+                    }
+                }
+            }
+        }),
+        json!({
+            "name": "get_app_info",
+            "description": "Get the Acrobat version, viewer type (Reader, Standard, Pro) and platform, to check whether features like OCR or signing are available",
+            "inputSchema": {
+                "type": "object",
+                "properties": {}
+            }
+        }),
         json!({
             "name": "get_page_count",
             "description": "Get the number of pages in the current document",
@@ -262,27 +654,163 @@ pub fn get_tool_definitions() -> Vec<Value> {
             }
         }),
         json!({
-            "name": "add_bookmark",
-            "description": "Add a bookmark to a specific page",
+            "name": "insert_blank_page",
+            "description": "Insert an empty page into the document",
             "inputSchema": {
                 "type": "object",
                 "properties": {
-                    "title": {
-                        "type": "string",
-                        "description": "Bookmark title"
-                    },
-                    "page": {
+                    "after_page": {
+                        "type": "integer",
+                        "description": "Insert after this page (1-based; 0 inserts before the first page). Defaults to the end",
+                        "minimum": 0
+                    },
+                    "page_size": {
+                        "type": "string",
+                        "description": "Page size preset",
+                        "enum": ["LETTER", "LEGAL", "A4", "A3", "A5", "B4", "B5", "TABLOID", "EXECUTIVE", "CUSTOM"],
+                        "default": "LETTER"
+                    },
+                    "width": {
+                        "type": "number",
+                        "description": "Custom width in points (for CUSTOM page_size)"
+                    },
+                    "height": {
+                        "type": "number",
+                        "description": "Custom height in points (for CUSTOM page_size)"
+                    }
+                }
+            }
+        }),
+        json!({
+            "name": "duplicate_page",
+            "description": "Copy a page to another position in the same document. The copy is taken from the saved file, so save first if the page has unsaved changes",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "page": {
+                        "type": "integer",
+                        "description": "Page to copy (1-based)",
+                        "minimum": 1
+                    },
+                    "after_page": {
+                        "type": "integer",
+                        "description": "Insert the copy after this page (1-based; 0 inserts before the first page). Defaults to right after the original",
+                        "minimum": 0
+                    }
+                },
+                "required": ["page"]
+            }
+        }),
+        json!({
+            "name": "replace_page",
+            "description": "Replace a page with a page from another PDF",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "page": {
+                        "type": "integer",
+                        "description": "Page to replace (1-based)",
+                        "minimum": 1
+                    },
+                    "source_path": {
+                        "type": "string",
+                        "description": "Absolute path to the PDF containing the replacement page"
+                    },
+                    "source_page": {
+                        "type": "integer",
+                        "description": "Page of the source PDF to use (1-based)",
+                        "minimum": 1,
+                        "default": 1
+                    }
+                },
+                "required": ["page", "source_path"]
+            }
+        }),
+        json!({
+            "name": "add_bookmark",
+            "description": "Add a bookmark to a specific page",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "title": {
+                        "type": "string",
+                        "description": "Bookmark title"
+                    },
+                    "page": {
                         "type": "integer",
                         "description": "Target page number (1-based)"
                     },
                     "parent": {
                         "type": "string",
                         "description": "Parent bookmark title (optional)"
+                    },
+                    "zoom": {
+                        "description": "Zoom applied when the bookmark is followed; the current zoom is kept when omitted",
+                        "oneOf": [
+                            { "type": "string", "enum": ["FitPage", "FitWidth", "FitHeight"] },
+                            { "type": "number", "exclusiveMinimum": 0, "description": "Zoom percentage" }
+                        ]
+                    },
+                    "left": {
+                        "type": "number",
+                        "description": "Horizontal scroll position in page coordinates (default 0 when top is given)"
+                    },
+                    "top": {
+                        "type": "number",
+                        "description": "Vertical scroll position in page coordinates (default 0 when left is given)"
                     }
                 },
                 "required": ["title", "page"]
             }
         }),
+        json!({
+            "name": "get_bookmarks",
+            "description": "Get the full bookmark tree with each bookmark's target page",
+            "inputSchema": {
+                "type": "object",
+                "properties": {}
+            }
+        }),
+        json!({
+            "name": "delete_bookmark",
+            "description": "Delete a bookmark (and its children) by its title path",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "path": {
+                        "type": "array",
+                        "items": { "type": "string" },
+                        "minItems": 1,
+                        "description": "Titles from the top level down, e.g. [\"Chapter 1\", \"Section 1.2\"]"
+                    }
+                },
+                "required": ["path"]
+            }
+        }),
+        json!({
+            "name": "generate_toc",
+            "description": "Insert a linked table of contents, built from the bookmarks, at the front of the document",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "title": {
+                        "type": "string",
+                        "description": "Heading of the contents page",
+                        "default": "Table of Contents"
+                    },
+                    "font_size": {
+                        "type": "number",
+                        "description": "Entry font size in points; the heading is 1.5x",
+                        "default": 12
+                    },
+                    "include_levels": {
+                        "type": "integer",
+                        "description": "Bookmark depth to include (1 = top level only); all levels when omitted",
+                        "minimum": 1
+                    }
+                }
+            }
+        }),
         json!({
             "name": "set_metadata",
             "description": "Set document metadata (title, author, subject, keywords)",
@@ -308,7 +836,235 @@ pub fn get_tool_definitions() -> Vec<Value> {
                 }
             }
         }),
-    ]
+        json!({
+            "name": "set_initial_view",
+            "description": "Control how the PDF opens: navigation panel, page layout, first page, and zoom",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "page_mode": {
+                        "type": "string",
+                        "description": "Panel shown on open",
+                        "enum": ["none", "bookmarks", "thumbnails", "fullscreen"]
+                    },
+                    "page_layout": {
+                        "type": "string",
+                        "description": "Page layout",
+                        "enum": ["single", "continuous", "facing"]
+                    },
+                    "page": {
+                        "type": "integer",
+                        "description": "Page to open on (1-based)",
+                        "minimum": 1
+                    },
+                    "zoom": {
+                        "description": "Zoom percentage, or fit-page / fit-width",
+                        "oneOf": [
+                            { "type": "number", "exclusiveMinimum": 0 },
+                            { "type": "string", "enum": ["fit-page", "fit-width"] }
+                        ]
+                    }
+                }
+            }
+        }),
+        json!({
+            "name": "sanitize_document",
+            "description": "Strip hidden data (metadata, JavaScript, attachments) before sharing; hidden layers are reported",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "metadata": {
+                        "type": "boolean",
+                        "description": "Clear document info and XMP metadata",
+                        "default": true
+                    },
+                    "javascript": {
+                        "type": "boolean",
+                        "description": "Clear document, page, and field actions",
+                        "default": true
+                    },
+                    "hidden_layers": {
+                        "type": "boolean",
+                        "description": "Report hidden layers (Acrobat JavaScript cannot delete them)",
+                        "default": true
+                    },
+                    "attachments": {
+                        "type": "boolean",
+                        "description": "Remove embedded files and file attachment annotations",
+                        "default": true
+                    },
+                    "dry_run": {
+                        "type": "boolean",
+                        "description": "Only report what would be removed",
+                        "default": false
+                    }
+                }
+            }
+        }),
+        json!({
+            "name": "remove_watermarks",
+            "description": "Remove watermarks (Watermark annotations and watermark layers), optionally only on some pages",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "page_range": {
+                        "type": "string",
+                        "description": "1-based pages to clean (e.g., '1-3,7'); all pages when omitted"
+                    }
+                }
+            }
+        }),
+        json!({
+            "name": "get_pdf_layers",
+            "description": "List the PDF's optional content groups (layers) and whether each is visible",
+            "inputSchema": {
+                "type": "object",
+                "properties": {}
+            }
+        }),
+        json!({
+            "name": "set_pdf_layer_visibility",
+            "description": "Show or hide a PDF layer (optional content group) by name",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "name": {
+                        "type": "string",
+                        "description": "Layer name; every layer with this name is changed"
+                    },
+                    "visible": {
+                        "type": "boolean",
+                        "description": "Whether the layer should be shown"
+                    }
+                },
+                "required": ["name", "visible"]
+            }
+        }),
+        json!({
+            "name": "execute_javascript",
+            "description": "Run an Acrobat JavaScript snippet and return its raw result. Disabled unless the server runs with --allow-raw-js and the bridge allows raw JavaScript",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "script": {
+                        "type": "string",
+                        "description": "JavaScript to evaluate with the document as `this`; the value of its last expression is returned"
+                    }
+                },
+                "required": ["script"]
+            }
+        }),
+    ];
+
+    for tool in &mut tools {
+        let targets_document = tool["name"]
+            .as_str()
+            .is_some_and(|name| !DOCUMENT_INDEPENDENT_TOOLS.contains(&name));
+        if let Some(properties) = tool["inputSchema"]["properties"].as_object_mut() {
+            if targets_document {
+                properties.insert(
+                    "document_path".to_string(),
+                    json!({
+                        "type": "string",
+                        "description": "Path of an open document to act on instead of the active one"
+                    }),
+                );
+            }
+            properties.insert(
+                "timeout_ms".to_string(),
+                json!({
+                    "type": "integer",
+                    "description": format!(
+                        "Override the command timeout for this call, in milliseconds (max {})",
+                        MAX_TIMEOUT_MS
+                    ),
+                    "minimum": 1,
+                    "maximum": MAX_TIMEOUT_MS
+                }),
+            );
+        }
+    }
+    tools
+}
+
+/// Schema for a color in any form `adobe_common::Color` accepts
+fn color_schema(purpose: &str) -> Value {
+    json!({
+        "description": format!(
+            "{}. \"#RRGGBB\", \"#RGB\", {{red, green, blue}} (0-255), or an Acrobat color array such as [\"CMYK\", 0, 0.5, 1, 0] or [\"G\", 0.5]",
+            purpose
+        ),
+        "oneOf": [
+            { "type": "string", "pattern": "^#?([0-9a-fA-F]{3}){1,2}$" },
+            {
+                "type": "object",
+                "properties": {
+                    "red": { "type": "integer", "minimum": 0, "maximum": 255 },
+                    "green": { "type": "integer", "minimum": 0, "maximum": 255 },
+                    "blue": { "type": "integer", "minimum": 0, "maximum": 255 }
+                },
+                "required": ["red", "green", "blue"]
+            },
+            {
+                "type": "array",
+                "description": "\"RGB\" + 3, \"CMYK\" + 4, or \"G\" + 1 components in 0.0-1.0",
+                "minItems": 2,
+                "maxItems": 5
+            }
+        ]
+    })
+}
+
+/// Schema for the annotation tools' `reject_out_of_bounds` flag
+fn out_of_bounds_schema() -> Value {
+    json!({
+        "type": "boolean",
+        "description": "Fail instead of clamping when the annotation extends past the page edges; one entirely off the page always fails",
+        "default": false
+    })
+}
+
+/// Tools that do not act on the active document, so take no `document_path`
+const DOCUMENT_INDEPENDENT_TOOLS: &[&str] = &[
+    "create_document",
+    "open_document",
+    "merge_documents",
+    "compare_documents",
+    "get_app_info",
+    "list_open_documents",
+    "set_active_document",
+];
+
+/// Add the caller's `document_path` target, if any, to command options
+fn with_document_path(mut options: Value, args: &Value) -> Value {
+    if let Some(path) = args.get("document_path").and_then(|v| v.as_str()) {
+        options["documentPath"] = json!(path);
+    }
+    options
+}
+
+/// Per-call `timeout_ms` override, if the caller supplied a positive one
+///
+/// 0 is ignored like any other invalid value rather than timing out at once.
+fn timeout_override(args: &Value) -> Option<u64> {
+    args.get("timeout_ms").and_then(|v| v.as_u64()).filter(|n| *n > 0)
+}
+
+/// Content blocks for a tool call's result
+///
+/// Most tools answer with a single text block; `extract_text` with a
+/// `chunk_size` answers with one block per chunk of pages.
+pub async fn handle_tool_call_content(
+    client: &Arc<AcrobatClient>,
+    tool_name: &str,
+    args: Value,
+) -> Result<Vec<String>> {
+    match tool_name {
+        "extract_text" => extract_text(client, args).await,
+        _ => handle_tool_call(client, tool_name, args)
+            .await
+            .map(|text| vec![text]),
+    }
 }
 
 /// Handle tool call and route to appropriate function
@@ -323,16 +1079,46 @@ pub async fn handle_tool_call(
         "save_document" => save_document(client, args).await,
         "close_document" => close_document(client, args).await,
         "get_document_info" => get_document_info(client, args).await,
+        "get_security_info" => get_security_info(client, args).await,
         "add_text" => add_text(client, args).await,
-        "extract_text" => extract_text(client, args).await,
+        "extract_text" => extract_text(client, args).await.map(|blocks| blocks.join("\n\n")),
+        "search_text" => search_text(client, args).await,
+        "get_statistics" => get_statistics(client, args).await,
+        "highlight_text" => highlight_text(client, args).await,
+        "add_comment" => add_comment(client, args).await,
         "export_as" => export_as(client, args).await,
+        "convert_to_pdfa" => convert_to_pdfa(client, args).await,
+        "check_pdfa_compliance" => check_pdfa_compliance(client, args).await,
+        "linearize_document" => linearize_document(client, args).await,
+        "export_form_data" => export_form_data(client, args).await,
+        "import_form_data" => import_form_data(client, args).await,
+        "attach_file" => attach_file(client, args).await,
+        "extract_attachments" => extract_attachments(client, args).await,
         "merge_documents" => merge_documents(client, args).await,
         "split_document" => split_document(client, args).await,
+        "split_by_bookmarks" => split_by_bookmarks(client, args).await,
+        "extract_pages" => extract_pages(client, args).await,
+        "compare_documents" => compare_documents(client, args).await,
         "get_page_count" => get_page_count(client, args).await,
+        "get_app_info" => get_app_info(client, args).await,
+        "list_open_documents" => list_open_documents(client, args).await,
+        "set_active_document" => set_active_document(client, args).await,
         "delete_pages" => delete_pages(client, args).await,
         "rotate_pages" => rotate_pages(client, args).await,
+        "insert_blank_page" => insert_blank_page(client, args).await,
+        "duplicate_page" => duplicate_page(client, args).await,
+        "replace_page" => replace_page(client, args).await,
         "add_bookmark" => add_bookmark(client, args).await,
+        "get_bookmarks" => get_bookmarks(client, args).await,
+        "delete_bookmark" => delete_bookmark(client, args).await,
+        "generate_toc" => generate_toc(client, args).await,
         "set_metadata" => set_metadata(client, args).await,
+        "set_initial_view" => set_initial_view(client, args).await,
+        "sanitize_document" => sanitize_document(client, args).await,
+        "remove_watermarks" => remove_watermarks(client, args).await,
+        "get_pdf_layers" => get_pdf_layers(client, args).await,
+        "set_pdf_layer_visibility" => set_pdf_layer_visibility(client, args).await,
+        "execute_javascript" => execute_javascript(client, args).await,
         _ => Err(anyhow!("Unknown tool: {}", tool_name)),
     }
 }
@@ -353,7 +1139,7 @@ async fn create_document(client: &Arc<AcrobatClient>, args: Value) -> Result<Str
         "height": args.get("height").and_then(|v| v.as_f64()),
     });
 
-    let response = client.send_command("createDocument", options).await?;
+    let response = client.send_command("createDocument", options, timeout_override(&args)).await?;
     Ok(format!(
         "Created document: {}",
         serde_json::to_string_pretty(&response.document)?
@@ -367,7 +1153,7 @@ async fn open_document(client: &Arc<AcrobatClient>, args: Value) -> Result<Strin
         .ok_or_else(|| anyhow!("Missing required field: file_path"))?;
 
     let options = json!({ "filePath": file_path });
-    let response = client.send_command("openDocument", options).await?;
+    let response = client.send_command("openDocument", options, timeout_override(&args)).await?;
 
     Ok(format!(
         "Opened document: {}",
@@ -386,7 +1172,7 @@ async fn save_document(client: &Arc<AcrobatClient>, args: Value) -> Result<Strin
         "format": args.get("format").and_then(|v| v.as_str()).unwrap_or("PDF"),
     });
 
-    let _response = client.send_command("saveDocument", options).await?;
+    let _response = client.send_command("saveDocument", with_document_path(options, &args), timeout_override(&args)).await?;
     Ok(format!("Document saved to: {}", file_path))
 }
 
@@ -395,19 +1181,97 @@ async fn close_document(client: &Arc<AcrobatClient>, args: Value) -> Result<Stri
         "saveChanges": args.get("save_changes").and_then(|v| v.as_bool()).unwrap_or(false),
     });
 
-    let _response = client.send_command("closeDocument", options).await?;
+    let _response = client.send_command("closeDocument", with_document_path(options, &args), timeout_override(&args)).await?;
     Ok("Document closed".to_string())
 }
 
-async fn get_document_info(client: &Arc<AcrobatClient>, _args: Value) -> Result<String> {
-    let response = client.send_command("getDocumentInfo", json!({})).await?;
+async fn get_document_info(client: &Arc<AcrobatClient>, args: Value) -> Result<String> {
+    let options = json!({
+        "pageBox": args.get("page_box").and_then(|v| v.as_str()).unwrap_or("Crop"),
+    });
+    let response = client.send_command("getDocumentInfo", with_document_path(options, &args), timeout_override(&args)).await?;
+    let info: DocumentSummary = response.parse_response()?;
 
     Ok(format!(
         "Document info:\n{}",
-        serde_json::to_string_pretty(&response.document)?
+        serde_json::to_string_pretty(&info)?
+    ))
+}
+
+/// `getDocumentInfo` payload
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct DocumentSummary {
+    #[serde(default)]
+    title: String,
+    #[serde(default)]
+    author: String,
+    #[serde(default)]
+    subject: String,
+    #[serde(default)]
+    keywords: String,
+    #[serde(default)]
+    creator: String,
+    #[serde(default)]
+    producer: String,
+    /// ISO 8601; absent when the document has no such date
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    created: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    modified: Option<String>,
+    num_pages: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    page_size: Option<PageDimensions>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    page_box: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    path: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct PageDimensions {
+    width: f64,
+    height: f64,
+}
+
+async fn get_security_info(client: &Arc<AcrobatClient>, args: Value) -> Result<String> {
+    let response = client.send_command("getSecurityInfo", with_document_path(json!({}), &args), timeout_override(&args)).await?;
+    let info: SecurityInfo = response.parse_response()?;
+
+    Ok(format!(
+        "Security info:\n{}",
+        serde_json::to_string_pretty(&info)?
     ))
 }
 
+/// `getSecurityInfo` payload
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SecurityInfo {
+    #[serde(default)]
+    security_handler: Option<String>,
+    #[serde(default)]
+    encrypted: bool,
+    #[serde(default)]
+    permissions: Permissions,
+}
+
+/// `None` where Acrobat cannot tell without side effects
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Permissions {
+    #[serde(default)]
+    printing: Option<bool>,
+    #[serde(default)]
+    copying: Option<bool>,
+    #[serde(default)]
+    modifying: Option<bool>,
+    #[serde(default)]
+    annotating: Option<bool>,
+    #[serde(default)]
+    form_filling: Option<bool>,
+}
+
 async fn add_text(client: &Arc<AcrobatClient>, args: Value) -> Result<String> {
     let text = args
         .get("text")
@@ -421,72 +1285,431 @@ async fn add_text(client: &Arc<AcrobatClient>, args: Value) -> Result<String> {
         "y": args.get("y").and_then(|v| v.as_f64()).unwrap_or(720.0),
         "fontSize": args.get("font_size").and_then(|v| v.as_f64()).unwrap_or(12.0),
         "fontName": args.get("font_name").and_then(|v| v.as_str()).unwrap_or("Helvetica"),
+        "mode": args.get("mode").and_then(|v| v.as_str()).unwrap_or("annotation"),
+        "color": args.get("color"),
+        "textAlign": args.get("text_align").and_then(|v| v.as_str()),
+        "rotation": args.get("rotation").and_then(|v| v.as_i64()),
+        "rejectOutOfBounds": args.get("reject_out_of_bounds").and_then(|v| v.as_bool()),
     });
 
-    let _response = client.send_command("addText", options).await?;
+    let _response = client.send_command("addText", with_document_path(options, &args), timeout_override(&args)).await?;
     Ok("Text added successfully".to_string())
 }
 
-async fn extract_text(client: &Arc<AcrobatClient>, args: Value) -> Result<String> {
+async fn extract_text(client: &Arc<AcrobatClient>, args: Value) -> Result<Vec<String>> {
+    let page_range: PageRange = args
+        .get("page_range")
+        .and_then(|v| v.as_str())
+        .unwrap_or("all")
+        .parse()?;
+
+    let output = args.get("output").and_then(|v| v.as_str()).unwrap_or("plain");
+
+    let Some(chunk_size) = args.get("chunk_size") else {
+        return Ok(vec![extract_text_block(client, &args, &page_range, output, None).await?]);
+    };
+    let chunk_size = chunk_size
+        .as_u64()
+        .filter(|&n| n > 0)
+        .ok_or_else(|| anyhow!("chunk_size must be a positive integer"))?;
+
+    // One extractText command per chunk, so no response has to carry the
+    // whole document's text
+    let response = client.send_command("getPageCount", with_document_path(json!({}), &args), timeout_override(&args)).await?;
+    let count: PageCount = response.parse_response()?;
+    let pages = page_range.pages(count.page_count)?;
+    if pages.is_empty() {
+        return Ok(vec!["No text extracted".to_string()]);
+    }
+
+    let mut blocks = Vec::new();
+    for chunk in pages.chunks(chunk_size as usize) {
+        let chunk_range = page_chunk_range(chunk);
+        blocks.push(extract_text_block(client, &args, &chunk_range, output, Some(&chunk_range)).await?);
+    }
+    Ok(blocks)
+}
+
+/// Extract `page_range` as one content block, headed by `chunk` when the
+/// result is split
+async fn extract_text_block(
+    client: &Arc<AcrobatClient>,
+    args: &Value,
+    page_range: &PageRange,
+    output: &str,
+    chunk: Option<&PageRange>,
+) -> Result<String> {
     let options = json!({
-        "pageRange": args.get("page_range").and_then(|v| v.as_str()).unwrap_or("all"),
+        "pageRange": page_range.to_string(),
+        "output": output,
     });
 
-    let response = client.send_command("extractText", options).await?;
+    let response = client
+        .send_command(
+            "extractText",
+            with_document_path(options, args),
+            timeout_override(args),
+        )
+        .await?;
+    let label = match chunk {
+        Some(chunk) => format!("{}, pages {}", output, chunk),
+        None => output.to_string(),
+    };
 
-    if let Some(data) = AcrobatClient::extract_response(&response) {
-        Ok(format!("Extracted text:\n{}", data))
-    } else {
-        Ok("No text extracted".to_string())
+    match AcrobatClient::extract_response(&response) {
+        Some(data) if data.get("pages").is_some() => Ok(format!(
+            "Extracted text ({}):\n{}",
+            label,
+            serde_json::to_string_pretty(&data["pages"])?
+        )),
+        Some(data) => match chunk {
+            Some(_) => Ok(format!(
+                "Extracted text ({}):\n{}",
+                label,
+                data.get("text")
+                    .and_then(|v| v.as_str())
+                    .unwrap_or_default()
+            )),
+            None => Ok(format!("Extracted text:\n{}", data)),
+        },
+        None => Ok("No text extracted".to_string()),
     }
 }
 
-async fn export_as(client: &Arc<AcrobatClient>, args: Value) -> Result<String> {
-    let file_path = args
-        .get("file_path")
-        .and_then(|v| v.as_str())
-        .ok_or_else(|| anyhow!("Missing required field: file_path"))?;
-    let format = args
-        .get("format")
+/// Sorted 1-based `pages` as a range of contiguous spans, such as `1-2,7`
+fn page_chunk_range(pages: &[u32]) -> PageRange {
+    let mut spans: Vec<PageSpan> = Vec::new();
+    for &page in pages {
+        match spans.last_mut() {
+            Some(PageSpan { end: Some(end), .. }) if *end + 1 == page => *end = page,
+            _ => spans.push(PageSpan::single(page)),
+        }
+    }
+    PageRange::Spans(spans)
+}
+
+async fn search_text(client: &Arc<AcrobatClient>, args: Value) -> Result<String> {
+    let query = args
+        .get("query")
         .and_then(|v| v.as_str())
-        .ok_or_else(|| anyhow!("Missing required field: format"))?;
+        .ok_or_else(|| anyhow!("Missing required field: query"))?;
 
     let options = json!({
-        "filePath": file_path,
-        "format": format,
-        "quality": args.get("quality").and_then(|v| v.as_i64()).unwrap_or(90),
+        "query": query,
+        "caseSensitive": args.get("case_sensitive").and_then(|v| v.as_bool()).unwrap_or(false),
+        "maxResults": args.get("max_results").and_then(|v| v.as_u64()).unwrap_or(100),
     });
 
-    let _response = client.send_command("exportAs", options).await?;
-    Ok(format!("Exported to: {} ({})", file_path, format))
+    let response = client.send_command("searchText", with_document_path(options, &args), timeout_override(&args)).await?;
+
+    match AcrobatClient::extract_response(&response) {
+        Some(data) => Ok(format!(
+            "Found {} matches for '{}':\n{}",
+            data.get("count").and_then(|v| v.as_u64()).unwrap_or(0),
+            query,
+            serde_json::to_string_pretty(data.get("matches").unwrap_or(data))?
+        )),
+        None => Ok(format!("No matches for '{}'", query)),
+    }
 }
 
-async fn merge_documents(client: &Arc<AcrobatClient>, args: Value) -> Result<String> {
-    let file_paths = args
-        .get("file_paths")
-        .and_then(|v| v.as_array())
-        .ok_or_else(|| anyhow!("Missing required field: file_paths"))?;
-    let output_path = args
-        .get("output_path")
-        .and_then(|v| v.as_str())
-        .ok_or_else(|| anyhow!("Missing required field: output_path"))?;
+async fn highlight_text(client: &Arc<AcrobatClient>, args: Value) -> Result<String> {
+    if args.get("quads").is_none() && args.get("rect").is_none() {
+        return Err(anyhow!("Missing required field: quads or rect"));
+    }
 
     let options = json!({
-        "filePaths": file_paths,
-        "outputPath": output_path,
+        "page": args.get("page").and_then(|v| v.as_i64()).unwrap_or(1),
+        "quads": args.get("quads"),
+        "rect": args.get("rect"),
+        "color": args.get("color"),
+        "contents": args.get("contents").and_then(|v| v.as_str()),
+        "author": args.get("author").and_then(|v| v.as_str()),
+        "rejectOutOfBounds": args.get("reject_out_of_bounds").and_then(|v| v.as_bool()),
     });
 
-    let _response = client.send_command("mergeDocuments", options).await?;
-    Ok(format!("Merged {} documents to: {}", file_paths.len(), output_path))
+    let response = client.send_command("highlightText", with_document_path(options, &args), timeout_override(&args)).await?;
+    let annot: AnnotationCreated = response.parse_response()?;
+    Ok(format!("Added {} annotation on page {}", annot.annotation_type, annot.page))
 }
 
-async fn split_document(client: &Arc<AcrobatClient>, args: Value) -> Result<String> {
-    let page_ranges = args
-        .get("page_ranges")
-        .and_then(|v| v.as_array())
-        .ok_or_else(|| anyhow!("Missing required field: page_ranges"))?;
-    let output_dir = args
-        .get("output_dir")
+async fn add_comment(client: &Arc<AcrobatClient>, args: Value) -> Result<String> {
+    let contents = args
+        .get("contents")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| anyhow!("Missing required field: contents"))?;
+
+    let options = json!({
+        "page": args.get("page").and_then(|v| v.as_i64()).unwrap_or(1),
+        "x": args.get("x").and_then(|v| v.as_f64()).unwrap_or(72.0),
+        "y": args.get("y").and_then(|v| v.as_f64()).unwrap_or(720.0),
+        "contents": contents,
+        "author": args.get("author").and_then(|v| v.as_str()),
+        "color": args.get("color"),
+        "rejectOutOfBounds": args.get("reject_out_of_bounds").and_then(|v| v.as_bool()),
+    });
+
+    let response = client.send_command("addComment", with_document_path(options, &args), timeout_override(&args)).await?;
+    let annot: AnnotationCreated = response.parse_response()?;
+    Ok(format!("Added {} annotation on page {}", annot.annotation_type, annot.page))
+}
+
+/// `highlightText` / `addComment` payload
+#[derive(Debug, Deserialize)]
+struct AnnotationCreated {
+    page: u32,
+    #[serde(rename = "type")]
+    annotation_type: String,
+}
+
+async fn get_statistics(client: &Arc<AcrobatClient>, args: Value) -> Result<String> {
+    let page_range: PageRange = args
+        .get("page_range")
+        .and_then(|v| v.as_str())
+        .unwrap_or("all")
+        .parse()?;
+
+    let options = json!({
+        "pageRange": page_range.to_string(),
+        "maxPages": args.get("max_pages").and_then(|v| v.as_u64()).unwrap_or(500),
+    });
+
+    let response = client.send_command("getStatistics", with_document_path(options, &args), timeout_override(&args)).await?;
+    let stats: DocumentStatistics = response.parse_response()?;
+
+    Ok(format!(
+        "Document statistics:\n{}",
+        serde_json::to_string_pretty(&stats)?
+    ))
+}
+
+/// `getStatistics` payload
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct DocumentStatistics {
+    num_pages: u32,
+    pages_counted: u32,
+    word_count: u64,
+    character_count: u64,
+    /// Counting stopped at `max_pages` before the range was covered
+    truncated: bool,
+}
+
+async fn export_as(client: &Arc<AcrobatClient>, args: Value) -> Result<String> {
+    let file_path = args
+        .get("file_path")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| anyhow!("Missing required field: file_path"))?;
+    let format = args
+        .get("format")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| anyhow!("Missing required field: format"))?;
+
+    let options = json!({
+        "filePath": file_path,
+        "format": format,
+        "quality": args.get("quality").and_then(|v| v.as_i64()).unwrap_or(90),
+    });
+
+    let _response = client.send_command("exportAs", with_document_path(options, &args), timeout_override(&args)).await?;
+    Ok(format!("Exported to: {} ({})", file_path, format))
+}
+
+async fn convert_to_pdfa(client: &Arc<AcrobatClient>, args: Value) -> Result<String> {
+    let file_path = args
+        .get("file_path")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| anyhow!("Missing required field: file_path"))?;
+    let version = args.get("version").and_then(|v| v.as_str()).unwrap_or("1b");
+
+    let options = json!({
+        "filePath": file_path,
+        "version": version,
+    });
+
+    let _response = client.send_command("convertToPdfA", with_document_path(options, &args), timeout_override(&args)).await?;
+    Ok(format!("Converted to PDF/A-{}: {}", version, file_path))
+}
+
+async fn linearize_document(client: &Arc<AcrobatClient>, args: Value) -> Result<String> {
+    let output_path = args
+        .get("output_path")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| anyhow!("Missing required field: output_path"))?;
+
+    let options = json!({
+        "outputPath": output_path,
+    });
+
+    let _response = client.send_command("linearizeDocument", with_document_path(options, &args), timeout_override(&args)).await?;
+    Ok(format!("Linearized copy saved to: {}", output_path))
+}
+
+async fn check_pdfa_compliance(client: &Arc<AcrobatClient>, args: Value) -> Result<String> {
+    let options = json!({
+        "version": args.get("version").and_then(|v| v.as_str()).unwrap_or("1b"),
+    });
+
+    let response = client.send_command("checkCompliance", with_document_path(options, &args), timeout_override(&args)).await?;
+
+    match AcrobatClient::extract_response(&response) {
+        Some(data) => Ok(format!(
+            "PDF/A compliance:\n{}",
+            serde_json::to_string_pretty(data)?
+        )),
+        None => Ok("No compliance report returned".to_string()),
+    }
+}
+
+async fn export_form_data(client: &Arc<AcrobatClient>, args: Value) -> Result<String> {
+    let file_path = args
+        .get("file_path")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| anyhow!("Missing required field: file_path"))?;
+
+    let options = json!({
+        "filePath": file_path,
+        "format": args.get("format"),
+    });
+
+    let response = client.send_command("exportFormData", with_document_path(options, &args), timeout_override(&args)).await?;
+    let written = AcrobatClient::extract_response(&response)
+        .and_then(|data| data.get("filePath"))
+        .and_then(|v| v.as_str())
+        .unwrap_or(file_path);
+
+    Ok(format!("Form data exported to: {}", written))
+}
+
+async fn import_form_data(client: &Arc<AcrobatClient>, args: Value) -> Result<String> {
+    let file_path = args
+        .get("file_path")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| anyhow!("Missing required field: file_path"))?;
+
+    let options = json!({
+        "filePath": file_path,
+        "format": args.get("format"),
+    });
+
+    let response = client.send_command("importFormData", with_document_path(options, &args), timeout_override(&args)).await?;
+    let imported = AcrobatClient::extract_response(&response)
+        .and_then(|data| data.get("fieldsImported"))
+        .and_then(|v| v.as_u64())
+        .unwrap_or(0);
+
+    Ok(format!("Imported {} form field(s) from: {}", imported, file_path))
+}
+
+async fn attach_file(client: &Arc<AcrobatClient>, args: Value) -> Result<String> {
+    let source_path = args
+        .get("source_path")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| anyhow!("Missing required field: source_path"))?;
+
+    let options = json!({
+        "sourcePath": source_path,
+        "name": args.get("name").and_then(|v| v.as_str()),
+    });
+
+    let response = client.send_command("attachFile", with_document_path(options, &args), timeout_override(&args)).await?;
+    let name = AcrobatClient::extract_response(&response)
+        .and_then(|data| data.get("name"))
+        .and_then(|v| v.as_str())
+        .unwrap_or(source_path);
+
+    Ok(format!("Attached {} as: {}", source_path, name))
+}
+
+async fn extract_attachments(client: &Arc<AcrobatClient>, args: Value) -> Result<String> {
+    let output_dir = args
+        .get("output_dir")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| anyhow!("Missing required field: output_dir"))?;
+
+    let options = json!({ "outputDir": output_dir });
+
+    let response = client.send_command("extractAttachments", with_document_path(options, &args), timeout_override(&args)).await?;
+    let ExtractedAttachments { attachments } = response.parse_response()?;
+    if attachments.is_empty() {
+        return Ok("Document has no attachments".to_string());
+    }
+
+    let mut output = format!("Extracted {} attachment(s) to {}:\n", attachments.len(), output_dir);
+    for attachment in &attachments {
+        output.push_str(&format!("- {} -> {}\n", attachment.name, attachment.path));
+    }
+    Ok(output)
+}
+
+/// `extractAttachments` payload
+#[derive(Debug, Deserialize)]
+struct ExtractedAttachments {
+    #[serde(default)]
+    attachments: Vec<Attachment>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Attachment {
+    name: String,
+    path: String,
+}
+
+async fn merge_documents(client: &Arc<AcrobatClient>, args: Value) -> Result<String> {
+    let file_paths = args
+        .get("file_paths")
+        .and_then(|v| v.as_array())
+        .ok_or_else(|| anyhow!("Missing required field: file_paths"))?;
+    let output_path = args
+        .get("output_path")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| anyhow!("Missing required field: output_path"))?;
+
+    let preserve_bookmarks = args
+        .get("preserve_bookmarks")
+        .and_then(|v| v.as_bool())
+        .unwrap_or(false);
+
+    let options = json!({
+        "filePaths": file_paths,
+        "outputPath": output_path,
+        "preserveBookmarks": preserve_bookmarks,
+        "titles": args.get("section_titles").cloned().unwrap_or_else(|| json!([])),
+    });
+
+    let response = client.send_command("mergeDocuments", options, timeout_override(&args)).await?;
+    let data = AcrobatClient::extract_response(&response);
+    let total_pages = data
+        .and_then(|d| d.get("totalPages"))
+        .and_then(|v| v.as_u64());
+
+    let mut result = format!("Merged {} documents to: {}", file_paths.len(), output_path);
+    if let Some(pages) = total_pages {
+        result.push_str(&format!(" ({} pages)", pages));
+    }
+    if preserve_bookmarks {
+        let bookmarks = data
+            .and_then(|d| d.get("bookmarks"))
+            .cloned()
+            .unwrap_or_else(|| json!([]));
+        result.push_str(&format!(
+            "\nBookmarks:\n{}",
+            serde_json::to_string_pretty(&bookmarks)?
+        ));
+    }
+    Ok(result)
+}
+
+async fn split_document(client: &Arc<AcrobatClient>, args: Value) -> Result<String> {
+    let page_ranges = args
+        .get("page_ranges")
+        .and_then(|v| v.as_array())
+        .ok_or_else(|| anyhow!("Missing required field: page_ranges"))?;
+    for range in page_ranges.iter().filter_map(|v| v.as_str()) {
+        range.parse::<PageRange>()?;
+    }
+    let output_dir = args
+        .get("output_dir")
         .and_then(|v| v.as_str())
         .ok_or_else(|| anyhow!("Missing required field: output_dir"))?;
 
@@ -496,20 +1719,169 @@ async fn split_document(client: &Arc<AcrobatClient>, args: Value) -> Result<Stri
         "namePattern": args.get("name_pattern").and_then(|v| v.as_str()).unwrap_or("split_{n}.pdf"),
     });
 
-    let _response = client.send_command("splitDocument", options).await?;
+    let _response = client.send_command("splitDocument", with_document_path(options, &args), timeout_override(&args)).await?;
     Ok(format!("Split document into {} parts in: {}", page_ranges.len(), output_dir))
 }
 
-async fn get_page_count(client: &Arc<AcrobatClient>, _args: Value) -> Result<String> {
-    let response = client.send_command("getPageCount", json!({})).await?;
+async fn extract_pages(client: &Arc<AcrobatClient>, args: Value) -> Result<String> {
+    let page_range: PageRange = args
+        .get("page_range")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| anyhow!("Missing required field: page_range"))?
+        .parse()?;
+    let output_path = args
+        .get("output_path")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| anyhow!("Missing required field: output_path"))?;
 
-    if let Some(data) = AcrobatClient::extract_response(&response) {
-        Ok(format!("Page count: {}", data))
-    } else {
-        Err(anyhow!("Failed to get page count"))
+    let options = json!({
+        "pageRange": page_range.to_string(),
+        "outputPath": output_path,
+    });
+
+    let response = client.send_command("extractPages", with_document_path(options, &args), timeout_override(&args)).await?;
+    let PageCount { page_count } = response.parse_response()?;
+    Ok(format!("Extracted {} pages to: {}", page_count, output_path))
+}
+
+async fn compare_documents(client: &Arc<AcrobatClient>, args: Value) -> Result<String> {
+    let base_path = args
+        .get("base_path")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| anyhow!("Missing required field: base_path"))?;
+    let compare_path = args
+        .get("compare_path")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| anyhow!("Missing required field: compare_path"))?;
+
+    let options = json!({
+        "basePath": base_path,
+        "comparePath": compare_path,
+        "mode": args.get("mode").and_then(|v| v.as_str()).unwrap_or("text"),
+    });
+
+    let response = client.send_command("compareDocuments", options, timeout_override(&args)).await?;
+    let report: ComparisonReport = response.parse_response()?;
+    let heading = if report.identical { "Documents match" } else { "Documents differ" };
+
+    Ok(format!("{}:\n{}", heading, serde_json::to_string_pretty(&report)?))
+}
+
+/// `compareDocuments` payload; `pages` is empty in `pageCount` mode
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ComparisonReport {
+    mode: String,
+    base_pages: u32,
+    compare_pages: u32,
+    #[serde(default)]
+    pages_added: Vec<u32>,
+    #[serde(default)]
+    pages_removed: Vec<u32>,
+    #[serde(default)]
+    pages: Vec<PageComparison>,
+    #[serde(default)]
+    differing_pages: Vec<u32>,
+    identical: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct PageComparison {
+    page: u32,
+    identical: bool,
+}
+
+async fn split_by_bookmarks(client: &Arc<AcrobatClient>, args: Value) -> Result<String> {
+    let output_dir = args
+        .get("output_dir")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| anyhow!("Missing required field: output_dir"))?;
+
+    let options = json!({
+        "outputDir": output_dir,
+        "sanitizeFilenames": args.get("sanitize_filenames").and_then(|v| v.as_bool()).unwrap_or(true),
+    });
+
+    let response = client.send_command("splitByBookmarks", with_document_path(options, &args), timeout_override(&args)).await?;
+    let data = AcrobatClient::extract_response(&response);
+    let files = data
+        .and_then(|data| data.get("files"))
+        .cloned()
+        .unwrap_or_else(|| json!([]));
+    let skipped: Vec<&str> = data
+        .and_then(|data| data.get("skipped"))
+        .and_then(|v| v.as_array())
+        .map(|arr| arr.iter().filter_map(|v| v.as_str()).collect())
+        .unwrap_or_default();
+
+    let mut text = format!(
+        "Split document by bookmarks in: {}\n{}",
+        output_dir,
+        serde_json::to_string_pretty(&files)?
+    );
+    if !skipped.is_empty() {
+        text.push_str(&format!("\nSkipped bookmarks without a target page: {}", skipped.join(", ")));
+    }
+    Ok(text)
+}
+
+async fn list_open_documents(client: &Arc<AcrobatClient>, args: Value) -> Result<String> {
+    let response = client.send_command("listOpenDocuments", json!({}), timeout_override(&args)).await?;
+
+    match AcrobatClient::extract_response(&response) {
+        Some(data) => Ok(format!(
+            "Open documents:\n{}",
+            serde_json::to_string_pretty(data.get("documents").unwrap_or(data))?
+        )),
+        None => Ok("No open documents".to_string()),
+    }
+}
+
+async fn set_active_document(client: &Arc<AcrobatClient>, args: Value) -> Result<String> {
+    let file_path = args.get("file_path").and_then(|v| v.as_str());
+    let index = args.get("index").and_then(|v| v.as_u64());
+    if file_path.is_none() && index.is_none() {
+        return Err(anyhow!("Missing required field: file_path or index"));
+    }
+
+    let options = json!({
+        "documentPath": file_path,
+        "index": index,
+    });
+
+    let response = client.send_command("setActiveDocument", options, timeout_override(&args)).await?;
+    let path = AcrobatClient::extract_response(&response)
+        .and_then(|data| data.get("path"))
+        .and_then(|v| v.as_str())
+        .or(file_path)
+        .unwrap_or("document");
+
+    Ok(format!("Active document: {}", path))
+}
+
+async fn get_app_info(client: &Arc<AcrobatClient>, args: Value) -> Result<String> {
+    let response = client.send_command("getAppInfo", json!({}), timeout_override(&args)).await?;
+
+    match AcrobatClient::extract_response(&response) {
+        Some(data) => Ok(format!("Acrobat info:\n{}", serde_json::to_string_pretty(data)?)),
+        None => Err(anyhow!("Failed to get Acrobat info")),
     }
 }
 
+async fn get_page_count(client: &Arc<AcrobatClient>, args: Value) -> Result<String> {
+    let response = client.send_command("getPageCount", with_document_path(json!({}), &args), timeout_override(&args)).await?;
+    let count: PageCount = response.parse_response()?;
+
+    Ok(format!("Page count: {}", count.page_count))
+}
+
+/// `getPageCount` payload
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PageCount {
+    page_count: u32,
+}
+
 async fn delete_pages(client: &Arc<AcrobatClient>, args: Value) -> Result<String> {
     let page_numbers = args
         .get("page_numbers")
@@ -518,9 +1890,10 @@ async fn delete_pages(client: &Arc<AcrobatClient>, args: Value) -> Result<String
 
     let options = json!({
         "pageNumbers": page_numbers,
+        "pageBase": 1,
     });
 
-    let _response = client.send_command("deletePages", options).await?;
+    let _response = client.send_command("deletePages", with_document_path(options, &args), timeout_override(&args)).await?;
     Ok(format!("Deleted {} pages", page_numbers.len()))
 }
 
@@ -536,13 +1909,67 @@ async fn rotate_pages(client: &Arc<AcrobatClient>, args: Value) -> Result<String
 
     let options = json!({
         "pageNumbers": page_numbers,
+        "pageBase": 1,
         "angle": angle,
     });
 
-    let _response = client.send_command("rotatePages", options).await?;
+    let _response = client.send_command("rotatePages", with_document_path(options, &args), timeout_override(&args)).await?;
     Ok(format!("Rotated {} pages by {} degrees", page_numbers.len(), angle))
 }
 
+async fn insert_blank_page(client: &Arc<AcrobatClient>, args: Value) -> Result<String> {
+    let options = json!({
+        "afterPage": args.get("after_page").and_then(|v| v.as_u64()),
+        "pageSize": args.get("page_size").and_then(|v| v.as_str()).unwrap_or("LETTER"),
+        "width": args.get("width").and_then(|v| v.as_f64()),
+        "height": args.get("height").and_then(|v| v.as_f64()),
+    });
+
+    let response = client.send_command("insertBlankPage", with_document_path(options, &args), timeout_override(&args)).await?;
+    let PageCount { page_count } = response.parse_response()?;
+    Ok(format!("Inserted blank page; document now has {} pages", page_count))
+}
+
+async fn duplicate_page(client: &Arc<AcrobatClient>, args: Value) -> Result<String> {
+    let page = args
+        .get("page")
+        .and_then(|v| v.as_u64())
+        .ok_or_else(|| anyhow!("Missing required field: page"))?;
+
+    let options = json!({
+        "page": page,
+        "afterPage": args.get("after_page").and_then(|v| v.as_u64()),
+    });
+
+    let response = client.send_command("duplicatePage", with_document_path(options, &args), timeout_override(&args)).await?;
+    let PageCount { page_count } = response.parse_response()?;
+    Ok(format!("Duplicated page {}; document now has {} pages", page, page_count))
+}
+
+async fn replace_page(client: &Arc<AcrobatClient>, args: Value) -> Result<String> {
+    let page = args
+        .get("page")
+        .and_then(|v| v.as_u64())
+        .ok_or_else(|| anyhow!("Missing required field: page"))?;
+    let source_path = args
+        .get("source_path")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| anyhow!("Missing required field: source_path"))?;
+
+    let options = json!({
+        "page": page,
+        "sourcePath": source_path,
+        "sourcePage": args.get("source_page").and_then(|v| v.as_u64()).unwrap_or(1),
+    });
+
+    let response = client.send_command("replacePage", with_document_path(options, &args), timeout_override(&args)).await?;
+    let PageCount { page_count } = response.parse_response()?;
+    Ok(format!(
+        "Replaced page {} from {}; document has {} pages",
+        page, source_path, page_count
+    ))
+}
+
 async fn add_bookmark(client: &Arc<AcrobatClient>, args: Value) -> Result<String> {
     let title = args
         .get("title")
@@ -557,12 +1984,241 @@ async fn add_bookmark(client: &Arc<AcrobatClient>, args: Value) -> Result<String
         "title": title,
         "page": page,
         "parent": args.get("parent").and_then(|v| v.as_str()),
+        "zoom": args.get("zoom"),
+        "left": args.get("left").and_then(|v| v.as_f64()),
+        "top": args.get("top").and_then(|v| v.as_f64()),
     });
 
-    let _response = client.send_command("addBookmark", options).await?;
+    let _response = client.send_command("addBookmark", with_document_path(options, &args), timeout_override(&args)).await?;
     Ok(format!("Added bookmark '{}' at page {}", title, page))
 }
 
+async fn get_bookmarks(client: &Arc<AcrobatClient>, args: Value) -> Result<String> {
+    let response = client.send_command("getBookmarks", with_document_path(json!({}), &args), timeout_override(&args)).await?;
+    let BookmarkTree { bookmarks } = response.parse_response()?;
+    if bookmarks.is_empty() {
+        return Ok("Document has no bookmarks".to_string());
+    }
+
+    Ok(format!("Bookmarks:\n{}", format_bookmarks(&bookmarks, 0)))
+}
+
+async fn delete_bookmark(client: &Arc<AcrobatClient>, args: Value) -> Result<String> {
+    let path: Vec<String> = args
+        .get("path")
+        .and_then(|v| v.as_array())
+        .ok_or_else(|| anyhow!("Missing required field: path"))?
+        .iter()
+        .filter_map(|v| v.as_str().map(str::to_string))
+        .collect();
+
+    let options = json!({ "path": path });
+
+    let response = client.send_command("deleteBookmark", with_document_path(options, &args), timeout_override(&args)).await?;
+    let BookmarkTree { bookmarks } = response.parse_response()?;
+    let remaining = if bookmarks.is_empty() {
+        "(no bookmarks left)\n".to_string()
+    } else {
+        format_bookmarks(&bookmarks, 0)
+    };
+
+    Ok(format!("Deleted bookmark '{}'. Remaining bookmarks:\n{}", path.join(" > "), remaining))
+}
+
+async fn generate_toc(client: &Arc<AcrobatClient>, args: Value) -> Result<String> {
+    let options = json!({
+        "title": args.get("title").and_then(|v| v.as_str()),
+        "fontSize": args.get("font_size").and_then(|v| v.as_f64()),
+        "includeLevels": args.get("include_levels").and_then(|v| v.as_u64()),
+    });
+
+    let response = client.send_command("generateToc", with_document_path(options, &args), timeout_override(&args)).await?;
+    let toc: TocSummary = response.parse_response()?;
+    Ok(format!(
+        "Added a table of contents with {} entries on {} page(s) at the front of the document",
+        toc.entries, toc.pages_added
+    ))
+}
+
+/// `generateToc` payload
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TocSummary {
+    entries: u32,
+    pages_added: u32,
+}
+
+/// `getBookmarks` / `deleteBookmark` payload
+#[derive(Debug, Deserialize)]
+struct BookmarkTree {
+    #[serde(default)]
+    bookmarks: Vec<Bookmark>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Bookmark {
+    name: String,
+    #[serde(default)]
+    page: Option<u32>,
+    #[serde(default)]
+    children: Vec<Bookmark>,
+}
+
+fn format_bookmarks(bookmarks: &[Bookmark], depth: usize) -> String {
+    let mut output = String::new();
+    for bookmark in bookmarks {
+        let page = bookmark.page.map(|p| format!(" (page {})", p)).unwrap_or_default();
+        output.push_str(&format!("{}- {}{}\n", "  ".repeat(depth), bookmark.name, page));
+        output.push_str(&format_bookmarks(&bookmark.children, depth + 1));
+    }
+    output
+}
+
+async fn sanitize_document(client: &Arc<AcrobatClient>, args: Value) -> Result<String> {
+    let dry_run = args.get("dry_run").and_then(|v| v.as_bool()).unwrap_or(false);
+    let options = json!({
+        "metadata": args.get("metadata").and_then(|v| v.as_bool()),
+        "javascript": args.get("javascript").and_then(|v| v.as_bool()),
+        "hiddenLayers": args.get("hidden_layers").and_then(|v| v.as_bool()),
+        "attachments": args.get("attachments").and_then(|v| v.as_bool()),
+        "dryRun": dry_run,
+    });
+
+    let response = client.send_command("sanitizeDocument", with_document_path(options, &args), timeout_override(&args)).await?;
+    let heading = if dry_run { "Would remove" } else { "Sanitized" };
+
+    match AcrobatClient::extract_response(&response) {
+        Some(data) => Ok(format!("{}:\n{}", heading, serde_json::to_string_pretty(data)?)),
+        None => Ok(format!("{}: no report returned", heading)),
+    }
+}
+
+async fn set_initial_view(client: &Arc<AcrobatClient>, args: Value) -> Result<String> {
+    let options = json!({
+        "pageMode": args.get("page_mode").and_then(|v| v.as_str()),
+        "pageLayout": args.get("page_layout").and_then(|v| v.as_str()),
+        "pageNum": args.get("page"),
+        "zoom": args.get("zoom"),
+    });
+
+    let response = client.send_command("setInitialView", with_document_path(options, &args), timeout_override(&args)).await?;
+    match AcrobatClient::extract_response(&response).and_then(|data| data.get("settings")) {
+        Some(settings) => Ok(format!("Initial view set:\n{}", serde_json::to_string_pretty(settings)?)),
+        None => Ok("Initial view set".to_string()),
+    }
+}
+
+async fn remove_watermarks(client: &Arc<AcrobatClient>, args: Value) -> Result<String> {
+    let page_range = args
+        .get("page_range")
+        .and_then(|v| v.as_str())
+        .map(str::parse::<PageRange>)
+        .transpose()?;
+
+    let options = json!({
+        "pageRange": page_range.map(|range| range.to_string()),
+    });
+
+    let response = client.send_command("removeWatermarks", with_document_path(options, &args), timeout_override(&args)).await?;
+    let removed: WatermarksRemoved = response.parse_response()?;
+    let mut message = format!("Removed {} watermark annotation(s)", removed.removed_annotations);
+    if removed.removed_layers > 0 {
+        message.push_str(&format!(" and {} watermark layer(s)", removed.removed_layers));
+    }
+    if removed.hidden_layers > 0 {
+        message.push_str(&format!(
+            "; {} watermark layer(s) hidden, since Acrobat JavaScript cannot delete layer content",
+            removed.hidden_layers
+        ));
+    }
+    Ok(message)
+}
+
+/// `removeWatermarks` payload
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct WatermarksRemoved {
+    #[serde(default)]
+    removed_annotations: u32,
+    #[serde(default)]
+    removed_layers: u32,
+    #[serde(default)]
+    hidden_layers: u32,
+}
+
+async fn get_pdf_layers(client: &Arc<AcrobatClient>, args: Value) -> Result<String> {
+    let response = client.send_command("getLayers", with_document_path(json!({}), &args), timeout_override(&args)).await?;
+    let PdfLayers { layers } = response.parse_response()?;
+    if layers.is_empty() {
+        return Ok("Document has no layers".to_string());
+    }
+
+    let mut output = format!("{} layer(s):\n", layers.len());
+    for layer in &layers {
+        let state = if layer.visible { "visible" } else { "hidden" };
+        output.push_str(&format!("- {} ({})\n", layer.name, state));
+    }
+    Ok(output)
+}
+
+async fn set_pdf_layer_visibility(client: &Arc<AcrobatClient>, args: Value) -> Result<String> {
+    let name = args
+        .get("name")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| anyhow!("Missing required field: name"))?;
+    let visible = args
+        .get("visible")
+        .and_then(|v| v.as_bool())
+        .ok_or_else(|| anyhow!("Missing required field: visible"))?;
+
+    let options = json!({
+        "name": name,
+        "visible": visible,
+    });
+
+    let response = client.send_command("setLayerVisibility", with_document_path(options, &args), timeout_override(&args)).await?;
+    let state = if visible { "Showing" } else { "Hiding" };
+
+    match AcrobatClient::extract_response(&response).and_then(|data| data.get("visibility")) {
+        Some(visibility) => Ok(format!("{} layer '{}'. Layer visibility:\n{}", state, name, serde_json::to_string_pretty(visibility)?)),
+        None => Ok(format!("{} layer '{}'", state, name)),
+    }
+}
+
+/// Message of the `execute_javascript` result when the server disallows it
+pub const RAW_JS_DISABLED: &str =
+    "Raw JavaScript disabled: start acrobat-mcp with --allow-raw-js to use execute_javascript";
+
+async fn execute_javascript(client: &Arc<AcrobatClient>, args: Value) -> Result<String> {
+    if !client.raw_js_allowed() {
+        return Err(anyhow!(RAW_JS_DISABLED));
+    }
+    let script = args
+        .get("script")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| anyhow!("Missing required field: script"))?;
+
+    let response = client.send_command("executeJavaScript", with_document_path(json!({"script": script}), &args), timeout_override(&args)).await?;
+    match AcrobatClient::extract_response(&response).and_then(|data| data.get("result")) {
+        Some(Value::String(result)) => Ok(result.clone()),
+        Some(Value::Null) | None => Ok("Script returned no value".to_string()),
+        Some(other) => Ok(serde_json::to_string_pretty(other)?),
+    }
+}
+
+/// `getLayers` payload
+#[derive(Debug, Deserialize)]
+struct PdfLayers {
+    #[serde(default)]
+    layers: Vec<PdfLayer>,
+}
+
+#[derive(Debug, Deserialize)]
+struct PdfLayer {
+    name: String,
+    visible: bool,
+}
+
 async fn set_metadata(client: &Arc<AcrobatClient>, args: Value) -> Result<String> {
     let options = json!({
         "title": args.get("title").and_then(|v| v.as_str()),
@@ -571,6 +2227,6 @@ async fn set_metadata(client: &Arc<AcrobatClient>, args: Value) -> Result<String
         "keywords": args.get("keywords").and_then(|v| v.as_str()),
     });
 
-    let _response = client.send_command("setMetadata", options).await?;
+    let _response = client.send_command("setMetadata", with_document_path(options, &args), timeout_override(&args)).await?;
     Ok("Metadata updated successfully".to_string())
 }
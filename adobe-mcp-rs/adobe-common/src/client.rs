@@ -0,0 +1,717 @@
+//! WebSocket client the MCP servers use to send commands through the proxy
+//!
+//! Each server wraps a [`ProxyCommandClient`] for its own application and
+//! adds whatever tool-specific helpers it needs on top.
+
+use std::collections::VecDeque;
+use std::sync::Arc;
+
+use futures_util::stream::{SplitSink, SplitStream};
+use futures_util::{SinkExt, StreamExt};
+use serde_json::Value;
+use tokio::net::TcpStream;
+use tokio::sync::{oneshot, Mutex};
+use tokio::task::JoinHandle;
+use tokio::time::{timeout, Duration};
+use tokio_tungstenite::tungstenite::client::IntoClientRequest;
+use tokio_tungstenite::tungstenite::http::header::{HeaderValue, AUTHORIZATION};
+use tokio_tungstenite::tungstenite::Message;
+use tokio_tungstenite::{connect_async, MaybeTlsStream, WebSocketStream};
+use tracing::{debug, info, warn};
+
+use crate::error::{AdobeError, AdobeResult};
+use crate::protocol::{Command, CommandPacket, CommandResponse, ResponseStatus};
+use crate::socket_io::{
+    decode_event, encode_event, engine_open_sid, split_payload, validate_proxy_url, ENGINE_PING, ENGINE_PONG,
+    SOCKET_IO_CONNECT, SOCKET_IO_DISCONNECT,
+};
+use crate::types::AdobeApplication;
+
+type WsStream = WebSocketStream<MaybeTlsStream<TcpStream>>;
+type WsWriter = SplitSink<WsStream, Message>;
+type ResponseSender = oneshot::Sender<AdobeResult<CommandResponse>>;
+type ClientId = Arc<std::sync::Mutex<Option<String>>>;
+
+/// Upper bound for per-call timeout overrides (10 minutes)
+pub const MAX_TIMEOUT_MS: u64 = 600_000;
+
+/// Connection attempts made when the proxy connection drops mid-session
+const RECONNECT_ATTEMPTS: u32 = 3;
+
+/// Delay before the first reconnect attempt, doubled after each failure
+const RECONNECT_BACKOFF: Duration = Duration::from_millis(100);
+
+/// Proxy connection that sends commands to one Adobe application
+///
+/// A background task reads the socket for the life of the connection, so
+/// Engine.IO pings are answered even while no command is in flight. If the
+/// proxy restarts, the next command reconnects transparently.
+pub struct ProxyCommandClient {
+    application: AdobeApplication,
+    proxy_url: String,
+    auth_token: Option<String>,
+    connection: Mutex<Connection>,
+    client_id: ClientId,
+    timeout_ms: u64,
+}
+
+impl ProxyCommandClient {
+    /// Connect to the proxy and open the Socket.IO session
+    ///
+    /// `auth_token` is sent as an `Authorization: Bearer` header when the proxy
+    /// requires a shared secret. A `proxy_url` that isn't a `ws://` or
+    /// `wss://` URL fails with [`AdobeError::InvalidProxyUrl`] before any
+    /// connection attempt.
+    pub async fn connect(
+        application: AdobeApplication,
+        proxy_url: &str,
+        timeout_ms: u64,
+        auth_token: Option<&str>,
+    ) -> AdobeResult<Self> {
+        validate_proxy_url(proxy_url)?;
+        let ws_stream = open_session(proxy_url, auth_token).await?;
+        let client_id = ClientId::default();
+
+        Ok(Self {
+            application,
+            proxy_url: proxy_url.to_string(),
+            auth_token: auth_token.map(str::to_string),
+            connection: Mutex::new(Connection::spawn(ws_stream, client_id.clone())),
+            client_id,
+            timeout_ms,
+        })
+    }
+
+    /// Application every command is addressed to
+    pub fn application(&self) -> AdobeApplication {
+        self.application
+    }
+
+    /// Id the proxy assigned this connection, which it reports as the
+    /// `senderId` of responses
+    ///
+    /// `None` until the proxy's Engine.IO open packet has been read, and
+    /// replaced with the new session's id after a reconnect.
+    pub fn client_id(&self) -> Option<String> {
+        lock_client_id(&self.client_id).clone()
+    }
+
+    /// Send a command and wait for its response
+    ///
+    /// When the connection has dropped before the command could be sent, the
+    /// client reconnects and sends it once more. A connection lost after
+    /// the command went out fails with [`AdobeError::ConnectionFailed`]
+    /// rather than risk running it twice. `timeout_ms` overrides
+    /// the configured timeout for this call and is capped at
+    /// [`MAX_TIMEOUT_MS`]. A `FAILURE` response becomes
+    /// [`AdobeError::ApplicationNotConnected`] when the proxy had no client for
+    /// the application, and [`AdobeError::CommandFailed`] otherwise.
+    pub async fn send_command(
+        &self,
+        action: impl Into<String>,
+        options: Value,
+        timeout_ms: Option<u64>,
+    ) -> AdobeResult<CommandResponse> {
+        let timeout_ms = self.call_timeout_ms(timeout_ms);
+        let command = Command::new(action, options);
+        let packet = CommandPacket::new(self.application, command);
+
+        debug!("Sending command: {:?}", packet);
+
+        let payload = serde_json::json!({
+            "type": packet.packet_type,
+            "application": packet.application,
+            "command": packet.command,
+        });
+
+        // Only a command that never went out is retried; one lost while
+        // awaiting its response may already have run
+        let sent = match self.send_packet(&payload).await {
+            Err(e) if is_disconnect(&e) => {
+                warn!("Proxy connection lost ({}), reconnecting", e);
+                self.reconnect().await?;
+                self.send_packet(&payload).await?
+            }
+            result => result?,
+        };
+        let response = sent.response(timeout_ms).await?;
+        if let Some(duration_ms) = response.duration_ms {
+            debug!("{} executed in {}ms", packet.command.action, duration_ms);
+        }
+
+        if response.status == ResponseStatus::Success {
+            Ok(response)
+        } else if response.is_not_connected() {
+            Err(AdobeError::ApplicationNotConnected(
+                response.message.unwrap_or_default(),
+            ))
+        } else {
+            Err(AdobeError::CommandFailed(
+                response.message.unwrap_or_else(|| "Unknown error".to_string()),
+            ))
+        }
+    }
+
+    /// Send `payload` tagged with a fresh `requestId`, registering it to
+    /// receive the reader task's `packet_response`
+    ///
+    /// An error here means the command was not written to the socket.
+    async fn send_packet(&self, payload: &Value) -> AdobeResult<SentCommand> {
+        let request_id = uuid::Uuid::new_v4().to_string();
+        let mut payload = payload.clone();
+        payload["command"]["requestId"] = Value::String(request_id.clone());
+        let message = encode_event("command_packet", payload);
+
+        let (tx, rx) = oneshot::channel();
+        let connection = self.connection.lock().await;
+        connection.pending.insert(request_id.clone(), tx)?;
+        let sent = connection.writer.lock().await.send(Message::Text(message)).await;
+        if let Err(e) = sent {
+            let reason = format!("Failed to send message: {}", e);
+            connection.pending.close(&reason);
+            return Err(AdobeError::WebSocketError(reason));
+        }
+
+        Ok(SentCommand {
+            request_id,
+            rx,
+            pending: connection.pending.clone(),
+        })
+    }
+
+    /// Replace a closed connection with a new session, backing off between
+    /// failed attempts
+    ///
+    /// Does nothing if another command already reconnected.
+    async fn reconnect(&self) -> AdobeResult<()> {
+        let mut connection = self.connection.lock().await;
+        if connection.pending.is_open() {
+            return Ok(());
+        }
+
+        let mut delay = RECONNECT_BACKOFF;
+        let mut attempt = 1;
+        loop {
+            match open_session(&self.proxy_url, self.auth_token.as_deref()).await {
+                Ok(ws) => {
+                    *connection = Connection::spawn(ws, self.client_id.clone());
+                    return Ok(());
+                }
+                Err(e) if attempt < RECONNECT_ATTEMPTS => {
+                    debug!("Reconnect attempt {} failed: {}", attempt, e);
+                    tokio::time::sleep(delay).await;
+                    delay *= 2;
+                    attempt += 1;
+                }
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    /// End the session with a Socket.IO disconnect packet and a WebSocket close
+    ///
+    /// The proxy unregisters the client as soon as it reads the close instead
+    /// of waiting for its keepalive to notice. Commands still in flight fail
+    /// with [`AdobeError::ConnectionFailed`]; a command sent afterwards opens
+    /// a new session.
+    pub async fn close(&self) -> AdobeResult<()> {
+        let connection = self.connection.lock().await;
+        connection.pending.close("Client closed the connection");
+
+        let mut writer = connection.writer.lock().await;
+        for message in [Message::Text(SOCKET_IO_DISCONNECT.to_string()), Message::Close(None)] {
+            writer
+                .send(message)
+                .await
+                .map_err(|e| AdobeError::WebSocketError(format!("Failed to close connection: {}", e)))?;
+        }
+        info!("Closed proxy connection");
+        Ok(())
+    }
+
+    /// Timeout for one call: the override if given, else the default, capped
+    pub fn call_timeout_ms(&self, timeout_ms: Option<u64>) -> u64 {
+        timeout_ms.unwrap_or(self.timeout_ms).min(MAX_TIMEOUT_MS)
+    }
+
+    /// Get response data as JSON value
+    pub fn extract_response(response: &CommandResponse) -> Option<&Value> {
+        response.response.as_ref()
+    }
+
+    /// Get document info from response
+    pub fn extract_document(response: &CommandResponse) -> Option<&Value> {
+        response.document.as_ref()
+    }
+}
+
+/// A command written to the proxy, waiting for its response
+struct SentCommand {
+    request_id: String,
+    rx: oneshot::Receiver<AdobeResult<CommandResponse>>,
+    pending: Arc<Pending>,
+}
+
+impl SentCommand {
+    async fn response(self, timeout_ms: u64) -> AdobeResult<CommandResponse> {
+        match timeout(Duration::from_millis(timeout_ms), self.rx).await {
+            Ok(Ok(result)) => result,
+            Ok(Err(_)) => Err(AdobeError::ConnectionFailed("WebSocket closed".to_string())),
+            Err(_) => {
+                self.pending.remove(&self.request_id);
+                Err(AdobeError::CommandTimeout(timeout_ms))
+            }
+        }
+    }
+}
+
+/// One proxy session: the write half, shared with the reader task that owns
+/// the read half
+struct Connection {
+    writer: Arc<Mutex<WsWriter>>,
+    pending: Arc<Pending>,
+    reader: JoinHandle<()>,
+}
+
+impl Connection {
+    /// Start the reader task; `client_id` is cleared until the new session's
+    /// open packet arrives
+    fn spawn(ws: WsStream, client_id: ClientId) -> Self {
+        let (writer, reader) = ws.split();
+        let writer = Arc::new(Mutex::new(writer));
+        let pending = Arc::new(Pending::new());
+        lock_client_id(&client_id).take();
+        let reader = tokio::spawn(read_frames(reader, writer.clone(), pending.clone(), client_id));
+        Self {
+            writer,
+            pending,
+            reader,
+        }
+    }
+}
+
+impl Drop for Connection {
+    fn drop(&mut self) {
+        self.reader.abort();
+        self.pending.close("Connection replaced");
+    }
+}
+
+/// Commands awaiting a `packet_response`, oldest first; `None` once the
+/// connection has closed
+struct Pending(std::sync::Mutex<Option<VecDeque<(String, ResponseSender)>>>);
+
+impl Pending {
+    fn new() -> Self {
+        Self(std::sync::Mutex::new(Some(VecDeque::new())))
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Option<VecDeque<(String, ResponseSender)>>> {
+        self.0.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn is_open(&self) -> bool {
+        self.lock().is_some()
+    }
+
+    fn insert(&self, request_id: String, tx: ResponseSender) -> AdobeResult<()> {
+        match self.lock().as_mut() {
+            Some(queue) => {
+                queue.push_back((request_id, tx));
+                Ok(())
+            }
+            None => Err(AdobeError::ConnectionFailed("WebSocket closed".to_string())),
+        }
+    }
+
+    fn remove(&self, request_id: &str) {
+        if let Some(queue) = self.lock().as_mut() {
+            queue.retain(|(id, _)| id != request_id);
+        }
+    }
+
+    /// Hand `result` to the command `request_id` names, or to the oldest
+    /// command when the response carries no id
+    fn complete(&self, request_id: Option<&str>, result: AdobeResult<CommandResponse>) {
+        let waiter = self.lock().as_mut().and_then(|queue| {
+            let index = match request_id {
+                Some(request_id) => queue.iter().position(|(id, _)| id == request_id)?,
+                None => 0,
+            };
+            queue.remove(index)
+        });
+        match waiter {
+            Some((_, tx)) => {
+                let _ = tx.send(result);
+            }
+            None => debug!("Dropping response no command is waiting for: {:?}", request_id),
+        }
+    }
+
+    /// Fail every waiting command and refuse new ones
+    fn close(&self, reason: &str) {
+        for (_, tx) in self.lock().take().into_iter().flatten() {
+            let _ = tx.send(Err(AdobeError::ConnectionFailed(reason.to_string())));
+        }
+    }
+}
+
+fn lock_client_id(client_id: &ClientId) -> std::sync::MutexGuard<'_, Option<String>> {
+    client_id.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+/// Read frames until the socket closes, answering pings, recording the
+/// session id from the open packet, and routing each `packet_response` to
+/// the command waiting for it
+async fn read_frames(
+    mut reader: SplitStream<WsStream>,
+    writer: Arc<Mutex<WsWriter>>,
+    pending: Arc<Pending>,
+    client_id: ClientId,
+) {
+    let reason = loop {
+        let frame = match reader.next().await {
+            Some(Ok(Message::Text(frame))) => frame,
+            Some(Ok(Message::Ping(_))) => {
+                send_pong(&writer).await;
+                continue;
+            }
+            Some(Ok(Message::Close(_))) => break "WebSocket connection closed".to_string(),
+            Some(Ok(_)) => continue,
+            Some(Err(e)) => break e.to_string(),
+            None => break "WebSocket closed".to_string(),
+        };
+
+        for text in split_payload(&frame) {
+            if text == ENGINE_PING {
+                send_pong(&writer).await;
+                continue;
+            }
+            if let Some(sid) = engine_open_sid(text) {
+                info!("Proxy assigned client id {}", sid);
+                *lock_client_id(&client_id) = Some(sid);
+                continue;
+            }
+
+            let data = match decode_event(text) {
+                Some((event, data)) if event == "packet_response" => Ok(data),
+                Some(_) => continue,
+                None if text.starts_with('{') => serde_json::from_str(text),
+                None => continue,
+            };
+            let request_id = data
+                .as_ref()
+                .ok()
+                .and_then(|data| data.get("requestId"))
+                .and_then(Value::as_str)
+                .map(str::to_string);
+            pending.complete(
+                request_id.as_deref(),
+                parse_response(data.and_then(serde_json::from_value)),
+            );
+        }
+    };
+
+    debug!("Proxy connection closed: {}", reason);
+    pending.close(&reason);
+}
+
+/// Connect to the proxy and send the Socket.IO connect packet
+async fn open_session(proxy_url: &str, auth_token: Option<&str>) -> AdobeResult<WsStream> {
+    info!("Connecting to proxy at {}", proxy_url);
+
+    let mut request = proxy_url
+        .into_client_request()
+        .map_err(|e| AdobeError::ConnectionFailed(format!("Invalid proxy URL: {}", e)))?;
+    if let Some(token) = auth_token {
+        let value = HeaderValue::from_str(&format!("Bearer {}", token))
+            .map_err(|e| AdobeError::ConnectionFailed(format!("Invalid auth token: {}", e)))?;
+        request.headers_mut().insert(AUTHORIZATION, value);
+    }
+
+    let (mut ws_stream, _) = connect_async(request).await.map_err(|e| {
+        AdobeError::ConnectionFailed(format!("Failed to connect to proxy: {}", e))
+    })?;
+
+    info!("WebSocket connection established");
+
+    ws_stream
+        .send(Message::Text(SOCKET_IO_CONNECT.to_string()))
+        .await
+        .map_err(|e| AdobeError::WebSocketError(format!("Failed to send Socket.IO connect: {}", e)))?;
+
+    Ok(ws_stream)
+}
+
+/// Whether `error` means the connection itself is gone, so a fresh one may succeed
+fn is_disconnect(error: &AdobeError) -> bool {
+    matches!(error, AdobeError::ConnectionFailed(_) | AdobeError::WebSocketError(_))
+}
+
+async fn send_pong(writer: &Mutex<WsWriter>) {
+    if let Err(e) = writer.lock().await.send(Message::Text(ENGINE_PONG.to_string())).await {
+        debug!("Failed to send pong: {}", e);
+    }
+}
+
+fn parse_response(parsed: serde_json::Result<CommandResponse>) -> AdobeResult<CommandResponse> {
+    parsed.map_err(|e| AdobeError::ProtocolError(format!("Failed to parse response: {}", e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Proxy stand-in that answers every command with `reply` after `delay`
+    async fn responding_proxy(delay: Duration, reply: Value) -> String {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            let mut ws = tokio_tungstenite::accept_async(stream).await.unwrap();
+            while let Some(Ok(Message::Text(text))) = ws.next().await {
+                if decode_event(&text).is_some_and(|(event, _)| event == "command_packet") {
+                    tokio::time::sleep(delay).await;
+                    let response = encode_event("packet_response", reply.clone());
+                    if ws.send(Message::Text(response)).await.is_err() {
+                        break;
+                    }
+                }
+            }
+        });
+        format!("ws://{}", addr)
+    }
+
+    /// Proxy stand-in that answers every command successfully after `delay`
+    async fn delayed_proxy(delay: Duration) -> String {
+        responding_proxy(delay, serde_json::json!({"senderId": "test", "status": "SUCCESS"})).await
+    }
+
+    #[tokio::test]
+    async fn test_command_packet_targets_application() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let (seen_tx, seen_rx) = tokio::sync::oneshot::channel();
+        tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            let mut ws = tokio_tungstenite::accept_async(stream).await.unwrap();
+            let mut seen_tx = Some(seen_tx);
+            while let Some(Ok(Message::Text(text))) = ws.next().await {
+                if let Some((event, data)) = decode_event(&text) {
+                    if event == "command_packet" {
+                        if let Some(tx) = seen_tx.take() {
+                            let _ = tx.send(data);
+                        }
+                        let response = encode_event(
+                            "packet_response",
+                            serde_json::json!({"senderId": "test", "status": "SUCCESS", "response": {"ok": true}}),
+                        );
+                        let _ = ws.send(Message::Text(response)).await;
+                    }
+                }
+            }
+        });
+
+        let client = ProxyCommandClient::connect(AdobeApplication::Photoshop, &format!("ws://{}", addr), 5_000, None)
+            .await
+            .unwrap();
+        assert_eq!(client.application(), AdobeApplication::Photoshop);
+
+        let response = client
+            .send_command("createLayer", serde_json::json!({"name": "Layer 1"}), None)
+            .await
+            .unwrap();
+        assert_eq!(ProxyCommandClient::extract_response(&response).unwrap()["ok"], true);
+
+        let packet = seen_rx.await.unwrap();
+        assert_eq!(packet["application"], "photoshop");
+        assert_eq!(packet["command"]["action"], "createLayer");
+        assert_eq!(packet["command"]["options"]["name"], "Layer 1");
+    }
+
+    #[tokio::test]
+    async fn test_timeout_override() {
+        let url = delayed_proxy(Duration::from_millis(200)).await;
+        let client = ProxyCommandClient::connect(AdobeApplication::Acrobat, &url, 5_000, None)
+            .await
+            .unwrap();
+
+        let err = client
+            .send_command("getPageCount", serde_json::json!({}), Some(50))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, AdobeError::CommandTimeout(50)));
+        assert!(err.to_string().contains("Command timeout after 50ms"));
+
+        let url = delayed_proxy(Duration::from_millis(200)).await;
+        let client = ProxyCommandClient::connect(AdobeApplication::Acrobat, &url, 50, None)
+            .await
+            .unwrap();
+        let response = client
+            .send_command("getPageCount", serde_json::json!({}), Some(2_000))
+            .await
+            .unwrap();
+        assert_eq!(response.status, ResponseStatus::Success);
+    }
+
+    #[tokio::test]
+    async fn test_command_failure() {
+        let url = responding_proxy(
+            Duration::ZERO,
+            serde_json::json!({"senderId": "test", "status": "FAILURE", "message": "File not found"}),
+        )
+        .await;
+        let client = ProxyCommandClient::connect(AdobeApplication::Acrobat, &url, 5_000, None)
+            .await
+            .unwrap();
+
+        let err = client
+            .send_command("openDocument", serde_json::json!({}), None)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, AdobeError::CommandFailed(ref msg) if msg == "File not found"));
+    }
+
+    #[tokio::test]
+    async fn test_batched_response_frame() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            let mut ws = tokio_tungstenite::accept_async(stream).await.unwrap();
+            while let Some(Ok(Message::Text(text))) = ws.next().await {
+                if decode_event(&text).is_some_and(|(event, _)| event == "command_packet") {
+                    let response = encode_event(
+                        "packet_response",
+                        serde_json::json!({"senderId": "test", "status": "SUCCESS", "response": {"pageCount": 4}}),
+                    );
+                    let frame = format!("{}\x1e{}", ENGINE_PING, response);
+                    if ws.send(Message::Text(frame)).await.is_err() {
+                        break;
+                    }
+                }
+            }
+        });
+
+        let client = ProxyCommandClient::connect(AdobeApplication::Acrobat, &format!("ws://{}", addr), 5_000, None)
+            .await
+            .unwrap();
+        let response = client
+            .send_command("getPageCount", serde_json::json!({}), None)
+            .await
+            .unwrap();
+        assert_eq!(response.response.unwrap()["pageCount"], 4);
+    }
+
+    #[tokio::test]
+    async fn test_out_of_order_responses_match_by_request_id() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            let mut ws = tokio_tungstenite::accept_async(stream).await.unwrap();
+            let mut commands = Vec::new();
+            while let Some(Ok(Message::Text(text))) = ws.next().await {
+                if let Some((event, data)) = decode_event(&text) {
+                    if event == "command_packet" {
+                        commands.push(data["command"].clone());
+                    }
+                }
+                if commands.len() == 2 {
+                    break;
+                }
+            }
+            for command in commands.iter().rev() {
+                let response = encode_event(
+                    "packet_response",
+                    serde_json::json!({
+                        "senderId": "test",
+                        "status": "SUCCESS",
+                        "requestId": command["requestId"],
+                        "response": {"action": command["action"]}
+                    }),
+                );
+                ws.send(Message::Text(response)).await.unwrap();
+            }
+            while ws.next().await.is_some() {}
+        });
+
+        let client = ProxyCommandClient::connect(AdobeApplication::Acrobat, &format!("ws://{}", addr), 5_000, None)
+            .await
+            .unwrap();
+        let (first, second) = tokio::join!(
+            client.send_command("getPageCount", serde_json::json!({}), None),
+            client.send_command("getDocumentInfo", serde_json::json!({}), None),
+        );
+        assert_eq!(first.unwrap().response.unwrap()["action"], "getPageCount");
+        assert_eq!(second.unwrap().response.unwrap()["action"], "getDocumentInfo");
+    }
+
+    #[tokio::test]
+    async fn test_timeout_override_is_capped() {
+        let url = delayed_proxy(Duration::ZERO).await;
+        let client = ProxyCommandClient::connect(AdobeApplication::Acrobat, &url, 5_000, None)
+            .await
+            .unwrap();
+
+        assert_eq!(client.call_timeout_ms(None), 5_000);
+        assert_eq!(client.call_timeout_ms(Some(120_000)), 120_000);
+        assert_eq!(client.call_timeout_ms(Some(u64::MAX)), MAX_TIMEOUT_MS);
+    }
+
+    #[tokio::test]
+    async fn test_connection_lost_after_send_is_not_retried() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let delivered = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let counter = delivered.clone();
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                let mut ws = tokio_tungstenite::accept_async(stream).await.unwrap();
+                let counter = counter.clone();
+                tokio::spawn(async move {
+                    while let Some(Ok(Message::Text(text))) = ws.next().await {
+                        if decode_event(&text).is_some_and(|(event, _)| event == "command_packet") {
+                            // Run the command, then drop the socket before replying
+                            counter.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+                            break;
+                        }
+                    }
+                });
+            }
+        });
+
+        let client = ProxyCommandClient::connect(AdobeApplication::Acrobat, &format!("ws://{}", addr), 5_000, None)
+            .await
+            .unwrap();
+        let err = client
+            .send_command("deletePages", serde_json::json!({"pages": [2]}), None)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, AdobeError::ConnectionFailed(_)));
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert_eq!(delivered.load(std::sync::atomic::Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_connect_rejects_invalid_proxy_url() {
+        let err = ProxyCommandClient::connect(AdobeApplication::Acrobat, "http://localhost:3001", 1_000, None)
+            .await
+            .err()
+            .unwrap();
+        assert!(matches!(err, AdobeError::InvalidProxyUrl(_)));
+    }
+
+    #[tokio::test]
+    async fn test_connect_refused() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        drop(listener);
+
+        let err = ProxyCommandClient::connect(AdobeApplication::Acrobat, &format!("ws://{}", addr), 1_000, None)
+            .await
+            .err()
+            .unwrap();
+        assert!(matches!(err, AdobeError::ConnectionFailed(_)));
+    }
+}
@@ -7,6 +7,9 @@ pub enum AdobeError {
     #[error("Unknown application: {0}")]
     UnknownApplication(String),
 
+    #[error("Unknown page size: {0}")]
+    UnknownPageSize(String),
+
     #[error("Application not connected: {0}")]
     ApplicationNotConnected(String),
 
@@ -19,6 +22,33 @@ pub enum AdobeError {
     #[error("Command failed: {0}")]
     CommandFailed(String),
 
+    #[error("Invalid page range: {0}")]
+    InvalidPageRange(String),
+
+    #[error("Invalid color: {0}")]
+    InvalidColor(String),
+
+    #[error("Invalid angle: {0}. Must be 0, 90, 180, or 270")]
+    InvalidRotation(i64),
+
+    #[error("Unknown page box: {0}. Must be Crop, Media, Bleed, Trim, or Art")]
+    UnknownPageBox(String),
+
+    #[error("Invalid cursor: {0}")]
+    InvalidCursor(String),
+
+    #[error("Unknown resource: {0}")]
+    UnknownResource(String),
+
+    #[error("Invalid log level: {0}")]
+    InvalidLogLevel(String),
+
+    #[error("Unknown framing: {0}. Must be line or lsp")]
+    UnknownFraming(String),
+
+    #[error("Invalid proxy URL: {0}")]
+    InvalidProxyUrl(String),
+
     #[error("Protocol error: {0}")]
     ProtocolError(String),
 
@@ -35,4 +65,35 @@ pub enum AdobeError {
     Internal(String),
 }
 
+impl AdobeError {
+    /// JSON-RPC error code an MCP server should report for this error
+    pub fn to_rpc_code(&self) -> i32 {
+        use crate::protocol::error_codes;
+
+        match self {
+            Self::UnknownApplication(_)
+            | Self::UnknownPageSize(_)
+            | Self::InvalidPageRange(_)
+            | Self::InvalidColor(_)
+            | Self::InvalidRotation(_)
+            | Self::UnknownPageBox(_)
+            | Self::InvalidCursor(_)
+            | Self::UnknownResource(_)
+            | Self::InvalidLogLevel(_)
+            | Self::UnknownFraming(_)
+            | Self::InvalidProxyUrl(_) => error_codes::INVALID_PARAMS,
+            Self::ApplicationNotConnected(_) | Self::ConnectionFailed(_) => {
+                error_codes::APPLICATION_NOT_CONNECTED
+            }
+            Self::CommandTimeout(_) => error_codes::COMMAND_TIMEOUT,
+            Self::CommandFailed(_) => error_codes::COMMAND_FAILED,
+            Self::ProtocolError(_)
+            | Self::WebSocketError(_)
+            | Self::JsonError(_)
+            | Self::IoError(_)
+            | Self::Internal(_) => error_codes::INTERNAL_ERROR,
+        }
+    }
+}
+
 pub type AdobeResult<T> = Result<T, AdobeError>;
@@ -2,12 +2,22 @@
 //!
 //! Shared types for communication between MCP servers, proxy, and native plugins.
 
+#[cfg(feature = "client")]
+pub mod client;
 pub mod error;
+#[cfg(feature = "logging")]
+pub mod logging;
+pub mod page_range;
 pub mod protocol;
 pub mod socket_io;
+#[cfg(feature = "stdio")]
+pub mod stdio;
+#[cfg(feature = "test-util")]
+pub mod test_util;
 pub mod types;
 
 pub use error::*;
+pub use page_range::*;
 pub use protocol::*;
 pub use socket_io::*;
 pub use types::*;
@@ -54,6 +64,24 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_new_adobe_applications_roundtrip() {
+        for (app, name, alias) in [
+            (AdobeApplication::Lightroom, "lightroom", "lr"),
+            (AdobeApplication::AfterEffects, "aftereffects", "ae"),
+            (AdobeApplication::XD, "xd", "XD"),
+        ] {
+            assert_eq!(app.as_str(), name);
+            assert_eq!(app.to_string(), name);
+            assert_eq!(name.parse::<AdobeApplication>().unwrap(), app);
+            assert_eq!(alias.parse::<AdobeApplication>().unwrap(), app);
+
+            let json = serde_json::to_string(&app).unwrap();
+            assert_eq!(json, format!("\"{}\"", name));
+            assert_eq!(serde_json::from_str::<AdobeApplication>(&json).unwrap(), app);
+        }
+    }
+
     #[test]
     fn test_adobe_application_from_str_error() {
         assert!("invalid".parse::<AdobeApplication>().is_err());
@@ -77,6 +105,133 @@ mod tests {
         assert_eq!(white.blue, 255);
     }
 
+    #[test]
+    fn test_rgb_color_from_hex() {
+        assert_eq!(RgbColor::from_hex("#ff8800").unwrap(), RgbColor::new(255, 136, 0));
+        assert_eq!(RgbColor::from_hex("FF8800").unwrap(), RgbColor::new(255, 136, 0));
+        assert_eq!(RgbColor::new(255, 136, 0).to_hex(), "#ff8800");
+    }
+
+    #[test]
+    fn test_rgb_color_from_short_hex() {
+        assert_eq!(RgbColor::from_hex("#f80").unwrap(), RgbColor::new(255, 136, 0));
+        assert_eq!(RgbColor::from_hex("fff").unwrap(), RgbColor::white());
+    }
+
+    #[test]
+    fn test_rgb_color_from_hex_invalid() {
+        for input in ["", "#", "#ff88", "#ff880", "#ff88001", "#gg8800", "+ff8800"] {
+            assert!(
+                matches!(RgbColor::from_hex(input), Err(AdobeError::InvalidColor(_))),
+                "{:?} should be rejected",
+                input
+            );
+        }
+    }
+
+    #[test]
+    fn test_rgb_color_deserialize() {
+        let from_hex: RgbColor = serde_json::from_value(serde_json::json!("#ff8800")).unwrap();
+        let from_object: RgbColor =
+            serde_json::from_value(serde_json::json!({"red": 255, "green": 136, "blue": 0})).unwrap();
+        assert_eq!(from_hex, from_object);
+        assert_eq!(
+            serde_json::to_value(from_hex).unwrap(),
+            serde_json::json!({"red": 255, "green": 136, "blue": 0})
+        );
+        assert!(serde_json::from_value::<RgbColor>(serde_json::json!("orange")).is_err());
+        assert!(serde_json::from_value::<RgbColor>(serde_json::json!({"red": 300, "green": 0, "blue": 0})).is_err());
+    }
+
+    #[test]
+    fn test_cmyk_to_rgb() {
+        assert_eq!(CmykColor::new(0.0, 0.0, 0.0, 0.0).to_rgb(), RgbColor::white());
+        assert_eq!(CmykColor::new(0.0, 0.0, 0.0, 1.0).to_rgb(), RgbColor::black());
+        assert_eq!(CmykColor::new(0.0, 1.0, 1.0, 0.0).to_rgb(), RgbColor::new(255, 0, 0));
+        assert_eq!(CmykColor::new(0.0, 0.5, 1.0, 0.0).to_rgb(), RgbColor::new(255, 128, 0));
+        assert_eq!(Color::Gray(0.5).to_rgb(), RgbColor::new(128, 128, 128));
+    }
+
+    #[test]
+    fn test_color_serializes_as_acrobat_array() {
+        assert_eq!(
+            serde_json::to_value(Color::Rgb(RgbColor::new(255, 0, 0))).unwrap(),
+            serde_json::json!(["RGB", 1.0, 0.0, 0.0])
+        );
+        assert_eq!(
+            serde_json::to_value(Color::Cmyk(CmykColor::new(0.0, 0.5, 1.0, 0.25))).unwrap(),
+            serde_json::json!(["CMYK", 0.0, 0.5, 1.0, 0.25])
+        );
+        assert_eq!(
+            serde_json::to_value(Color::Gray(0.5)).unwrap(),
+            serde_json::json!(["G", 0.5])
+        );
+    }
+
+    #[test]
+    fn test_color_deserialize() {
+        let parse = |v: serde_json::Value| serde_json::from_value::<Color>(v);
+        assert_eq!(
+            parse(serde_json::json!(["CMYK", 0.0, 0.5, 1.0, 0.25])).unwrap(),
+            Color::Cmyk(CmykColor::new(0.0, 0.5, 1.0, 0.25))
+        );
+        assert_eq!(parse(serde_json::json!(["G", 0.5])).unwrap(), Color::Gray(0.5));
+        assert_eq!(
+            parse(serde_json::json!(["RGB", 1.0, 0.0, 0.0])).unwrap(),
+            Color::Rgb(RgbColor::new(255, 0, 0))
+        );
+        assert_eq!(parse(serde_json::json!("#ff0000")).unwrap(), Color::Rgb(RgbColor::new(255, 0, 0)));
+        assert_eq!(
+            parse(serde_json::json!({"red": 0, "green": 0, "blue": 255})).unwrap(),
+            Color::Rgb(RgbColor::new(0, 0, 255))
+        );
+
+        for bad in [
+            serde_json::json!(["HSV", 0.1, 0.2, 0.3]),
+            serde_json::json!(["CMYK", 0.0, 0.5, 1.0]),
+            serde_json::json!(["G", 1.5]),
+            serde_json::json!([0.5]),
+        ] {
+            assert!(parse(bad.clone()).is_err(), "{} should be rejected", bad);
+        }
+    }
+
+    #[test]
+    fn test_rotation_degrees() {
+        for degrees in [0, 90, 180, 270] {
+            assert_eq!(Rotation::from_degrees(degrees).unwrap().to_degrees(), degrees);
+        }
+        assert_eq!(Rotation::from_degrees(45), None);
+        assert_eq!(Rotation::from_degrees(360), None);
+        assert_eq!(Rotation::from_degrees(-90), None);
+    }
+
+    #[test]
+    fn test_rotation_serde() {
+        assert_eq!(serde_json::to_value(Rotation::Deg180).unwrap(), serde_json::json!(180));
+        assert_eq!(
+            serde_json::from_value::<Rotation>(serde_json::json!(270)).unwrap(),
+            Rotation::Deg270
+        );
+        assert!(serde_json::from_value::<Rotation>(serde_json::json!(45)).is_err());
+        assert!(matches!(Rotation::try_from(45), Err(AdobeError::InvalidRotation(45))));
+    }
+
+    #[test]
+    fn test_page_box() {
+        assert_eq!("media".parse::<PageBox>().unwrap(), PageBox::Media);
+        assert_eq!("TRIM".parse::<PageBox>().unwrap().as_str(), "Trim");
+        assert!(matches!(
+            "Bounding".parse::<PageBox>(),
+            Err(AdobeError::UnknownPageBox(_))
+        ));
+        assert_eq!(serde_json::to_value(PageBox::Bleed).unwrap(), serde_json::json!("Bleed"));
+        assert_eq!(
+            serde_json::from_value::<PageBox>(serde_json::json!("Art")).unwrap(),
+            PageBox::Art
+        );
+    }
+
     #[test]
     fn test_bounds() {
         let bounds = Bounds::new(10, 20, 100, 200);
@@ -84,6 +239,68 @@ mod tests {
         assert_eq!(bounds.height(), 90);
     }
 
+    #[test]
+    fn test_bounds_overlapping() {
+        let a = Bounds::new(0, 0, 100, 100);
+        let b = Bounds::new(50, 60, 150, 160);
+        assert!(a.intersects(&b));
+        assert_eq!(a.intersection(&b), Some(Bounds::new(50, 60, 100, 100)));
+        assert_eq!(a.union(&b), Bounds::new(0, 0, 150, 160));
+        assert!(a.contains(Point { x: 100, y: 0 }));
+        assert!(!a.contains(Point { x: 101, y: 50 }));
+    }
+
+    #[test]
+    fn test_bounds_disjoint() {
+        let a = Bounds::new(0, 0, 10, 10);
+        let b = Bounds::new(20, 20, 30, 30);
+        assert!(!a.intersects(&b));
+        assert_eq!(a.intersection(&b), None);
+        assert_eq!(a.union(&b), Bounds::new(0, 0, 30, 30));
+
+        // Sharing an edge is not an overlap
+        let c = Bounds::new(0, 10, 10, 20);
+        assert!(!a.intersects(&c));
+    }
+
+    #[test]
+    fn test_bounds_contained() {
+        let outer = Bounds::new(0, 0, 100, 100);
+        let inner = Bounds::new(10, 10, 20, 20);
+        assert_eq!(outer.intersection(&inner), Some(inner));
+        assert_eq!(inner.intersection(&outer), Some(inner));
+        assert_eq!(outer.union(&inner), outer);
+    }
+
+    #[test]
+    fn test_bounds_inverted() {
+        // PDF-style rect: top is the larger y
+        let flipped = Bounds::new(100, 0, 0, 100);
+        assert_eq!(flipped.normalized(), Bounds::new(0, 0, 100, 100));
+        assert!(flipped.contains(Point { x: 50, y: 50 }));
+        assert_eq!(
+            flipped.intersection(&Bounds::new(50, 50, 150, 150)),
+            Some(Bounds::new(50, 50, 100, 100))
+        );
+    }
+
+    #[test]
+    fn test_point_translate() {
+        let p = Point::new(10, 20);
+        assert_eq!(p.translate(5, -30), Point::new(15, -10));
+        assert_eq!(p.translate(0, 0), p);
+        assert_eq!(p + Point::new(-10, 5), Point::new(0, 25));
+        assert_eq!(p - Point::new(15, -5), Point::new(-5, 25));
+    }
+
+    #[test]
+    fn test_point_distance() {
+        let origin = Point::default();
+        assert_eq!(origin.distance_to(&Point::new(3, 4)), 5.0);
+        assert_eq!(Point::new(-3, -4).distance_to(&origin), 5.0);
+        assert_eq!(origin.distance_to(&origin), 0.0);
+    }
+
     #[test]
     fn test_page_size_dimensions() {
         let (w, h) = PageSize::Letter.dimensions();
@@ -93,6 +310,39 @@ mod tests {
         let (w, h) = PageSize::A4.dimensions();
         assert_eq!(w, 595.0);
         assert_eq!(h, 842.0);
+
+        assert_eq!(PageSize::A5.dimensions(), (420.0, 595.0));
+        assert_eq!(PageSize::B4.dimensions(), (709.0, 1001.0));
+        assert_eq!(PageSize::B5.dimensions(), (499.0, 709.0));
+        assert_eq!(PageSize::Tabloid.dimensions(), (792.0, 1224.0));
+        assert_eq!(PageSize::Executive.dimensions(), (522.0, 756.0));
+    }
+
+    #[test]
+    fn test_page_size_from_str() {
+        assert_eq!("tabloid".parse::<PageSize>().unwrap(), PageSize::Tabloid);
+        assert_eq!(" B5 ".parse::<PageSize>().unwrap(), PageSize::B5);
+        assert_eq!("CUSTOM".parse::<PageSize>().unwrap(), PageSize::Custom);
+        assert!(matches!(
+            "B6".parse::<PageSize>(),
+            Err(AdobeError::UnknownPageSize(_))
+        ));
+        for preset in PageSize::PRESETS {
+            assert_eq!(preset.to_string().parse::<PageSize>().unwrap(), preset);
+            assert_eq!(
+                serde_json::to_value(preset).unwrap(),
+                serde_json::json!(preset.as_str())
+            );
+        }
+    }
+
+    #[test]
+    fn test_page_size_from_dimensions() {
+        assert_eq!(PageSize::from_dimensions(612.0, 792.0, 0.0), Some(PageSize::Letter));
+        assert_eq!(PageSize::from_dimensions(595.28, 841.89, 1.0), Some(PageSize::A4));
+        assert_eq!(PageSize::from_dimensions(1224.0, 792.0, 1.0), Some(PageSize::Tabloid));
+        assert_eq!(PageSize::from_dimensions(595.28, 841.89, 0.1), None);
+        assert_eq!(PageSize::from_dimensions(100.0, 100.0, 5.0), None);
     }
 
     // ==========================================================================
@@ -114,6 +364,205 @@ mod tests {
         assert_eq!(packet.application, "acrobat");
     }
 
+    #[test]
+    fn test_command_builder() {
+        let cmd = CommandBuilder::new("addText")
+            .option("text", "hi")
+            .option("page", 1)
+            .build();
+        assert_eq!(
+            serde_json::to_value(&cmd).unwrap(),
+            serde_json::json!({"action": "addText", "options": {"text": "hi", "page": 1}})
+        );
+    }
+
+    #[test]
+    fn test_command_builder_merges_options() {
+        let mut extra = serde_json::Map::new();
+        extra.insert("page".to_string(), serde_json::json!(2));
+        extra.insert("fontSize".to_string(), serde_json::json!(14.0));
+
+        let cmd = CommandBuilder::default()
+            .action("addText")
+            .option("page", 1)
+            .options(extra)
+            .build();
+        assert_eq!(cmd.action, "addText");
+        assert_eq!(cmd.options, serde_json::json!({"page": 2, "fontSize": 14.0}));
+
+        let empty = CommandBuilder::new("getPageCount").build();
+        assert_eq!(empty.options, serde_json::json!({}));
+    }
+
+    #[test]
+    fn test_command_response_correlation_fields() {
+        let response: CommandResponse = serde_json::from_value(serde_json::json!({
+            "senderId": "abc",
+            "status": "SUCCESS",
+            "requestId": "req-1",
+            "timestamp": 1_700_000_000_000_i64,
+            "durationMs": 42
+        }))
+        .unwrap();
+        assert_eq!(response.request_id.as_deref(), Some("req-1"));
+        assert_eq!(response.timestamp, Some(1_700_000_000_000));
+        assert_eq!(response.duration_ms, Some(42));
+
+        let json = serde_json::to_value(&response).unwrap();
+        assert_eq!(json["requestId"], "req-1");
+        assert_eq!(json["timestamp"], 1_700_000_000_000_i64);
+        assert_eq!(json["durationMs"], 42);
+
+        let bare = CommandResponse {
+            request_id: None,
+            timestamp: None,
+            duration_ms: None,
+            code: None,
+            ..response
+        };
+        let json = serde_json::to_value(&bare).unwrap();
+        assert!(json.get("requestId").is_none());
+        assert!(json.get("timestamp").is_none());
+        assert!(json.get("durationMs").is_none());
+    }
+
+    #[test]
+    fn test_register_message_client_details() {
+        let bare: RegisterMessage =
+            serde_json::from_value(serde_json::json!({"application": "acrobat"})).unwrap();
+        assert!(bare.version.is_none());
+        assert!(bare.client_name.is_none());
+        assert_eq!(
+            serde_json::to_value(&bare).unwrap(),
+            serde_json::json!({"application": "acrobat"})
+        );
+
+        let full: RegisterMessage = serde_json::from_value(serde_json::json!({
+            "application": "acrobat",
+            "version": "0.2.0",
+            "clientName": "acrobat-bridge"
+        }))
+        .unwrap();
+        assert_eq!(full.version.as_deref(), Some("0.2.0"));
+        assert_eq!(full.client_name.as_deref(), Some("acrobat-bridge"));
+    }
+
+    #[test]
+    fn test_adobe_error_rpc_codes() {
+        let cases = [
+            (AdobeError::UnknownApplication("x".into()), error_codes::INVALID_PARAMS),
+            (AdobeError::UnknownPageSize("x".into()), error_codes::INVALID_PARAMS),
+            (AdobeError::InvalidPageRange("x".into()), error_codes::INVALID_PARAMS),
+            (AdobeError::InvalidColor("x".into()), error_codes::INVALID_PARAMS),
+            (AdobeError::InvalidRotation(45), error_codes::INVALID_PARAMS),
+            (AdobeError::UnknownPageBox("x".into()), error_codes::INVALID_PARAMS),
+            (AdobeError::InvalidCursor("x".into()), error_codes::INVALID_PARAMS),
+            (AdobeError::UnknownResource("x".into()), error_codes::INVALID_PARAMS),
+            (AdobeError::InvalidLogLevel("x".into()), error_codes::INVALID_PARAMS),
+            (AdobeError::UnknownFraming("x".into()), error_codes::INVALID_PARAMS),
+            (AdobeError::InvalidProxyUrl("x".into()), error_codes::INVALID_PARAMS),
+            (
+                AdobeError::ApplicationNotConnected("acrobat".into()),
+                error_codes::APPLICATION_NOT_CONNECTED,
+            ),
+            (
+                AdobeError::ConnectionFailed("refused".into()),
+                error_codes::APPLICATION_NOT_CONNECTED,
+            ),
+            (AdobeError::CommandTimeout(30_000), error_codes::COMMAND_TIMEOUT),
+            (AdobeError::CommandFailed("x".into()), error_codes::COMMAND_FAILED),
+            (AdobeError::ProtocolError("x".into()), error_codes::INTERNAL_ERROR),
+            (AdobeError::WebSocketError("x".into()), error_codes::INTERNAL_ERROR),
+            (
+                AdobeError::IoError(std::io::Error::other("x")),
+                error_codes::INTERNAL_ERROR,
+            ),
+            (AdobeError::Internal("x".into()), error_codes::INTERNAL_ERROR),
+        ];
+        for (error, code) in cases {
+            assert_eq!(error.to_rpc_code(), code, "{:?}", error);
+        }
+
+        let json_error = serde_json::from_str::<serde_json::Value>("{").unwrap_err();
+        assert_eq!(
+            AdobeError::JsonError(json_error).to_rpc_code(),
+            error_codes::INTERNAL_ERROR
+        );
+    }
+
+    #[test]
+    fn test_mcp_error_from_adobe_error() {
+        let error = McpError::from(AdobeError::CommandTimeout(500));
+        assert_eq!(error.code, error_codes::COMMAND_TIMEOUT);
+        assert_eq!(error.message, "Command timeout after 500ms");
+        assert!(error.data.is_none());
+    }
+
+    #[test]
+    fn test_command_response_is_not_connected() {
+        let response: CommandResponse = serde_json::from_value(serde_json::json!({
+            "senderId": "abc",
+            "status": "FAILURE",
+            "message": "No clients registered for application: acrobat"
+        }))
+        .unwrap();
+        assert!(response.is_not_connected());
+
+        let response: CommandResponse = serde_json::from_value(serde_json::json!({
+            "senderId": "abc",
+            "status": "FAILURE",
+            "message": "Invalid angle: 45"
+        }))
+        .unwrap();
+        assert!(!response.is_not_connected());
+
+        // The code alone marks the failure, whatever the message says
+        let response: CommandResponse = serde_json::from_value(serde_json::json!({
+            "senderId": "abc",
+            "status": "FAILURE",
+            "code": NOT_CONNECTED_CODE,
+            "message": "Acrobat is not running"
+        }))
+        .unwrap();
+        assert!(response.is_not_connected());
+        assert_eq!(serde_json::to_value(&response).unwrap()["code"], NOT_CONNECTED_CODE);
+    }
+
+    #[derive(Debug, serde::Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    struct PageCount {
+        page_count: u32,
+    }
+
+    #[test]
+    fn test_command_response_parse_response() {
+        let response: CommandResponse = serde_json::from_value(serde_json::json!({
+            "senderId": "abc",
+            "status": "SUCCESS",
+            "response": {"success": true, "pageCount": 12},
+            "document": {"pageCount": 3}
+        }))
+        .unwrap();
+        assert_eq!(response.parse_response::<PageCount>().unwrap().page_count, 12);
+        assert_eq!(response.parse_document::<PageCount>().unwrap().page_count, 3);
+    }
+
+    #[test]
+    fn test_command_response_parse_response_errors() {
+        let response: CommandResponse = serde_json::from_value(serde_json::json!({
+            "senderId": "abc",
+            "status": "SUCCESS",
+            "response": {"success": true}
+        }))
+        .unwrap();
+        let err = response.parse_response::<PageCount>().unwrap_err();
+        assert!(matches!(err, AdobeError::JsonError(_)));
+        assert!(err.to_string().contains("pageCount"));
+
+        let err = response.parse_document::<PageCount>().unwrap_err();
+        assert!(matches!(err, AdobeError::ProtocolError(_)));
+    }
+
     #[test]
     fn test_response_status_serialization() {
         let success = serde_json::to_string(&ResponseStatus::Success).unwrap();
@@ -173,6 +622,32 @@ mod tests {
         assert_eq!(error_codes::COMMAND_FAILED, -32002);
     }
 
+    #[test]
+    fn test_paginate() {
+        let items: Vec<u32> = (1..=5).collect();
+
+        let (page, cursor) = paginate(&items, None, 2).unwrap();
+        assert_eq!(page, vec![1, 2]);
+        let (page, cursor) = paginate(&items, cursor.as_deref(), 2).unwrap();
+        assert_eq!(page, vec![3, 4]);
+        let (page, cursor) = paginate(&items, cursor.as_deref(), 2).unwrap();
+        assert_eq!(page, vec![5]);
+        assert!(cursor.is_none());
+
+        let (page, cursor) = paginate(&items, None, 10).unwrap();
+        assert_eq!(page.len(), 5);
+        assert!(cursor.is_none());
+    }
+
+    #[test]
+    fn test_paginate_invalid_cursor() {
+        let items = [1, 2, 3];
+        for cursor in ["abc", "-1", "4"] {
+            let err = paginate(&items, Some(cursor), 2).unwrap_err();
+            assert!(matches!(err, AdobeError::InvalidCursor(_)), "{:?}", cursor);
+        }
+    }
+
     // ==========================================================================
     // Error Tests
     // ==========================================================================
@@ -189,10 +664,15 @@ mod tests {
         // Test all error variants can be created and display correctly
         let errors = vec![
             AdobeError::UnknownApplication("test".into()),
+            AdobeError::UnknownPageSize("B6".into()),
             AdobeError::ApplicationNotConnected("acrobat".into()),
             AdobeError::ConnectionFailed("timeout".into()),
             AdobeError::CommandTimeout(5000),
             AdobeError::CommandFailed("invalid".into()),
+            AdobeError::InvalidPageRange("0".into()),
+            AdobeError::InvalidColor("#zz".into()),
+            AdobeError::InvalidRotation(45),
+            AdobeError::UnknownPageBox("Bounding".into()),
             AdobeError::ProtocolError("parse".into()),
             AdobeError::WebSocketError("closed".into()),
             AdobeError::Internal("unexpected".into()),
@@ -229,6 +709,10 @@ mod tests {
             response: Some(serde_json::json!({"pageCount": 5})),
             message: None,
             document: Some(serde_json::json!({"title": "Test Doc"})),
+            request_id: None,
+            timestamp: None,
+            duration_ms: None,
+            code: None,
         };
 
         let json = serde_json::to_string(&response).unwrap();
@@ -266,10 +750,53 @@ mod tests {
             height: 792,
             page_count: Some(10),
             has_unsaved_changes: false,
+            author: None,
+            subject: None,
+            keywords: None,
+            creator: None,
+            producer: None,
+            created: None,
+            modified: None,
         };
 
         let json = serde_json::to_string(&doc).unwrap();
         assert!(json.contains("Test Document"));
         assert!(json.contains("doc-123"));
+        assert!(!json.contains("author"));
+        assert!(!json.contains("created"));
+    }
+
+    #[test]
+    fn test_document_info_metadata_fields() {
+        let value = serde_json::json!({
+            "id": null,
+            "name": "report.pdf",
+            "path": "/C/docs/report.pdf",
+            "width": 612,
+            "height": 792,
+            "page_count": 4,
+            "has_unsaved_changes": true,
+            "author": "Jane Doe",
+            "subject": "Quarterly results",
+            "keywords": "finance, q3",
+            "creator": "Microsoft Word",
+            "producer": "Acrobat PDFMaker",
+            "created": "2024-01-15T09:30:00.000Z",
+            "modified": "2024-02-01T17:05:00.000Z"
+        });
+
+        let doc: DocumentInfo = serde_json::from_value(value.clone()).unwrap();
+        assert_eq!(doc.author.as_deref(), Some("Jane Doe"));
+        assert_eq!(doc.producer.as_deref(), Some("Acrobat PDFMaker"));
+        assert_eq!(doc.modified.as_deref(), Some("2024-02-01T17:05:00.000Z"));
+        assert_eq!(serde_json::to_value(&doc).unwrap(), value);
+
+        let response: CommandResponse = serde_json::from_value(serde_json::json!({
+            "senderId": "abc",
+            "status": "SUCCESS",
+            "document": value
+        }))
+        .unwrap();
+        assert_eq!(response.parse_document::<DocumentInfo>().unwrap().created, doc.created);
     }
 }
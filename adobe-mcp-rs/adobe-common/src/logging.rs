@@ -0,0 +1,206 @@
+//! Runtime log level control for the MCP servers (`logging/setLevel`)
+//!
+//! Logs always go to stderr; stdout carries JSON-RPC. After a client sets a
+//! level, events that pass it are also handed to a sink that writes them to
+//! the client as `notifications/message`.
+
+use std::cell::Cell;
+use std::fmt;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, OnceLock};
+
+use serde_json::{json, Map, Value};
+use tracing::field::{Field, Visit};
+use tracing::{Event, Level, Subscriber};
+use tracing_subscriber::filter::LevelFilter;
+use tracing_subscriber::layer::{Context, Layer, SubscriberExt};
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::{fmt as tracing_fmt, reload, Registry};
+
+use crate::error::{AdobeError, AdobeResult};
+
+/// Level names defined by MCP, most verbose first
+pub const MCP_LOG_LEVELS: [&str; 8] = [
+    "debug",
+    "info",
+    "notice",
+    "warning",
+    "error",
+    "critical",
+    "alert",
+    "emergency",
+];
+
+/// Receives the MCP level name and `data` of each forwarded event
+pub type LogSink = fn(&str, Value);
+
+/// Tracing filter for an MCP level name
+///
+/// tracing has fewer levels than MCP, so `notice` shares `info` and the
+/// levels above `error` all map to `error`.
+pub fn level_filter(level: &str) -> AdobeResult<LevelFilter> {
+    match level {
+        "debug" => Ok(LevelFilter::DEBUG),
+        "info" | "notice" => Ok(LevelFilter::INFO),
+        "warning" => Ok(LevelFilter::WARN),
+        "error" | "critical" | "alert" | "emergency" => Ok(LevelFilter::ERROR),
+        other => Err(AdobeError::InvalidLogLevel(other.to_string())),
+    }
+}
+
+/// MCP level name reported for a tracing event level
+pub fn mcp_level(level: &Level) -> &'static str {
+    match *level {
+        Level::ERROR => "error",
+        Level::WARN => "warning",
+        Level::INFO => "info",
+        Level::DEBUG | Level::TRACE => "debug",
+    }
+}
+
+struct LogControl {
+    filter: reload::Handle<LevelFilter, Registry>,
+    forwarding: Arc<AtomicBool>,
+}
+
+static LOG_CONTROL: OnceLock<LogControl> = OnceLock::new();
+
+/// Install the global subscriber: formatted output on stderr at `info`, plus
+/// forwarding to `sink` once [`set_level`] has been called
+pub fn init(sink: LogSink) {
+    let (filter, handle) = reload::Layer::new(LevelFilter::INFO);
+    let forwarding = Arc::new(AtomicBool::new(false));
+
+    tracing_subscriber::registry()
+        .with(filter)
+        .with(tracing_fmt::layer().with_writer(std::io::stderr))
+        .with(NotificationLayer {
+            forwarding: forwarding.clone(),
+            sink,
+        })
+        .init();
+
+    let _ = LOG_CONTROL.set(LogControl {
+        filter: handle,
+        forwarding,
+    });
+}
+
+/// Apply a `logging/setLevel` request
+///
+/// Without [`init`] (as in tests) the level is only validated.
+pub fn set_level(level: &str) -> AdobeResult<LevelFilter> {
+    let filter = level_filter(level)?;
+    if let Some(control) = LOG_CONTROL.get() {
+        control
+            .filter
+            .reload(filter)
+            .map_err(|e| AdobeError::Internal(format!("Failed to change log level: {}", e)))?;
+        control.forwarding.store(true, Ordering::SeqCst);
+    }
+    Ok(filter)
+}
+
+thread_local! {
+    /// Set while a sink runs, so events it logs are not forwarded again
+    static IN_SINK: Cell<bool> = const { Cell::new(false) };
+}
+
+struct NotificationLayer {
+    forwarding: Arc<AtomicBool>,
+    sink: LogSink,
+}
+
+impl<S: Subscriber> Layer<S> for NotificationLayer {
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        if !self.forwarding.load(Ordering::SeqCst) || IN_SINK.with(Cell::get) {
+            return;
+        }
+
+        let metadata = event.metadata();
+        let mut fields = FieldMap(Map::new());
+        event.record(&mut fields);
+        fields
+            .0
+            .insert("target".to_string(), json!(metadata.target()));
+
+        IN_SINK.with(|in_sink| in_sink.set(true));
+        (self.sink)(mcp_level(metadata.level()), Value::Object(fields.0));
+        IN_SINK.with(|in_sink| in_sink.set(false));
+    }
+}
+
+/// Collects event fields, including `message`, as JSON
+struct FieldMap(Map<String, Value>);
+
+impl Visit for FieldMap {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.0.insert(field.name().to_string(), json!(value));
+    }
+
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        self.0.insert(field.name().to_string(), json!(value));
+    }
+
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        self.0.insert(field.name().to_string(), json!(value));
+    }
+
+    fn record_bool(&mut self, field: &Field, value: bool) {
+        self.0.insert(field.name().to_string(), json!(value));
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        self.0
+            .insert(field.name().to_string(), json!(format!("{:?}", value)));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    #[test]
+    fn test_level_filter() {
+        assert_eq!(level_filter("debug").unwrap(), LevelFilter::DEBUG);
+        assert_eq!(level_filter("notice").unwrap(), LevelFilter::INFO);
+        assert_eq!(level_filter("warning").unwrap(), LevelFilter::WARN);
+        assert_eq!(level_filter("emergency").unwrap(), LevelFilter::ERROR);
+        for level in MCP_LOG_LEVELS {
+            assert!(level_filter(level).is_ok(), "{}", level);
+        }
+        assert!(matches!(
+            level_filter("verbose"),
+            Err(AdobeError::InvalidLogLevel(_))
+        ));
+    }
+
+    static FORWARDED: Mutex<Vec<(String, Value)>> = Mutex::new(Vec::new());
+
+    fn record(level: &str, data: Value) {
+        FORWARDED.lock().unwrap().push((level.to_string(), data));
+    }
+
+    #[test]
+    fn test_notification_layer_forwards_once_enabled() {
+        let forwarding = Arc::new(AtomicBool::new(false));
+        let subscriber = tracing_subscriber::registry().with(NotificationLayer {
+            forwarding: forwarding.clone(),
+            sink: record,
+        });
+
+        tracing::subscriber::with_default(subscriber, || {
+            tracing::info!("before setLevel");
+            forwarding.store(true, Ordering::SeqCst);
+            tracing::warn!(page = 3, "page missing");
+        });
+
+        let forwarded = FORWARDED.lock().unwrap();
+        assert_eq!(forwarded.len(), 1);
+        assert_eq!(forwarded[0].0, "warning");
+        assert_eq!(forwarded[0].1["message"], "page missing");
+        assert_eq!(forwarded[0].1["page"], 3);
+        assert!(forwarded[0].1["target"].is_string());
+    }
+}
@@ -0,0 +1,251 @@
+//! Page range selection shared by the MCP servers and native plugins
+//!
+//! Ranges are written the way users type them in print dialogs: `"all"`,
+//! `"3"`, `"2-5"`, or comma lists such as `"1-3,7,9-"`. Page numbers are
+//! 1-based; a trailing `-` runs to the last page.
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+use std::ops::RangeInclusive;
+use std::str::FromStr;
+
+use crate::error::{AdobeError, AdobeResult};
+
+/// A contiguous run of 1-based pages; `end: None` runs to the last page
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PageSpan {
+    pub start: u32,
+    pub end: Option<u32>,
+}
+
+impl PageSpan {
+    pub fn single(page: u32) -> Self {
+        Self {
+            start: page,
+            end: Some(page),
+        }
+    }
+
+    /// Resolve to inclusive 1-based bounds within a document of `page_count` pages
+    pub fn resolve(&self, page_count: u32) -> AdobeResult<RangeInclusive<u32>> {
+        let end = self.end.unwrap_or(page_count);
+        for page in [self.start, end] {
+            if page > page_count {
+                return Err(AdobeError::InvalidPageRange(format!(
+                    "page {} is beyond the last page ({})",
+                    page, page_count
+                )));
+            }
+        }
+        Ok(self.start..=end)
+    }
+}
+
+impl fmt::Display for PageSpan {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.end {
+            Some(end) if end == self.start => write!(f, "{}", self.start),
+            Some(end) => write!(f, "{}-{}", self.start, end),
+            None => write!(f, "{}-", self.start),
+        }
+    }
+}
+
+/// Page selection such as `"all"`, `"3"`, `"2-5"` or `"1-3,7,9-"`
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum PageRange {
+    #[default]
+    All,
+    Spans(Vec<PageSpan>),
+}
+
+impl PageRange {
+    /// The selection as spans; `All` is the single open span `1-`
+    pub fn spans(&self) -> Vec<PageSpan> {
+        match self {
+            Self::All => vec![PageSpan { start: 1, end: None }],
+            Self::Spans(spans) => spans.clone(),
+        }
+    }
+
+    /// Resolve every span against a document of `page_count` pages
+    pub fn resolve(&self, page_count: u32) -> AdobeResult<Vec<RangeInclusive<u32>>> {
+        match self {
+            Self::All if page_count == 0 => Ok(Vec::new()),
+            Self::All => Ok(vec![1..=page_count]),
+            Self::Spans(spans) => spans.iter().map(|span| span.resolve(page_count)).collect(),
+        }
+    }
+
+    /// Sorted, de-duplicated 1-based page numbers in the selection
+    pub fn pages(&self, page_count: u32) -> AdobeResult<Vec<u32>> {
+        let mut pages: Vec<u32> = self.resolve(page_count)?.into_iter().flatten().collect();
+        pages.sort_unstable();
+        pages.dedup();
+        Ok(pages)
+    }
+}
+
+fn parse_page_number(raw: &str, input: &str) -> AdobeResult<u32> {
+    let page = raw.trim().parse::<u32>().map_err(|_| {
+        AdobeError::InvalidPageRange(format!("'{}' is not a page number in '{}'", raw.trim(), input))
+    })?;
+    if page == 0 {
+        return Err(AdobeError::InvalidPageRange(format!(
+            "pages are numbered from 1 in '{}'",
+            input
+        )));
+    }
+    Ok(page)
+}
+
+impl FromStr for PageRange {
+    type Err = AdobeError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let trimmed = s.trim();
+        if trimmed.is_empty() {
+            return Err(AdobeError::InvalidPageRange("empty page range".to_string()));
+        }
+        if trimmed.eq_ignore_ascii_case("all") {
+            return Ok(Self::All);
+        }
+
+        let mut spans = Vec::new();
+        for part in trimmed.split(',') {
+            let part = part.trim();
+            if part.is_empty() {
+                return Err(AdobeError::InvalidPageRange(format!(
+                    "empty entry in '{}'",
+                    trimmed
+                )));
+            }
+
+            let span = match part.split_once('-') {
+                Some((start_raw, end_raw)) => {
+                    let start = parse_page_number(start_raw, trimmed)?;
+                    let end = if end_raw.trim().is_empty() {
+                        None
+                    } else {
+                        Some(parse_page_number(end_raw, trimmed)?)
+                    };
+                    if end.is_some_and(|end| end < start) {
+                        return Err(AdobeError::InvalidPageRange(format!(
+                            "'{}' ends before it starts",
+                            part
+                        )));
+                    }
+                    PageSpan { start, end }
+                }
+                None => PageSpan::single(parse_page_number(part, trimmed)?),
+            };
+            spans.push(span);
+        }
+
+        Ok(Self::Spans(spans))
+    }
+}
+
+impl fmt::Display for PageRange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::All => f.write_str("all"),
+            Self::Spans(spans) => {
+                for (i, span) in spans.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(",")?;
+                    }
+                    write!(f, "{}", span)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl Serialize for PageRange {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for PageRange {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let raw = String::deserialize(deserializer)?;
+        raw.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_all() {
+        assert_eq!("all".parse::<PageRange>().unwrap(), PageRange::All);
+        assert_eq!(" ALL ".parse::<PageRange>().unwrap(), PageRange::All);
+    }
+
+    #[test]
+    fn test_parse_spans() {
+        let range: PageRange = "1-3, 7,9-".parse().unwrap();
+        assert_eq!(
+            range,
+            PageRange::Spans(vec![
+                PageSpan { start: 1, end: Some(3) },
+                PageSpan::single(7),
+                PageSpan { start: 9, end: None },
+            ])
+        );
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        for input in ["", "0", "0-3", "5-2", "a", "1,,2", "1-x", "-3"] {
+            let err = input.parse::<PageRange>().unwrap_err();
+            assert!(
+                matches!(err, AdobeError::InvalidPageRange(_)),
+                "{:?} should be rejected",
+                input
+            );
+        }
+    }
+
+    #[test]
+    fn test_display_roundtrip() {
+        for input in ["all", "3", "2-5", "1-3,7,9-"] {
+            let range: PageRange = input.parse().unwrap();
+            assert_eq!(range.to_string(), input);
+            assert_eq!(range.to_string().parse::<PageRange>().unwrap(), range);
+        }
+    }
+
+    #[test]
+    fn test_serde_as_string() {
+        let range: PageRange = serde_json::from_str("\"2-4\"").unwrap();
+        assert_eq!(serde_json::to_string(&range).unwrap(), "\"2-4\"");
+        assert!(serde_json::from_str::<PageRange>("\"0\"").is_err());
+    }
+
+    #[test]
+    fn test_resolve() {
+        let range: PageRange = "1-3,7,9-".parse().unwrap();
+        assert_eq!(range.resolve(10).unwrap(), vec![1..=3, 7..=7, 9..=10]);
+        assert_eq!(PageRange::All.resolve(4).unwrap(), vec![1..=4]);
+        assert!(PageRange::All.resolve(0).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_resolve_out_of_bounds() {
+        let range: PageRange = "2-5".parse().unwrap();
+        assert!(range.resolve(4).is_err());
+        let range: PageRange = "6-".parse().unwrap();
+        assert!(range.resolve(5).is_err());
+    }
+
+    #[test]
+    fn test_pages_sorted_unique() {
+        let range: PageRange = "4-5,1,2-4".parse().unwrap();
+        assert_eq!(range.pages(5).unwrap(), vec![1, 2, 3, 4, 5]);
+    }
+}
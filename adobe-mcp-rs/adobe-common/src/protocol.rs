@@ -1,6 +1,8 @@
 //! WebSocket protocol messages for Adobe MCP proxy communication
 
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
+use crate::error::{AdobeError, AdobeResult};
 use crate::types::AdobeApplication;
 
 /// Command sent from MCP server to proxy
@@ -44,6 +46,44 @@ impl Command {
     }
 }
 
+/// Fluent construction of a [`Command`] one option at a time
+#[derive(Debug, Clone, Default)]
+pub struct CommandBuilder {
+    action: String,
+    options: serde_json::Map<String, serde_json::Value>,
+}
+
+impl CommandBuilder {
+    pub fn new(action: impl Into<String>) -> Self {
+        Self {
+            action: action.into(),
+            options: serde_json::Map::new(),
+        }
+    }
+
+    /// Replace the action name
+    pub fn action(mut self, action: impl Into<String>) -> Self {
+        self.action = action.into();
+        self
+    }
+
+    /// Set one option, replacing any earlier value for `key`
+    pub fn option(mut self, key: impl Into<String>, value: impl Into<serde_json::Value>) -> Self {
+        self.options.insert(key.into(), value.into());
+        self
+    }
+
+    /// Merge every entry of `options`, replacing earlier values for the same keys
+    pub fn options(mut self, options: serde_json::Map<String, serde_json::Value>) -> Self {
+        self.options.extend(options);
+        self
+    }
+
+    pub fn build(self) -> Command {
+        Command::new(self.action, serde_json::Value::Object(self.options))
+    }
+}
+
 /// Response from application via proxy
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CommandResponse {
@@ -61,6 +101,66 @@ pub struct CommandResponse {
     /// Document info (optional)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub document: Option<serde_json::Value>,
+    /// Echo of the command's `requestId`, for correlating responses
+    #[serde(rename = "requestId", default, skip_serializing_if = "Option::is_none")]
+    pub request_id: Option<String>,
+    /// When the response was produced, in epoch milliseconds
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timestamp: Option<i64>,
+    /// How long the application took to execute the command, in milliseconds
+    #[serde(rename = "durationMs", default, skip_serializing_if = "Option::is_none")]
+    pub duration_ms: Option<u64>,
+    /// Machine-readable reason for a failure the proxy generated, such as
+    /// [`NOT_CONNECTED_CODE`]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub code: Option<String>,
+}
+
+/// Milliseconds since the Unix epoch, the unit of [`CommandResponse::timestamp`]
+pub fn epoch_millis() -> i64 {
+    chrono::Utc::now().timestamp_millis()
+}
+
+/// Message prefix of the proxy's immediate failure when no client is
+/// registered for the target application
+pub const NOT_CONNECTED_PREFIX: &str = "No clients registered for application";
+
+/// `code` of the proxy's failure when no client is registered for the
+/// target application
+pub const NOT_CONNECTED_CODE: &str = "APPLICATION_NOT_CONNECTED";
+
+impl CommandResponse {
+    /// Whether this is the proxy reporting that the application isn't connected
+    ///
+    /// Checks [`NOT_CONNECTED_CODE`], falling back to the message prefix for
+    /// proxies that predate the code.
+    pub fn is_not_connected(&self) -> bool {
+        self.status == ResponseStatus::Failure
+            && (self.code.as_deref() == Some(NOT_CONNECTED_CODE)
+                || self
+                    .message
+                    .as_deref()
+                    .is_some_and(|message| message.starts_with(NOT_CONNECTED_PREFIX)))
+    }
+
+    /// Deserialize the `response` payload into `T`
+    pub fn parse_response<T: DeserializeOwned>(&self) -> AdobeResult<T> {
+        Self::parse_payload(self.response.as_ref(), "response")
+    }
+
+    /// Deserialize the `document` payload into `T`
+    pub fn parse_document<T: DeserializeOwned>(&self) -> AdobeResult<T> {
+        Self::parse_payload(self.document.as_ref(), "document")
+    }
+
+    fn parse_payload<T: DeserializeOwned>(
+        payload: Option<&serde_json::Value>,
+        field: &str,
+    ) -> AdobeResult<T> {
+        let payload = payload
+            .ok_or_else(|| AdobeError::ProtocolError(format!("Response has no {} payload", field)))?;
+        Ok(T::deserialize(payload)?)
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
@@ -74,6 +174,12 @@ pub enum ResponseStatus {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RegisterMessage {
     pub application: String,
+    /// Version of the registering plugin or bridge
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    /// Name of the registering plugin or bridge, e.g. `acrobat-bridge`
+    #[serde(rename = "clientName", default, skip_serializing_if = "Option::is_none")]
+    pub client_name: Option<String>,
 }
 
 /// Registration response from proxy
@@ -138,15 +244,22 @@ impl McpResponse {
     }
 
     pub fn error(id: serde_json::Value, code: i32, message: impl Into<String>) -> Self {
-        Self {
-            jsonrpc: "2.0".to_string(),
+        Self::with_error(
             id,
-            result: None,
-            error: Some(McpError {
+            McpError {
                 code,
                 message: message.into(),
                 data: None,
-            }),
+            },
+        )
+    }
+
+    pub fn with_error(id: serde_json::Value, error: McpError) -> Self {
+        Self {
+            jsonrpc: "2.0".to_string(),
+            id,
+            result: None,
+            error: Some(error),
         }
     }
 }
@@ -159,6 +272,41 @@ pub struct McpError {
     pub data: Option<serde_json::Value>,
 }
 
+impl From<AdobeError> for McpError {
+    fn from(error: AdobeError) -> Self {
+        Self {
+            code: error.to_rpc_code(),
+            message: error.to_string(),
+            data: None,
+        }
+    }
+}
+
+/// Tools returned per `tools/list` page
+pub const TOOLS_PAGE_SIZE: usize = 20;
+
+/// One page of a cursor-paginated MCP list, plus the cursor for the next page
+///
+/// Cursors are opaque to clients; here they carry the offset of the next
+/// page's first item. The last page has no next cursor.
+pub fn paginate<T: Clone>(
+    items: &[T],
+    cursor: Option<&str>,
+    page_size: usize,
+) -> AdobeResult<(Vec<T>, Option<String>)> {
+    let start = match cursor {
+        None => 0,
+        Some(cursor) => cursor
+            .parse::<usize>()
+            .ok()
+            .filter(|&offset| offset <= items.len())
+            .ok_or_else(|| AdobeError::InvalidCursor(cursor.to_string()))?,
+    };
+    let end = start.saturating_add(page_size.max(1)).min(items.len());
+    let next_cursor = (end < items.len()).then(|| end.to_string());
+    Ok((items[start..end].to_vec(), next_cursor))
+}
+
 /// MCP error codes
 pub mod error_codes {
     pub const PARSE_ERROR: i32 = -32700;
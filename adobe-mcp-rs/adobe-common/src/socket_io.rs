@@ -1,29 +1,66 @@
 //! Minimal Socket.IO framing helpers (Engine.IO v4 + Socket.IO v4)
 
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine as _;
 use serde_json::Value;
 
+use crate::error::{AdobeError, AdobeResult};
+
+/// Engine.IO protocol revision spoken (the `EIO` query parameter)
+pub const ENGINE_IO_PROTOCOL: u8 = 4;
+/// Socket.IO protocol revision spoken by Socket.IO v3 and v4 libraries
+pub const SOCKET_IO_PROTOCOL: u8 = 5;
+
 pub const ENGINE_OPEN_PREFIX: &str = "0";
 pub const ENGINE_PING: &str = "2";
 pub const ENGINE_PONG: &str = "3";
 pub const SOCKET_IO_CONNECT: &str = "40";
 pub const SOCKET_IO_DISCONNECT: &str = "41";
 pub const SOCKET_IO_EVENT_PREFIX: &str = "42";
+/// Socket.IO BINARY_EVENT packet inside an Engine.IO message
+pub const SOCKET_IO_BINARY_EVENT_PREFIX: &str = "45";
+/// Base64-encoded Engine.IO message carrying one binary attachment
+pub const BINARY_ATTACHMENT_PREFIX: &str = "b4";
+/// Engine.IO v4 record separator between packets batched into one payload
+pub const RECORD_SEPARATOR: char = '\x1e';
+
+/// Split a payload into its Engine.IO packets, skipping empty records
+///
+/// JSON escapes control characters, so the separator never occurs inside a packet.
+pub fn split_payload(payload: &str) -> Vec<&str> {
+    payload
+        .split(RECORD_SEPARATOR)
+        .filter(|packet| !packet.is_empty())
+        .collect()
+}
 
 pub fn encode_event(event: &str, data: Value) -> String {
     format!("{}{}", SOCKET_IO_EVENT_PREFIX, serde_json::json!([event, data]))
 }
 
+/// Decode an event packet into its name and the first argument
+///
+/// Events emitted without arguments yield `None`; use [`decode_event_args`]
+/// to receive those or to keep every argument of a multi-argument emit.
 pub fn decode_event(message: &str) -> Option<(String, Value)> {
+    let (event, args) = decode_event_args(message)?;
+    let data = args.into_iter().next()?;
+    Some((event, data))
+}
+
+/// Decode an event packet into its name and all arguments after it
+pub fn decode_event_args(message: &str) -> Option<(String, Vec<Value>)> {
     if !message.starts_with(SOCKET_IO_EVENT_PREFIX) {
         return None;
     }
 
     let json_start = message.find('[')?;
     let json_str = &message[json_start..];
-    if let Ok(Value::Array(arr)) = serde_json::from_str::<Value>(json_str) {
-        if arr.len() >= 2 {
-            if let Some(event) = arr[0].as_str() {
-                return Some((event.to_string(), arr[1].clone()));
+    if let Ok(Value::Array(mut arr)) = serde_json::from_str::<Value>(json_str) {
+        if let Some(Value::String(_)) = arr.first() {
+            let args = arr.split_off(1);
+            if let Some(Value::String(event)) = arr.pop() {
+                return Some((event, args));
             }
         }
     }
@@ -31,10 +68,89 @@ pub fn decode_event(message: &str) -> Option<(String, Value)> {
     None
 }
 
+/// JSON stand-in for the `num`th binary attachment of an event
+pub fn binary_placeholder(num: usize) -> Value {
+    serde_json::json!({ "_placeholder": true, "num": num })
+}
+
+/// Encode an event whose `data` references `attachments` through [`binary_placeholder`]
+///
+/// Returns the `45<N>-[...]` header followed by one `b4<base64>` record per
+/// attachment, to be sent in order as separate text frames (or joined with
+/// [`RECORD_SEPARATOR`]).
+///
+/// Limitations: attachments always travel base64-encoded in text frames, never
+/// as binary WebSocket frames, so they cost a third more bytes and the peer
+/// must accept the base64 form. Namespaces and acknowledgement ids are not
+/// supported, and placeholders are not checked against `attachments`.
+pub fn encode_event_binary(event: &str, data: Value, attachments: Vec<Vec<u8>>) -> Vec<String> {
+    let mut packets = Vec::with_capacity(attachments.len() + 1);
+    packets.push(format!(
+        "{}{}-{}",
+        SOCKET_IO_BINARY_EVENT_PREFIX,
+        attachments.len(),
+        serde_json::json!([event, data])
+    ));
+    packets.extend(
+        attachments
+            .iter()
+            .map(|bytes| format!("{}{}", BINARY_ATTACHMENT_PREFIX, BASE64.encode(bytes))),
+    );
+    packets
+}
+
+/// A binary event with its attachments collected, placeholders left in `data`
+#[derive(Debug, Clone, PartialEq)]
+pub struct BinaryEvent {
+    pub event: String,
+    pub data: Value,
+    pub attachments: Vec<Vec<u8>>,
+}
+
+/// Decode a `45<N>-[...]` header into its event, data and attachment count
+pub fn decode_binary_header(message: &str) -> Option<(String, Value, usize)> {
+    let rest = message.strip_prefix(SOCKET_IO_BINARY_EVENT_PREFIX)?;
+    let (count, json_str) = rest.split_once('-')?;
+    let count = count.parse::<usize>().ok()?;
+    let (event, data) = decode_event(&format!("{}{}", SOCKET_IO_EVENT_PREFIX, json_str))?;
+    Some((event, data, count))
+}
+
+/// Decode one `b4<base64>` attachment record
+pub fn decode_binary_attachment(message: &str) -> Option<Vec<u8>> {
+    let encoded = message.strip_prefix(BINARY_ATTACHMENT_PREFIX)?;
+    BASE64.decode(encoded).ok()
+}
+
+/// Decode a header and exactly the attachment records it announces
+pub fn decode_event_binary(packets: &[&str]) -> Option<BinaryEvent> {
+    let (header, records) = packets.split_first()?;
+    let (event, data, count) = decode_binary_header(header)?;
+    if records.len() != count {
+        return None;
+    }
+
+    let attachments = records
+        .iter()
+        .map(|record| decode_binary_attachment(record))
+        .collect::<Option<Vec<_>>>()?;
+    Some(BinaryEvent {
+        event,
+        data,
+        attachments,
+    })
+}
+
 pub fn is_engine_open(message: &str) -> bool {
     message.starts_with(ENGINE_OPEN_PREFIX)
 }
 
+/// Session id the server assigned in an Engine.IO open packet (`0{"sid":...}`)
+pub fn engine_open_sid(message: &str) -> Option<String> {
+    let handshake: Value = serde_json::from_str(message.strip_prefix(ENGINE_OPEN_PREFIX)?).ok()?;
+    handshake.get("sid")?.as_str().map(str::to_string)
+}
+
 pub fn is_connect(message: &str) -> bool {
     message == SOCKET_IO_CONNECT || message.starts_with("40/")
 }
@@ -43,6 +159,44 @@ pub fn is_disconnect(message: &str) -> bool {
     message == SOCKET_IO_DISCONNECT || message.starts_with("41")
 }
 
+/// Check that `url` is a `ws://` or `wss://` URL with a host, so a typo
+/// fails up front rather than deep inside the WebSocket handshake
+pub fn validate_proxy_url(url: &str) -> AdobeResult<()> {
+    let invalid = |reason: &str| Err(AdobeError::InvalidProxyUrl(format!("{} ({})", url, reason)));
+
+    let Some(rest) = url.strip_prefix("ws://").or_else(|| url.strip_prefix("wss://")) else {
+        return invalid("scheme must be ws:// or wss://");
+    };
+    let authority = rest.split(['/', '?', '#']).next().unwrap_or_default();
+    let host_port = authority.rsplit_once('@').map_or(authority, |(_, host_port)| host_port);
+
+    let (host, port) = match host_port.strip_prefix('[') {
+        Some(bracketed) => match bracketed.split_once(']') {
+            Some((host, "")) => (host, None),
+            Some((host, after)) => match after.strip_prefix(':') {
+                Some(port) => (host, Some(port)),
+                None => return invalid("unexpected text after IPv6 address"),
+            },
+            None => return invalid("unclosed IPv6 address"),
+        },
+        None => match host_port.rsplit_once(':') {
+            Some((host, port)) => (host, Some(port)),
+            None => (host_port, None),
+        },
+    };
+
+    if host.is_empty() {
+        return invalid("missing host");
+    }
+    if host.chars().any(|c| c.is_whitespace() || c.is_control()) {
+        return invalid("host contains whitespace");
+    }
+    if port.is_some_and(|port| port.parse::<u16>().is_err()) {
+        return invalid("port must be a number from 0 to 65535");
+    }
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -65,6 +219,9 @@ mod tests {
     #[test]
     fn test_engine_helpers() {
         assert!(is_engine_open("0{\"sid\":\"abc\"}"));
+        assert_eq!(engine_open_sid("0{\"sid\":\"abc\",\"upgrades\":[]}").as_deref(), Some("abc"));
+        assert_eq!(engine_open_sid("0{}"), None);
+        assert_eq!(engine_open_sid("40"), None);
         assert!(is_connect("40"));
         assert!(is_disconnect("41"));
     }
@@ -76,4 +233,101 @@ mod tests {
         assert_eq!(decoded.0, "command_packet");
         assert_eq!(decoded.1, serde_json::json!({ "a": 1 }));
     }
+
+    #[test]
+    fn test_validate_proxy_url() {
+        for url in [
+            "ws://localhost:3001",
+            "wss://proxy.example.com",
+            "ws://127.0.0.1:3001/socket.io/?EIO=4",
+            "ws://[::1]:3001",
+            "wss://user@proxy.local:8443/path",
+        ] {
+            assert!(validate_proxy_url(url).is_ok(), "{:?} should be accepted", url);
+        }
+
+        for url in [
+            "",
+            "http://localhost:3001",
+            "localhost:3001",
+            "ws://",
+            "wss:///path",
+            "ws://:3001",
+            "ws://local host",
+            "ws://localhost:port",
+            "ws://localhost:99999",
+            "ws://[::1",
+            "not a url",
+        ] {
+            let err = validate_proxy_url(url).unwrap_err();
+            assert!(matches!(err, AdobeError::InvalidProxyUrl(_)), "{:?}", url);
+        }
+        assert_eq!(
+            validate_proxy_url("http://localhost:3001").unwrap_err().to_string(),
+            "Invalid proxy URL: http://localhost:3001 (scheme must be ws:// or wss://)"
+        );
+    }
+
+    #[test]
+    fn test_split_payload() {
+        let event = encode_event("command_packet", serde_json::json!({ "a": "x\u{1e}y" }));
+        let frame = format!("{}\x1e{}", ENGINE_PING, event);
+        let packets = split_payload(&frame);
+        assert_eq!(packets, vec![ENGINE_PING, event.as_str()]);
+        assert_eq!(decode_event(packets[1]).unwrap().1["a"], "x\u{1e}y");
+
+        assert_eq!(split_payload("42[\"a\",1]"), vec!["42[\"a\",1]"]);
+        assert!(split_payload("").is_empty());
+        assert_eq!(split_payload("2\x1e\x1e3"), vec!["2", "3"]);
+    }
+
+    #[test]
+    fn test_binary_event_roundtrip() {
+        let image = vec![0x89, b'P', b'N', b'G', 0x00, 0xff];
+        let data = serde_json::json!({ "format": "png", "image": binary_placeholder(0) });
+        let packets = encode_event_binary("generated_image", data.clone(), vec![image.clone()]);
+
+        assert_eq!(packets.len(), 2);
+        assert!(packets[0].starts_with("451-[\"generated_image\""));
+        assert!(packets[1].starts_with(BINARY_ATTACHMENT_PREFIX));
+
+        let refs: Vec<&str> = packets.iter().map(String::as_str).collect();
+        let decoded = decode_event_binary(&refs).unwrap();
+        assert_eq!(decoded.event, "generated_image");
+        assert_eq!(decoded.data, data);
+        assert_eq!(decoded.attachments, vec![image]);
+
+        // Joined into one frame, the packets split back apart
+        let frame = packets.join(&RECORD_SEPARATOR.to_string());
+        assert_eq!(decode_event_binary(&split_payload(&frame)).unwrap().event, "generated_image");
+    }
+
+    #[test]
+    fn test_binary_event_rejects_missing_attachment() {
+        let packets = encode_event_binary("e", binary_placeholder(0), vec![vec![1, 2, 3]]);
+        assert!(decode_event_binary(&[packets[0].as_str()]).is_none());
+        assert!(decode_binary_attachment("b4not base64!").is_none());
+        assert!(decode_event("451-[\"e\",{}]").is_none());
+    }
+
+    #[test]
+    fn test_decode_event_args() {
+        let (event, args) = decode_event_args("42[\"ping\"]").unwrap();
+        assert_eq!(event, "ping");
+        assert!(args.is_empty());
+        assert!(decode_event("42[\"ping\"]").is_none());
+
+        let (event, args) = decode_event_args("42[\"one\",{\"a\":1}]").unwrap();
+        assert_eq!(event, "one");
+        assert_eq!(args, vec![serde_json::json!({ "a": 1 })]);
+
+        let msg = "42/uxp,[\"two\",{\"a\":1},[2,3]]";
+        let (event, args) = decode_event_args(msg).unwrap();
+        assert_eq!(event, "two");
+        assert_eq!(args, vec![serde_json::json!({ "a": 1 }), serde_json::json!([2, 3])]);
+        assert_eq!(decode_event(msg).unwrap().1, serde_json::json!({ "a": 1 }));
+
+        assert!(decode_event_args("42[1,2]").is_none());
+        assert!(decode_event_args("2").is_none());
+    }
 }
@@ -0,0 +1,221 @@
+//! Message framing for the MCP servers' stdio transport
+//!
+//! `Line` is the MCP stdio transport: one JSON message per line. `Lsp` frames
+//! each message the way the Language Server Protocol does, with a
+//! `Content-Length: N` header, a blank line, and then N bytes of JSON, for
+//! clients that pretty-print messages across lines.
+
+use std::io::{self, Write};
+use std::str::FromStr;
+use std::sync::OnceLock;
+
+use tokio::io::{AsyncBufRead, AsyncBufReadExt, AsyncReadExt};
+
+use crate::error::AdobeError;
+
+/// Largest `Content-Length` accepted, so a bad header can't force a huge allocation
+pub const MAX_LSP_MESSAGE_BYTES: usize = 16 * 1024 * 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Framing {
+    #[default]
+    Line,
+    Lsp,
+}
+
+impl FromStr for Framing {
+    type Err = AdobeError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "line" => Ok(Self::Line),
+            "lsp" => Ok(Self::Lsp),
+            _ => Err(AdobeError::UnknownFraming(s.to_string())),
+        }
+    }
+}
+
+impl Framing {
+    /// Next message from `reader`, or `None` at end of input
+    ///
+    /// Blank lines between messages are skipped in both modes. In `Lsp` mode
+    /// headers other than `Content-Length` (such as `Content-Type`) are ignored.
+    pub async fn read_message<R>(self, reader: &mut R) -> io::Result<Option<String>>
+    where
+        R: AsyncBufRead + Unpin,
+    {
+        match self {
+            Self::Line => read_line_message(reader).await,
+            Self::Lsp => read_lsp_message(reader).await,
+        }
+    }
+
+    /// `message` with the delimiter or header this framing puts around it
+    pub fn frame(self, message: &str) -> String {
+        match self {
+            Self::Line => format!("{}\n", message),
+            Self::Lsp => format!("Content-Length: {}\r\n\r\n{}", message.len(), message),
+        }
+    }
+}
+
+async fn read_line_message<R: AsyncBufRead + Unpin>(reader: &mut R) -> io::Result<Option<String>> {
+    let mut line = String::new();
+    loop {
+        line.clear();
+        if reader.read_line(&mut line).await? == 0 {
+            return Ok(None);
+        }
+        let message = line.trim();
+        if !message.is_empty() {
+            return Ok(Some(message.to_string()));
+        }
+    }
+}
+
+async fn read_lsp_message<R: AsyncBufRead + Unpin>(reader: &mut R) -> io::Result<Option<String>> {
+    let mut content_length = None;
+    let mut in_headers = false;
+    let mut header = String::new();
+    loop {
+        header.clear();
+        if reader.read_line(&mut header).await? == 0 {
+            return if in_headers {
+                Err(io::Error::new(
+                    io::ErrorKind::UnexpectedEof,
+                    "input ended inside message headers",
+                ))
+            } else {
+                Ok(None)
+            };
+        }
+
+        let header = header.trim_end_matches(['\r', '\n']);
+        if header.is_empty() {
+            if in_headers {
+                break;
+            }
+            continue;
+        }
+        in_headers = true;
+
+        if let Some((name, value)) = header.split_once(':') {
+            if name.trim().eq_ignore_ascii_case("content-length") {
+                let length = value.trim().parse::<usize>().map_err(|_| {
+                    invalid_data(format!("invalid Content-Length: {}", value.trim()))
+                })?;
+                content_length = Some(length);
+            }
+        }
+    }
+
+    let length =
+        content_length.ok_or_else(|| invalid_data("missing Content-Length header".to_string()))?;
+    if length > MAX_LSP_MESSAGE_BYTES {
+        return Err(invalid_data(format!(
+            "Content-Length {} exceeds {} bytes",
+            length, MAX_LSP_MESSAGE_BYTES
+        )));
+    }
+
+    let mut body = vec![0; length];
+    reader.read_exact(&mut body).await?;
+    String::from_utf8(body)
+        .map(Some)
+        .map_err(|e| invalid_data(e.to_string()))
+}
+
+fn invalid_data(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+static OUTPUT_FRAMING: OnceLock<Framing> = OnceLock::new();
+
+/// Framing [`write_message`] uses; line framing until set. Only the first
+/// call takes effect.
+pub fn set_output_framing(framing: Framing) {
+    let _ = OUTPUT_FRAMING.set(framing);
+}
+
+/// Write one message to stdout in the configured framing
+///
+/// Responses and notifications both go through here, each written and
+/// flushed under one stdout lock so they never interleave.
+pub fn write_message(message: &str) {
+    let framing = OUTPUT_FRAMING.get().copied().unwrap_or_default();
+    let mut stdout = io::stdout().lock();
+    if let Err(e) = stdout
+        .write_all(framing.frame(message).as_bytes())
+        .and_then(|()| stdout.flush())
+    {
+        eprintln!("Failed to write to stdout: {}", e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn read_all(framing: Framing, input: &str) -> Vec<String> {
+        let mut reader = tokio::io::BufReader::new(input.as_bytes());
+        let mut messages = Vec::new();
+        while let Some(message) = framing.read_message(&mut reader).await.unwrap() {
+            messages.push(message);
+        }
+        messages
+    }
+
+    #[tokio::test]
+    async fn test_line_framing_skips_blank_lines() {
+        let messages = read_all(Framing::Line, "{\"id\":1}\n\n  \n{\"id\":2}\r\n").await;
+        assert_eq!(messages, vec![r#"{"id":1}"#, r#"{"id":2}"#]);
+        assert_eq!(Framing::Line.frame("{}"), "{}\n");
+    }
+
+    #[tokio::test]
+    async fn test_lsp_framing_reads_multi_line_bodies() {
+        let first = "{\n  \"id\": 1\n}";
+        let second = r#"{"text":"é"}"#;
+        let input = format!(
+            "Content-Length: {}\r\ncontent-type: application/vscode-jsonrpc; charset=utf-8\r\n\r\n{}\r\n{}",
+            first.len(),
+            first,
+            Framing::Lsp.frame(second)
+        );
+
+        let messages = read_all(Framing::Lsp, &input).await;
+        assert_eq!(messages, vec![first.to_string(), second.to_string()]);
+        // Length counts bytes, not characters
+        assert!(Framing::Lsp
+            .frame(second)
+            .starts_with("Content-Length: 13\r\n\r\n"));
+    }
+
+    #[tokio::test]
+    async fn test_lsp_framing_rejects_bad_headers() {
+        for input in [
+            "Content-Type: x\r\n\r\n{}",
+            "Content-Length: abc\r\n\r\n{}",
+            "Content-Length: 99999999999\r\n\r\n{}",
+            "Content-Length: 10\r\n",
+            "Content-Length: 10\r\n\r\n{}",
+        ] {
+            let mut reader = tokio::io::BufReader::new(input.as_bytes());
+            assert!(
+                Framing::Lsp.read_message(&mut reader).await.is_err(),
+                "{:?}",
+                input
+            );
+        }
+    }
+
+    #[test]
+    fn test_framing_from_str() {
+        assert_eq!("line".parse::<Framing>().unwrap(), Framing::Line);
+        assert_eq!("LSP".parse::<Framing>().unwrap(), Framing::Lsp);
+        assert!(matches!(
+            "http".parse::<Framing>(),
+            Err(AdobeError::UnknownFraming(_))
+        ));
+    }
+}
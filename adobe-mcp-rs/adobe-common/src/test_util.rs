@@ -0,0 +1,256 @@
+//! In-process stand-in for `adobe-proxy`, for end-to-end client tests
+//!
+//! [`MockProxy`] speaks just enough Socket.IO for the MCP clients: it sends
+//! the Engine.IO open and Socket.IO connect packets, answers pings, and
+//! replies to every `command_packet` with a successful `packet_response`.
+//! With [`MockProxy::ping_interval`] it also pings each client and drops
+//! those that don't pong in time, like the real proxy. Disconnect packets
+//! are counted so tests can check that a client shut down cleanly.
+
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use axum::extract::ws::{Message, WebSocket, WebSocketUpgrade};
+use axum::extract::State;
+use axum::response::Response;
+use axum::routing::get;
+use axum::Router;
+use futures_util::{SinkExt, StreamExt};
+use serde_json::{json, Value};
+use tokio::sync::watch;
+use tokio::task::JoinHandle;
+
+use crate::socket_io::{
+    decode_event, encode_event, split_payload, ENGINE_PING, ENGINE_PONG, SOCKET_IO_CONNECT, SOCKET_IO_DISCONNECT,
+};
+
+/// Canned responses by action and the shutdown signal, shared by every connection
+#[derive(Clone)]
+struct MockState {
+    responses: Arc<HashMap<String, Value>>,
+    ping_interval: Option<Duration>,
+    connections: Arc<AtomicUsize>,
+    disconnects: Arc<AtomicUsize>,
+    shutdown: watch::Receiver<bool>,
+}
+
+/// Mock proxy configuration
+///
+/// Actions without a canned response get `{"action": ..., "options": ...}`
+/// echoed back as their `response` payload.
+#[derive(Debug, Clone, Default)]
+pub struct MockProxy {
+    responses: HashMap<String, Value>,
+    ping_interval: Option<Duration>,
+}
+
+/// Stops a running [`MockProxy`]; dropping it stops the proxy too
+pub struct ShutdownHandle {
+    shutdown: watch::Sender<bool>,
+    connections: Arc<AtomicUsize>,
+    disconnects: Arc<AtomicUsize>,
+    task: Option<JoinHandle<()>>,
+}
+
+impl ShutdownHandle {
+    /// WebSocket sessions accepted so far
+    pub fn connections(&self) -> usize {
+        self.connections.load(Ordering::SeqCst)
+    }
+
+    /// Socket.IO disconnect packets received from clients so far
+    pub fn disconnects(&self) -> usize {
+        self.disconnects.load(Ordering::SeqCst)
+    }
+
+    /// Close every connection and wait for the server to stop
+    pub async fn shutdown(mut self) {
+        let _ = self.shutdown.send(true);
+        if let Some(task) = self.task.take() {
+            let _ = task.await;
+        }
+    }
+}
+
+impl Drop for ShutdownHandle {
+    fn drop(&mut self) {
+        let _ = self.shutdown.send(true);
+    }
+}
+
+impl MockProxy {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Reply to `action` with `response` as the `response` payload
+    pub fn respond(mut self, action: impl Into<String>, response: Value) -> Self {
+        self.responses.insert(action.into(), response);
+        self
+    }
+
+    /// Ping every client each `interval`, closing any that hasn't answered
+    /// the previous ping by the time the next one is due
+    pub fn ping_interval(mut self, interval: Duration) -> Self {
+        self.ping_interval = Some(interval);
+        self
+    }
+
+    /// Start a mock proxy that echoes every command
+    pub async fn start() -> (String, ShutdownHandle) {
+        Self::new().serve().await
+    }
+
+    /// Listen on an ephemeral local port and return its `ws://` URL
+    ///
+    /// # Panics
+    ///
+    /// Panics if no local port can be bound; this is a test utility.
+    pub async fn serve(self) -> (String, ShutdownHandle) {
+        self.serve_on(SocketAddr::from(([127, 0, 0, 1], 0))).await
+    }
+
+    /// Listen on `addr`, such as the address of a proxy that was just shut
+    /// down to simulate a restart
+    ///
+    /// # Panics
+    ///
+    /// Panics if `addr` can't be bound; this is a test utility.
+    pub async fn serve_on(self, addr: SocketAddr) -> (String, ShutdownHandle) {
+        let listener = tokio::net::TcpListener::bind(addr)
+            .await
+            .expect("bind mock proxy");
+        let addr = listener.local_addr().expect("mock proxy address");
+
+        let (shutdown, shutdown_rx) = watch::channel(false);
+        let connections = Arc::new(AtomicUsize::new(0));
+        let disconnects = Arc::new(AtomicUsize::new(0));
+        let state = MockState {
+            responses: Arc::new(self.responses),
+            ping_interval: self.ping_interval,
+            connections: connections.clone(),
+            disconnects: disconnects.clone(),
+            shutdown: shutdown_rx.clone(),
+        };
+        let app = Router::new()
+            .route("/", get(upgrade))
+            .route("/socket.io/", get(upgrade))
+            .with_state(state);
+
+        let task = tokio::spawn(async move {
+            let mut shutdown_rx = shutdown_rx;
+            let _ = axum::serve(listener, app)
+                .with_graceful_shutdown(async move {
+                    let _ = shutdown_rx.wait_for(|stop| *stop).await;
+                })
+                .await;
+        });
+
+        (
+            format!("ws://{}", addr),
+            ShutdownHandle {
+                shutdown,
+                connections,
+                disconnects,
+                task: Some(task),
+            },
+        )
+    }
+}
+
+async fn upgrade(ws: WebSocketUpgrade, State(state): State<MockState>) -> Response {
+    ws.on_upgrade(move |socket| session(socket, state))
+}
+
+async fn session(socket: WebSocket, mut state: MockState) {
+    let (mut sender, mut receiver) = socket.split();
+    let sid = uuid::Uuid::new_v4().to_string();
+    state.connections.fetch_add(1, Ordering::SeqCst);
+
+    let ping_ms = state.ping_interval.map_or(25_000, |interval| interval.as_millis() as u64);
+    let open = format!(
+        "0{}",
+        json!({"sid": sid, "upgrades": [], "pingInterval": ping_ms, "pingTimeout": ping_ms})
+    );
+    if sender.send(Message::Text(open)).await.is_err()
+        || sender.send(Message::Text(SOCKET_IO_CONNECT.to_string())).await.is_err()
+    {
+        return;
+    }
+
+    let mut pings = state.ping_interval.map(|interval| {
+        tokio::time::interval_at(tokio::time::Instant::now() + interval, interval)
+    });
+    let mut awaiting_pong = false;
+
+    loop {
+        let frame = tokio::select! {
+            msg = receiver.next() => match msg {
+                Some(Ok(Message::Text(frame))) => Some(frame),
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                Some(Ok(_)) => continue,
+            },
+            _ = next_ping(&mut pings) => None,
+            _ = state.shutdown.wait_for(|stop| *stop) => break,
+        };
+        let Some(frame) = frame else {
+            if awaiting_pong || sender.send(Message::Text(ENGINE_PING.to_string())).await.is_err() {
+                break;
+            }
+            awaiting_pong = true;
+            continue;
+        };
+
+        for text in split_payload(&frame) {
+            let reply = if text == ENGINE_PING {
+                ENGINE_PONG.to_string()
+            } else if text == ENGINE_PONG {
+                awaiting_pong = false;
+                continue;
+            } else if text == SOCKET_IO_DISCONNECT {
+                state.disconnects.fetch_add(1, Ordering::SeqCst);
+                continue;
+            } else {
+                match decode_event(text) {
+                    Some((event, data)) if event == "command_packet" => {
+                        encode_event("packet_response", command_response(&state.responses, &sid, &data))
+                    }
+                    _ => continue,
+                }
+            };
+            if sender.send(Message::Text(reply)).await.is_err() {
+                return;
+            }
+        }
+    }
+
+    let _ = sender.send(Message::Close(None)).await;
+}
+
+/// Wait for the next ping tick, or forever when pings are off
+async fn next_ping(pings: &mut Option<tokio::time::Interval>) {
+    match pings {
+        Some(interval) => {
+            interval.tick().await;
+        }
+        None => std::future::pending().await,
+    }
+}
+
+fn command_response(responses: &HashMap<String, Value>, sid: &str, packet: &Value) -> Value {
+    let command = &packet["command"];
+    let action = command["action"].as_str().unwrap_or_default();
+    let payload = responses
+        .get(action)
+        .cloned()
+        .unwrap_or_else(|| json!({"action": action, "options": command["options"]}));
+
+    let mut response = json!({"senderId": sid, "status": "SUCCESS", "response": payload});
+    if let Some(request_id) = command.get("requestId") {
+        response["requestId"] = request_id.clone();
+    }
+    response
+}
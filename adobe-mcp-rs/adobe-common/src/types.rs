@@ -1,6 +1,7 @@
 //! Core types for Adobe MCP
 
-use serde::{Deserialize, Serialize};
+use serde::ser::SerializeSeq;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
 /// Supported Adobe applications
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
@@ -11,6 +12,9 @@ pub enum AdobeApplication {
     InDesign,
     Premiere,
     Acrobat,
+    Lightroom,
+    AfterEffects,
+    XD,
 }
 
 impl AdobeApplication {
@@ -21,6 +25,9 @@ impl AdobeApplication {
             Self::InDesign => "indesign",
             Self::Premiere => "premiere",
             Self::Acrobat => "acrobat",
+            Self::Lightroom => "lightroom",
+            Self::AfterEffects => "aftereffects",
+            Self::XD => "xd",
         }
     }
 }
@@ -41,13 +48,20 @@ impl std::str::FromStr for AdobeApplication {
             "indesign" | "id" => Ok(Self::InDesign),
             "premiere" | "pr" => Ok(Self::Premiere),
             "acrobat" | "pdf" => Ok(Self::Acrobat),
+            "lightroom" | "lr" => Ok(Self::Lightroom),
+            "aftereffects" | "ae" => Ok(Self::AfterEffects),
+            "xd" => Ok(Self::XD),
             _ => Err(crate::error::AdobeError::UnknownApplication(s.to_string())),
         }
     }
 }
 
 /// RGB Color
-#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default)]
+///
+/// Serializes as a `{red, green, blue}` object; deserializes from either that
+/// object or a hex string such as `"#ff8800"`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(try_from = "RgbColorRepr")]
 pub struct RgbColor {
     pub red: u8,
     pub green: u8,
@@ -66,10 +80,296 @@ impl RgbColor {
     pub fn white() -> Self {
         Self::new(255, 255, 255)
     }
+
+    /// Parse `#RRGGBB`, `RRGGBB`, `#RGB` or `RGB`
+    pub fn from_hex(hex: &str) -> Result<Self, crate::error::AdobeError> {
+        let invalid = || {
+            crate::error::AdobeError::InvalidColor(format!(
+                "'{}' is not a #RRGGBB or #RGB hex color",
+                hex
+            ))
+        };
+        let digits = hex.trim().strip_prefix('#').unwrap_or(hex.trim());
+        if !digits.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(invalid());
+        }
+
+        let channel = |raw: &str| u8::from_str_radix(raw, 16).map_err(|_| invalid());
+        match digits.len() {
+            6 => Ok(Self::new(
+                channel(&digits[0..2])?,
+                channel(&digits[2..4])?,
+                channel(&digits[4..6])?,
+            )),
+            3 => {
+                let short = |i: usize| channel(&digits[i..i + 1]).map(|v| v * 17);
+                Ok(Self::new(short(0)?, short(1)?, short(2)?))
+            }
+            _ => Err(invalid()),
+        }
+    }
+
+    /// Lowercase `#rrggbb`
+    pub fn to_hex(&self) -> String {
+        format!("#{:02x}{:02x}{:02x}", self.red, self.green, self.blue)
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum RgbColorRepr {
+    Hex(String),
+    Channels { red: u8, green: u8, blue: u8 },
+}
+
+impl TryFrom<RgbColorRepr> for RgbColor {
+    type Error = crate::error::AdobeError;
+
+    fn try_from(repr: RgbColorRepr) -> Result<Self, Self::Error> {
+        match repr {
+            RgbColorRepr::Hex(hex) => Self::from_hex(&hex),
+            RgbColorRepr::Channels { red, green, blue } => Ok(Self::new(red, green, blue)),
+        }
+    }
+}
+
+/// CMYK color with each ink coverage in 0.0-1.0
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, Default)]
+pub struct CmykColor {
+    pub c: f32,
+    pub m: f32,
+    pub y: f32,
+    pub k: f32,
+}
+
+impl CmykColor {
+    pub fn new(c: f32, m: f32, y: f32, k: f32) -> Self {
+        Self { c, m, y, k }
+    }
+
+    /// Naive device conversion without a color profile
+    pub fn to_rgb(&self) -> RgbColor {
+        let channel = |ink: f32| {
+            (255.0 * (1.0 - ink.clamp(0.0, 1.0)) * (1.0 - self.k.clamp(0.0, 1.0))).round() as u8
+        };
+        RgbColor::new(channel(self.c), channel(self.m), channel(self.y))
+    }
+}
+
+/// A color in any of the spaces Acrobat accepts
+///
+/// Serializes in Acrobat's color-array convention: `["RGB", r, g, b]`,
+/// `["CMYK", c, m, y, k]` or `["G", v]`, with components in 0.0-1.0.
+/// Deserializes from those arrays or from anything [`RgbColor`] accepts.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Color {
+    Rgb(RgbColor),
+    Cmyk(CmykColor),
+    Gray(f32),
+}
+
+impl Color {
+    pub fn to_rgb(&self) -> RgbColor {
+        match self {
+            Self::Rgb(rgb) => *rgb,
+            Self::Cmyk(cmyk) => cmyk.to_rgb(),
+            Self::Gray(v) => {
+                let level = (255.0 * v.clamp(0.0, 1.0)).round() as u8;
+                RgbColor::new(level, level, level)
+            }
+        }
+    }
+}
+
+impl From<RgbColor> for Color {
+    fn from(color: RgbColor) -> Self {
+        Self::Rgb(color)
+    }
+}
+
+impl From<CmykColor> for Color {
+    fn from(color: CmykColor) -> Self {
+        Self::Cmyk(color)
+    }
+}
+
+impl Serialize for Color {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let (space, components): (&str, Vec<f32>) = match self {
+            Self::Rgb(rgb) => (
+                "RGB",
+                [rgb.red, rgb.green, rgb.blue]
+                    .iter()
+                    .map(|c| f32::from(*c) / 255.0)
+                    .collect(),
+            ),
+            Self::Cmyk(cmyk) => ("CMYK", vec![cmyk.c, cmyk.m, cmyk.y, cmyk.k]),
+            Self::Gray(v) => ("G", vec![*v]),
+        };
+
+        let mut seq = serializer.serialize_seq(Some(components.len() + 1))?;
+        seq.serialize_element(space)?;
+        for component in components {
+            seq.serialize_element(&component)?;
+        }
+        seq.end()
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum ColorArrayItem {
+    Space(String),
+    Component(f32),
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum ColorRepr {
+    Array(Vec<ColorArrayItem>),
+    Rgb(RgbColor),
+}
+
+impl<'de> Deserialize<'de> for Color {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let items = match ColorRepr::deserialize(deserializer)? {
+            ColorRepr::Rgb(rgb) => return Ok(Self::Rgb(rgb)),
+            ColorRepr::Array(items) => items,
+        };
+
+        let invalid = |msg: &str| {
+            serde::de::Error::custom(crate::error::AdobeError::InvalidColor(msg.to_string()))
+        };
+        let (space, rest) = match items.split_first() {
+            Some((ColorArrayItem::Space(space), rest)) => (space.as_str(), rest),
+            _ => {
+                return Err(invalid(
+                    "color array must start with \"RGB\", \"CMYK\" or \"G\"",
+                ))
+            }
+        };
+        let components = rest
+            .iter()
+            .map(|item| match item {
+                ColorArrayItem::Component(v) if (0.0..=1.0).contains(v) => Ok(*v),
+                _ => Err(invalid("color components must be numbers in 0.0-1.0")),
+            })
+            .collect::<Result<Vec<f32>, _>>()?;
+
+        let to_byte = |v: f32| (v * 255.0).round() as u8;
+        match (space, components.as_slice()) {
+            ("RGB", [r, g, b]) => Ok(Self::Rgb(RgbColor::new(
+                to_byte(*r),
+                to_byte(*g),
+                to_byte(*b),
+            ))),
+            ("CMYK", [c, m, y, k]) => Ok(Self::Cmyk(CmykColor::new(*c, *m, *y, *k))),
+            ("G", [v]) => Ok(Self::Gray(*v)),
+            ("RGB" | "CMYK" | "G", _) => Err(invalid(&format!(
+                "wrong number of components for {}",
+                space
+            ))),
+            _ => Err(invalid(&format!("unknown color space '{}'", space))),
+        }
+    }
+}
+
+/// Page rotation in quarter turns; serializes as degrees
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+#[serde(into = "i64", try_from = "i64")]
+pub enum Rotation {
+    #[default]
+    Deg0,
+    Deg90,
+    Deg180,
+    Deg270,
+}
+
+impl Rotation {
+    pub fn from_degrees(degrees: i64) -> Option<Self> {
+        match degrees {
+            0 => Some(Self::Deg0),
+            90 => Some(Self::Deg90),
+            180 => Some(Self::Deg180),
+            270 => Some(Self::Deg270),
+            _ => None,
+        }
+    }
+
+    pub fn to_degrees(&self) -> i64 {
+        match self {
+            Self::Deg0 => 0,
+            Self::Deg90 => 90,
+            Self::Deg180 => 180,
+            Self::Deg270 => 270,
+        }
+    }
+}
+
+impl From<Rotation> for i64 {
+    fn from(rotation: Rotation) -> Self {
+        rotation.to_degrees()
+    }
+}
+
+impl TryFrom<i64> for Rotation {
+    type Error = crate::error::AdobeError;
+
+    fn try_from(degrees: i64) -> Result<Self, Self::Error> {
+        Self::from_degrees(degrees).ok_or(crate::error::AdobeError::InvalidRotation(degrees))
+    }
+}
+
+/// PDF page boundary boxes, as named by Acrobat's `getPageBox`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+pub enum PageBox {
+    #[default]
+    Crop,
+    Media,
+    Bleed,
+    Trim,
+    Art,
+}
+
+impl PageBox {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Crop => "Crop",
+            Self::Media => "Media",
+            Self::Bleed => "Bleed",
+            Self::Trim => "Trim",
+            Self::Art => "Art",
+        }
+    }
+}
+
+impl std::fmt::Display for PageBox {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl std::str::FromStr for PageBox {
+    type Err = crate::error::AdobeError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "crop" => Ok(Self::Crop),
+            "media" => Ok(Self::Media),
+            "bleed" => Ok(Self::Bleed),
+            "trim" => Ok(Self::Trim),
+            "art" => Ok(Self::Art),
+            _ => Err(crate::error::AdobeError::UnknownPageBox(s.to_string())),
+        }
+    }
 }
 
 /// Bounding box
-#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default)]
+///
+/// Coordinates grow rightwards and downwards, so `top <= bottom` and
+/// `left <= right`. The comparison helpers normalize boxes given the other
+/// way round (e.g. PDF rects with the origin at the bottom left) first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
 pub struct Bounds {
     pub top: i32,
     pub left: i32,
@@ -89,15 +389,92 @@ impl Bounds {
     pub fn height(&self) -> i32 {
         self.bottom - self.top
     }
+
+    /// The same box with `top <= bottom` and `left <= right`
+    pub fn normalized(&self) -> Self {
+        Self {
+            top: self.top.min(self.bottom),
+            left: self.left.min(self.right),
+            bottom: self.top.max(self.bottom),
+            right: self.left.max(self.right),
+        }
+    }
+
+    /// Whether `p` lies inside the box or on its edge
+    pub fn contains(&self, p: Point) -> bool {
+        let b = self.normalized();
+        (b.left..=b.right).contains(&p.x) && (b.top..=b.bottom).contains(&p.y)
+    }
+
+    /// Whether the boxes overlap by a non-zero area; boxes that only share
+    /// an edge don't intersect
+    pub fn intersects(&self, other: &Bounds) -> bool {
+        self.intersection(other).is_some()
+    }
+
+    /// The overlapping area, if any (see [`Bounds::intersects`])
+    pub fn intersection(&self, other: &Bounds) -> Option<Bounds> {
+        let (a, b) = (self.normalized(), other.normalized());
+        let overlap = Bounds::new(
+            a.top.max(b.top),
+            a.left.max(b.left),
+            a.bottom.min(b.bottom),
+            a.right.min(b.right),
+        );
+        (overlap.width() > 0 && overlap.height() > 0).then_some(overlap)
+    }
+
+    /// The smallest box containing both
+    pub fn union(&self, other: &Bounds) -> Bounds {
+        let (a, b) = (self.normalized(), other.normalized());
+        Bounds::new(
+            a.top.min(b.top),
+            a.left.min(b.left),
+            a.bottom.max(b.bottom),
+            a.right.max(b.right),
+        )
+    }
 }
 
 /// Point position
-#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
 pub struct Point {
     pub x: i32,
     pub y: i32,
 }
 
+impl Point {
+    pub fn new(x: i32, y: i32) -> Self {
+        Self { x, y }
+    }
+
+    /// This point moved by `dx` across and `dy` down
+    pub fn translate(&self, dx: i32, dy: i32) -> Self {
+        Self::new(self.x + dx, self.y + dy)
+    }
+
+    /// Straight-line distance to `other`
+    pub fn distance_to(&self, other: &Point) -> f64 {
+        f64::from(other.x - self.x).hypot(f64::from(other.y - self.y))
+    }
+}
+
+impl std::ops::Add for Point {
+    type Output = Point;
+
+    fn add(self, rhs: Point) -> Point {
+        self.translate(rhs.x, rhs.y)
+    }
+}
+
+impl std::ops::Sub for Point {
+    type Output = Point;
+
+    fn sub(self, rhs: Point) -> Point {
+        self.translate(-rhs.x, -rhs.y)
+    }
+}
+
 /// Document metadata
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DocumentInfo {
@@ -108,20 +485,55 @@ pub struct DocumentInfo {
     pub height: u32,
     pub page_count: Option<u32>,
     pub has_unsaved_changes: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub author: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub subject: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub keywords: Option<String>,
+    /// Application that created the original document
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub creator: Option<String>,
+    /// Application that wrote the PDF
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub producer: Option<String>,
+    /// Creation and last modification times, as ISO 8601 strings
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub created: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub modified: Option<String>,
 }
 
 /// Page size presets
-#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum PageSize {
     Letter,
     Legal,
     A4,
     A3,
+    A5,
+    B4,
+    B5,
+    Tabloid,
+    Executive,
     Custom,
 }
 
 impl PageSize {
+    /// Every named preset, excluding `Custom`
+    pub const PRESETS: [PageSize; 9] = [
+        Self::Letter,
+        Self::Legal,
+        Self::A4,
+        Self::A3,
+        Self::A5,
+        Self::B4,
+        Self::B5,
+        Self::Tabloid,
+        Self::Executive,
+    ];
+
     /// Returns (width, height) in points (1/72 inch)
     pub fn dimensions(&self) -> (f64, f64) {
         match self {
@@ -129,7 +541,55 @@ impl PageSize {
             Self::Legal => (612.0, 1008.0),   // 8.5 x 14 inches
             Self::A4 => (595.0, 842.0),       // 210 x 297 mm
             Self::A3 => (842.0, 1191.0),      // 297 x 420 mm
+            Self::A5 => (420.0, 595.0),       // 148 x 210 mm
+            Self::B4 => (709.0, 1001.0),      // 250 x 353 mm
+            Self::B5 => (499.0, 709.0),       // 176 x 250 mm
+            Self::Tabloid => (792.0, 1224.0), // 11 x 17 inches
+            Self::Executive => (522.0, 756.0), // 7.25 x 10.5 inches
             Self::Custom => (612.0, 792.0),   // Default to letter
         }
     }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Letter => "LETTER",
+            Self::Legal => "LEGAL",
+            Self::A4 => "A4",
+            Self::A3 => "A3",
+            Self::A5 => "A5",
+            Self::B4 => "B4",
+            Self::B5 => "B5",
+            Self::Tabloid => "TABLOID",
+            Self::Executive => "EXECUTIVE",
+            Self::Custom => "CUSTOM",
+        }
+    }
+
+    /// Find the preset whose dimensions match `width` x `height` (in either
+    /// orientation) to within `tolerance` points
+    pub fn from_dimensions(width: f64, height: f64, tolerance: f64) -> Option<PageSize> {
+        let close = |a: f64, b: f64| (a - b).abs() <= tolerance;
+        Self::PRESETS.into_iter().find(|preset| {
+            let (w, h) = preset.dimensions();
+            (close(width, w) && close(height, h)) || (close(width, h) && close(height, w))
+        })
+    }
+}
+
+impl std::fmt::Display for PageSize {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl std::str::FromStr for PageSize {
+    type Err = crate::error::AdobeError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::PRESETS
+            .into_iter()
+            .chain([Self::Custom])
+            .find(|preset| preset.as_str().eq_ignore_ascii_case(s.trim()))
+            .ok_or_else(|| crate::error::AdobeError::UnknownPageSize(s.to_string()))
+    }
 }
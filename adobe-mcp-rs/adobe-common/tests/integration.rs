@@ -43,6 +43,10 @@ fn test_full_response_flow() {
         response: Some(json!({"pageCount": 10, "title": "Test Doc"})),
         message: None,
         document: Some(json!({"path": "/test/doc.pdf"})),
+        request_id: None,
+        timestamp: None,
+        duration_ms: None,
+        code: None,
     };
 
     // 2. Serialize
@@ -234,6 +238,10 @@ fn test_failure_response_with_message() {
         response: None,
         message: Some("File not found: /nonexistent.pdf".to_string()),
         document: None,
+        request_id: None,
+        timestamp: None,
+        duration_ms: None,
+        code: None,
     };
 
     let json_str = serde_json::to_string(&response).unwrap();
@@ -331,6 +339,10 @@ fn test_large_response_data() {
         response: Some(json!({"items": large_array})),
         message: None,
         document: None,
+        request_id: None,
+        timestamp: None,
+        duration_ms: None,
+        code: None,
     };
 
     let json_str = serde_json::to_string(&response).unwrap();
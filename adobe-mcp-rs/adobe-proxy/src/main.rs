@@ -23,34 +23,50 @@
 use axum::{
     extract::{
         ws::{Message, WebSocket},
-        State, WebSocketUpgrade,
+        Query, Request, State, WebSocketUpgrade,
     },
-    response::{Json, Response},
+    http::{header, HeaderMap, HeaderValue, Method, StatusCode, Uri},
+    middleware::{self, Next},
+    response::{IntoResponse, Json, Response},
     routing::get,
     Router,
 };
-use clap::Parser;
+use axum_server::tls_rustls::RustlsConfig;
+use clap::parser::ValueSource;
+use clap::{ArgMatches, CommandFactory, FromArgMatches, Parser, ValueEnum};
 use dashmap::DashMap;
 use futures_util::{SinkExt, StreamExt};
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
 use std::{
-    collections::HashMap,
+    collections::{HashMap, VecDeque},
+    ffi::OsString,
     net::SocketAddr,
+    path::{Path, PathBuf},
+    fmt::Write as _,
     process::Command,
-    sync::Arc,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
     time::Instant,
 };
-use tokio::sync::broadcast;
+use tokio::sync::{broadcast, Notify};
 use tokio::time::{sleep, Duration};
-use tracing::{debug, error, info, warn};
+use tower_http::cors::CorsLayer;
+use tracing::{debug, error, info, warn, Instrument};
 use uuid::Uuid;
-use adobe_common::socket_io::{decode_event, encode_event, ENGINE_PING, ENGINE_PONG, is_connect, is_disconnect};
+use adobe_common::socket_io::{decode_event, encode_event, split_payload, ENGINE_IO_PROTOCOL, ENGINE_PING, ENGINE_PONG, SOCKET_IO_PROTOCOL, is_connect, is_disconnect};
+use adobe_common::{epoch_millis, NOT_CONNECTED_CODE, NOT_CONNECTED_PREFIX};
 
 #[derive(Parser, Debug)]
 #[command(name = "adobe-proxy")]
 #[command(about = "WebSocket proxy server for Adobe MCP applications", long_about = None)]
 struct Args {
+    /// TOML file with default settings; flags and environment variables override it
+    #[arg(long, env = "ADOBE_PROXY_CONFIG")]
+    config: Option<PathBuf>,
+
     /// Host to bind to
     #[arg(long, default_value = "127.0.0.1")]
     host: String,
@@ -66,17 +82,190 @@ struct Args {
     /// Auto-launch wait time in milliseconds before returning failure
     #[arg(long, env = "ADOBE_PROXY_AUTO_LAUNCH_TIMEOUT_MS", default_value_t = 20000)]
     auto_launch_timeout_ms: u64,
+
+    /// Milliseconds between checks for an auto-launched app's client; a
+    /// registration ends the wait immediately regardless
+    #[arg(long, env = "ADOBE_PROXY_AUTO_LAUNCH_POLL_MS", default_value_t = 500)]
+    auto_launch_poll_ms: u64,
+
+    /// PEM certificate chain; serves HTTPS/WSS when given together with --tls-key
+    #[arg(long, env = "ADOBE_PROXY_TLS_CERT")]
+    tls_cert: Option<PathBuf>,
+
+    /// PEM private key for --tls-cert
+    #[arg(long, env = "ADOBE_PROXY_TLS_KEY")]
+    tls_key: Option<PathBuf>,
+
+    /// Shared secret clients must present (`?token=` or `Authorization: Bearer`)
+    #[arg(long, env = "ADOBE_PROXY_AUTH_TOKEN")]
+    auth_token: Option<String>,
+
+    /// Commands buffered per application until a client registers (0 disables)
+    #[arg(long, env = "ADOBE_PROXY_PENDING_QUEUE_SIZE", default_value_t = 0)]
+    pending_queue_size: usize,
+
+    /// Engine.IO ping interval in milliseconds
+    #[arg(long, env = "ADOBE_PROXY_PING_INTERVAL_MS", default_value_t = 25000)]
+    ping_interval_ms: u64,
+
+    /// Milliseconds a client may take to answer a ping before it is evicted
+    #[arg(long, env = "ADOBE_PROXY_PING_TIMEOUT_MS", default_value_t = 20000)]
+    ping_timeout_ms: u64,
+
+    /// Commands each client may send per second (0 = unlimited)
+    #[arg(long, env = "ADOBE_PROXY_MAX_COMMANDS_PER_SEC", default_value_t = 0)]
+    max_commands_per_sec: u32,
+
+    /// JSON or TOML file mapping application names to executables for auto-launch
+    #[arg(long, env = "ADOBE_PROXY_LAUNCH_MAP")]
+    launch_map: Option<PathBuf>,
+
+    /// Origin allowed to read the HTTP endpoints from a browser (`*` for any)
+    #[arg(long, env = "ADOBE_PROXY_CORS_ORIGIN")]
+    cors_origin: Option<String>,
+
+    /// Applications that must have a registered client before `/ready` reports
+    /// ready (comma-separated); by default any one application client will do
+    #[arg(long, env = "ADOBE_PROXY_READY_APPLICATIONS", value_delimiter = ',')]
+    ready_applications: Vec<String>,
+
+    /// Largest WebSocket message a client may send, in bytes
+    #[arg(long, env = "ADOBE_PROXY_MAX_MESSAGE_BYTES", default_value_t = DEFAULT_MAX_MESSAGE_BYTES)]
+    max_message_bytes: usize,
+
+    /// Largest `command_packet` the proxy forwards, in bytes
+    #[arg(long, env = "ADOBE_PROXY_MAX_COMMAND_BYTES", default_value_t = DEFAULT_MAX_COMMAND_BYTES)]
+    max_command_bytes: usize,
+
+    /// Milliseconds a disconnected client's `resumeToken` stays valid (0 disables resumption)
+    #[arg(long, env = "ADOBE_PROXY_RESUME_TTL_MS", default_value_t = 30000)]
+    resume_ttl_ms: u64,
+
+    /// Log output: human-readable lines, or one JSON record per line
+    #[arg(long, env = "ADOBE_PROXY_LOG_FORMAT", value_enum, default_value_t = LogFormat::Pretty)]
+    log_format: LogFormat,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum LogFormat {
+    Pretty,
+    Json,
+}
+
+impl Args {
+    /// Parse `args`, then fill in anything not given on the command line or
+    /// in the environment from the `--config` file. Returns the file's
+    /// settings too, for the parts (like `[launch]`) that aren't flags.
+    fn load_from<I, T>(args: I) -> Result<(Self, Config), String>
+    where
+        I: IntoIterator<Item = T>,
+        T: Into<OsString> + Clone,
+    {
+        let matches = Self::command().get_matches_from(args);
+        let mut args = Self::from_arg_matches(&matches).map_err(|e| e.to_string())?;
+        let config = match &args.config {
+            Some(path) => load_config(path)?,
+            None => Config::default(),
+        };
+        args.apply_config(&config, &matches);
+        Ok((args, config))
+    }
+
+    /// Take each value from `config` unless `matches` shows it was set explicitly
+    fn apply_config(&mut self, config: &Config, matches: &ArgMatches) {
+        let explicit = |id: &str| {
+            matches
+                .value_source(id)
+                .is_some_and(|source| source != ValueSource::DefaultValue)
+        };
+        macro_rules! merge {
+            ($($field:ident),*) => {
+                $(
+                    if let Some(value) = &config.$field {
+                        if !explicit(stringify!($field)) {
+                            self.$field = value.clone().into();
+                        }
+                    }
+                )*
+            };
+        }
+        merge!(
+            host,
+            port,
+            auto_launch,
+            auto_launch_timeout_ms,
+            auto_launch_poll_ms,
+            auth_token,
+            pending_queue_size,
+            ping_interval_ms,
+            ping_timeout_ms,
+            max_commands_per_sec,
+            max_message_bytes,
+            max_command_bytes,
+            cors_origin,
+            ready_applications,
+            resume_ttl_ms,
+            log_format
+        );
+    }
+}
+
+/// Settings read from `--config`; every key is optional
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Config {
+    host: Option<String>,
+    port: Option<u16>,
+    auto_launch: Option<bool>,
+    auto_launch_timeout_ms: Option<u64>,
+    auto_launch_poll_ms: Option<u64>,
+    auth_token: Option<String>,
+    pending_queue_size: Option<usize>,
+    ping_interval_ms: Option<u64>,
+    ping_timeout_ms: Option<u64>,
+    max_commands_per_sec: Option<u32>,
+    max_message_bytes: Option<usize>,
+    max_command_bytes: Option<usize>,
+    cors_origin: Option<String>,
+    ready_applications: Option<Vec<String>>,
+    resume_ttl_ms: Option<u64>,
+    log_format: Option<LogFormat>,
+    /// Same shape as a TOML `--launch-map` file, which takes precedence
+    #[serde(default)]
+    launch: HashMap<String, LaunchTargets>,
+}
+
+fn parse_config(contents: &str) -> Result<Config, String> {
+    toml::from_str(contents).map_err(|e| e.to_string())
+}
+
+fn load_config(path: &Path) -> Result<Config, String> {
+    let contents = std::fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read config {}: {}", path.display(), e))?;
+    parse_config(&contents).map_err(|e| format!("Invalid config {}: {}", path.display(), e))
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 struct RegisterMessage {
     application: String,
+    #[serde(rename = "instanceId", default)]
+    instance_id: Option<String>,
+    #[serde(default)]
+    version: Option<String>,
+    #[serde(rename = "clientName", default)]
+    client_name: Option<String>,
+    /// Token from an earlier `registration_response`, to resume that session
+    #[serde(rename = "resumeToken", default)]
+    resume_token: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 struct CommandPacket {
     application: String,
     command: Value,
+    #[serde(rename = "targetInstance", default)]
+    target_instance: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -85,6 +274,9 @@ struct CommandPacketWithSender {
     sender_id: String,
     application: String,
     command: Value,
+    /// Deliver only to the client registered with this instance id
+    #[serde(rename = "targetInstance", default, skip_serializing_if = "Option::is_none")]
+    target_instance: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -98,76 +290,554 @@ struct RegistrationResponse {
     response_type: String,
     status: String,
     message: String,
+    #[serde(rename = "instanceId")]
+    instance_id: String,
+    #[serde(rename = "resumeToken", skip_serializing_if = "Option::is_none")]
+    resume_token: Option<String>,
+    /// Whether the `resumeToken` sent with `register` was accepted
+    resumed: bool,
+}
+
+/// What `register_client` assigned to a registering client
+#[derive(Debug, Clone, PartialEq)]
+struct Registration {
+    instance_id: String,
+    resume_token: Option<String>,
+    resumed: bool,
+}
+
+/// A registered client that a new connection may take over with its token
+#[derive(Debug, Clone)]
+struct ResumeSession {
+    /// Connection currently holding the session
+    client_id: String,
+    instance_id: String,
+    connected_at: Instant,
+    /// Set while no connection holds the session; the token expires
+    /// `resume_ttl` after this
+    disconnected_at: Option<Instant>,
+}
+
+/// A client registered for an application, addressable by its instance id
+#[derive(Debug, Clone, PartialEq)]
+struct ApplicationClient {
+    client_id: String,
+    instance_id: String,
 }
 
 #[derive(Debug, Clone)]
 struct ClientInfo {
-    #[allow(dead_code)] // Reserved for future use (metrics, logging)
     id: String,
     application: Option<String>,
+    /// Plugin/bridge version and name reported at registration
+    version: Option<String>,
+    client_name: Option<String>,
+    resume_token: Option<String>,
     tx: broadcast::Sender<SocketIoMessage>,
+    connected_at: Instant,
+    last_activity: Instant,
+    rate_limit: TokenBucket,
+}
+
+/// Token bucket allowing `rate` commands per second with bursts of up to `rate`
+#[derive(Debug, Clone)]
+struct TokenBucket {
+    rate: f64,
+    tokens: f64,
+    last_refill: Instant,
+}
+
+impl TokenBucket {
+    fn new(rate: u32) -> Self {
+        Self {
+            rate: f64::from(rate),
+            tokens: f64::from(rate),
+            last_refill: Instant::now(),
+        }
+    }
+
+    /// Take one token; always succeeds when the rate is 0 (unlimited)
+    fn try_acquire(&mut self) -> bool {
+        if self.rate == 0.0 {
+            return true;
+        }
+
+        let now = Instant::now();
+        let elapsed = now.duration_since(self.last_refill).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.rate).min(self.rate);
+        self.last_refill = now;
+
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            true
+        } else {
+            false
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
 enum SocketIoMessage {
     Text(String),
-    #[allow(dead_code)] // Reserved for graceful shutdown implementation
     Close,
 }
 
+#[derive(Debug, Default)]
+struct ApplicationCounters {
+    commands: AtomicU64,
+    failures: AtomicU64,
+}
+
+/// Routing counters exposed at `/metrics`
+#[derive(Debug, Default)]
+struct Metrics {
+    applications: DashMap<String, ApplicationCounters>,
+    responses: AtomicU64,
+}
+
+impl Metrics {
+    fn application(&self, application: &str) -> dashmap::mapref::one::Ref<'_, String, ApplicationCounters> {
+        if let Some(counters) = self.applications.get(application) {
+            return counters;
+        }
+        // Only the first command for an application allocates
+        self.applications
+            .entry(application.to_string())
+            .or_default()
+            .downgrade()
+    }
+
+    fn record_command(&self, application: &str) {
+        self.application(application)
+            .commands
+            .fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn record_failure(&self, application: &str) {
+        self.application(application)
+            .failures
+            .fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn record_response(&self) {
+        self.responses.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Render in the Prometheus text exposition format
+    fn render(&self) -> String {
+        let mut applications: Vec<(String, u64, u64)> = self
+            .applications
+            .iter()
+            .map(|entry| {
+                (
+                    entry.key().clone(),
+                    entry.commands.load(Ordering::Relaxed),
+                    entry.failures.load(Ordering::Relaxed),
+                )
+            })
+            .collect();
+        applications.sort();
+
+        let mut out = String::new();
+        out.push_str("# HELP adobe_proxy_commands_total Commands received for each application.\n");
+        out.push_str("# TYPE adobe_proxy_commands_total counter\n");
+        for (application, commands, _) in &applications {
+            let _ = writeln!(
+                out,
+                "adobe_proxy_commands_total{{application=\"{}\"}} {}",
+                escape_label(application),
+                commands
+            );
+        }
+        out.push_str("# HELP adobe_proxy_command_failures_total Commands that could not be delivered to any client.\n");
+        out.push_str("# TYPE adobe_proxy_command_failures_total counter\n");
+        for (application, _, failures) in &applications {
+            let _ = writeln!(
+                out,
+                "adobe_proxy_command_failures_total{{application=\"{}\"}} {}",
+                escape_label(application),
+                failures
+            );
+        }
+        out.push_str("# HELP adobe_proxy_responses_total Command responses forwarded back to MCP servers.\n");
+        out.push_str("# TYPE adobe_proxy_responses_total counter\n");
+        let _ = writeln!(
+            out,
+            "adobe_proxy_responses_total {}",
+            self.responses.load(Ordering::Relaxed)
+        );
+        out
+    }
+}
+
+fn escape_label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
 #[derive(Clone)]
 struct AppState {
     clients: Arc<DashMap<String, ClientInfo>>,
-    application_clients: Arc<DashMap<String, Vec<String>>>,
+    application_clients: Arc<DashMap<String, Vec<ApplicationClient>>>,
     start_time: Instant,
+    port: u16,
     auto_launch: bool,
     auto_launch_timeout: Duration,
+    auto_launch_poll: Duration,
+    registration_notify: Arc<DashMap<String, Arc<Notify>>>,
+    auth_token: Option<Arc<str>>,
+    pending: Arc<DashMap<String, VecDeque<CommandPacketWithSender>>>,
+    pending_queue_size: usize,
+    ping_interval: Duration,
+    ping_timeout: Duration,
+    metrics: Arc<Metrics>,
+    max_commands_per_sec: u32,
+    launch_map: Arc<LaunchMap>,
+    max_message_bytes: usize,
+    max_command_bytes: usize,
+    cors_origin: Option<HeaderValue>,
+    ready_applications: Arc<[String]>,
+    resume_tokens: Arc<DashMap<String, ResumeSession>>,
+    resume_ttl: Duration,
 }
 
 impl AppState {
-    fn new(auto_launch: bool, auto_launch_timeout: Duration) -> Self {
+    fn new(port: u16, auto_launch: bool, auto_launch_timeout: Duration) -> Self {
         Self {
             clients: Arc::new(DashMap::new()),
             application_clients: Arc::new(DashMap::new()),
             start_time: Instant::now(),
+            port,
             auto_launch,
             auto_launch_timeout,
+            auto_launch_poll: DEFAULT_AUTO_LAUNCH_POLL,
+            registration_notify: Arc::new(DashMap::new()),
+            auth_token: None,
+            pending: Arc::new(DashMap::new()),
+            pending_queue_size: 0,
+            ping_interval: DEFAULT_PING_INTERVAL,
+            ping_timeout: DEFAULT_PING_TIMEOUT,
+            metrics: Arc::new(Metrics::default()),
+            max_commands_per_sec: 0,
+            launch_map: Arc::new(LaunchMap::new()),
+            max_message_bytes: DEFAULT_MAX_MESSAGE_BYTES,
+            max_command_bytes: DEFAULT_MAX_COMMAND_BYTES,
+            cors_origin: None,
+            ready_applications: Arc::from([]),
+            resume_tokens: Arc::new(DashMap::new()),
+            resume_ttl: DEFAULT_RESUME_TTL,
+        }
+    }
+
+    /// Require clients to present this token on every HTTP and WebSocket request
+    fn with_auth_token(mut self, token: Option<String>) -> Self {
+        self.auth_token = token.filter(|t| !t.is_empty()).map(Arc::from);
+        self
+    }
+
+    /// Re-check for an auto-launched application's client every `poll` while
+    /// waiting (at least every 10ms)
+    fn with_auto_launch_poll(mut self, poll: Duration) -> Self {
+        self.auto_launch_poll = poll.max(Duration::from_millis(10));
+        self
+    }
+
+    /// Buffer up to `size` undeliverable commands per application
+    fn with_pending_queue_size(mut self, size: usize) -> Self {
+        self.pending_queue_size = size;
+        self
+    }
+
+    /// Override the Engine.IO ping interval/timeout advertised to clients
+    fn with_heartbeat(mut self, ping_interval: Duration, ping_timeout: Duration) -> Self {
+        self.ping_interval = ping_interval;
+        self.ping_timeout = ping_timeout;
+        self
+    }
+
+    /// Limit each client to `per_sec` commands per second (0 = unlimited)
+    fn with_rate_limit(mut self, per_sec: u32) -> Self {
+        self.max_commands_per_sec = per_sec;
+        self
+    }
+
+    /// Close connections that send a message larger than `bytes`
+    fn with_max_message_bytes(mut self, bytes: usize) -> Self {
+        self.max_message_bytes = bytes;
+        self
+    }
+
+    /// Answer `command_packet`s larger than `bytes` with a `FAILURE` instead
+    /// of forwarding them; only matters below `max_message_bytes`
+    fn with_max_command_bytes(mut self, bytes: usize) -> Self {
+        self.max_command_bytes = bytes;
+        self
+    }
+
+    /// Send CORS headers allowing `origin` on the HTTP endpoints
+    fn with_cors_origin(mut self, origin: Option<HeaderValue>) -> Self {
+        self.cors_origin = origin;
+        self
+    }
+
+    /// Hold `/ready` at 503 until each of `applications` has a client
+    /// (until any application client registers when empty)
+    fn with_ready_applications(mut self, applications: Vec<String>) -> Self {
+        self.ready_applications = applications
+            .into_iter()
+            .map(|app| app.trim().to_lowercase())
+            .filter(|app| !app.is_empty())
+            .collect();
+        self
+    }
+
+    /// Keep a disconnected client's session resumable for `ttl` (zero disables
+    /// resume tokens)
+    fn with_resume_ttl(mut self, ttl: Duration) -> Self {
+        self.resume_ttl = ttl;
+        self
+    }
+
+    /// Executables to try before the built-in candidates for each application
+    fn with_launch_map(mut self, launch_map: LaunchMap) -> Self {
+        self.launch_map = Arc::new(launch_map);
+        self
+    }
+
+    fn is_authorized(&self, headers: &HeaderMap, uri: &Uri) -> bool {
+        let Some(expected) = self.auth_token.as_deref() else {
+            return true;
+        };
+
+        let bearer = headers
+            .get(header::AUTHORIZATION)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.strip_prefix("Bearer "));
+        if bearer.is_some_and(|token| constant_time_eq(token, expected)) {
+            return true;
         }
+
+        Query::<HashMap<String, String>>::try_from_uri(uri)
+            .ok()
+            .and_then(|Query(params)| params.get("token").cloned())
+            .is_some_and(|token| constant_time_eq(&token, expected))
     }
 
-    fn register_client(&self, client_id: String, application: String) {
+    /// Register a client for an application. The instance id defaults to the
+    /// resumed session's, then to the client id; a valid `resumeToken` hands
+    /// the client its earlier session, otherwise it gets a new token.
+    fn register_client(&self, client_id: String, register: RegisterMessage) -> Registration {
+        let RegisterMessage {
+            application,
+            instance_id,
+            version,
+            client_name,
+            resume_token,
+        } = register;
+
+        let resumed = resume_token.and_then(|token| {
+            self.resume_session(&token, &client_id)
+                .map(|session| (token, session))
+        });
+        let instance_id = instance_id
+            .filter(|id| !id.is_empty())
+            .or_else(|| {
+                resumed
+                    .as_ref()
+                    .map(|(_, session)| session.instance_id.clone())
+            })
+            .unwrap_or_else(|| client_id.clone());
+
         // Update client info
+        let mut resume_token = None;
         if let Some(mut client) = self.clients.get_mut(&client_id) {
             client.application = Some(application.clone());
+            client.version = version;
+            client.client_name = client_name;
+            resume_token = match &resumed {
+                Some((token, session)) => {
+                    client.connected_at = session.connected_at;
+                    Some(token.clone())
+                }
+                None if self.resume_ttl.is_zero() => None,
+                None => client
+                    .resume_token
+                    .clone()
+                    .or_else(|| Some(Uuid::new_v4().to_string())),
+            };
+            client.resume_token = resume_token.clone();
+        }
+        if let Some(token) = &resume_token {
+            let connected_at = resumed
+                .as_ref()
+                .map_or_else(Instant::now, |(_, session)| session.connected_at);
+            self.resume_tokens.insert(
+                token.clone(),
+                ResumeSession {
+                    client_id: client_id.clone(),
+                    instance_id: instance_id.clone(),
+                    connected_at,
+                    disconnected_at: None,
+                },
+            );
         }
 
         // Add to application clients
-        self.application_clients
-            .entry(application.clone())
-            .and_modify(|clients| {
-                if !clients.contains(&client_id) {
-                    clients.push(client_id.clone());
+        let mut clients = self.application_clients.entry(application.clone()).or_default();
+        let newly_registered = match clients.iter_mut().find(|c| c.client_id == client_id) {
+            Some(existing) => {
+                existing.instance_id = instance_id.clone();
+                false
+            }
+            None => {
+                clients.push(ApplicationClient {
+                    client_id: client_id.clone(),
+                    instance_id: instance_id.clone(),
+                });
+                true
+            }
+        };
+        let count = clients.len();
+        drop(clients);
+        if let Some(notify) = self.registration_notify.get(&application) {
+            notify.notify_waiters();
+        }
+
+        info!(
+            "Client {} registered for application: {} (instance {})",
+            client_id, application, instance_id
+        );
+        if newly_registered {
+            self.broadcast_presence(&client_id, &application, true, count);
+        }
+        Registration {
+            instance_id,
+            resume_token,
+            resumed: resumed.is_some(),
+        }
+    }
+
+    /// Hand the session behind `token` to `client_id`. Fails for unknown and
+    /// expired tokens; a connection still holding the session is closed, since
+    /// its client has evidently reconnected.
+    fn resume_session(&self, token: &str, client_id: &str) -> Option<ResumeSession> {
+        let session = self.resume_tokens.get(token)?.clone();
+        match session.disconnected_at {
+            Some(at) if at.elapsed() > self.resume_ttl => {
+                warn!("Client {} sent an expired resume token", client_id);
+                self.resume_tokens.remove(token);
+                return None;
+            }
+            Some(_) => {}
+            None if session.client_id == client_id => {}
+            None => {
+                if let Some(stale) = self.clients.get(&session.client_id) {
+                    let _ = stale.tx.send(SocketIoMessage::Close);
                 }
-            })
-            .or_insert_with(|| vec![client_id.clone()]);
+                self.unregister_client(&session.client_id);
+            }
+        }
+        info!(
+            "Client {} resumed the session of {}",
+            client_id, session.client_id
+        );
+        Some(session)
+    }
+
+    /// Forget sessions whose token has expired, or whose connection is gone
+    /// without having been marked disconnected
+    fn prune_resume_tokens(&self) {
+        let ttl = self.resume_ttl;
+        self.resume_tokens
+            .retain(|_, session| match session.disconnected_at {
+                Some(at) => at.elapsed() <= ttl,
+                None => self.clients.contains_key(&session.client_id),
+            });
+    }
+
+    /// Tell every client except `client_id` that a client for `application`
+    /// came or went, and how many are registered now
+    fn broadcast_presence(
+        &self,
+        client_id: &str,
+        application: &str,
+        connected: bool,
+        count: usize,
+    ) {
+        let socket_io_msg = encode_event(
+            "client_presence",
+            json!({ "application": application, "connected": connected, "count": count }),
+        );
+        for client in self.clients.iter() {
+            if client.id != client_id {
+                let _ = client.tx.send(SocketIoMessage::Text(socket_io_msg.clone()));
+            }
+        }
+    }
+
+    /// Hold a command until a client for its application registers.
+    /// Returns false when queueing is disabled.
+    fn enqueue_pending(&self, packet: CommandPacketWithSender) -> bool {
+        if self.pending_queue_size == 0 {
+            return false;
+        }
 
+        let mut queue = self.pending.entry(packet.application.clone()).or_default();
+        if queue.len() >= self.pending_queue_size {
+            if let Some(dropped) = queue.pop_front() {
+                warn!(
+                    "Pending queue for {} is full, dropping oldest command from {}",
+                    dropped.application, dropped.sender_id
+                );
+            }
+        }
         info!(
-            "Client {} registered for application: {}",
-            client_id, application
+            "Queued command from {} for application {} ({} pending)",
+            packet.sender_id,
+            packet.application,
+            queue.len() + 1
         );
+        queue.push_back(packet);
+        true
+    }
+
+    fn flush_pending(&self, application: &str) {
+        let Some((_, queue)) = self.pending.remove(application) else {
+            return;
+        };
+
+        info!("Flushing {} pending commands for {}", queue.len(), application);
+        for packet in queue {
+            // The MCP server may have given up and disconnected in the meantime
+            if self.clients.contains_key(&packet.sender_id) {
+                self.send_to_application(&packet);
+            }
+        }
     }
 
     fn unregister_client(&self, client_id: &str) {
         // Remove from clients
         if let Some((_, client_info)) = self.clients.remove(client_id) {
+            // Keep the session for a reconnect to resume
+            if let Some(token) = &client_info.resume_token {
+                if let Some(mut session) = self.resume_tokens.get_mut(token) {
+                    if session.client_id == client_id {
+                        session.disconnected_at = Some(Instant::now());
+                    }
+                }
+            }
             // Remove from application clients
             if let Some(app) = &client_info.application {
                 if let Some(mut clients) = self.application_clients.get_mut(app) {
-                    clients.retain(|id| id != client_id);
-                    if clients.is_empty() {
-                        drop(clients);
+                    clients.retain(|c| c.client_id != client_id);
+                    let count = clients.len();
+                    drop(clients);
+                    if count == 0 {
                         self.application_clients.remove(app);
                     }
+                    self.broadcast_presence(client_id, app, false, count);
                 }
             }
         }
@@ -178,10 +848,23 @@ impl AppState {
         let application = &packet.application;
 
         if let Some(clients) = self.application_clients.get(application) {
-            let client_count = clients.len();
+            let targets: Vec<&ApplicationClient> = match &packet.target_instance {
+                Some(instance) => clients.iter().filter(|c| &c.instance_id == instance).collect(),
+                None => clients.iter().collect(),
+            };
+
+            if targets.is_empty() {
+                warn!(
+                    "No client for application {} with instance {:?}",
+                    application, packet.target_instance
+                );
+                return false;
+            }
+
             info!(
                 "Sending to {} clients for application: {}",
-                client_count, application
+                targets.len(),
+                application
             );
 
             let event_data = json!({
@@ -192,8 +875,8 @@ impl AppState {
 
             let socket_io_msg = encode_event("command_packet", event_data);
 
-            for client_id in clients.iter() {
-                if let Some(client) = self.clients.get(client_id) {
+            for target in targets {
+                if let Some(client) = self.clients.get(&target.client_id) {
                     let _ = client.tx.send(SocketIoMessage::Text(socket_io_msg.clone()));
                 }
             }
@@ -216,6 +899,121 @@ impl AppState {
         }
     }
 
+    /// Answer a `command_packet` over `max_command_bytes` with a `FAILURE`
+    ///
+    /// `bytes` is the size of the Socket.IO packet as received, so the
+    /// command is never re-serialized just to measure it.
+    fn reject_oversize_command(&self, client_id: &str, data: &Value, bytes: usize) {
+        warn!(
+            "Rejected {} byte command from {} for application {}",
+            bytes,
+            client_id,
+            data.get("application").and_then(|v| v.as_str()).unwrap_or("unknown")
+        );
+        let response = json!({
+            "senderId": client_id,
+            "status": "FAILURE",
+            "message": format!(
+                "Command too large: {} bytes exceeds the {} byte limit",
+                bytes, self.max_command_bytes
+            )
+        });
+        let request_id = data.get("command").and_then(|command| command.get("requestId"));
+        self.send_packet_response(client_id, response, request_id);
+    }
+
+    /// Send a `packet_response`, stamping `timestamp` and, when known, the command's `requestId`
+    fn send_packet_response(&self, client_id: &str, mut packet: Value, request_id: Option<&Value>) {
+        stamp_packet_response(&mut packet, request_id);
+        self.send_to_client(client_id, "packet_response", packet);
+    }
+
+    /// Forward an `app_event` from an application client to every client not
+    /// registered as an application (the MCP servers). Returns how many
+    /// clients it reached; events from unregistered clients are dropped.
+    fn forward_app_event(&self, client_id: &str, data: &Value) -> usize {
+        let Some(application) = self
+            .clients
+            .get(client_id)
+            .and_then(|c| c.application.clone())
+        else {
+            warn!("Dropping app_event from unregistered client {}", client_id);
+            return 0;
+        };
+
+        let socket_io_msg = encode_event(
+            "app_event",
+            json!({
+                "senderId": client_id,
+                "application": application,
+                "event": data.get("event").cloned().unwrap_or(Value::Null),
+                "data": data.get("data").cloned().unwrap_or(Value::Null),
+                "timestamp": epoch_millis(),
+            }),
+        );
+
+        let mut forwarded = 0;
+        for client in self.clients.iter() {
+            if client.application.is_none() && client.id != client_id {
+                let _ = client.tx.send(SocketIoMessage::Text(socket_io_msg.clone()));
+                forwarded += 1;
+            }
+        }
+        debug!(
+            "Forwarded app_event from {} to {} clients",
+            application, forwarded
+        );
+        forwarded
+    }
+
+    fn allow_command(&self, client_id: &str) -> bool {
+        self.clients
+            .get_mut(client_id)
+            .map(|mut client| client.rate_limit.try_acquire())
+            .unwrap_or(true)
+    }
+
+    fn touch_client(&self, client_id: &str) {
+        if let Some(mut client) = self.clients.get_mut(client_id) {
+            client.last_activity = Instant::now();
+        }
+    }
+
+    fn ping_clients(&self) {
+        for client in self.clients.iter() {
+            let _ = client.tx.send(SocketIoMessage::Text(ENGINE_PING.to_string()));
+        }
+    }
+
+    /// Drop clients that missed a ping/pong round; returns how many were evicted
+    fn evict_idle_clients(&self) -> usize {
+        // A healthy client answers within ping_timeout of a ping sent every ping_interval
+        let max_idle = self.ping_interval + self.ping_timeout;
+        let idle: Vec<String> = self
+            .clients
+            .iter()
+            .filter(|client| client.last_activity.elapsed() > max_idle)
+            .map(|client| client.id.clone())
+            .collect();
+
+        for client_id in &idle {
+            warn!("Client {} missed ping timeout, evicting", client_id);
+            if let Some(client) = self.clients.get(client_id) {
+                let _ = client.tx.send(SocketIoMessage::Close);
+            }
+            self.unregister_client(client_id);
+        }
+        idle.len()
+    }
+
+    /// Ask every connected client to close its socket; returns how many were notified
+    fn close_all_clients(&self) -> usize {
+        self.clients
+            .iter()
+            .filter(|client| client.tx.send(SocketIoMessage::Close).is_ok())
+            .count()
+    }
+
     fn get_status(&self) -> StatusResponse {
         let mut clients_map = HashMap::new();
 
@@ -223,12 +1021,69 @@ impl AppState {
             clients_map.insert(entry.key().clone(), entry.value().len());
         }
 
+        let mut versions: HashMap<String, Vec<String>> = HashMap::new();
+        for client in self.clients.iter() {
+            if let (Some(application), Some(version)) = (&client.application, &client.version) {
+                let known = versions.entry(application.clone()).or_default();
+                if !known.contains(version) {
+                    known.push(version.clone());
+                }
+            }
+        }
+        for known in versions.values_mut() {
+            known.sort();
+        }
+
         StatusResponse {
             status: "running".to_string(),
-            port: 3001, // Will be updated by the handler
+            port: self.port,
             clients: clients_map,
+            versions,
             uptime: self.start_time.elapsed().as_secs(),
+            build: Some(VERSION_INFO),
+        }
+    }
+
+    fn list_clients(&self) -> Vec<ClientSummary> {
+        let mut clients: Vec<ClientSummary> = self
+            .clients
+            .iter()
+            .map(|entry| ClientSummary {
+                id: entry.id.clone(),
+                application: entry.application.clone(),
+                version: entry.version.clone(),
+                client_name: entry.client_name.clone(),
+                uptime: entry.connected_at.elapsed().as_secs(),
+            })
+            .collect();
+        // Oldest connections first so the listing is stable between calls
+        clients.sort_by(|a, b| b.uptime.cmp(&a.uptime).then_with(|| a.id.cmp(&b.id)));
+        clients
+    }
+
+    /// Applications `/ready` is still waiting for; `["*"]` while waiting
+    /// for any application client at all
+    fn missing_ready_applications(&self) -> Vec<String> {
+        if self.ready_applications.is_empty() {
+            return if self.application_clients.is_empty() {
+                vec!["*".to_string()]
+            } else {
+                Vec::new()
+            };
         }
+        self.ready_applications
+            .iter()
+            .filter(|app| !self.has_application_client(app))
+            .cloned()
+            .collect()
+    }
+
+    /// Woken each time a client registers for `application`
+    fn registration_notify(&self, application: &str) -> Arc<Notify> {
+        self.registration_notify
+            .entry(application.to_string())
+            .or_default()
+            .clone()
     }
 
     fn has_application_client(&self, application: &str) -> bool {
@@ -244,11 +1099,72 @@ struct StatusResponse {
     status: String,
     port: u16,
     clients: HashMap<String, usize>,
+    /// Distinct client versions reported per application
+    versions: HashMap<String, Vec<String>>,
+    uptime: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    build: Option<VersionInfo>,
+}
+
+/// What `/version` reports; all static, so serving it allocates only the body
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct VersionInfo {
+    version: &'static str,
+    /// Set from `ADOBE_PROXY_GIT_COMMIT` at build time, when the build provides it
+    git_commit: Option<&'static str>,
+    engine_io_protocol: u8,
+    socket_io_protocol: u8,
+}
+
+const VERSION_INFO: VersionInfo = VersionInfo {
+    version: env!("CARGO_PKG_VERSION"),
+    git_commit: option_env!("ADOBE_PROXY_GIT_COMMIT"),
+    engine_io_protocol: ENGINE_IO_PROTOCOL,
+    socket_io_protocol: SOCKET_IO_PROTOCOL,
+};
+
+#[derive(Debug, Serialize)]
+struct ClientSummary {
+    id: String,
+    application: Option<String>,
+    version: Option<String>,
+    #[serde(rename = "clientName")]
+    client_name: Option<String>,
     uptime: u64,
 }
 
+const DEFAULT_PING_INTERVAL: Duration = Duration::from_millis(25000);
+const DEFAULT_PING_TIMEOUT: Duration = Duration::from_millis(20000);
+const DEFAULT_MAX_MESSAGE_BYTES: usize = 16 * 1024 * 1024;
+const DEFAULT_MAX_COMMAND_BYTES: usize = 1024 * 1024;
+const DEFAULT_RESUME_TTL: Duration = Duration::from_millis(30000);
+const DEFAULT_AUTO_LAUNCH_POLL: Duration = Duration::from_millis(500);
+
+/// Time given to clients to process the close frame before the listener stops
+const SHUTDOWN_GRACE_PERIOD: Duration = Duration::from_millis(500);
+
 // Socket.IO protocol encoding/decoding helpers are centralized in adobe-common::socket_io.
 
+/// Compare secrets without short-circuiting on the first mismatched byte
+fn constant_time_eq(a: &str, b: &str) -> bool {
+    a.len() == b.len()
+        && a
+            .bytes()
+            .zip(b.bytes())
+            .fold(0u8, |acc, (x, y)| acc | (x ^ y))
+            == 0
+}
+
+async fn require_auth(State(state): State<AppState>, request: Request, next: Next) -> Response {
+    if state.is_authorized(request.headers(), request.uri()) {
+        next.run(request).await
+    } else {
+        warn!("Rejected unauthenticated request to {}", request.uri().path());
+        StatusCode::UNAUTHORIZED.into_response()
+    }
+}
+
 async fn handle_socket(
     socket: WebSocket,
     state: AppState,
@@ -263,81 +1179,122 @@ async fn handle_socket(
         ClientInfo {
             id: client_id.clone(),
             application: None,
+            version: None,
+            client_name: None,
+            resume_token: None,
             tx: tx.clone(),
+            connected_at: Instant::now(),
+            last_activity: Instant::now(),
+            rate_limit: TokenBucket::new(state.max_commands_per_sec),
         },
     );
 
     info!("User connected: {}", client_id);
 
     // Engine.IO open + Socket.IO connect (required by socket.io clients)
-    let connect_msg = format!("0{}", json!({"sid": client_id, "upgrades": [], "pingInterval": 25000, "pingTimeout": 20000}));
+    let connect_msg = format!(
+        "0{}",
+        json!({
+            "sid": client_id,
+            "upgrades": [],
+            "pingInterval": state.ping_interval.as_millis() as u64,
+            "pingTimeout": state.ping_timeout.as_millis() as u64,
+        })
+    );
     sender.send(Message::Text(connect_msg)).await?;
     sender.send(Message::Text("40".to_string())).await?;
 
     // Spawn task to send outgoing messages
     let client_id_clone = client_id.clone();
-    let mut send_task = tokio::spawn(async move {
-        while let Ok(msg) = rx.recv().await {
-            match msg {
-                SocketIoMessage::Text(text) => {
-                    if sender.send(Message::Text(text)).await.is_err() {
+    let mut send_task = tokio::spawn(
+        async move {
+            while let Ok(msg) = rx.recv().await {
+                match msg {
+                    SocketIoMessage::Text(text) => {
+                        if sender.send(Message::Text(text)).await.is_err() {
+                            break;
+                        }
+                    }
+                    SocketIoMessage::Close => {
+                        let _ = sender.send(Message::Close(None)).await;
                         break;
                     }
                 }
-                SocketIoMessage::Close => {
-                    let _ = sender.send(Message::Close(None)).await;
-                    break;
-                }
             }
+            debug!("Send task completed for client: {}", client_id_clone);
         }
-        debug!("Send task completed for client: {}", client_id_clone);
-    });
+        .in_current_span(),
+    );
 
     // Handle incoming messages
     let state_clone = state.clone();
     let client_id_clone = client_id.clone();
-    let mut recv_task = tokio::spawn(async move {
-        while let Some(Ok(msg)) = receiver.next().await {
-            match msg {
-                Message::Text(text) => {
-                    debug!("Received from {}: {}", client_id_clone, text);
-
-                    if text == ENGINE_PING {
-                        let _ = tx.send(SocketIoMessage::Text(ENGINE_PONG.to_string()));
-                        continue;
+    let mut recv_task = tokio::spawn(
+        async move {
+            while let Some(msg) = receiver.next().await {
+                // Includes messages over `max_message_bytes`, which end the connection
+                let msg = match msg {
+                    Ok(msg) => msg,
+                    Err(e) => {
+                        warn!("Closing connection to {}: {}", client_id_clone, e);
+                        break;
                     }
+                };
+                state_clone.touch_client(&client_id_clone);
+                match msg {
+                    Message::Text(frame) => {
+                        debug!("Received from {}: {}", client_id_clone, frame);
+
+                        // One frame may batch several Engine.IO packets
+                        for text in split_payload(&frame) {
+                            if text == ENGINE_PING {
+                                let _ = tx.send(SocketIoMessage::Text(ENGINE_PONG.to_string()));
+                                continue;
+                            }
 
-                    if is_connect(&text) || is_disconnect(&text) {
-                        debug!("Socket.IO connection control: {}", text);
-                        continue;
-                    }
+                            if text == ENGINE_PONG {
+                                continue;
+                            }
 
-                    // Decode Socket.IO event
-                    if let Some((event, data)) = decode_event(&text) {
-                        handle_event(
-                            &state_clone,
-                            &client_id_clone,
-                            &tx,
-                            event.as_str(),
-                            data,
-                        )
-                        .await;
-                    } else {
-                        warn!("Failed to parse Socket.IO message: {}", text);
+                            if is_connect(text) || is_disconnect(text) {
+                                debug!("Socket.IO connection control: {}", text);
+                                continue;
+                            }
+
+                            // Decode Socket.IO event
+                            if let Some((event, data)) = decode_event(text) {
+                                tracing::Span::current().record("event", event.as_str());
+                                if event == "command_packet" && text.len() > state_clone.max_command_bytes {
+                                    state_clone.reject_oversize_command(&client_id_clone, &data, text.len());
+                                    continue;
+                                }
+                                handle_event(
+                                    &state_clone,
+                                    &client_id_clone,
+                                    &tx,
+                                    event.as_str(),
+                                    data,
+                                )
+                                .await;
+                            } else {
+                                warn!("Failed to parse Socket.IO message: {}", text);
+                            }
+                        }
                     }
+                    Message::Close(_) => {
+                        debug!("Client {} sent close message", client_id_clone);
+                        break;
+                    }
+                    Message::Ping(_) => {
+                        let _ = tx.send(SocketIoMessage::Text(ENGINE_PONG.to_string()));
+                    }
+                    _ => {}
                 }
-                Message::Close(_) => {
-                    debug!("Client {} sent close message", client_id_clone);
-                    break;
-                }
-                Message::Ping(_) => {
-                    let _ = tx.send(SocketIoMessage::Text(ENGINE_PONG.to_string()));
-                }
-                _ => {}
             }
+            debug!("Receive task completed for client: {}", client_id_clone);
         }
-        debug!("Receive task completed for client: {}", client_id_clone);
-    });
+        .in_current_span(),
+    );
 
     // Wait for either task to complete
     tokio::select! {
@@ -367,36 +1324,81 @@ async fn handle_event(
     match event {
         "register" => {
             if let Ok(register_msg) = serde_json::from_value::<RegisterMessage>(data) {
-                state.register_client(client_id.to_string(), register_msg.application.clone());
+                // Runs inside the connection's span (see `websocket_handler`)
+                tracing::Span::current().record("application", register_msg.application.as_str());
+                let application = register_msg.application.clone();
+                let registration = state.register_client(client_id.to_string(), register_msg);
 
                 let response = RegistrationResponse {
                     response_type: "registration".to_string(),
                     status: "success".to_string(),
-                    message: format!("Registered for {}", register_msg.application),
+                    message: format!("Registered for {}", application),
+                    instance_id: registration.instance_id,
+                    resume_token: registration.resume_token,
+                    resumed: registration.resumed,
                 };
 
                 let msg = encode_event("registration_response", json!(response));
                 let _ = tx.send(SocketIoMessage::Text(msg));
+
+                // Deliver anything that arrived before this client registered
+                state.flush_pending(&application);
             }
         }
         "command_packet" => {
             if let Ok(cmd_packet) = serde_json::from_value::<CommandPacket>(data) {
+                let request_id = cmd_packet.command.get("requestId").cloned();
+
                 info!(
                     "Command from {} for application {}: {:?}",
                     client_id, cmd_packet.application, cmd_packet.command
                 );
 
+                if !state.allow_command(client_id) {
+                    warn!("Rate limited command from {}", client_id);
+                    let response = json!({
+                        "senderId": client_id,
+                        "status": "FAILURE",
+                        "message": format!(
+                            "Rate limited: more than {} commands per second",
+                            state.max_commands_per_sec
+                        )
+                    });
+                    state.send_packet_response(client_id, response, request_id.as_ref());
+                    return;
+                }
+                state.metrics.record_command(&cmd_packet.application);
+
                 let packet_with_sender = CommandPacketWithSender {
                     sender_id: client_id.to_string(),
                     application: cmd_packet.application,
                     command: cmd_packet.command,
+                    target_instance: cmd_packet.target_instance,
                 };
 
                 if !state.send_to_application(&packet_with_sender) {
+                    // The application is up, just not the requested instance
+                    if let Some(instance) = &packet_with_sender.target_instance {
+                        if state.has_application_client(&packet_with_sender.application) {
+                            state.metrics.record_failure(&packet_with_sender.application);
+                            let response = json!({
+                                "senderId": client_id,
+                                "status": "FAILURE",
+                                "message": format!(
+                                    "No client registered for application {} with instance: {}",
+                                    packet_with_sender.application, instance
+                                )
+                            });
+                            state.send_packet_response(client_id, response, request_id.as_ref());
+                            return;
+                        }
+                    }
+
                     let mut auto_launch_note = None;
 
                     if state.auto_launch {
-                        if try_launch_application(&packet_with_sender.application) {
+                        if try_launch_application(&packet_with_sender.application, &state.launch_map)
+                        {
                             if wait_for_application(
                                 state,
                                 &packet_with_sender.application,
@@ -420,20 +1422,28 @@ async fn handle_event(
                         }
                     }
 
-                    let mut message = format!(
-                        "No clients registered for application: {}",
-                        packet_with_sender.application
+                    if auto_launch_note.is_none()
+                        && state.enqueue_pending(packet_with_sender.clone())
+                    {
+                        return;
+                    }
+
+                    let mut message = format!(
+                        "{}: {}",
+                        NOT_CONNECTED_PREFIX, packet_with_sender.application
                     );
                     if let Some(note) = auto_launch_note {
                         message = format!("{}. {}", message, note);
                     }
 
+                    state.metrics.record_failure(&packet_with_sender.application);
                     let response = json!({
                         "senderId": client_id,
                         "status": "FAILURE",
+                        "code": NOT_CONNECTED_CODE,
                         "message": message
                     });
-                    state.send_to_client(client_id, "packet_response", response);
+                    state.send_packet_response(client_id, response, request_id.as_ref());
                 }
             }
         }
@@ -442,67 +1452,247 @@ async fn handle_event(
                 if let Some(sender_id) = response.packet.get("senderId").and_then(|v| v.as_str()) {
                     let sender_id = sender_id.to_string();
                     info!("Sending response to client {}", sender_id);
-                    state.send_to_client(&sender_id, "packet_response", response.packet);
+                    state.metrics.record_response();
+                    state.send_packet_response(&sender_id, response.packet, None);
                 } else {
                     warn!("No sender ID in command_packet_response");
                 }
             }
         }
+        "app_event" => {
+            state.forward_app_event(client_id, &data);
+        }
         _ => {
             warn!("Unknown event: {}", event);
         }
     }
 }
 
+/// Fill in `timestamp` if the sender left it out, and `requestId` when one is given
+fn stamp_packet_response(packet: &mut Value, request_id: Option<&Value>) {
+    let Some(fields) = packet.as_object_mut() else {
+        return;
+    };
+    let missing = |fields: &serde_json::Map<String, Value>, key: &str| {
+        fields.get(key).is_none_or(Value::is_null)
+    };
+
+    if missing(fields, "timestamp") {
+        fields.insert("timestamp".to_string(), json!(epoch_millis()));
+    }
+    if let Some(request_id) = request_id {
+        if missing(fields, "requestId") {
+            fields.insert("requestId".to_string(), request_id.clone());
+        }
+    }
+}
+
 async fn websocket_handler(
     ws: WebSocketUpgrade,
     State(state): State<AppState>,
 ) -> Response {
     let client_id = Uuid::new_v4().to_string();
+    // Structured logs carry these on every record for the connection
+    let span = tracing::info_span!(
+        "client",
+        client_id = %client_id,
+        application = tracing::field::Empty,
+        event = tracing::field::Empty
+    );
 
-    ws.on_upgrade(move |socket| async move {
-        if let Err(e) = handle_socket(socket, state, client_id).await {
-            error!("WebSocket error: {}", e);
-        }
-    })
+    let max_message_bytes = state.max_message_bytes;
+    ws.max_message_size(max_message_bytes)
+        .max_frame_size(max_message_bytes)
+        .on_upgrade(move |socket| {
+            async move {
+                if let Err(e) = handle_socket(socket, state, client_id).await {
+                    error!("WebSocket error: {}", e);
+                }
+            }
+            .instrument(span)
+        })
+}
+
+/// Liveness probe: answers without looking at any client state
+async fn health_handler() -> Json<Value> {
+    Json(json!({ "status": "ok" }))
+}
+
+/// Readiness probe: 503 until the required application clients have registered
+async fn ready_handler(State(state): State<AppState>) -> Response {
+    let missing = state.missing_ready_applications();
+    if missing.is_empty() {
+        Json(json!({ "status": "ready" })).into_response()
+    } else {
+        (
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(json!({ "status": "not ready", "missing": missing })),
+        )
+            .into_response()
+    }
 }
 
 async fn status_handler(State(state): State<AppState>) -> Json<StatusResponse> {
-    let mut status = state.get_status();
-    // Update port from the actual server config if needed
-    status.port = 3001; // This could be passed through state if needed
-    Json(status)
+    Json(state.get_status())
+}
+
+async fn version_handler() -> Json<VersionInfo> {
+    Json(VERSION_INFO)
+}
+
+async fn clients_handler(State(state): State<AppState>) -> Json<Vec<ClientSummary>> {
+    Json(state.list_clients())
+}
+
+async fn metrics_handler(State(state): State<AppState>) -> impl IntoResponse {
+    (
+        [(header::CONTENT_TYPE, "text/plain; version=0.0.4")],
+        state.metrics.render(),
+    )
+}
+
+/// User-supplied executables keyed by application name
+type LaunchMap = HashMap<String, Vec<PathBuf>>;
+
+/// Accepts either a single path or a list of paths per application
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum LaunchTargets {
+    One(PathBuf),
+    Many(Vec<PathBuf>),
 }
 
-fn try_launch_application(application: &str) -> bool {
-    let candidates = match application {
-        "acrobat" => vec![
+/// Parse a launch map; TOML when `is_toml`, JSON otherwise
+fn parse_launch_map(contents: &str, is_toml: bool) -> Result<LaunchMap, String> {
+    let raw: HashMap<String, LaunchTargets> = if is_toml {
+        toml::from_str(contents).map_err(|e| e.to_string())?
+    } else {
+        serde_json::from_str(contents).map_err(|e| e.to_string())?
+    };
+    Ok(normalize_launch_map(raw))
+}
+
+/// Lower-case application names and flatten single paths into lists
+fn normalize_launch_map(raw: HashMap<String, LaunchTargets>) -> LaunchMap {
+    raw.into_iter()
+        .map(|(application, targets)| {
+            let paths = match targets {
+                LaunchTargets::One(path) => vec![path],
+                LaunchTargets::Many(paths) => paths,
+            };
+            (application.to_lowercase(), paths)
+        })
+        .collect()
+}
+
+fn load_launch_map(path: &Path) -> Result<LaunchMap, String> {
+    let contents = std::fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read launch map {}: {}", path.display(), e))?;
+    let is_toml = path
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("toml"));
+    parse_launch_map(&contents, is_toml)
+        .map_err(|e| format!("Invalid launch map {}: {}", path.display(), e))
+}
+
+#[cfg(target_os = "macos")]
+fn builtin_candidates(application: &str) -> &'static [&'static str] {
+    match application {
+        "acrobat" => &[
+            "/Applications/Adobe Acrobat DC/Adobe Acrobat.app",
+            "/Applications/Adobe Acrobat Reader.app",
+        ],
+        "photoshop" => &[
+            "/Applications/Adobe Photoshop 2024/Adobe Photoshop 2024.app",
+            "/Applications/Adobe Photoshop 2025/Adobe Photoshop 2025.app",
+        ],
+        "illustrator" => &[
+            "/Applications/Adobe Illustrator 2024/Adobe Illustrator.app",
+            "/Applications/Adobe Illustrator 2025/Adobe Illustrator.app",
+        ],
+        "indesign" => &[
+            "/Applications/Adobe InDesign 2024/Adobe InDesign 2024.app",
+            "/Applications/Adobe InDesign 2025/Adobe InDesign 2025.app",
+        ],
+        "premiere" => &[
+            "/Applications/Adobe Premiere Pro 2024/Adobe Premiere Pro 2024.app",
+            "/Applications/Adobe Premiere Pro 2025/Adobe Premiere Pro 2025.app",
+        ],
+        "lightroom" => &[
+            "/Applications/Adobe Lightroom Classic/Adobe Lightroom Classic.app",
+            "/Applications/Adobe Lightroom/Adobe Lightroom.app",
+        ],
+        "aftereffects" => &[
+            "/Applications/Adobe After Effects 2024/Adobe After Effects 2024.app",
+            "/Applications/Adobe After Effects 2025/Adobe After Effects 2025.app",
+        ],
+        "xd" => &["/Applications/Adobe XD/Adobe XD.app"],
+        _ => &[],
+    }
+}
+
+#[cfg(not(target_os = "macos"))]
+fn builtin_candidates(application: &str) -> &'static [&'static str] {
+    match application {
+        "acrobat" => &[
             r"C:\Program Files\Adobe\Acrobat DC\Acrobat\Acrobat.exe",
             r"C:\Program Files (x86)\Adobe\Acrobat Reader DC\Reader\AcroRd32.exe",
         ],
-        "photoshop" => vec![
+        "photoshop" => &[
             r"C:\Program Files\Adobe\Adobe Photoshop 2024\Photoshop.exe",
             r"C:\Program Files\Adobe\Adobe Photoshop 2025\Photoshop.exe",
         ],
-        "illustrator" => vec![
+        "illustrator" => &[
             r"C:\Program Files\Adobe\Adobe Illustrator 2024\Support Files\Contents\Windows\Illustrator.exe",
             r"C:\Program Files\Adobe\Adobe Illustrator 2025\Support Files\Contents\Windows\Illustrator.exe",
         ],
-        "indesign" => vec![
+        "indesign" => &[
             r"C:\Program Files\Adobe\Adobe InDesign 2024\InDesign.exe",
             r"C:\Program Files\Adobe\Adobe InDesign 2025\InDesign.exe",
         ],
-        "premiere" => vec![
+        "premiere" => &[
             r"C:\Program Files\Adobe\Adobe Premiere Pro 2024\Adobe Premiere Pro.exe",
             r"C:\Program Files\Adobe\Adobe Premiere Pro 2025\Adobe Premiere Pro.exe",
         ],
-        _ => vec![],
-    };
+        "lightroom" => &[
+            r"C:\Program Files\Adobe\Adobe Lightroom Classic\Lightroom.exe",
+            r"C:\Program Files\Adobe\Adobe Lightroom\lightroom.exe",
+        ],
+        "aftereffects" => &[
+            r"C:\Program Files\Adobe\Adobe After Effects 2024\Support Files\AfterFX.exe",
+            r"C:\Program Files\Adobe\Adobe After Effects 2025\Support Files\AfterFX.exe",
+        ],
+        // XD installs as a Store app with no fixed path; use --launch-map for it
+        _ => &[],
+    }
+}
+
+/// Overrides from the launch map win; otherwise use the platform's built-in paths
+fn launch_candidates(application: &str, launch_map: &LaunchMap) -> Vec<PathBuf> {
+    match launch_map.get(application) {
+        Some(paths) if !paths.is_empty() => paths.clone(),
+        _ => builtin_candidates(application)
+            .iter()
+            .map(PathBuf::from)
+            .collect(),
+    }
+}
+
+fn launch_command(path: &Path) -> Command {
+    // App bundles are directories and have to go through LaunchServices
+    if cfg!(target_os = "macos") && path.extension().is_some_and(|ext| ext == "app") {
+        let mut command = Command::new("open");
+        command.arg("-a").arg(path);
+        command
+    } else {
+        Command::new(path)
+    }
+}
 
-    for exe in candidates {
-        if std::path::Path::new(exe).exists()
-            && Command::new(exe).spawn().is_ok() {
-            info!("Auto-launched {} via {}", application, exe);
+fn try_launch_application(application: &str, launch_map: &LaunchMap) -> bool {
+    for exe in launch_candidates(application, launch_map) {
+        if exe.exists() && launch_command(&exe).spawn().is_ok() {
+            info!("Auto-launched {} via {}", application, exe.display());
             return true;
         }
     }
@@ -510,51 +1700,1307 @@ fn try_launch_application(application: &str) -> bool {
     false
 }
 
+/// Wait up to `timeout` for a client to register for `application`,
+/// returning as soon as one does
 async fn wait_for_application(
     state: &AppState,
     application: &str,
     timeout: Duration,
 ) -> bool {
-    let start = Instant::now();
-    while start.elapsed() < timeout {
+    let notify = state.registration_notify(application);
+    let deadline = Instant::now() + timeout;
+    loop {
+        // Listen before checking, so a registration in between isn't missed
+        let registered = notify.notified();
+        tokio::pin!(registered);
+        registered.as_mut().enable();
+
         if state.has_application_client(application) {
             return true;
         }
-        sleep(Duration::from_millis(500)).await;
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        if remaining.is_zero() {
+            return false;
+        }
+        let _ = tokio::time::timeout(state.auto_launch_poll.min(remaining), registered).await;
+    }
+}
+
+/// Ping clients every interval and evict the ones that stopped answering
+async fn run_heartbeat(state: AppState) {
+    let mut ticker = tokio::time::interval(state.ping_interval);
+    ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+    loop {
+        ticker.tick().await;
+        let evicted = state.evict_idle_clients();
+        if evicted > 0 {
+            info!("Evicted {} unresponsive clients", evicted);
+        }
+        state.prune_resume_tokens();
+        state.ping_clients();
     }
-    false
+}
+
+/// Resolve on Ctrl+C after telling every client the proxy is going away
+async fn shutdown_signal(state: AppState) {
+    if let Err(e) = tokio::signal::ctrl_c().await {
+        error!("Failed to listen for shutdown signal: {}", e);
+        std::future::pending::<()>().await;
+    }
+
+    let notified = state.close_all_clients();
+    info!("Shutdown requested, sent close to {} clients", notified);
+    sleep(SHUTDOWN_GRACE_PERIOD).await;
+}
+
+fn build_router(state: AppState) -> Router {
+    let auth = middleware::from_fn_with_state(state.clone(), require_auth);
+
+    let mut http_routes = Router::new()
+        .route("/status", get(status_handler))
+        .route("/version", get(version_handler))
+        .route("/clients", get(clients_handler))
+        .route("/metrics", get(metrics_handler))
+        .route_layer(auth.clone());
+    // Outside the auth layer: browsers send preflight requests without credentials
+    if let Some(origin) = state.cors_origin.clone() {
+        http_routes = http_routes.layer(
+            CorsLayer::new()
+                .allow_origin(origin)
+                .allow_methods([Method::GET])
+                .allow_headers([header::AUTHORIZATION]),
+        );
+    }
+
+    let socket_routes = Router::new()
+        .route("/socket.io/", get(websocket_handler))
+        .route_layer(auth);
+
+    // Probes for supervisors and load balancers, which don't carry the token
+    let probe_routes = Router::new()
+        .route("/health", get(health_handler))
+        .route("/ready", get(ready_handler));
+
+    http_routes
+        .merge(socket_routes)
+        .merge(probe_routes)
+        .with_state(state)
 }
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let (args, config) = Args::load_from(std::env::args_os())?;
+
     // Initialize tracing
-    tracing_subscriber::fmt()
+    let subscriber = tracing_subscriber::fmt()
         .with_target(false)
         .with_thread_ids(false)
-        .with_level(true)
-        .init();
+        .with_level(true);
+    match args.log_format {
+        LogFormat::Pretty => subscriber.init(),
+        LogFormat::Json => subscriber.json().init(),
+    }
+
+    let cors_origin = args
+        .cors_origin
+        .as_deref()
+        .map(HeaderValue::from_str)
+        .transpose()
+        .map_err(|e| format!("Invalid --cors-origin: {}", e))?;
+    let launch_map = match &args.launch_map {
+        Some(path) => load_launch_map(path)?,
+        None => normalize_launch_map(config.launch),
+    };
 
-    let args = Args::parse();
     let state = AppState::new(
+        args.port,
         args.auto_launch,
         Duration::from_millis(args.auto_launch_timeout_ms),
     );
 
-    let app = Router::new()
-        .route("/status", get(status_handler))
-        .route("/socket.io/", get(websocket_handler))
-        .with_state(state);
+    let state = state
+        .with_auth_token(args.auth_token)
+        .with_auto_launch_poll(Duration::from_millis(args.auto_launch_poll_ms))
+        .with_pending_queue_size(args.pending_queue_size)
+        .with_rate_limit(args.max_commands_per_sec)
+        .with_launch_map(launch_map)
+        .with_max_message_bytes(args.max_message_bytes)
+        .with_max_command_bytes(args.max_command_bytes)
+        .with_cors_origin(cors_origin)
+        .with_ready_applications(args.ready_applications)
+        .with_resume_ttl(Duration::from_millis(args.resume_ttl_ms))
+        .with_heartbeat(
+            Duration::from_millis(args.ping_interval_ms),
+            Duration::from_millis(args.ping_timeout_ms),
+        );
+    if state.auth_token.is_some() {
+        info!("Shared-secret authentication enabled");
+    }
+
+    tokio::spawn(run_heartbeat(state.clone()));
+    let app = build_router(state.clone());
 
     let addr = SocketAddr::from((
         args.host.parse::<std::net::IpAddr>()?,
         args.port,
     ));
 
-    info!("adobe-mcp Command proxy server running on ws://{}", addr);
-    info!("Status endpoint: http://{}/status", addr);
+    match (args.tls_cert, args.tls_key) {
+        (Some(cert), Some(key)) => {
+            // Several rustls providers may be linked in; pin the one we ship with
+            let _ = rustls::crypto::ring::default_provider().install_default();
+            let tls_config = RustlsConfig::from_pem_file(&cert, &key)
+                .await
+                .map_err(|e| format!("Failed to load TLS certificate/key: {}", e))?;
+
+            info!("adobe-mcp Command proxy server running on wss://{}", addr);
+            info!("Status endpoint: https://{}/status", addr);
+
+            let handle = axum_server::Handle::new();
+            let shutdown_handle = handle.clone();
+            tokio::spawn(async move {
+                shutdown_signal(state).await;
+                shutdown_handle.graceful_shutdown(Some(SHUTDOWN_GRACE_PERIOD));
+            });
 
-    let listener = tokio::net::TcpListener::bind(addr).await?;
-    axum::serve(listener, app).await?;
+            axum_server::bind_rustls(addr, tls_config)
+                .handle(handle)
+                .serve(app.into_make_service())
+                .await?;
+        }
+        (None, None) => {
+            info!("adobe-mcp Command proxy server running on ws://{}", addr);
+            info!("Status endpoint: http://{}/status", addr);
+
+            let listener = tokio::net::TcpListener::bind(addr).await?;
+            axum::serve(listener, app)
+                .with_graceful_shutdown(shutdown_signal(state))
+                .await?;
+        }
+        _ => {
+            return Err("--tls-cert and --tls-key must be provided together".into());
+        }
+    }
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::Body;
+    use tokio_tungstenite::tungstenite::Message as WsMessage;
+    use tower::ServiceExt;
+
+    type TestSocket =
+        tokio_tungstenite::WebSocketStream<tokio_tungstenite::MaybeTlsStream<tokio::net::TcpStream>>;
+
+    fn test_state() -> AppState {
+        AppState::new(3001, false, Duration::from_millis(0))
+    }
+
+    /// Serve the proxy on an ephemeral port, sharing `state` with the caller
+    async fn spawn_server(state: AppState) -> SocketAddr {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            axum::serve(listener, build_router(state)).await.unwrap();
+        });
+        addr
+    }
+
+    /// Connect a Socket.IO client and consume the open/connect handshake
+    async fn connect_client(addr: SocketAddr) -> TestSocket {
+        let url = format!("ws://{}/socket.io/", addr);
+        let (mut ws, _) = tokio_tungstenite::connect_async(url).await.unwrap();
+        let open = next_text(&mut ws).await;
+        assert!(open.starts_with('0'));
+        assert_eq!(next_text(&mut ws).await, "40");
+        ws
+    }
+
+    async fn next_text(ws: &mut TestSocket) -> String {
+        loop {
+            let msg = tokio::time::timeout(Duration::from_secs(5), ws.next())
+                .await
+                .expect("timed out waiting for frame")
+                .expect("socket closed")
+                .unwrap();
+            if let WsMessage::Text(text) = msg {
+                return text;
+            }
+        }
+    }
+
+    /// Wait for the next Socket.IO event with the given name
+    async fn next_event(ws: &mut TestSocket, name: &str) -> Value {
+        loop {
+            let text = next_text(ws).await;
+            if let Some((event, data)) = decode_event(&text) {
+                if event == name {
+                    return data;
+                }
+            }
+        }
+    }
+
+    async fn register(ws: &mut TestSocket, application: &str) -> Value {
+        ws.send(WsMessage::Text(encode_event(
+            "register",
+            json!({ "application": application }),
+        )))
+        .await
+        .unwrap();
+        next_event(ws, "registration_response").await
+    }
+
+    async fn get_body(state: &AppState, uri: &str) -> Vec<u8> {
+        let response = build_router(state.clone())
+            .oneshot(axum::http::Request::get(uri).body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap()
+            .to_vec()
+    }
+
+    async fn get_json(state: &AppState, uri: &str) -> Value {
+        serde_json::from_slice(&get_body(state, uri).await).unwrap()
+    }
+
+    fn upgrade_request(uri: &str) -> axum::http::request::Builder {
+        axum::http::Request::builder()
+            .uri(uri)
+            .header(header::CONNECTION, "upgrade")
+            .header(header::UPGRADE, "websocket")
+            .header(header::SEC_WEBSOCKET_VERSION, "13")
+            .header(header::SEC_WEBSOCKET_KEY, "dGhlIHNhbXBsZSBub25jZQ==")
+    }
+
+    #[tokio::test]
+    async fn test_unauthenticated_upgrade_rejected() {
+        let app = build_router(test_state().with_auth_token(Some("secret".into())));
+
+        let response = app
+            .oneshot(upgrade_request("/socket.io/").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    }
+
+    #[tokio::test]
+    async fn test_wrong_token_rejected() {
+        let app = build_router(test_state().with_auth_token(Some("secret".into())));
+
+        let response = app
+            .oneshot(
+                upgrade_request("/socket.io/?token=wrong")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    }
+
+    #[tokio::test]
+    async fn test_status_requires_token() {
+        let state = test_state().with_auth_token(Some("secret".into()));
+
+        let response = build_router(state.clone())
+            .oneshot(axum::http::Request::get("/status").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+        let response = build_router(state.clone())
+            .oneshot(
+                axum::http::Request::get("/status")
+                    .header(header::AUTHORIZATION, "Bearer secret")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let response = build_router(state)
+            .oneshot(
+                axum::http::Request::get("/status?token=secret")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    async fn get_status_code(state: &AppState, uri: &str) -> StatusCode {
+        build_router(state.clone())
+            .oneshot(axum::http::Request::get(uri).body(Body::empty()).unwrap())
+            .await
+            .unwrap()
+            .status()
+    }
+
+    #[tokio::test]
+    async fn test_version_reports_crate_version() {
+        let state = test_state();
+        let version = get_json(&state, "/version").await;
+        assert_eq!(version["version"], env!("CARGO_PKG_VERSION"));
+        assert_eq!(version["engineIoProtocol"], 4);
+        assert_eq!(version["socketIoProtocol"], 5);
+        assert!(version.get("gitCommit").is_some());
+
+        let status = get_json(&state, "/status").await;
+        assert_eq!(status["build"]["version"], env!("CARGO_PKG_VERSION"));
+    }
+
+    #[tokio::test]
+    async fn test_health_needs_no_token() {
+        let state = test_state().with_auth_token(Some("secret".into()));
+        assert_eq!(get_json(&state, "/health").await, json!({ "status": "ok" }));
+        assert_eq!(
+            get_status_code(&state, "/status").await,
+            StatusCode::UNAUTHORIZED
+        );
+    }
+
+    #[tokio::test]
+    async fn test_ready_waits_for_any_application_client() {
+        let state = test_state();
+        let addr = spawn_server(state.clone()).await;
+        assert_eq!(
+            get_status_code(&state, "/ready").await,
+            StatusCode::SERVICE_UNAVAILABLE
+        );
+
+        // An MCP server connecting doesn't make the proxy ready
+        let _mcp = connect_client(addr).await;
+        assert_eq!(
+            get_status_code(&state, "/ready").await,
+            StatusCode::SERVICE_UNAVAILABLE
+        );
+
+        let mut bridge = connect_client(addr).await;
+        register(&mut bridge, "acrobat").await;
+        assert_eq!(
+            get_json(&state, "/ready").await,
+            json!({ "status": "ready" })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_ready_waits_for_configured_applications() {
+        let state = test_state()
+            .with_ready_applications(vec!["Acrobat".to_string(), "photoshop".to_string()]);
+        let addr = spawn_server(state.clone()).await;
+
+        let mut acrobat = connect_client(addr).await;
+        register(&mut acrobat, "acrobat").await;
+        let response = build_router(state.clone())
+            .oneshot(
+                axum::http::Request::get("/ready")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let body: Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body["missing"], json!(["photoshop"]));
+
+        let mut photoshop = connect_client(addr).await;
+        register(&mut photoshop, "photoshop").await;
+        assert_eq!(get_status_code(&state, "/ready").await, StatusCode::OK);
+    }
+
+    fn preflight(uri: &str) -> axum::http::Request<Body> {
+        axum::http::Request::options(uri)
+            .header(header::ORIGIN, "http://localhost:8080")
+            .header(header::ACCESS_CONTROL_REQUEST_METHOD, "GET")
+            .body(Body::empty())
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_cors_preflight_allowed_origin() {
+        let state = test_state()
+            .with_auth_token(Some("secret".into()))
+            .with_cors_origin(Some(HeaderValue::from_static("http://localhost:8080")));
+
+        let response = build_router(state)
+            .oneshot(preflight("/status"))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            response.headers()[header::ACCESS_CONTROL_ALLOW_ORIGIN],
+            "http://localhost:8080"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_no_cors_headers_by_default() {
+        let response = build_router(test_state())
+            .oneshot(preflight("/status"))
+            .await
+            .unwrap();
+        assert!(response
+            .headers()
+            .get(header::ACCESS_CONTROL_ALLOW_ORIGIN)
+            .is_none());
+
+        let state = test_state().with_cors_origin(Some(HeaderValue::from_static("*")));
+        let response = build_router(state)
+            .oneshot(preflight("/socket.io/"))
+            .await
+            .unwrap();
+        assert!(response
+            .headers()
+            .get(header::ACCESS_CONTROL_ALLOW_ORIGIN)
+            .is_none());
+    }
+
+    #[tokio::test]
+    async fn test_no_token_configured_allows_all() {
+        let response = build_router(test_state())
+            .oneshot(axum::http::Request::get("/status").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_clients_lists_registered_client() {
+        let state = test_state();
+        let addr = spawn_server(state.clone()).await;
+
+        let mut registered = connect_client(addr).await;
+        register(&mut registered, "acrobat").await;
+        let _unregistered = connect_client(addr).await;
+
+        let clients = get_json(&state, "/clients").await;
+        let clients = clients.as_array().unwrap();
+        assert_eq!(clients.len(), 2);
+
+        let acrobat = clients
+            .iter()
+            .find(|c| c["application"] == "acrobat")
+            .expect("registered client listed");
+        assert!(acrobat["id"].as_str().is_some_and(|id| !id.is_empty()));
+        assert!(acrobat["uptime"].is_u64());
+        assert!(clients.iter().any(|c| c["application"].is_null()));
+    }
+
+    #[tokio::test]
+    async fn test_client_version_reported() {
+        let state = test_state();
+        let addr = spawn_server(state.clone()).await;
+
+        let mut bridge = connect_client(addr).await;
+        bridge
+            .send(WsMessage::Text(encode_event(
+                "register",
+                json!({ "application": "acrobat", "version": "0.2.0", "clientName": "acrobat-bridge" }),
+            )))
+            .await
+            .unwrap();
+        next_event(&mut bridge, "registration_response").await;
+        // Older plugins register without the extra fields
+        let mut legacy = connect_client(addr).await;
+        register(&mut legacy, "acrobat").await;
+
+        let clients = get_json(&state, "/clients").await;
+        let clients = clients.as_array().unwrap();
+        let reported = clients
+            .iter()
+            .find(|c| c["version"] == "0.2.0")
+            .expect("versioned client listed");
+        assert_eq!(reported["clientName"], "acrobat-bridge");
+        let bare = clients
+            .iter()
+            .find(|c| c["version"].is_null())
+            .expect("legacy client listed");
+        assert_eq!(bare["application"], "acrobat");
+        assert!(bare["clientName"].is_null());
+
+        let status = get_json(&state, "/status").await;
+        assert_eq!(status["clients"]["acrobat"], 2);
+        assert_eq!(status["versions"]["acrobat"], json!(["0.2.0"]));
+    }
+
+    #[tokio::test]
+    async fn test_presence_broadcast_on_register_and_disconnect() {
+        let state = test_state();
+        let addr = spawn_server(state.clone()).await;
+
+        let mut first = connect_client(addr).await;
+        register(&mut first, "acrobat").await;
+        let mut second = connect_client(addr).await;
+        register(&mut second, "acrobat").await;
+
+        let presence = next_event(&mut first, "client_presence").await;
+        assert_eq!(
+            presence,
+            json!({ "application": "acrobat", "connected": true, "count": 2 })
+        );
+
+        second.close(None).await.unwrap();
+        let presence = next_event(&mut first, "client_presence").await;
+        assert_eq!(
+            presence,
+            json!({ "application": "acrobat", "connected": false, "count": 1 })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_close_all_clients_sends_close_frame() {
+        let state = test_state();
+        let addr = spawn_server(state.clone()).await;
+        let mut ws = connect_client(addr).await;
+        register(&mut ws, "acrobat").await;
+
+        assert_eq!(state.close_all_clients(), 1);
+
+        let frame = tokio::time::timeout(Duration::from_secs(5), ws.next())
+            .await
+            .expect("timed out waiting for close")
+            .expect("socket closed without a frame")
+            .unwrap();
+        assert!(matches!(frame, WsMessage::Close(_)));
+    }
+
+    async fn send_command(ws: &mut TestSocket, application: &str, action: &str) {
+        ws.send(WsMessage::Text(encode_event(
+            "command_packet",
+            json!({ "application": application, "command": { "action": action } }),
+        )))
+        .await
+        .unwrap();
+    }
+
+    async fn wait_for_pending(state: &AppState, application: &str, expected: usize) {
+        for _ in 0..100 {
+            let len = state.pending.get(application).map(|q| q.len()).unwrap_or(0);
+            if len == expected {
+                return;
+            }
+            sleep(Duration::from_millis(10)).await;
+        }
+        panic!("pending queue for {} never reached {}", application, expected);
+    }
+
+    fn queued_packet(sender_id: &str, action: &str) -> CommandPacketWithSender {
+        CommandPacketWithSender {
+            sender_id: sender_id.to_string(),
+            application: "acrobat".to_string(),
+            command: json!({ "action": action }),
+            target_instance: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_command_without_client_fails_with_not_connected_code() {
+        let state = test_state();
+        let addr = spawn_server(state.clone()).await;
+        let mut mcp = connect_client(addr).await;
+
+        send_command(&mut mcp, "acrobat", "getDocumentInfo").await;
+        let response = next_event(&mut mcp, "packet_response").await;
+        assert_eq!(response["status"], "FAILURE");
+        assert_eq!(response["code"], NOT_CONNECTED_CODE);
+        assert!(response["message"].as_str().unwrap().starts_with(NOT_CONNECTED_PREFIX));
+
+        let response: adobe_common::CommandResponse = serde_json::from_value(response).unwrap();
+        assert!(response.is_not_connected());
+    }
+
+    #[tokio::test]
+    async fn test_command_queued_when_no_client_registered() {
+        let state = test_state().with_pending_queue_size(4);
+        let addr = spawn_server(state.clone()).await;
+        let mut mcp = connect_client(addr).await;
+
+        send_command(&mut mcp, "acrobat", "getDocumentInfo").await;
+        wait_for_pending(&state, "acrobat", 1).await;
+    }
+
+    #[tokio::test]
+    async fn test_pending_commands_flushed_in_order_on_register() {
+        let state = test_state().with_pending_queue_size(4);
+        let addr = spawn_server(state.clone()).await;
+        let mut mcp = connect_client(addr).await;
+
+        send_command(&mut mcp, "acrobat", "first").await;
+        send_command(&mut mcp, "acrobat", "second").await;
+        wait_for_pending(&state, "acrobat", 2).await;
+
+        let mut bridge = connect_client(addr).await;
+        register(&mut bridge, "acrobat").await;
+
+        let first = next_event(&mut bridge, "command_packet").await;
+        let second = next_event(&mut bridge, "command_packet").await;
+        assert_eq!(first["command"]["action"], "first");
+        assert_eq!(second["command"]["action"], "second");
+        assert!(first["senderId"].is_string());
+        assert!(state.pending.get("acrobat").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_app_event_forwarded_to_mcp_servers() {
+        let state = test_state();
+        let addr = spawn_server(state.clone()).await;
+
+        let mut mcp = connect_client(addr).await;
+        let mut bridge = connect_client(addr).await;
+        let mut other_app = connect_client(addr).await;
+        register(&mut bridge, "acrobat").await;
+        register(&mut other_app, "photoshop").await;
+
+        bridge
+            .send(WsMessage::Text(encode_event(
+                "app_event",
+                json!({ "event": "documentOpened", "data": { "path": "/C/report.pdf" } }),
+            )))
+            .await
+            .unwrap();
+
+        let event = next_event(&mut mcp, "app_event").await;
+        assert_eq!(event["application"], "acrobat");
+        assert_eq!(event["event"], "documentOpened");
+        assert_eq!(event["data"]["path"], "/C/report.pdf");
+        assert!(event["senderId"].is_string());
+        assert!(event["timestamp"].is_u64());
+
+        // Application clients are not sent each other's events
+        let next =
+            tokio::time::timeout(Duration::from_millis(200), next_text(&mut other_app)).await;
+        assert!(next.is_err(), "unexpected frame: {:?}", next);
+    }
+
+    #[tokio::test]
+    async fn test_app_event_from_unregistered_client_dropped() {
+        let state = test_state();
+        let addr = spawn_server(state.clone()).await;
+        let _sender = connect_client(addr).await;
+        let _listener = connect_client(addr).await;
+
+        let sender_id = state.clients.iter().map(|c| c.id.clone()).next().unwrap();
+        assert_eq!(
+            state.forward_app_event(&sender_id, &json!({ "event": "documentOpened" })),
+            0
+        );
+    }
+
+    #[tokio::test]
+    async fn test_oversize_message_closes_connection() {
+        let state = test_state().with_max_message_bytes(1024);
+        let addr = spawn_server(state.clone()).await;
+        let mut ws = connect_client(addr).await;
+
+        let command = json!({ "action": "x".repeat(4096) });
+        ws.send(WsMessage::Text(encode_event(
+            "command_packet",
+            json!({ "application": "acrobat", "command": command }),
+        )))
+        .await
+        .unwrap();
+
+        let closed = tokio::time::timeout(Duration::from_secs(5), async {
+            loop {
+                match ws.next().await {
+                    Some(Ok(WsMessage::Close(_))) | Some(Err(_)) | None => return,
+                    Some(Ok(_)) => continue,
+                }
+            }
+        })
+        .await;
+        assert!(closed.is_ok(), "connection stayed open");
+        assert!(wait_until(|| state.clients.is_empty()).await);
+    }
+
+    #[tokio::test]
+    async fn test_oversize_command_rejected() {
+        let state = test_state().with_max_message_bytes(4096).with_max_command_bytes(256);
+        let addr = spawn_server(state.clone()).await;
+        let mut mcp = connect_client(addr).await;
+
+        // Under the message limit, so it reaches the command check
+        let packet = json!({
+            "application": "acrobat",
+            "command": { "action": "insertText", "requestId": "big-1", "text": "x".repeat(512) }
+        });
+        mcp.send(WsMessage::Text(encode_event("command_packet", packet)))
+            .await
+            .unwrap();
+
+        let response = next_event(&mut mcp, "packet_response").await;
+        assert_eq!(response["status"], "FAILURE");
+        assert_eq!(response["requestId"], "big-1");
+        assert!(response["message"].as_str().unwrap().contains("too large"));
+        assert_eq!(state.clients.len(), 1, "connection should stay open");
+    }
+
+    #[test]
+    fn test_pending_queue_drops_oldest_when_full() {
+        let state = test_state().with_pending_queue_size(2);
+        for action in ["a", "b", "c"] {
+            assert!(state.enqueue_pending(queued_packet("mcp", action)));
+        }
+
+        let queue = state.pending.get("acrobat").unwrap();
+        let actions: Vec<_> = queue.iter().map(|p| p.command["action"].clone()).collect();
+        assert_eq!(actions, vec![json!("b"), json!("c")]);
+    }
+
+    #[test]
+    fn test_pending_queue_disabled_by_default() {
+        let state = test_state();
+        assert!(!state.enqueue_pending(queued_packet("mcp", "a")));
+        assert!(state.pending.is_empty());
+    }
+
+    fn register_message(application: &str) -> RegisterMessage {
+        RegisterMessage {
+            application: application.to_string(),
+            instance_id: None,
+            version: None,
+            client_name: None,
+            resume_token: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_registration_ends_auto_launch_wait() {
+        // A long poll interval, so only the registration can end the wait early
+        let state = test_state().with_auto_launch_poll(Duration::from_secs(10));
+        let waiting = state.clone();
+        let wait = tokio::spawn(async move {
+            wait_for_application(&waiting, "acrobat", Duration::from_secs(20)).await
+        });
+
+        sleep(Duration::from_millis(50)).await;
+        assert!(!wait.is_finished());
+        let registered_at = Instant::now();
+        state.register_client("acrobat-1".to_string(), register_message("acrobat"));
+
+        let found = tokio::time::timeout(Duration::from_secs(2), wait).await.unwrap().unwrap();
+        assert!(found);
+        assert!(registered_at.elapsed() < Duration::from_millis(500));
+    }
+
+    #[tokio::test]
+    async fn test_auto_launch_wait_times_out() {
+        let state = test_state().with_auto_launch_poll(Duration::from_millis(20));
+        state.register_client("photoshop-1".to_string(), register_message("photoshop"));
+
+        let started = Instant::now();
+        assert!(!wait_for_application(&state, "acrobat", Duration::from_millis(100)).await);
+        assert!(started.elapsed() >= Duration::from_millis(100));
+        assert!(wait_for_application(&state, "photoshop", Duration::from_millis(100)).await);
+    }
+
+    async fn wait_until(mut condition: impl FnMut() -> bool) -> bool {
+        for _ in 0..200 {
+            if condition() {
+                return true;
+            }
+            sleep(Duration::from_millis(10)).await;
+        }
+        false
+    }
+
+    fn heartbeat_state() -> AppState {
+        test_state().with_heartbeat(Duration::from_millis(50), Duration::from_millis(50))
+    }
+
+    #[tokio::test]
+    async fn test_unresponsive_client_evicted() {
+        let state = heartbeat_state();
+        let addr = spawn_server(state.clone()).await;
+        tokio::spawn(run_heartbeat(state.clone()));
+
+        let mut ws = connect_client(addr).await;
+        register(&mut ws, "acrobat").await;
+        assert!(state.has_application_client("acrobat"));
+
+        // Never read again, so pings go unanswered
+        assert!(wait_until(|| !state.has_application_client("acrobat")).await);
+        assert!(state.clients.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_client_answering_pings_kept() {
+        let state = heartbeat_state();
+        let addr = spawn_server(state.clone()).await;
+        tokio::spawn(run_heartbeat(state.clone()));
+
+        let mut ws = connect_client(addr).await;
+        register(&mut ws, "acrobat").await;
+        let responder = tokio::spawn(async move {
+            while let Some(Ok(msg)) = ws.next().await {
+                if msg == WsMessage::Text(ENGINE_PING.to_string()) {
+                    ws.send(WsMessage::Text(ENGINE_PONG.to_string())).await.unwrap();
+                }
+            }
+        });
+
+        sleep(Duration::from_millis(400)).await;
+        assert!(state.has_application_client("acrobat"));
+        responder.abort();
+    }
+
+    #[tokio::test]
+    async fn test_metrics_count_routed_command() {
+        let state = test_state();
+        let addr = spawn_server(state.clone()).await;
+        let mut bridge = connect_client(addr).await;
+        register(&mut bridge, "acrobat").await;
+        let mut mcp = connect_client(addr).await;
+
+        send_command(&mut mcp, "acrobat", "getDocumentInfo").await;
+        next_event(&mut bridge, "command_packet").await;
+        send_command(&mut mcp, "photoshop", "getLayers").await;
+        next_event(&mut mcp, "packet_response").await;
+
+        let body = String::from_utf8(get_body(&state, "/metrics").await).unwrap();
+        assert!(body.contains("adobe_proxy_commands_total{application=\"acrobat\"} 1\n"));
+        assert!(body.contains("adobe_proxy_command_failures_total{application=\"acrobat\"} 0\n"));
+        assert!(body.contains("adobe_proxy_command_failures_total{application=\"photoshop\"} 1\n"));
+        assert!(body.contains("adobe_proxy_responses_total 0\n"));
+    }
+
+    #[test]
+    fn test_escape_label() {
+        assert_eq!(escape_label(r#"a"b\c"#), r#"a\"b\\c"#);
+    }
+
+    #[tokio::test]
+    async fn test_batched_frame_fully_processed() {
+        let state = test_state();
+        let addr = spawn_server(state.clone()).await;
+        let mut ws = connect_client(addr).await;
+
+        let register = encode_event("register", json!({ "application": "acrobat" }));
+        ws.send(WsMessage::Text(format!("{}\x1e{}", ENGINE_PING, register)))
+            .await
+            .unwrap();
+
+        assert_eq!(next_text(&mut ws).await, ENGINE_PONG);
+        let response = next_event(&mut ws, "registration_response").await;
+        assert_eq!(response["status"], "success");
+        assert!(state.has_application_client("acrobat"));
+    }
+
+    #[test]
+    fn test_stamp_packet_response() {
+        let mut packet = json!({"senderId": "a", "status": "SUCCESS", "timestamp": null});
+        stamp_packet_response(&mut packet, None);
+        assert!(packet["timestamp"].as_i64().unwrap() > 0);
+        assert!(packet.get("requestId").is_none());
+
+        // Values already set by the bridge are kept
+        let mut packet = json!({"senderId": "a", "requestId": "r-1", "timestamp": 5});
+        stamp_packet_response(&mut packet, Some(&json!("other")));
+        assert_eq!(packet["requestId"], "r-1");
+        assert_eq!(packet["timestamp"], 5);
+    }
+
+    #[tokio::test]
+    async fn test_failure_response_echoes_request_id() {
+        let state = test_state();
+        let addr = spawn_server(state.clone()).await;
+        let mut mcp = connect_client(addr).await;
+
+        mcp.send(WsMessage::Text(encode_event(
+            "command_packet",
+            json!({
+                "application": "photoshop",
+                "command": { "action": "getLayers", "requestId": "req-7" },
+            }),
+        )))
+        .await
+        .unwrap();
+
+        let response = next_event(&mut mcp, "packet_response").await;
+        assert_eq!(response["status"], "FAILURE");
+        assert_eq!(response["requestId"], "req-7");
+        assert!(response["timestamp"].as_i64().is_some());
+    }
+
+    #[tokio::test]
+    async fn test_commands_over_rate_limit_rejected() {
+        let state = test_state().with_rate_limit(3);
+        let addr = spawn_server(state.clone()).await;
+        let mut bridge = connect_client(addr).await;
+        register(&mut bridge, "acrobat").await;
+        let mut mcp = connect_client(addr).await;
+
+        for action in ["a", "b", "c", "d"] {
+            send_command(&mut mcp, "acrobat", action).await;
+        }
+        for expected in ["a", "b", "c"] {
+            let packet = next_event(&mut bridge, "command_packet").await;
+            assert_eq!(packet["command"]["action"], expected);
+        }
+
+        let rejected = next_event(&mut mcp, "packet_response").await;
+        assert_eq!(rejected["status"], "FAILURE");
+        assert!(rejected["message"].as_str().unwrap().starts_with("Rate limited"));
+    }
+
+    #[test]
+    fn test_token_bucket_refills() {
+        let mut bucket = TokenBucket::new(2);
+        assert!(bucket.try_acquire());
+        assert!(bucket.try_acquire());
+        assert!(!bucket.try_acquire());
+
+        bucket.last_refill -= Duration::from_secs(1);
+        assert!(bucket.try_acquire());
+        assert!(bucket.try_acquire());
+        assert!(!bucket.try_acquire());
+
+        let mut unlimited = TokenBucket::new(0);
+        assert!((0..1000).all(|_| unlimited.try_acquire()));
+    }
+
+    async fn register_instance(ws: &mut TestSocket, application: &str, instance: &str) -> Value {
+        ws.send(WsMessage::Text(encode_event(
+            "register",
+            json!({ "application": application, "instanceId": instance }),
+        )))
+        .await
+        .unwrap();
+        next_event(ws, "registration_response").await
+    }
+
+    async fn send_targeted(ws: &mut TestSocket, application: &str, instance: &str, action: &str) {
+        ws.send(WsMessage::Text(encode_event(
+            "command_packet",
+            json!({
+                "application": application,
+                "targetInstance": instance,
+                "command": { "action": action },
+            }),
+        )))
+        .await
+        .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_registration_response_includes_instance_id() {
+        let state = test_state();
+        let addr = spawn_server(state.clone()).await;
+
+        let mut named = connect_client(addr).await;
+        let response = register_instance(&mut named, "acrobat", "window-1").await;
+        assert_eq!(response["instanceId"], "window-1");
+
+        // Without an explicit id the client id doubles as the instance id
+        let mut unnamed = connect_client(addr).await;
+        let response = register(&mut unnamed, "acrobat").await;
+        let clients = get_json(&state, "/clients").await;
+        assert!(clients
+            .as_array()
+            .unwrap()
+            .iter()
+            .any(|c| c["id"] == response["instanceId"]));
+    }
+
+    async fn register_with_token(ws: &mut TestSocket, application: &str, token: &Value) -> Value {
+        ws.send(WsMessage::Text(encode_event(
+            "register",
+            json!({ "application": application, "resumeToken": token }),
+        )))
+        .await
+        .unwrap();
+        next_event(ws, "registration_response").await
+    }
+
+    /// Register a bridge, drop its connection, and wait for the proxy to notice
+    async fn register_and_drop(state: &AppState, addr: SocketAddr) -> Value {
+        let mut bridge = connect_client(addr).await;
+        let response = register(&mut bridge, "acrobat").await;
+        bridge.close(None).await.unwrap();
+        assert!(wait_until(|| !state.has_application_client("acrobat")).await);
+        response
+    }
+
+    #[tokio::test]
+    async fn test_registration_issues_resume_token() {
+        let state = test_state();
+        let addr = spawn_server(state.clone()).await;
+
+        let mut bridge = connect_client(addr).await;
+        let response = register(&mut bridge, "acrobat").await;
+        assert!(response["resumeToken"]
+            .as_str()
+            .is_some_and(|t| !t.is_empty()));
+        assert_eq!(response["resumed"], false);
+
+        // Registering again on the same connection keeps the token
+        let again = register(&mut bridge, "acrobat").await;
+        assert_eq!(again["resumeToken"], response["resumeToken"]);
+    }
+
+    #[tokio::test]
+    async fn test_resume_token_restores_session() {
+        let state = test_state().with_pending_queue_size(4);
+        let addr = spawn_server(state.clone()).await;
+        let first = register_and_drop(&state, addr).await;
+
+        let mut mcp = connect_client(addr).await;
+        send_command(&mut mcp, "acrobat", "getDocumentInfo").await;
+        wait_for_pending(&state, "acrobat", 1).await;
+
+        let mut bridge = connect_client(addr).await;
+        let response = register_with_token(&mut bridge, "acrobat", &first["resumeToken"]).await;
+        assert_eq!(response["resumed"], true);
+        assert_eq!(response["resumeToken"], first["resumeToken"]);
+        assert_eq!(response["instanceId"], first["instanceId"]);
+
+        let command = next_event(&mut bridge, "command_packet").await;
+        assert_eq!(command["command"]["action"], "getDocumentInfo");
+    }
+
+    #[tokio::test]
+    async fn test_expired_resume_token_rejected() {
+        let state = test_state().with_resume_ttl(Duration::from_millis(20));
+        let addr = spawn_server(state.clone()).await;
+        let first = register_and_drop(&state, addr).await;
+        sleep(Duration::from_millis(100)).await;
+
+        let mut bridge = connect_client(addr).await;
+        let response = register_with_token(&mut bridge, "acrobat", &first["resumeToken"]).await;
+        assert_eq!(response["resumed"], false);
+        assert_ne!(response["resumeToken"], first["resumeToken"]);
+        assert_ne!(response["instanceId"], first["instanceId"]);
+        assert!(!state
+            .resume_tokens
+            .contains_key(first["resumeToken"].as_str().unwrap()));
+    }
+
+    #[tokio::test]
+    async fn test_targeted_command_delivered_to_one_instance() {
+        let state = test_state();
+        let addr = spawn_server(state.clone()).await;
+        let mut first = connect_client(addr).await;
+        register_instance(&mut first, "acrobat", "a").await;
+        let mut second = connect_client(addr).await;
+        register_instance(&mut second, "acrobat", "b").await;
+        let mut mcp = connect_client(addr).await;
+
+        send_targeted(&mut mcp, "acrobat", "b", "targeted").await;
+        send_command(&mut mcp, "acrobat", "broadcast").await;
+
+        // The first instance sees only the broadcast; the second sees both, in order
+        let packet = next_event(&mut first, "command_packet").await;
+        assert_eq!(packet["command"]["action"], "broadcast");
+        let packet = next_event(&mut second, "command_packet").await;
+        assert_eq!(packet["command"]["action"], "targeted");
+        let packet = next_event(&mut second, "command_packet").await;
+        assert_eq!(packet["command"]["action"], "broadcast");
+    }
+
+    #[tokio::test]
+    async fn test_targeted_command_for_unknown_instance_fails() {
+        let state = test_state();
+        let addr = spawn_server(state.clone()).await;
+        let mut bridge = connect_client(addr).await;
+        register_instance(&mut bridge, "acrobat", "a").await;
+        let mut mcp = connect_client(addr).await;
+
+        send_targeted(&mut mcp, "acrobat", "missing", "getDocumentInfo").await;
+        let response = next_event(&mut mcp, "packet_response").await;
+        assert_eq!(response["status"], "FAILURE");
+        assert!(response["message"].as_str().unwrap().contains("missing"));
+    }
+
+    #[test]
+    fn test_parse_launch_map_json() {
+        let map = parse_launch_map(
+            r#"{"Acrobat": "D:/Adobe/Acrobat.exe", "photoshop": ["a.exe", "b.exe"]}"#,
+            false,
+        )
+        .unwrap();
+        assert_eq!(map["acrobat"], vec![PathBuf::from("D:/Adobe/Acrobat.exe")]);
+        assert_eq!(map["photoshop"], vec![PathBuf::from("a.exe"), PathBuf::from("b.exe")]);
+    }
+
+    #[test]
+    fn test_parse_launch_map_toml() {
+        let map = parse_launch_map(
+            "acrobat = \"/opt/acrobat\"\nillustrator = [\"/opt/ai-2025\", \"/opt/ai-2024\"]\n",
+            true,
+        )
+        .unwrap();
+        assert_eq!(map["acrobat"], vec![PathBuf::from("/opt/acrobat")]);
+        assert_eq!(map["illustrator"].len(), 2);
+    }
+
+    #[test]
+    fn test_parse_launch_map_rejects_invalid() {
+        assert!(parse_launch_map(r#"{"acrobat": 42}"#, false).is_err());
+        assert!(parse_launch_map("acrobat = ", true).is_err());
+    }
+
+    const SAMPLE_CONFIG: &str = r#"
+host = "0.0.0.0"
+port = 4000
+auto_launch = true
+auto_launch_timeout_ms = 5000
+auto_launch_poll_ms = 100
+auth_token = "from-file"
+ping_interval_ms = 10000
+max_commands_per_sec = 50
+
+[launch]
+Acrobat = "D:/Adobe/Acrobat.exe"
+photoshop = ["a.exe", "b.exe"]
+"#;
+
+    fn args_with_config(argv: &[&str], config: &Config) -> Args {
+        let matches = Args::command().get_matches_from(argv);
+        let mut args = Args::from_arg_matches(&matches).unwrap();
+        args.apply_config(config, &matches);
+        args
+    }
+
+    #[test]
+    fn test_parse_config() {
+        let config = parse_config(SAMPLE_CONFIG).unwrap();
+        assert_eq!(config.port, Some(4000));
+        assert_eq!(config.auto_launch, Some(true));
+        assert!(config.pending_queue_size.is_none());
+
+        let launch = normalize_launch_map(config.launch);
+        assert_eq!(
+            launch["acrobat"],
+            vec![PathBuf::from("D:/Adobe/Acrobat.exe")]
+        );
+        assert_eq!(launch["photoshop"].len(), 2);
+    }
+
+    #[test]
+    fn test_config_fills_unset_args() {
+        let config = parse_config(SAMPLE_CONFIG).unwrap();
+        let args = args_with_config(&["adobe-proxy"], &config);
+        assert_eq!(args.host, "0.0.0.0");
+        assert_eq!(args.port, 4000);
+        assert!(args.auto_launch);
+        assert_eq!(args.auto_launch_timeout_ms, 5000);
+        assert_eq!(args.auto_launch_poll_ms, 100);
+        assert_eq!(args.auth_token.as_deref(), Some("from-file"));
+        assert_eq!(args.max_commands_per_sec, 50);
+        // Keys missing from the file keep their defaults
+        assert_eq!(args.ping_timeout_ms, 20000);
+    }
+
+    #[test]
+    fn test_cli_overrides_config() {
+        let config = parse_config(SAMPLE_CONFIG).unwrap();
+        let args = args_with_config(
+            &[
+                "adobe-proxy",
+                "--port",
+                "5000",
+                "--auth-token",
+                "from-cli",
+                "--ping-interval-ms",
+                "25000",
+            ],
+            &config,
+        );
+        assert_eq!(args.port, 5000);
+        assert_eq!(args.auth_token.as_deref(), Some("from-cli"));
+        // Passing the default value explicitly still wins over the file
+        assert_eq!(args.ping_interval_ms, 25000);
+        assert_eq!(args.host, "0.0.0.0");
+    }
+
+    #[test]
+    fn test_log_format_arg() {
+        let args = Args::try_parse_from(["adobe-proxy"]).unwrap();
+        assert_eq!(args.log_format, LogFormat::Pretty);
+
+        let args = Args::try_parse_from(["adobe-proxy", "--log-format", "json"]).unwrap();
+        assert_eq!(args.log_format, LogFormat::Json);
+
+        let err = Args::try_parse_from(["adobe-proxy", "--log-format", "xml"]).unwrap_err();
+        assert_eq!(err.kind(), clap::error::ErrorKind::InvalidValue);
+
+        let config = parse_config("log_format = \"json\"").unwrap();
+        assert_eq!(
+            args_with_config(&["adobe-proxy"], &config).log_format,
+            LogFormat::Json
+        );
+    }
+
+    #[test]
+    fn test_malformed_config_error() {
+        let err = parse_config("port = \"not a number\"").unwrap_err();
+        assert!(err.contains("port"), "{}", err);
+
+        let err = parse_config("prot = 3001").unwrap_err();
+        assert!(err.contains("unknown field"), "{}", err);
+
+        let path = std::env::temp_dir().join(format!("adobe-proxy-config-{}.toml", Uuid::new_v4()));
+        std::fs::write(&path, "port = [").unwrap();
+        let err = load_config(&path).unwrap_err();
+        std::fs::remove_file(&path).unwrap();
+        assert!(err.starts_with("Invalid config"), "{}", err);
+        assert!(err.contains(&path.display().to_string()), "{}", err);
+    }
+
+    #[test]
+    fn test_launch_candidates_prefer_override() {
+        let mut map = LaunchMap::new();
+        map.insert("acrobat".to_string(), vec![PathBuf::from("/custom/acrobat")]);
+        assert_eq!(
+            launch_candidates("acrobat", &map),
+            vec![PathBuf::from("/custom/acrobat")]
+        );
+        // Applications without an override keep the built-in list
+        assert_eq!(
+            launch_candidates("photoshop", &map).len(),
+            builtin_candidates("photoshop").len()
+        );
+        assert!(launch_candidates("unknown", &map).is_empty());
+    }
+
+    #[cfg(target_os = "macos")]
+    #[test]
+    fn test_builtin_candidates_macos() {
+        assert!(builtin_candidates("acrobat")
+            .iter()
+            .all(|path| path.starts_with("/Applications/") && path.ends_with(".app")));
+    }
+
+    #[cfg(not(target_os = "macos"))]
+    #[test]
+    fn test_builtin_candidates_windows() {
+        assert!(builtin_candidates("acrobat")
+            .iter()
+            .all(|path| path.starts_with(r"C:\") && path.ends_with(".exe")));
+    }
+
+    #[test]
+    fn test_status_reports_configured_port() {
+        let state = AppState::new(4000, false, Duration::from_millis(0));
+        assert_eq!(state.get_status().port, 4000);
+    }
+
+    #[test]
+    fn test_constant_time_eq() {
+        assert!(constant_time_eq("secret", "secret"));
+        assert!(!constant_time_eq("secret", "secreT"));
+        assert!(!constant_time_eq("secret", "secret2"));
+    }
+}
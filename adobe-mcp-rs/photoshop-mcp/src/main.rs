@@ -7,71 +7,61 @@ mod tools;
 
 use clap::Parser;
 use serde_json::json;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
-use tokio::io::{AsyncBufReadExt, BufReader};
-use tracing::{error, info};
-use adobe_common::{McpRequest, McpResponse, error_codes};
+use tokio::io::BufReader;
+use tracing::{debug, error, info};
+use adobe_common::stdio::{self, Framing};
+use adobe_common::{AdobeError, McpError, McpRequest, McpResponse, TOOLS_PAGE_SIZE, error_codes, paginate};
 
 #[derive(Parser, Debug)]
 #[command(author, version, about, long_about = None)]
 struct Args {
-    /// WebSocket proxy URL
+    /// WebSocket proxy URL (`ws://` or `wss://` selects the transport)
     #[arg(long, env = "PHOTOSHOP_PROXY_URL", default_value = "ws://localhost:3001")]
     proxy_url: String,
 
     /// Command timeout in milliseconds
     #[arg(long, env = "PHOTOSHOP_TIMEOUT", default_value = "30000")]
     timeout: u64,
+
+    /// Shared secret for a proxy started with `--auth-token`
+    #[arg(long, env = "ADOBE_PROXY_AUTH_TOKEN")]
+    auth_token: Option<String>,
+
+    /// Stdio message framing: `line` (one JSON message per line) or `lsp`
+    /// (`Content-Length` headers, allowing multi-line JSON)
+    #[arg(long, env = "PHOTOSHOP_MCP_FRAMING", default_value = "line")]
+    framing: Framing,
 }
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
-    // CRITICAL: Log to stderr to avoid corrupting JSON-RPC stdout stream
-    tracing_subscriber::fmt()
-        .with_writer(std::io::stderr)
-        .init();
+    // CRITICAL: Log to stderr to avoid corrupting JSON-RPC stdout stream;
+    // only `notifications/message` log entries are written to stdout
+    adobe_common::logging::init(print_log_message);
 
     let args = Args::parse();
+    stdio::set_output_framing(args.framing);
     info!("Starting photoshop-mcp with proxy: {}", args.proxy_url);
 
     // Initialize WebSocket client
-    let client = Arc::new(client::PhotoshopClient::new(&args.proxy_url, args.timeout).await?);
+    let client = Arc::new(client::PhotoshopClient::new(&args.proxy_url, args.timeout, args.auth_token.as_deref()).await?);
     info!("Connected to proxy at {}", args.proxy_url);
 
     // Start JSON-RPC loop over stdio
     info!("Listening on stdin for MCP requests...");
 
-    let stdin = tokio::io::stdin();
-    let reader = BufReader::new(stdin);
-    let mut lines = reader.lines();
+    let mut reader = BufReader::new(tokio::io::stdin());
+    let session = Session::default();
 
     loop {
         tokio::select! {
-            line_result = lines.next_line() => {
-                match line_result {
-                    Ok(Some(line)) => {
-                        let line = line.trim();
-                        if line.is_empty() {
-                            continue;
-                        }
-
-                        let request: Result<McpRequest, _> = serde_json::from_str(line);
-                        match request {
-                            Ok(req) => {
-                                let _id = req.id.clone();
-                                let response = handle_request(req, &client).await;
-                                let response_json = serde_json::to_string(&response)?;
-                                println!("{}", response_json);
-                            }
-                            Err(e) => {
-                                error!("Failed to parse JSON-RPC: {}", e);
-                                let err_resp = McpResponse::error(
-                                    json!(null),
-                                    error_codes::PARSE_ERROR,
-                                    format!("Parse error: {}", e)
-                                );
-                                println!("{}", serde_json::to_string(&err_resp)?);
-                            }
+            message_result = args.framing.read_message(&mut reader) => {
+                match message_result {
+                    Ok(Some(message)) => {
+                        if let Some(output) = handle_line(&message, &session, &client).await? {
+                            stdio::write_message(&output);
                         }
                     }
                     Ok(None) => break, // EOF
@@ -88,24 +78,121 @@ async fn main() -> anyhow::Result<()> {
         }
     }
 
+    if let Err(e) = client.close().await {
+        debug!("Failed to close proxy connection: {}", e);
+    }
     info!("Photoshop MCP server shutting down. Goodbye.");
     Ok(())
 }
 
+/// Handshake state for the one MCP client on stdio
+#[derive(Debug, Default)]
+struct Session {
+    /// Set by the first `initialize`; until then only `initialize` and
+    /// `ping` are answered
+    initialized: AtomicBool,
+}
+
+/// Dispatch one stdin message holding either a single request or a batch array
+///
+/// Returns the message to write back, or `None` when the message (or every
+/// element of a batch) was a notification.
+async fn handle_line(
+    line: &str,
+    session: &Session,
+    client: &Arc<client::PhotoshopClient>,
+) -> anyhow::Result<Option<String>> {
+    let parsed = serde_json::from_str::<serde_json::Value>(line).and_then(|value| match value {
+        serde_json::Value::Array(batch) => Ok(Err(batch)),
+        value => serde_json::from_value::<McpRequest>(value).map(Ok),
+    });
+
+    let batch = match parsed {
+        Ok(Ok(req)) => {
+            return match handle_request(req, session, client).await {
+                Some(response) => Ok(Some(serde_json::to_string(&response)?)),
+                None => Ok(None),
+            };
+        }
+        Ok(Err(batch)) => batch,
+        Err(e) => {
+            error!("Failed to parse JSON-RPC: {}", e);
+            let err_resp = McpResponse::error(
+                json!(null),
+                error_codes::PARSE_ERROR,
+                format!("Parse error: {}", e)
+            );
+            return Ok(Some(serde_json::to_string(&err_resp)?));
+        }
+    };
+
+    if batch.is_empty() {
+        let err_resp = McpResponse::error(
+            json!(null),
+            error_codes::INVALID_REQUEST,
+            "Invalid Request: empty batch",
+        );
+        return Ok(Some(serde_json::to_string(&err_resp)?));
+    }
+
+    let mut responses = Vec::new();
+    for item in batch {
+        match serde_json::from_value::<McpRequest>(item) {
+            Ok(req) => {
+                if let Some(response) = handle_request(req, session, client).await {
+                    responses.push(response);
+                }
+            }
+            Err(e) => responses.push(McpResponse::error(
+                json!(null),
+                error_codes::INVALID_REQUEST,
+                format!("Invalid Request: {}", e),
+            )),
+        }
+    }
+
+    if responses.is_empty() {
+        return Ok(None);
+    }
+    Ok(Some(serde_json::to_string(&responses)?))
+}
+
+/// Answer a request, or return `None` for a notification (no `id`)
+///
+/// Requests other than `initialize` and `ping` are rejected with
+/// `INVALID_REQUEST` until the client has sent `initialize`; a repeated
+/// `initialize` gets the same result again.
 async fn handle_request(
     req: McpRequest,
+    session: &Session,
     client: &Arc<client::PhotoshopClient>,
-) -> McpResponse {
+) -> Option<McpResponse> {
+    if req.id.is_none() {
+        handle_notification(&req.method);
+        return None;
+    }
     let id = req.id.clone();
 
-    match req.method.as_str() {
+    if req.method == "initialize" {
+        if session.initialized.swap(true, Ordering::SeqCst) {
+            debug!("Client sent initialize again");
+        }
+    } else if req.method != "ping" && !session.initialized.load(Ordering::SeqCst) {
+        return Some(McpResponse::error(
+            id.unwrap_or(json!(null)),
+            error_codes::INVALID_REQUEST,
+            format!("Invalid Request: {} sent before initialize", req.method),
+        ));
+    }
+
+    let response = match req.method.as_str() {
         "ping" => McpResponse::success(id.unwrap_or(json!(null)), json!({"status": "ok"})),
 
         "initialize" => McpResponse::success(
             id.unwrap_or(json!(null)),
             json!({
                 "protocolVersion": "2024-11-05",
-                "capabilities": { "tools": { "listChanged": false } },
+                "capabilities": { "tools": { "listChanged": false }, "logging": {} },
                 "serverInfo": {
                     "name": "photoshop-mcp",
                     "version": env!("CARGO_PKG_VERSION")
@@ -114,8 +201,31 @@ async fn handle_request(
         ),
 
         "tools/list" => {
-            let tools = tools::get_tool_definitions();
-            McpResponse::success(id.unwrap_or(json!(null)), json!({"tools": tools}))
+            let cursor = req.params.as_ref().and_then(|p| p.get("cursor")).and_then(|c| c.as_str());
+            match paginate(&tools::get_tool_definitions(), cursor, TOOLS_PAGE_SIZE) {
+                Ok((tools, next_cursor)) => {
+                    let mut result = json!({"tools": tools});
+                    if let Some(next_cursor) = next_cursor {
+                        result["nextCursor"] = json!(next_cursor);
+                    }
+                    McpResponse::success(id.unwrap_or(json!(null)), result)
+                }
+                Err(e) => McpResponse::with_error(id.unwrap_or(json!(null)), e.into()),
+            }
+        }
+
+        "logging/setLevel" => {
+            match req.params.as_ref().and_then(|p| p.get("level")).and_then(|l| l.as_str()) {
+                Some(level) => match adobe_common::logging::set_level(level) {
+                    Ok(_) => McpResponse::success(id.unwrap_or(json!(null)), json!({})),
+                    Err(e) => McpResponse::with_error(id.unwrap_or(json!(null)), e.into()),
+                },
+                None => McpResponse::error(
+                    id.unwrap_or(json!(null)),
+                    error_codes::INVALID_PARAMS,
+                    "Missing level",
+                ),
+            }
         }
 
         "tools/call" => {
@@ -132,13 +242,16 @@ async fn handle_request(
                                 "isError": false
                             }),
                         ),
-                        Err(e) => McpResponse::success(
-                            id.unwrap_or(json!(null)),
-                            json!({
-                                "content": [{ "type": "text", "text": format!("Error: {}", e) }],
-                                "isError": true
-                            }),
-                        ),
+                        Err(e) => match rpc_error(e) {
+                            Ok(error) => McpResponse::with_error(id.unwrap_or(json!(null)), error),
+                            Err(e) => McpResponse::success(
+                                id.unwrap_or(json!(null)),
+                                json!({
+                                    "content": [{ "type": "text", "text": format!("Error: {}", e) }],
+                                    "isError": true
+                                }),
+                            ),
+                        },
                     }
                 } else {
                     McpResponse::error(
@@ -161,6 +274,173 @@ async fn handle_request(
             error_codes::METHOD_NOT_FOUND,
             "Method not found",
         ),
+    };
+
+    Some(response)
+}
+
+/// Typed failures that should surface as JSON-RPC errors; the original error
+/// comes back for tool-level failures, which stay `isError` results
+fn rpc_error(error: anyhow::Error) -> Result<McpError, anyhow::Error> {
+    match error.downcast::<AdobeError>() {
+        Ok(error) if error.to_rpc_code() != error_codes::COMMAND_FAILED => Ok(error.into()),
+        Ok(error) => Err(error.into()),
+        Err(error) => Err(error),
+    }
+}
+
+/// Write a log entry to the client as a `notifications/message` message
+fn print_log_message(level: &str, data: serde_json::Value) {
+    let notification = json!({
+        "jsonrpc": "2.0",
+        "method": "notifications/message",
+        "params": { "level": level, "logger": "photoshop-mcp", "data": data }
+    });
+    stdio::write_message(&notification.to_string());
+}
+
+/// Side effects for client notifications, which never get a response
+fn handle_notification(method: &str) {
+    match method {
+        "notifications/initialized" => info!("Client finished initialization"),
+        "notifications/cancelled" => debug!("Client cancelled a request"),
+        other => debug!("Ignoring notification: {}", other),
     }
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures_util::StreamExt;
+    use serde_json::Value;
+
+    /// Session past the `initialize` handshake
+    fn initialized_session() -> Session {
+        Session {
+            initialized: AtomicBool::new(true),
+        }
+    }
+
+    /// Client connected to a local WebSocket that accepts and ignores everything
+    async fn test_client() -> Arc<client::PhotoshopClient> {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            let mut ws = tokio_tungstenite::accept_async(stream).await.unwrap();
+            while let Some(Ok(_)) = ws.next().await {}
+        });
+        Arc::new(
+            client::PhotoshopClient::new(&format!("ws://{}", addr), 1000, None)
+                .await
+                .unwrap(),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_batch_with_call_and_notification() {
+        let client = test_client().await;
+        let line = r#"[{"jsonrpc":"2.0","id":1,"method":"tools/list"},{"jsonrpc":"2.0","method":"notifications/initialized"}]"#;
+
+        let output = handle_line(line, &initialized_session(), &client).await.unwrap().unwrap();
+        let responses: Value = serde_json::from_str(&output).unwrap();
+        let responses = responses.as_array().unwrap();
+        assert_eq!(responses.len(), 1);
+        assert_eq!(responses[0]["id"], 1);
+        assert!(responses[0]["result"]["tools"].is_array());
+    }
+
+    #[tokio::test]
+    async fn test_tools_list_pages_cover_every_tool() {
+        let client = test_client().await;
+        let mut names = Vec::new();
+        let mut cursor: Option<String> = None;
+
+        loop {
+            let params = match &cursor {
+                Some(cursor) => json!({ "cursor": cursor }),
+                None => json!({}),
+            };
+            let line = json!({"jsonrpc": "2.0", "id": 1, "method": "tools/list", "params": params}).to_string();
+            let output = handle_line(&line, &initialized_session(), &client).await.unwrap().unwrap();
+            let response: Value = serde_json::from_str(&output).unwrap();
+            let result = &response["result"];
+
+            for tool in result["tools"].as_array().unwrap() {
+                names.push(tool["name"].as_str().unwrap().to_string());
+            }
+            match result.get("nextCursor") {
+                Some(next) => cursor = Some(next.as_str().unwrap().to_string()),
+                None => break,
+            }
+        }
+
+        let expected: Vec<String> = tools::get_tool_definitions()
+            .iter()
+            .map(|tool| tool["name"].as_str().unwrap().to_string())
+            .collect();
+        assert_eq!(names, expected);
+    }
+
+    #[tokio::test]
+    async fn test_logging_set_level() {
+        let client = test_client().await;
+
+        let output = handle_line(
+            r#"{"jsonrpc":"2.0","id":1,"method":"logging/setLevel","params":{"level":"debug"}}"#,
+            &initialized_session(),
+            &client,
+        )
+        .await
+        .unwrap()
+        .unwrap();
+        let response: Value = serde_json::from_str(&output).unwrap();
+        assert_eq!(response["id"], 1);
+        assert_eq!(response["result"], json!({}));
+        assert!(response.get("error").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_batch_of_notifications_produces_no_output() {
+        let client = test_client().await;
+        let line = r#"[{"jsonrpc":"2.0","method":"notifications/initialized"}]"#;
+        assert!(handle_line(line, &initialized_session(), &client).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_notification_yields_no_output() {
+        let client = test_client().await;
+
+        let output = handle_line(r#"{"jsonrpc":"2.0","id":1,"method":"initialize","params":{}}"#, &initialized_session(), &client)
+            .await
+            .unwrap()
+            .unwrap();
+        let response: Value = serde_json::from_str(&output).unwrap();
+        assert_eq!(response["id"], 1);
+
+        let output = handle_line(r#"{"jsonrpc":"2.0","method":"notifications/initialized"}"#, &initialized_session(), &client)
+            .await
+            .unwrap();
+        assert!(output.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_requests_before_initialize_rejected() {
+        let client = test_client().await;
+        let session = Session::default();
+        let tools_list = r#"{"jsonrpc":"2.0","id":1,"method":"tools/list"}"#;
+
+        let output = handle_line(tools_list, &session, &client).await.unwrap().unwrap();
+        let response: Value = serde_json::from_str(&output).unwrap();
+        assert_eq!(response["error"]["code"], error_codes::INVALID_REQUEST);
+
+        let initialize = r#"{"jsonrpc":"2.0","id":2,"method":"initialize","params":{}}"#;
+        let first = handle_line(initialize, &session, &client).await.unwrap().unwrap();
+        let output = handle_line(tools_list, &session, &client).await.unwrap().unwrap();
+        let response: Value = serde_json::from_str(&output).unwrap();
+        assert!(response["result"]["tools"].is_array());
+
+        let again = handle_line(initialize, &session, &client).await.unwrap().unwrap();
+        assert_eq!(again, first);
+    }
+}
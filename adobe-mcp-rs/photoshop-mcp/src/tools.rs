@@ -1,6 +1,7 @@
 //! Photoshop tool definitions and handlers
 
 use crate::client::PhotoshopClient;
+use adobe_common::RgbColor;
 use anyhow::{anyhow, Result};
 use serde_json::{json, Value};
 use std::sync::Arc;
@@ -34,13 +35,18 @@ pub fn get_tool_definitions() -> Vec<Value> {
                         "default": 72
                     },
                     "fill_color": {
-                        "type": "object",
-                        "properties": {
-                            "red": { "type": "integer" },
-                            "green": { "type": "integer" },
-                            "blue": { "type": "integer" }
-                        },
-                        "description": "Background fill color"
+                        "description": "Background fill color as \"#RRGGBB\" or {red, green, blue} (0-255)",
+                        "oneOf": [
+                            { "type": "string" },
+                            {
+                                "type": "object",
+                                "properties": {
+                                    "red": { "type": "integer" },
+                                    "green": { "type": "integer" },
+                                    "blue": { "type": "integer" }
+                                }
+                            }
+                        ]
                     },
                     "color_mode": {
                         "type": "string",
@@ -142,9 +148,214 @@ pub fn get_tool_definitions() -> Vec<Value> {
                 "required": ["layer_name", "prompt"]
             }
         }),
+        json!({
+            "name": "resize_image",
+            "description": "Resample the current document to a new size",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "width": {
+                        "type": "integer",
+                        "description": "New width in pixels",
+                        "minimum": 1
+                    },
+                    "height": {
+                        "type": "integer",
+                        "description": "New height in pixels",
+                        "minimum": 1
+                    },
+                    "resolution": {
+                        "type": "integer",
+                        "description": "New resolution in pixels/inch (default: unchanged)",
+                        "minimum": 1
+                    },
+                    "resample_method": {
+                        "type": "string",
+                        "description": "Interpolation used to resample",
+                        "enum": RESAMPLE_METHODS,
+                        "default": "bicubic"
+                    }
+                },
+                "required": ["width", "height"]
+            }
+        }),
+        json!({
+            "name": "resize_canvas",
+            "description": "Change the canvas size of the current document without resampling",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "width": {
+                        "type": "integer",
+                        "description": "New canvas width in pixels",
+                        "minimum": 1
+                    },
+                    "height": {
+                        "type": "integer",
+                        "description": "New canvas height in pixels",
+                        "minimum": 1
+                    },
+                    "anchor": {
+                        "type": "string",
+                        "description": "Point of the existing image that stays fixed",
+                        "enum": ANCHORS,
+                        "default": "middleCenter"
+                    }
+                },
+                "required": ["width", "height"]
+            }
+        }),
+        json!({
+            "name": "apply_filter",
+            "description": "Apply a filter to a layer",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "filter": {
+                        "type": "string",
+                        "description": "Filter to apply",
+                        "enum": FILTERS
+                    },
+                    "layer_name": {
+                        "type": "string",
+                        "description": "Layer to filter (default: active layer)"
+                    },
+                    "parameters": {
+                        "type": "object",
+                        "description": "Filter settings, e.g. {\"radius\": 4} for gaussianBlur or {\"amount\": 50, \"radius\": 1, \"threshold\": 0} for unsharpMask"
+                    }
+                },
+                "required": ["filter"]
+            }
+        }),
+        json!({
+            "name": "apply_adjustment",
+            "description": "Apply a tonal or color adjustment to a layer",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "adjustment": {
+                        "type": "string",
+                        "description": "Adjustment to apply",
+                        "enum": ADJUSTMENTS
+                    },
+                    "layer_name": {
+                        "type": "string",
+                        "description": "Layer to adjust (default: active layer)"
+                    },
+                    "parameters": {
+                        "type": "object",
+                        "description": "Adjustment settings, e.g. {\"brightness\": 20, \"contrast\": 10}, {\"hue\": 0, \"saturation\": -30, \"lightness\": 0} or {\"inputBlack\": 10, \"inputWhite\": 245, \"gamma\": 1.0}"
+                    }
+                },
+                "required": ["adjustment"]
+            }
+        }),
+        json!({
+            "name": "export_layers",
+            "description": "Export each top-level layer, or the named layers, to its own file",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "output_dir": {
+                        "type": "string",
+                        "description": "Directory to write the files to"
+                    },
+                    "layers": {
+                        "type": "array",
+                        "items": { "type": "string" },
+                        "description": "Names of the layers to export (default: every top-level layer)"
+                    },
+                    "name_pattern": {
+                        "type": "string",
+                        "description": "File name pattern; {name} is replaced by the layer name and the extension comes from file_type",
+                        "default": "{name}"
+                    },
+                    "file_type": {
+                        "type": "string",
+                        "description": "File format",
+                        "enum": EXPORT_FILE_TYPES,
+                        "default": "PNG"
+                    }
+                },
+                "required": ["output_dir"]
+            }
+        }),
+        json!({
+            "name": "set_layer_properties",
+            "description": "Change the opacity, visibility, blend mode or name of an existing layer",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "layer_name": {
+                        "type": "string",
+                        "description": "Layer to change"
+                    },
+                    "opacity": {
+                        "type": "integer",
+                        "description": "Opacity (0-100)",
+                        "minimum": 0,
+                        "maximum": 100
+                    },
+                    "visible": {
+                        "type": "boolean",
+                        "description": "Show or hide the layer"
+                    },
+                    "blend_mode": {
+                        "type": "string",
+                        "description": "Blend mode, e.g. NORMAL or MULTIPLY"
+                    },
+                    "rename": {
+                        "type": "string",
+                        "description": "New name for the layer"
+                    }
+                },
+                "required": ["layer_name"]
+            }
+        }),
     ]
 }
 
+/// `file_type` values for `export_layers`
+const EXPORT_FILE_TYPES: [&str; 2] = ["PNG", "JPG"];
+
+/// `filter` values for `apply_filter`
+const FILTERS: [&str; 6] = [
+    "gaussianBlur",
+    "motionBlur",
+    "sharpen",
+    "unsharpMask",
+    "addNoise",
+    "median",
+];
+
+/// `adjustment` values for `apply_adjustment`
+const ADJUSTMENTS: [&str; 3] = ["brightnessContrast", "hueSaturation", "levels"];
+
+/// `resample_method` values for `resize_image`
+const RESAMPLE_METHODS: [&str; 7] = [
+    "bicubic",
+    "bicubicSharper",
+    "bicubicSmoother",
+    "bilinear",
+    "nearestNeighbor",
+    "preserveDetails",
+    "automatic",
+];
+
+/// `anchor` values for `resize_canvas`
+const ANCHORS: [&str; 9] = [
+    "topLeft",
+    "topCenter",
+    "topRight",
+    "middleLeft",
+    "middleCenter",
+    "middleRight",
+    "bottomLeft",
+    "bottomCenter",
+    "bottomRight",
+];
+
 /// Handle tool call and route to appropriate function
 pub async fn handle_tool_call(
     client: &Arc<PhotoshopClient>,
@@ -159,6 +370,12 @@ pub async fn handle_tool_call(
         "get_layers" => get_layers(client, args).await,
         "create_pixel_layer" => create_pixel_layer(client, args).await,
         "generate_image" => generate_image(client, args).await,
+        "resize_image" => resize_image(client, args).await,
+        "resize_canvas" => resize_canvas(client, args).await,
+        "apply_filter" => apply_filter(client, args).await,
+        "apply_adjustment" => apply_adjustment(client, args).await,
+        "export_layers" => export_layers(client, args).await,
+        "set_layer_properties" => set_layer_properties(client, args).await,
         _ => Err(anyhow!("Unknown tool: {}", tool_name)),
     }
 }
@@ -175,7 +392,11 @@ async fn create_document(client: &Arc<PhotoshopClient>, args: Value) -> Result<S
     let height = args.get("height").and_then(|v| v.as_i64()).unwrap_or(1080);
     let resolution = args.get("resolution").and_then(|v| v.as_i64()).unwrap_or(72);
     let color_mode = args.get("color_mode").and_then(|v| v.as_str()).unwrap_or("RGB");
-    let fill_color = args.get("fill_color").cloned().unwrap_or(json!({"red": 255, "green": 255, "blue": 255}));
+    let fill_color = match args.get("fill_color") {
+        Some(value) => serde_json::from_value::<RgbColor>(value.clone())
+            .map_err(|e| anyhow!("Invalid fill_color: {}", e))?,
+        None => RgbColor::white(),
+    };
 
     let options = json!({
         "name": name,
@@ -259,9 +480,247 @@ async fn generate_image(client: &Arc<PhotoshopClient>, args: Value) -> Result<St
     Ok(format!("Generated image '{}' with prompt: {}", layer_name, prompt))
 }
 
+/// A required pixel dimension, rejected unless it is a positive integer
+fn positive_dimension(args: &Value, field: &str) -> Result<u64> {
+    let value = args
+        .get(field)
+        .ok_or_else(|| anyhow!("Missing required field: {}", field))?;
+    value
+        .as_u64()
+        .filter(|v| *v > 0)
+        .ok_or_else(|| anyhow!("{} must be a positive number of pixels, got {}", field, value))
+}
+
+fn checked_choice<'a>(field: &str, value: &'a str, allowed: &[&str]) -> Result<&'a str> {
+    if allowed.contains(&value) {
+        Ok(value)
+    } else {
+        Err(anyhow!(
+            "Invalid {}: {}. Must be one of: {}",
+            field,
+            value,
+            allowed.join(", ")
+        ))
+    }
+}
+
+/// A string option restricted to `allowed`, or `default` when absent
+fn one_of<'a>(args: &'a Value, field: &str, allowed: &[&str], default: &'a str) -> Result<&'a str> {
+    match args.get(field).and_then(|v| v.as_str()) {
+        Some(value) => checked_choice(field, value, allowed),
+        None => Ok(default),
+    }
+}
+
+/// A required string argument restricted to `allowed`
+fn required_one_of<'a>(args: &'a Value, field: &str, allowed: &[&str]) -> Result<&'a str> {
+    let value = args
+        .get(field)
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| anyhow!("Missing required field: {}", field))?;
+    checked_choice(field, value, allowed)
+}
+
+/// Width and height reported back by a resize, falling back to the requested size
+fn resized_dimensions(response: &adobe_common::CommandResponse, requested: &Value) -> (u64, u64) {
+    let data = PhotoshopClient::extract_response(response);
+    let dimension = |field: &str| {
+        data.and_then(|d| d.get(field))
+            .and_then(|v| v.as_u64())
+            .or_else(|| requested[field].as_u64())
+            .unwrap_or_default()
+    };
+    (dimension("width"), dimension("height"))
+}
+
+fn resize_image_options(args: &Value) -> Result<Value> {
+    let width = positive_dimension(args, "width")?;
+    let height = positive_dimension(args, "height")?;
+    let resolution = match args.get("resolution") {
+        Some(_) => Some(positive_dimension(args, "resolution")?),
+        None => None,
+    };
+    let resample_method = one_of(args, "resample_method", &RESAMPLE_METHODS, "bicubic")?;
+
+    Ok(json!({
+        "width": width,
+        "height": height,
+        "resolution": resolution,
+        "resampleMethod": resample_method
+    }))
+}
+
+async fn resize_image(client: &Arc<PhotoshopClient>, args: Value) -> Result<String> {
+    let options = resize_image_options(&args)?;
+
+    let response = client.send_command("resizeImage", options.clone()).await?;
+    let (width, height) = resized_dimensions(&response, &options);
+    Ok(format!("Image resized to {}x{}", width, height))
+}
+
+fn resize_canvas_options(args: &Value) -> Result<Value> {
+    let width = positive_dimension(args, "width")?;
+    let height = positive_dimension(args, "height")?;
+    let anchor = one_of(args, "anchor", &ANCHORS, "middleCenter")?;
+
+    Ok(json!({
+        "width": width,
+        "height": height,
+        "anchor": anchor
+    }))
+}
+
+async fn resize_canvas(client: &Arc<PhotoshopClient>, args: Value) -> Result<String> {
+    let options = resize_canvas_options(&args)?;
+
+    let response = client.send_command("resizeCanvas", options.clone()).await?;
+    let (width, height) = resized_dimensions(&response, &options);
+    Ok(format!("Canvas resized to {}x{}", width, height))
+}
+
+/// Options shared by `applyFilter` and `applyAdjustment`: the layer (absent
+/// for the active layer) and a settings object
+fn layer_operation_options(args: &Value) -> Result<Value> {
+    let parameters = match args.get("parameters") {
+        Some(Value::Object(parameters)) => Value::Object(parameters.clone()),
+        Some(other) => return Err(anyhow!("parameters must be an object, got {}", other)),
+        None => json!({}),
+    };
+
+    Ok(json!({
+        "layerName": args.get("layer_name").and_then(|v| v.as_str()),
+        "parameters": parameters
+    }))
+}
+
+/// Layer the command reports it changed, else the requested one
+fn affected_layer(response: &adobe_common::CommandResponse, args: &Value) -> String {
+    PhotoshopClient::extract_response(response)
+        .and_then(|data| data.get("layerName"))
+        .or_else(|| args.get("layer_name"))
+        .and_then(|v| v.as_str())
+        .map(|name| format!("layer '{}'", name))
+        .unwrap_or_else(|| "the active layer".to_string())
+}
+
+async fn apply_filter(client: &Arc<PhotoshopClient>, args: Value) -> Result<String> {
+    let filter = required_one_of(&args, "filter", &FILTERS)?;
+    let mut options = layer_operation_options(&args)?;
+    options["filter"] = json!(filter);
+
+    let response = client.send_command("applyFilter", options).await?;
+    Ok(format!("Applied {} to {}", filter, affected_layer(&response, &args)))
+}
+
+async fn apply_adjustment(client: &Arc<PhotoshopClient>, args: Value) -> Result<String> {
+    let adjustment = required_one_of(&args, "adjustment", &ADJUSTMENTS)?;
+    let mut options = layer_operation_options(&args)?;
+    options["adjustment"] = json!(adjustment);
+
+    let response = client.send_command("applyAdjustment", options).await?;
+    Ok(format!("Applied {} to {}", adjustment, affected_layer(&response, &args)))
+}
+
+fn export_layers_options(args: &Value) -> Result<Value> {
+    let output_dir = args
+        .get("output_dir")
+        .and_then(|v| v.as_str())
+        .filter(|dir| !dir.trim().is_empty())
+        .ok_or_else(|| anyhow!("Missing required field: output_dir"))?;
+    let name_pattern = args.get("name_pattern").and_then(|v| v.as_str()).unwrap_or("{name}");
+    if !name_pattern.contains("{name}") {
+        return Err(anyhow!(
+            "name_pattern must contain {{name}} so each layer gets its own file, got {}",
+            name_pattern
+        ));
+    }
+    let file_type = one_of(args, "file_type", &EXPORT_FILE_TYPES, "PNG")?;
+    let layers = match args.get("layers") {
+        Some(layers) => Some(
+            serde_json::from_value::<Vec<String>>(layers.clone())
+                .map_err(|_| anyhow!("layers must be an array of layer names"))?,
+        ),
+        None => None,
+    };
+
+    Ok(json!({
+        "outputDir": output_dir,
+        "layers": layers,
+        "namePattern": name_pattern,
+        "fileType": file_type
+    }))
+}
+
+async fn export_layers(client: &Arc<PhotoshopClient>, args: Value) -> Result<String> {
+    let options = export_layers_options(&args)?;
+
+    let response = client.send_command("exportLayers", options).await?;
+    match PhotoshopClient::extract_response(&response).and_then(|data| data.get("files")) {
+        Some(files) => Ok(format!("Exported layers:\n{}", serde_json::to_string_pretty(files)?)),
+        None => Ok("No layers exported".to_string()),
+    }
+}
+
+/// Options for `setLayerProperties`; only the properties given are sent
+fn set_layer_properties_options(args: &Value) -> Result<Value> {
+    let layer_name = args
+        .get("layer_name")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| anyhow!("Missing required field: layer_name"))?;
+
+    let mut properties = serde_json::Map::new();
+    if let Some(opacity) = args.get("opacity") {
+        let opacity = opacity
+            .as_u64()
+            .filter(|v| *v <= 100)
+            .ok_or_else(|| anyhow!("opacity must be between 0 and 100, got {}", opacity))?;
+        properties.insert("opacity".to_string(), json!(opacity));
+    }
+    if let Some(visible) = args.get("visible") {
+        let visible = visible
+            .as_bool()
+            .ok_or_else(|| anyhow!("visible must be true or false, got {}", visible))?;
+        properties.insert("visible".to_string(), json!(visible));
+    }
+    for (field, key) in [("blend_mode", "blendMode"), ("rename", "name")] {
+        if let Some(value) = args.get(field) {
+            let value = value
+                .as_str()
+                .filter(|s| !s.trim().is_empty())
+                .ok_or_else(|| anyhow!("{} must be a non-empty string, got {}", field, value))?;
+            properties.insert(key.to_string(), json!(value));
+        }
+    }
+    if properties.is_empty() {
+        return Err(anyhow!(
+            "Nothing to change: provide at least one of opacity, visible, blend_mode or rename"
+        ));
+    }
+
+    Ok(json!({
+        "layerName": layer_name,
+        "properties": properties
+    }))
+}
+
+async fn set_layer_properties(client: &Arc<PhotoshopClient>, args: Value) -> Result<String> {
+    let options = set_layer_properties_options(&args)?;
+
+    let response = client.send_command("setLayerProperties", options.clone()).await?;
+    let properties = PhotoshopClient::extract_response(&response)
+        .and_then(|data| data.get("properties"))
+        .unwrap_or(&options["properties"]);
+    Ok(format!(
+        "Updated layer '{}':\n{}",
+        options["layerName"].as_str().unwrap_or_default(),
+        serde_json::to_string_pretty(properties)?
+    ))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use adobe_common::test_util::MockProxy;
 
     #[test]
     fn test_tool_definitions() {
@@ -274,4 +733,181 @@ mod tests {
             assert!(tool.get("inputSchema").is_some());
         }
     }
+
+    fn tool(name: &str) -> Value {
+        get_tool_definitions()
+            .into_iter()
+            .find(|tool| tool["name"] == name)
+            .unwrap_or_else(|| panic!("{} is not defined", name))
+    }
+
+    #[test]
+    fn test_resize_tool_schemas() {
+        let image = tool("resize_image");
+        let schema = &image["inputSchema"];
+        assert_eq!(schema["required"], json!(["width", "height"]));
+        assert_eq!(schema["properties"]["width"]["type"], "integer");
+        assert_eq!(schema["properties"]["width"]["minimum"], 1);
+        assert_eq!(schema["properties"]["resolution"]["type"], "integer");
+        assert_eq!(schema["properties"]["resample_method"]["enum"], json!(RESAMPLE_METHODS));
+
+        let canvas = tool("resize_canvas");
+        let schema = &canvas["inputSchema"];
+        assert_eq!(schema["required"], json!(["width", "height"]));
+        assert_eq!(schema["properties"]["anchor"]["enum"], json!(ANCHORS));
+        assert_eq!(schema["properties"]["anchor"]["default"], "middleCenter");
+    }
+
+    #[test]
+    fn test_filter_and_adjustment_schemas() {
+        let filter = tool("apply_filter");
+        assert_eq!(filter["inputSchema"]["required"], json!(["filter"]));
+        assert_eq!(filter["inputSchema"]["properties"]["filter"]["enum"], json!(FILTERS));
+        assert_eq!(filter["inputSchema"]["properties"]["parameters"]["type"], "object");
+
+        let adjustment = tool("apply_adjustment");
+        assert_eq!(adjustment["inputSchema"]["required"], json!(["adjustment"]));
+        assert_eq!(adjustment["inputSchema"]["properties"]["adjustment"]["enum"], json!(ADJUSTMENTS));
+        assert_eq!(adjustment["inputSchema"]["properties"]["layer_name"]["type"], "string");
+    }
+
+    #[tokio::test]
+    async fn test_apply_filter_and_adjustment() {
+        let (url, proxy) = MockProxy::new()
+            .respond("applyFilter", json!({"layerName": "Background"}))
+            .serve()
+            .await;
+        let client = Arc::new(PhotoshopClient::new(&url, 5_000, None).await.unwrap());
+
+        let result = handle_tool_call(
+            &client,
+            "apply_filter",
+            json!({"filter": "gaussianBlur", "parameters": {"radius": 4}}),
+        )
+        .await
+        .unwrap();
+        assert_eq!(result, "Applied gaussianBlur to layer 'Background'");
+
+        let result = handle_tool_call(
+            &client,
+            "apply_adjustment",
+            json!({"adjustment": "hueSaturation", "layer_name": "Sky", "parameters": {"saturation": -30}}),
+        )
+        .await
+        .unwrap();
+        assert_eq!(result, "Applied hueSaturation to layer 'Sky'");
+
+        for args in [
+            json!({"filter": "emboss"}),
+            json!({"filter": "sharpen", "parameters": [1, 2]}),
+            json!({}),
+        ] {
+            assert!(handle_tool_call(&client, "apply_filter", args).await.is_err());
+        }
+
+        proxy.shutdown().await;
+    }
+
+    #[test]
+    fn test_export_layers_options() {
+        let options = export_layers_options(&json!({
+            "output_dir": "C:/exports",
+            "layers": ["Sky", "Logo"],
+            "name_pattern": "banner_{name}"
+        }))
+        .unwrap();
+        assert_eq!(options["outputDir"], "C:/exports");
+        assert_eq!(options["namePattern"], "banner_{name}");
+        assert_eq!(options["layers"], json!(["Sky", "Logo"]));
+        assert_eq!(options["fileType"], "PNG");
+
+        let options = export_layers_options(&json!({"output_dir": "/tmp/out", "file_type": "JPG"})).unwrap();
+        assert_eq!(options["namePattern"], "{name}");
+        assert!(options["layers"].is_null());
+
+        for args in [
+            json!({}),
+            json!({"output_dir": "  "}),
+            json!({"output_dir": "/tmp/out", "name_pattern": "layer"}),
+            json!({"output_dir": "/tmp/out", "file_type": "GIF"}),
+            json!({"output_dir": "/tmp/out", "layers": "Sky"}),
+        ] {
+            assert!(export_layers_options(&args).is_err(), "{}", args);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_set_layer_properties() {
+        let (url, proxy) = MockProxy::new()
+            .respond(
+                "setLayerProperties",
+                json!({"properties": {"opacity": 40, "visible": true, "blendMode": "NORMAL", "name": "Clouds"}}),
+            )
+            .serve()
+            .await;
+        let client = Arc::new(PhotoshopClient::new(&url, 5_000, None).await.unwrap());
+
+        let result = handle_tool_call(
+            &client,
+            "set_layer_properties",
+            json!({"layer_name": "Sky", "opacity": 40, "rename": "Clouds"}),
+        )
+        .await
+        .unwrap();
+        assert!(result.starts_with("Updated layer 'Sky':"));
+        assert!(result.contains("\"opacity\": 40"));
+        assert!(result.contains("\"name\": \"Clouds\""));
+
+        for args in [
+            json!({"layer_name": "Sky"}),
+            json!({"layer_name": "Sky", "opacity": 101}),
+            json!({"layer_name": "Sky", "opacity": -1}),
+            json!({"layer_name": "Sky", "visible": "yes"}),
+            json!({"layer_name": "Sky", "rename": ""}),
+            json!({"opacity": 50}),
+        ] {
+            assert!(
+                handle_tool_call(&client, "set_layer_properties", args.clone()).await.is_err(),
+                "{}",
+                args
+            );
+        }
+
+        proxy.shutdown().await;
+    }
+
+    #[test]
+    fn test_set_layer_properties_options() {
+        let options = set_layer_properties_options(&json!({"layer_name": "Sky", "visible": false, "blend_mode": "MULTIPLY"})).unwrap();
+        assert_eq!(
+            options,
+            json!({"layerName": "Sky", "properties": {"visible": false, "blendMode": "MULTIPLY"}})
+        );
+
+        let err = set_layer_properties_options(&json!({"layer_name": "Sky"})).unwrap_err();
+        assert!(err.to_string().contains("at least one of"));
+    }
+
+    #[test]
+    fn test_resize_options() {
+        let options = resize_image_options(&json!({"width": 800, "height": 600, "resample_method": "bilinear"})).unwrap();
+        assert_eq!(
+            options,
+            json!({"width": 800, "height": 600, "resolution": null, "resampleMethod": "bilinear"})
+        );
+
+        let options = resize_canvas_options(&json!({"width": 1000, "height": 1000})).unwrap();
+        assert_eq!(options["anchor"], "middleCenter");
+
+        for args in [
+            json!({"width": 0, "height": 600}),
+            json!({"width": -5, "height": 600}),
+            json!({"width": 800}),
+            json!({"width": 800, "height": 600, "resolution": 0}),
+            json!({"width": 800, "height": 600, "resample_method": "sinc"}),
+        ] {
+            assert!(resize_image_options(&args).is_err(), "{}", args);
+        }
+        assert!(resize_canvas_options(&json!({"width": 10, "height": 10, "anchor": "center"})).is_err());
+    }
 }